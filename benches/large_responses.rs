@@ -0,0 +1,114 @@
+//! Time and peak heap use of decoding a large `getArtists` response, through an
+//! intermediate `serde_json::Value` (`get_raw`) and straight into the data types
+//! (`get_artists`).
+//!
+//! Run with `cargo bench --features testing`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use opensubsonic::data::ArtistsId3;
+use opensubsonic::testing::MockServer;
+use serde_json::{Value, json};
+
+/// Number of artists in the generated library.
+const ARTISTS: usize = 100_000;
+/// Runs per measurement; the median is reported.
+const RUNS: usize = 9;
+
+/// The system allocator, tracking the current and peak number of allocated bytes.
+struct Counting;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(current, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+fn library() -> Value {
+    let index: Vec<Value> = (b'A'..=b'Z')
+        .map(|letter| {
+            let letter = char::from(letter);
+            let artists: Vec<Value> = (0..ARTISTS / 26)
+                .map(|i| {
+                    json!({
+                        "id": format!("ar-{letter}{i}"),
+                        "name": format!("{letter} Artist {i}"),
+                        "albumCount": 3,
+                        "coverArt": format!("ar-{letter}{i}"),
+                        "artistImageUrl": format!("https://images.example.com/{letter}{i}.jpg"),
+                        "starred": "2024-03-05T10:20:30Z",
+                        "musicBrainzId": "5b11f4ce-a62d-471e-81fc-a69a8278c7da",
+                        "sortName": format!("artist {i}, {letter}"),
+                        "roles": ["artist", "albumartist"]
+                    })
+                })
+                .collect();
+            json!({ "name": letter.to_string(), "artist": artists })
+        })
+        .collect();
+    json!({ "artists": { "ignoredArticles": "The El La Los Las Le Les", "index": index } })
+}
+
+/// Median duration and peak heap growth of `runs` calls of `f`.
+async fn measure<F, Fut>(mut f: F) -> (Duration, usize)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ArtistsId3>,
+{
+    let mut times = Vec::with_capacity(RUNS);
+    let mut peak = 0;
+    for _ in 0..RUNS {
+        let base = CURRENT.load(Ordering::Relaxed);
+        PEAK.store(base, Ordering::Relaxed);
+        let start = Instant::now();
+        let artists = f().await;
+        times.push(start.elapsed());
+        peak = peak.max(PEAK.load(Ordering::Relaxed) - base);
+        assert_eq!(artists.index.len(), 26);
+    }
+    times.sort();
+    (times[RUNS / 2], peak)
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let server = MockServer::start().await;
+    server.respond_with("getArtists", library()).await;
+    let client = server.client();
+
+    let (value_time, value_peak) = measure(|| async {
+        let mut data = client.get_raw("getArtists", &[]).await.unwrap();
+        serde_json::from_value(data.remove("artists").unwrap()).unwrap()
+    })
+    .await;
+    let (typed_time, typed_peak) =
+        measure(|| async { client.get_artists(None).await.unwrap() }).await;
+
+    println!("getArtists with {ARTISTS} artists (median of {RUNS} runs, peak heap growth):");
+    println!(
+        "  via serde_json::Value  {value_time:>10.2?}  {:>8.1} MiB",
+        value_peak as f64 / 1048576.0
+    );
+    println!(
+        "  direct                 {typed_time:>10.2?}  {:>8.1} MiB",
+        typed_peak as f64 / 1048576.0
+    );
+}
@@ -1,7 +1,13 @@
 //! Bookmarks API endpoints.
+//!
+//! Positions are milliseconds on the wire; the `_at` variants take a [`Duration`] instead.
+//! [`PlayQueueSession`] keeps a local copy of the play queue and saves it back.
+
+use std::time::{Duration, Instant};
 
 use crate::Client;
-use crate::data::{Bookmark, PlayQueue, PlayQueueByIndex};
+use crate::client::take_list;
+use crate::data::{Bookmark, Child, PlayQueue, PlayQueueByIndex, SongId};
 use crate::error::Error;
 
 impl Client {
@@ -9,13 +15,9 @@ impl Client {
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getbookmarks/>
     pub async fn get_bookmarks(&self) -> Result<Vec<Bookmark>, Error> {
-        let data = self.get_response("getBookmarks", &[]).await?;
-        let bookmarks = data
-            .get("bookmarks")
-            .and_then(|v| v.get("bookmark"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(bookmarks)?)
+        let mut data = self.get_response("getBookmarks", &[]).await?;
+        let bookmarks = take_list(&mut data, "bookmarks", "bookmark");
+        self.decode_list(bookmarks)
     }
 
     /// Create or update a bookmark.
@@ -23,12 +25,13 @@ impl Client {
     /// See <https://opensubsonic.netlify.app/docs/endpoints/createbookmark/>
     pub async fn create_bookmark(
         &self,
-        id: &str,
+        id: impl Into<SongId>,
         position: i64,
         comment: Option<&str>,
     ) -> Result<(), Error> {
+        let id: SongId = id.into();
         let pos_str = position.to_string();
-        let mut params = vec![("id", id), ("position", &pos_str)];
+        let mut params = vec![("id", id.as_str()), ("position", &pos_str)];
         if let Some(c) = comment {
             params.push(("comment", c));
         }
@@ -36,11 +39,24 @@ impl Client {
         Ok(())
     }
 
+    /// Create or update a bookmark at `position`.
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/createbookmark/>
+    pub async fn create_bookmark_at(
+        &self,
+        id: impl Into<SongId>,
+        position: Duration,
+        comment: Option<&str>,
+    ) -> Result<(), Error> {
+        self.create_bookmark(id, millis(position), comment).await
+    }
+
     /// Delete a bookmark.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/deletebookmark/>
-    pub async fn delete_bookmark(&self, id: &str) -> Result<(), Error> {
-        self.get_response("deleteBookmark", &[("id", id)]).await?;
+    pub async fn delete_bookmark(&self, id: impl Into<SongId>) -> Result<(), Error> {
+        let id: SongId = id.into();
+        self.get_response("deleteBookmark", &[("id", &id)]).await?;
         Ok(())
     }
 
@@ -48,11 +64,11 @@ impl Client {
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getplayqueue/>
     pub async fn get_play_queue(&self) -> Result<PlayQueue, Error> {
-        let data = self.get_response("getPlayQueue", &[]).await?;
+        let mut data = self.get_response("getPlayQueue", &[]).await?;
         let queue = data
-            .get("playQueue")
+            .remove("playQueue")
             .ok_or_else(|| Error::Parse("Missing 'playQueue' in response".into()))?;
-        Ok(serde_json::from_value(queue.clone())?)
+        self.decode(queue)
     }
 
     /// Save the play queue.
@@ -61,7 +77,7 @@ impl Client {
     pub async fn save_play_queue(
         &self,
         ids: &[&str],
-        current: Option<&str>,
+        current: Option<&SongId>,
         position: Option<i64>,
     ) -> Result<(), Error> {
         let mut params = Vec::new();
@@ -79,15 +95,28 @@ impl Client {
         Ok(())
     }
 
+    /// Save the play queue with the position in the current track as a [`Duration`].
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/saveplayqueue/>
+    pub async fn save_play_queue_at(
+        &self,
+        ids: &[&str],
+        current: Option<&SongId>,
+        position: Duration,
+    ) -> Result<(), Error> {
+        self.save_play_queue(ids, current, Some(millis(position)))
+            .await
+    }
+
     /// Get the play queue by index (OpenSubsonic extension).
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getplayqueuebyindex/>
     pub async fn get_play_queue_by_index(&self) -> Result<PlayQueueByIndex, Error> {
-        let data = self.get_response("getPlayQueueByIndex", &[]).await?;
+        let mut data = self.get_response("getPlayQueueByIndex", &[]).await?;
         let queue = data
-            .get("playQueueByIndex")
+            .remove("playQueueByIndex")
             .ok_or_else(|| Error::Parse("Missing 'playQueueByIndex' in response".into()))?;
-        Ok(serde_json::from_value(queue.clone())?)
+        self.decode(queue)
     }
 
     /// Save the play queue by index (OpenSubsonic extension).
@@ -114,4 +143,351 @@ impl Client {
             .await?;
         Ok(())
     }
+
+    /// Save the play queue by index (OpenSubsonic extension), with the position in the
+    /// current track as a [`Duration`].
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/saveplayqueuebyindex/>
+    pub async fn save_play_queue_by_index_at(
+        &self,
+        ids: &[&str],
+        current_index: Option<i32>,
+        position: Duration,
+    ) -> Result<(), Error> {
+        self.save_play_queue_by_index(ids, current_index, Some(millis(position)))
+            .await
+    }
+}
+
+/// How often [`PlayQueueSession::needs_save`] asks for a save while only the position moves.
+const POSITION_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A local copy of the saved play queue, for resuming playback on another device.
+///
+/// Load it with [`Client::play_queue_session`], change it as playback goes on, and persist it
+/// with [`Client::save_play_queue_session`] whenever [`needs_save`](Self::needs_save) says so:
+/// changes to the songs or the current track right away, and position updates at most every
+/// 30 seconds (see [`save_interval`](Self::save_interval)).
+///
+/// ```no_run
+/// # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+/// use std::time::Duration;
+///
+/// let mut session = client.play_queue_session().await?;
+/// session.seek(Duration::from_secs(42));
+/// session.advance();
+/// if session.needs_save() {
+///     client.save_play_queue_session(&mut session).await?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PlayQueueSession {
+    songs: Vec<Child>,
+    current: Option<usize>,
+    position: Duration,
+    by_index: bool,
+    queue_changed: bool,
+    position_changed: bool,
+    saved_at: Option<Instant>,
+    save_interval: Duration,
+}
+
+impl Default for PlayQueueSession {
+    fn default() -> Self {
+        Self {
+            songs: Vec::new(),
+            current: None,
+            position: Duration::ZERO,
+            by_index: false,
+            queue_changed: false,
+            position_changed: false,
+            saved_at: None,
+            save_interval: POSITION_SAVE_INTERVAL,
+        }
+    }
+}
+
+impl PlayQueueSession {
+    /// An empty queue, saved with `savePlayQueue`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How often position updates alone make [`needs_save`](Self::needs_save) true.
+    #[must_use]
+    pub fn save_interval(mut self, interval: Duration) -> Self {
+        self.save_interval = interval;
+        self
+    }
+
+    /// The songs of the queue.
+    pub fn songs(&self) -> &[Child] {
+        &self.songs
+    }
+
+    /// Index of the current song.
+    pub fn current_index(&self) -> Option<usize> {
+        self.current
+    }
+
+    /// The current song.
+    pub fn current(&self) -> Option<&Child> {
+        self.songs.get(self.current?)
+    }
+
+    /// Position in the current song.
+    pub fn position(&self) -> Duration {
+        self.position
+    }
+
+    /// Replace the songs, making the song at `index` current (none if out of range).
+    pub fn set_songs(&mut self, songs: Vec<Child>, index: usize) {
+        self.current = (index < songs.len()).then_some(index);
+        self.songs = songs;
+        self.position = Duration::ZERO;
+        self.queue_changed = true;
+    }
+
+    /// Make the song at `index` current, from its start.
+    ///
+    /// # Errors
+    /// Returns [`Error::Other`] if `index` is out of range.
+    pub fn jump_to(&mut self, index: usize) -> Result<&Child, Error> {
+        if index >= self.songs.len() {
+            return Err(Error::Other(format!(
+                "Cannot play entry {index} of a queue of {} entries",
+                self.songs.len()
+            )));
+        }
+        self.current = Some(index);
+        self.position = Duration::ZERO;
+        self.queue_changed = true;
+        Ok(&self.songs[index])
+    }
+
+    /// Move to the next song, returning it, or `None` (leaving the queue as is) at the end.
+    pub fn advance(&mut self) -> Option<&Child> {
+        let next = self.current.map_or(0, |i| i + 1);
+        self.jump_to(next).ok()
+    }
+
+    /// Move to the previous song, returning it, or `None` at the start.
+    pub fn previous(&mut self) -> Option<&Child> {
+        let previous = self.current?.checked_sub(1)?;
+        self.jump_to(previous).ok()
+    }
+
+    /// Set the position in the current song, on seeks and as playback progresses.
+    pub fn seek(&mut self, position: Duration) {
+        if position != self.position {
+            self.position = position;
+            self.position_changed = true;
+        }
+    }
+
+    /// Insert `songs` before the entry at `index` (appending if past the end). The current
+    /// song stays current.
+    pub fn insert(&mut self, index: usize, songs: impl IntoIterator<Item = Child>) {
+        let index = index.min(self.songs.len());
+        let before = self.songs.len();
+        self.songs.splice(index..index, songs);
+        let added = self.songs.len() - before;
+        if let Some(current) = self.current.as_mut().filter(|c| **c >= index) {
+            *current += added;
+        }
+        self.queue_changed |= added > 0;
+    }
+
+    /// Insert `songs` right after the current song (at the start if there is none).
+    pub fn play_next(&mut self, songs: impl IntoIterator<Item = Child>) {
+        self.insert(self.current.map_or(0, |i| i + 1), songs);
+    }
+
+    /// Append `songs` to the queue.
+    pub fn push(&mut self, songs: impl IntoIterator<Item = Child>) {
+        self.insert(self.songs.len(), songs);
+    }
+
+    /// Remove the entry at `index`, returning it. Removing the current song makes the next
+    /// one current, from its start.
+    pub fn remove(&mut self, index: usize) -> Option<Child> {
+        if index >= self.songs.len() {
+            return None;
+        }
+        let song = self.songs.remove(index);
+        self.current = match self.current {
+            Some(current) if current > index => Some(current - 1),
+            Some(current) if current == index => {
+                self.position = Duration::ZERO;
+                (index < self.songs.len()).then_some(index)
+            }
+            current => current,
+        };
+        self.queue_changed = true;
+        Some(song)
+    }
+
+    /// Whether the session should be saved now: the songs or the current song changed, or
+    /// the position changed and the last save is older than the save interval.
+    pub fn needs_save(&self) -> bool {
+        self.queue_changed
+            || (self.position_changed
+                && self
+                    .saved_at
+                    .is_none_or(|at| at.elapsed() >= self.save_interval))
+    }
+}
+
+impl Client {
+    /// Load the saved play queue into a [`PlayQueueSession`].
+    ///
+    /// Uses `getPlayQueueByIndex` when the server advertises the `indexBasedQueue`
+    /// extension, which keeps the current entry exact when a song is queued twice, and
+    /// `getPlayQueue` otherwise, including when the extensions cannot be fetched (a plain
+    /// Subsonic server). A server without a saved queue gives an empty session.
+    pub async fn play_queue_session(&self) -> Result<PlayQueueSession, Error> {
+        let by_index = self
+            .capabilities()
+            .await
+            .is_ok_and(|c| c.index_based_queue());
+        let mut session = PlayQueueSession {
+            by_index,
+            ..PlayQueueSession::default()
+        };
+        if by_index {
+            let mut data = self.get_response("getPlayQueueByIndex", &[]).await?;
+            if let Some(queue) = data.remove("playQueueByIndex") {
+                let queue: PlayQueueByIndex = self.decode(queue)?;
+                session.position = queue.position();
+                session.current = queue
+                    .current_index
+                    .and_then(|i| usize::try_from(i).ok())
+                    .filter(|i| *i < queue.entry.len());
+                session.songs = queue.entry;
+            }
+        } else {
+            let mut data = self.get_response("getPlayQueue", &[]).await?;
+            if let Some(queue) = data.remove("playQueue") {
+                let queue: PlayQueue = self.decode(queue)?;
+                session.position = queue.position();
+                session.current = queue
+                    .current
+                    .as_ref()
+                    .and_then(|id| queue.entry.iter().position(|s| &s.id == id));
+                session.songs = queue.entry;
+            }
+        }
+        Ok(session)
+    }
+
+    /// Save a [`PlayQueueSession`], with `savePlayQueueByIndex` if it was loaded from a
+    /// server with the `indexBasedQueue` extension and `savePlayQueue` otherwise.
+    pub async fn save_play_queue_session(
+        &self,
+        session: &mut PlayQueueSession,
+    ) -> Result<(), Error> {
+        let ids: Vec<&str> = session.songs.iter().map(|s| s.id.as_str()).collect();
+        if session.by_index {
+            let index = session.current.and_then(|i| i32::try_from(i).ok());
+            self.save_play_queue_by_index_at(&ids, index, session.position)
+                .await?;
+        } else {
+            let current = session.current().map(|s| &s.id);
+            self.save_play_queue_at(&ids, current, session.position)
+                .await?;
+        }
+        session.queue_changed = false;
+        session.position_changed = false;
+        session.saved_at = Some(Instant::now());
+        Ok(())
+    }
+}
+
+/// A position as milliseconds, saturating at `i64::MAX`.
+fn millis(position: Duration) -> i64 {
+    i64::try_from(position.as_millis()).unwrap_or(i64::MAX)
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::testing::MockServer;
+
+    #[tokio::test]
+    async fn positions_are_durations() {
+        let server = MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::path("/rest/createBookmark"))
+            .and(wiremock::matchers::query_param("position", "90500"))
+            .respond_with(
+                wiremock::ResponseTemplate::new(200)
+                    .set_body_json(crate::testing::ok_envelope(serde_json::json!({}))),
+            )
+            .expect(1)
+            .mount(server.inner())
+            .await;
+        let client = server.client();
+        client
+            .create_bookmark_at("tr-1", Duration::from_millis(90_500), None)
+            .await
+            .unwrap();
+        let bookmarks = client.get_bookmarks().await.unwrap();
+        assert_eq!(bookmarks[0].position(), Duration::from_secs(60));
+        assert_eq!(
+            client.get_play_queue().await.unwrap().position(),
+            Duration::ZERO
+        );
+    }
+
+    #[tokio::test]
+    async fn play_queue_session_round_trips() {
+        let server = MockServer::start().await;
+        server
+            .respond_with(
+                "getOpenSubsonicExtensions",
+                serde_json::json!({
+                    "openSubsonicExtensions": [{ "name": "indexBasedQueue", "versions": [1] }]
+                }),
+            )
+            .await;
+        let client = server.client();
+        let mut session = client
+            .play_queue_session()
+            .await
+            .unwrap()
+            .save_interval(Duration::from_secs(3600));
+        assert_eq!(session.current().map(|s| s.id.as_str()), Some("tr-1"));
+        assert!(!session.needs_save());
+
+        session.insert(0, [Child::new("tr-0", "Intro")]);
+        session.push([Child::new("tr-2", "Outro")]);
+        assert_eq!(session.current_index(), Some(1));
+        assert_eq!(session.advance().map(|s| s.title.as_str()), Some("Outro"));
+        assert!(session.advance().is_none());
+        assert!(session.needs_save());
+        client.save_play_queue_session(&mut session).await.unwrap();
+        assert_eq!(server.calls("savePlayQueueByIndex").await, 1);
+
+        session.seek(Duration::from_secs(5));
+        assert!(!session.needs_save());
+        assert!(session.remove(2).is_some());
+        assert_eq!(session.current_index(), None);
+        assert!(session.needs_save());
+    }
+
+    #[tokio::test]
+    async fn play_queue_session_without_extensions() {
+        let server = MockServer::start().await;
+        server
+            .fail_with("getOpenSubsonicExtensions", 70, "Unknown method")
+            .await;
+        let client = server.client();
+        let mut session = client.play_queue_session().await.unwrap();
+        assert_eq!(session.current().map(|s| s.id.as_str()), Some("tr-1"));
+        session.push([Child::new("tr-2", "Outro")]);
+        client.save_play_queue_session(&mut session).await.unwrap();
+        assert_eq!(server.calls("getPlayQueueByIndex").await, 0);
+        assert_eq!(server.calls("savePlayQueue").await, 1);
+    }
 }
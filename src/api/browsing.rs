@@ -1,24 +1,39 @@
 //! Browsing API endpoints.
 
+use futures_util::{Stream, StreamExt, TryStreamExt, stream};
+
 use crate::Client;
+use crate::client::take_list;
+#[cfg(feature = "video")]
+use crate::data::VideoInfo;
 use crate::data::{
-    AlbumInfo, AlbumWithSongsId3, ArtistInfo, ArtistInfo2, ArtistWithAlbumsId3, ArtistsId3, Child,
-    Directory, Genre, Indexes, MusicFolder, VideoInfo,
+    AlbumId, AlbumInfo, AlbumWithSongsId3, ArtistId, ArtistInfo, ArtistInfo2, ArtistWithAlbumsId3,
+    ArtistsId3, Child, Directory, Genre, Indexes, MusicFolder, SongId,
 };
 use crate::error::Error;
 
+/// Number of requests [`Client::get_artist_discography`] and [`Client::walk_library`] run
+/// at a time.
+const FETCH_CONCURRENCY: usize = 4;
+
+/// An artist with all of its albums and their songs, returned by
+/// [`Client::get_artist_discography`].
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Discography {
+    /// The artist, as returned by `getArtist`.
+    pub artist: ArtistWithAlbumsId3,
+    /// The artist's albums with their songs, in the order `getArtist` lists them.
+    pub albums: Vec<AlbumWithSongsId3>,
+}
+
 impl Client {
     /// Get all configured music folders.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getmusicfolders/>
     pub async fn get_music_folders(&self) -> Result<Vec<MusicFolder>, Error> {
-        let data = self.get_response("getMusicFolders", &[]).await?;
-        let folders = data
-            .get("musicFolders")
-            .and_then(|v| v.get("musicFolder"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(folders)?)
+        let mut data = self.get_response("getMusicFolders", &[]).await?;
+        let folders = take_list(&mut data, "musicFolders", "musicFolder");
+        self.decode_list(folders)
     }
 
     /// Get an indexed structure of all artists (folder-based).
@@ -40,37 +55,31 @@ impl Client {
             since_str = since.to_string();
             params.push(("ifModifiedSince", since_str.as_str()));
         }
-        let data = self.get_response("getIndexes", &params).await?;
-        let indexes = data
-            .get("indexes")
-            .ok_or_else(|| Error::Parse("Missing 'indexes' in response".into()))?;
-        Ok(serde_json::from_value(indexes.clone())?)
+        self.get_typed("getIndexes", &params, "indexes")
+            .await?
+            .ok_or_else(|| Error::Parse("Missing 'indexes' in response".into()))
     }
 
     /// Get a directory listing (folder-based browsing).
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getmusicdirectory/>
     pub async fn get_music_directory(&self, id: &str) -> Result<Directory, Error> {
-        let data = self
+        let mut data = self
             .get_response("getMusicDirectory", &[("id", id)])
             .await?;
         let dir = data
-            .get("directory")
+            .remove("directory")
             .ok_or_else(|| Error::Parse("Missing 'directory' in response".into()))?;
-        Ok(serde_json::from_value(dir.clone())?)
+        self.decode(dir)
     }
 
     /// Get all genres.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getgenres/>
     pub async fn get_genres(&self) -> Result<Vec<Genre>, Error> {
-        let data = self.get_response("getGenres", &[]).await?;
-        let genres = data
-            .get("genres")
-            .and_then(|v| v.get("genre"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(genres)?)
+        let mut data = self.get_response("getGenres", &[]).await?;
+        let genres = take_list(&mut data, "genres", "genre");
+        self.decode_list(genres)
     }
 
     /// Get all artists (ID3-based).
@@ -81,68 +90,134 @@ impl Client {
         if let Some(id) = music_folder_id {
             params.push(("musicFolderId", id));
         }
-        let data = self.get_response("getArtists", &params).await?;
-        let artists = data
-            .get("artists")
-            .ok_or_else(|| Error::Parse("Missing 'artists' in response".into()))?;
-        Ok(serde_json::from_value(artists.clone())?)
+        self.get_typed("getArtists", &params, "artists")
+            .await?
+            .ok_or_else(|| Error::Parse("Missing 'artists' in response".into()))
     }
 
     /// Get details for an artist, including a list of albums (ID3-based).
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getartist/>
-    pub async fn get_artist(&self, id: &str) -> Result<ArtistWithAlbumsId3, Error> {
-        let data = self.get_response("getArtist", &[("id", id)]).await?;
+    pub async fn get_artist(&self, id: impl Into<ArtistId>) -> Result<ArtistWithAlbumsId3, Error> {
+        let id: ArtistId = id.into();
+        let mut data = self.get_response("getArtist", &[("id", &id)]).await?;
         let artist = data
-            .get("artist")
+            .remove("artist")
             .ok_or_else(|| Error::Parse("Missing 'artist' in response".into()))?;
-        Ok(serde_json::from_value(artist.clone())?)
+        self.decode(artist)
     }
 
     /// Get details for an album, including a list of songs (ID3-based).
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getalbum/>
-    pub async fn get_album(&self, id: &str) -> Result<AlbumWithSongsId3, Error> {
-        let data = self.get_response("getAlbum", &[("id", id)]).await?;
+    pub async fn get_album(&self, id: impl Into<AlbumId>) -> Result<AlbumWithSongsId3, Error> {
+        let id: AlbumId = id.into();
+        let mut data = self.get_response("getAlbum", &[("id", &id)]).await?;
         let album = data
-            .get("album")
+            .remove("album")
             .ok_or_else(|| Error::Parse("Missing 'album' in response".into()))?;
-        Ok(serde_json::from_value(album.clone())?)
+        self.decode(album)
+    }
+
+    /// Get an artist and the songs of all of its albums.
+    ///
+    /// Fetches the artist with `getArtist`, then each of its albums with `getAlbum`, four
+    /// at a time.
+    ///
+    /// ```no_run
+    /// # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+    /// let discography = client.get_artist_discography("ar-1").await?;
+    /// for album in &discography.albums {
+    ///     println!("{}: {} songs", album.name, album.song.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_artist_discography(
+        &self,
+        id: impl Into<ArtistId>,
+    ) -> Result<Discography, Error> {
+        let artist = self.get_artist(id).await?;
+        let albums = stream::iter(&artist.album)
+            .map(|album| self.get_album(&album.id))
+            .buffered(FETCH_CONCURRENCY)
+            .try_collect()
+            .await?;
+        Ok(Discography { artist, albums })
+    }
+
+    /// Stream every song in the library, or in one music folder.
+    ///
+    /// Walks `getArtists`, then `getArtist` for each artist and `getAlbum` for each album,
+    /// with up to four requests of each kind in flight. Requests are only issued as the
+    /// stream is polled, so a slow consumer slows the walk down instead of buffering the
+    /// library. Songs come in artist and album order; the first error ends the walk.
+    ///
+    /// ```no_run
+    /// # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+    /// use futures_util::TryStreamExt;
+    ///
+    /// let mut songs = std::pin::pin!(client.walk_library(None));
+    /// while let Some(song) = songs.try_next().await? {
+    ///     println!("{}", song.title);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn walk_library(
+        &self,
+        music_folder_id: Option<&str>,
+    ) -> impl Stream<Item = Result<Child, Error>> + '_ {
+        let music_folder_id = music_folder_id.map(str::to_owned);
+        stream::once(async move { self.get_artists(music_folder_id.as_deref()).await })
+            .map_ok(|artists| {
+                let ids = artists.index.into_iter().flat_map(|index| index.artist);
+                stream::iter(ids.map(|artist| Ok(artist.id)))
+            })
+            .try_flatten()
+            .map_ok(|id| self.get_artist(id))
+            .try_buffered(FETCH_CONCURRENCY)
+            .map_ok(|artist| stream::iter(artist.album.into_iter().map(|album| Ok(album.id))))
+            .try_flatten()
+            .map_ok(|id| self.get_album(id))
+            .try_buffered(FETCH_CONCURRENCY)
+            .map_ok(|album| stream::iter(album.song.into_iter().map(Ok)))
+            .try_flatten()
     }
 
     /// Get details for a song.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getsong/>
-    pub async fn get_song(&self, id: &str) -> Result<Child, Error> {
-        let data = self.get_response("getSong", &[("id", id)]).await?;
+    pub async fn get_song(&self, id: impl Into<SongId>) -> Result<Child, Error> {
+        let id: SongId = id.into();
+        let mut data = self.get_response("getSong", &[("id", &id)]).await?;
         let song = data
-            .get("song")
+            .remove("song")
             .ok_or_else(|| Error::Parse("Missing 'song' in response".into()))?;
-        Ok(serde_json::from_value(song.clone())?)
+        self.decode(song)
     }
 
     /// Get all video files. Returns an empty list if the server has no videos.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getvideos/>
+    #[cfg(feature = "video")]
     pub async fn get_videos(&self) -> Result<Vec<Child>, Error> {
-        let data = self.get_response("getVideos", &[]).await?;
-        let videos = data
-            .get("videos")
-            .and_then(|v| v.get("video"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(videos)?)
+        let mut data = self.get_response("getVideos", &[]).await?;
+        let videos = take_list(&mut data, "videos", "video");
+        self.decode_list(videos)
     }
 
     /// Get additional info for a video: captions, audio tracks, conversions.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getvideoinfo/>
-    pub async fn get_video_info(&self, id: &str) -> Result<VideoInfo, Error> {
-        let data = self.get_response("getVideoInfo", &[("id", id)]).await?;
+    #[cfg(feature = "video")]
+    pub async fn get_video_info(&self, id: impl Into<SongId>) -> Result<VideoInfo, Error> {
+        let id: SongId = id.into();
+        let mut data = self.get_response("getVideoInfo", &[("id", &id)]).await?;
         let info = data
-            .get("videoInfo")
+            .remove("videoInfo")
             .ok_or_else(|| Error::Parse("Missing 'videoInfo' in response".into()))?;
-        Ok(serde_json::from_value(info.clone())?)
+        self.decode(info)
     }
 
     /// Get artist info (folder-based).
@@ -150,10 +225,11 @@ impl Client {
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getartistinfo/>
     pub async fn get_artist_info(
         &self,
-        id: &str,
+        id: impl Into<ArtistId>,
         count: Option<i32>,
         include_not_present: Option<bool>,
     ) -> Result<ArtistInfo, Error> {
+        let id: ArtistId = id.into();
         let mut params = vec![("id", id.to_string())];
         if let Some(c) = count {
             params.push(("count", c.to_string()));
@@ -162,11 +238,11 @@ impl Client {
             params.push(("includeNotPresent", b.to_string()));
         }
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let data = self.get_response("getArtistInfo", &param_refs).await?;
+        let mut data = self.get_response("getArtistInfo", &param_refs).await?;
         let info = data
-            .get("artistInfo")
+            .remove("artistInfo")
             .ok_or_else(|| Error::Parse("Missing 'artistInfo' in response".into()))?;
-        Ok(serde_json::from_value(info.clone())?)
+        self.decode(info)
     }
 
     /// Get artist info (ID3-based).
@@ -174,10 +250,11 @@ impl Client {
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getartistinfo2/>
     pub async fn get_artist_info2(
         &self,
-        id: &str,
+        id: impl Into<ArtistId>,
         count: Option<i32>,
         include_not_present: Option<bool>,
     ) -> Result<ArtistInfo2, Error> {
+        let id: ArtistId = id.into();
         let mut params = vec![("id", id.to_string())];
         if let Some(c) = count {
             params.push(("count", c.to_string()));
@@ -186,33 +263,35 @@ impl Client {
             params.push(("includeNotPresent", b.to_string()));
         }
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let data = self.get_response("getArtistInfo2", &param_refs).await?;
+        let mut data = self.get_response("getArtistInfo2", &param_refs).await?;
         let info = data
-            .get("artistInfo2")
+            .remove("artistInfo2")
             .ok_or_else(|| Error::Parse("Missing 'artistInfo2' in response".into()))?;
-        Ok(serde_json::from_value(info.clone())?)
+        self.decode(info)
     }
 
     /// Get album info (external metadata).
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getalbuminfo/>
-    pub async fn get_album_info(&self, id: &str) -> Result<AlbumInfo, Error> {
-        let data = self.get_response("getAlbumInfo", &[("id", id)]).await?;
+    pub async fn get_album_info(&self, id: impl Into<AlbumId>) -> Result<AlbumInfo, Error> {
+        let id: AlbumId = id.into();
+        let mut data = self.get_response("getAlbumInfo", &[("id", &id)]).await?;
         let info = data
-            .get("albumInfo")
+            .remove("albumInfo")
             .ok_or_else(|| Error::Parse("Missing 'albumInfo' in response".into()))?;
-        Ok(serde_json::from_value(info.clone())?)
+        self.decode(info)
     }
 
     /// Get album info (ID3-based).
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getalbuminfo2/>
-    pub async fn get_album_info2(&self, id: &str) -> Result<AlbumInfo, Error> {
-        let data = self.get_response("getAlbumInfo2", &[("id", id)]).await?;
+    pub async fn get_album_info2(&self, id: impl Into<AlbumId>) -> Result<AlbumInfo, Error> {
+        let id: AlbumId = id.into();
+        let mut data = self.get_response("getAlbumInfo2", &[("id", &id)]).await?;
         let info = data
-            .get("albumInfo")
+            .remove("albumInfo")
             .ok_or_else(|| Error::Parse("Missing 'albumInfo' in response".into()))?;
-        Ok(serde_json::from_value(info.clone())?)
+        self.decode(info)
     }
 
     /// Get similar songs (folder-based).
@@ -228,13 +307,9 @@ impl Client {
             params.push(("count", c.to_string()));
         }
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let data = self.get_response("getSimilarSongs", &param_refs).await?;
-        let songs = data
-            .get("similarSongs")
-            .and_then(|v| v.get("song"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(songs)?)
+        let mut data = self.get_response("getSimilarSongs", &param_refs).await?;
+        let songs = take_list(&mut data, "similarSongs", "song");
+        self.decode_list(songs)
     }
 
     /// Get similar songs (ID3-based).
@@ -250,13 +325,9 @@ impl Client {
             params.push(("count", c.to_string()));
         }
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let data = self.get_response("getSimilarSongs2", &param_refs).await?;
-        let songs = data
-            .get("similarSongs2")
-            .and_then(|v| v.get("song"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(songs)?)
+        let mut data = self.get_response("getSimilarSongs2", &param_refs).await?;
+        let songs = take_list(&mut data, "similarSongs2", "song");
+        self.decode_list(songs)
     }
 
     /// Get top songs for an artist.
@@ -272,12 +343,36 @@ impl Client {
             params.push(("count", c.to_string()));
         }
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let data = self.get_response("getTopSongs", &param_refs).await?;
-        let songs = data
-            .get("topSongs")
-            .and_then(|v| v.get("song"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(songs)?)
+        let mut data = self.get_response("getTopSongs", &param_refs).await?;
+        let songs = take_list(&mut data, "topSongs", "song");
+        self.decode_list(songs)
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use futures_util::TryStreamExt;
+    use serde_json::json;
+
+    use crate::testing::MockServer;
+
+    #[tokio::test]
+    async fn walk_library_visits_every_album() {
+        let server = MockServer::start().await;
+        let client = server.client();
+        let artists: Vec<_> = (1..=3)
+            .map(|i| json!({ "id": format!("ar-{i}"), "name": format!("Artist {i}") }))
+            .collect();
+        server
+            .respond_with(
+                "getArtists",
+                json!({ "artists": { "index": [{ "name": "A", "artist": artists }] } }),
+            )
+            .await;
+
+        let songs: Vec<_> = client.walk_library(None).try_collect().await.unwrap();
+        assert_eq!(songs.len(), 3);
+        assert_eq!(server.calls("getArtist").await, 3);
+        assert_eq!(server.calls("getAlbum").await, 3);
     }
 }
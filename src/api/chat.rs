@@ -1,6 +1,11 @@
 //! Chat API endpoints.
 
+use std::time::Duration;
+
+use futures_util::{Stream, TryStreamExt, stream};
+
 use crate::Client;
+use crate::client::take_list;
 use crate::data::ChatMessage;
 use crate::error::Error;
 
@@ -15,13 +20,9 @@ impl Client {
             since_str = s.to_string();
             params.push(("since", since_str.as_str()));
         }
-        let data = self.get_response("getChatMessages", &params).await?;
-        let messages = data
-            .get("chatMessages")
-            .and_then(|v| v.get("chatMessage"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(messages)?)
+        let mut data = self.get_response("getChatMessages", &params).await?;
+        let messages = take_list(&mut data, "chatMessages", "chatMessage");
+        self.decode_list(messages)
     }
 
     /// Add a chat message.
@@ -32,4 +33,86 @@ impl Client {
             .await?;
         Ok(())
     }
+
+    /// Watch the chat, yielding each new message once, oldest first.
+    ///
+    /// The stream polls `getChatMessages` every `poll_interval`, passing the time of the
+    /// newest message seen as `since`. Messages already on the server when the stream starts
+    /// are not yielded; fetch them with [`get_chat_messages`](Self::get_chat_messages). The
+    /// stream ends at the first error.
+    ///
+    /// ```no_run
+    /// # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+    /// use std::time::Duration;
+    ///
+    /// use futures_util::TryStreamExt;
+    ///
+    /// let mut messages = std::pin::pin!(client.chat_stream(Duration::from_secs(5)));
+    /// while let Some(message) = messages.try_next().await? {
+    ///     println!("{}: {}", message.username, message.message);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn chat_stream(
+        &self,
+        poll_interval: Duration,
+    ) -> impl Stream<Item = Result<ChatMessage, Error>> + '_ {
+        // State: the cursor, or `None` before the first poll.
+        stream::try_unfold(None, move |cursor: Option<Cursor>| async move {
+            let Some(mut cursor) = cursor else {
+                let mut cursor = Cursor::default();
+                cursor.advance(self.get_chat_messages(None).await?);
+                return Ok::<_, Error>(Some((Vec::new(), Some(cursor))));
+            };
+            tokio::time::sleep(poll_interval).await;
+            let messages = self.get_chat_messages(Some(cursor.time)).await?;
+            let new = cursor.advance(messages);
+            Ok(Some((new, Some(cursor))))
+        })
+        .map_ok(|messages| stream::iter(messages.into_iter().map(Ok)))
+        .try_flatten()
+    }
+}
+
+/// The newest message time seen, and the messages sent at that time, which servers may
+/// return again for `since` equal to it.
+#[derive(Default)]
+struct Cursor {
+    time: i64,
+    at_time: Vec<ChatMessage>,
+}
+
+impl Cursor {
+    /// Keep the messages not seen before, oldest first, and move past them.
+    fn advance(&mut self, mut messages: Vec<ChatMessage>) -> Vec<ChatMessage> {
+        messages
+            .retain(|m| m.time > self.time || (m.time == self.time && !self.at_time.contains(m)));
+        messages.sort_by_key(|m| m.time);
+        for message in &messages {
+            if message.time > self.time {
+                self.time = message.time;
+                self.at_time.clear();
+            }
+            self.at_time.push(message.clone());
+        }
+        messages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_skips_seen_messages() {
+        let mut cursor = Cursor::default();
+        let old = ChatMessage::new("bob", 100, "hi");
+        assert_eq!(cursor.advance(vec![old.clone()]).len(), 1);
+        let new = ChatMessage::new("amy", 100, "hello");
+        let newer = ChatMessage::new("bob", 200, "bye");
+        let messages = cursor.advance(vec![newer.clone(), new.clone(), old]);
+        assert_eq!(messages, [new, newer]);
+        assert_eq!(cursor.time, 200);
+    }
 }
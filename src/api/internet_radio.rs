@@ -1,7 +1,8 @@
 //! Internet Radio API endpoints.
 
 use crate::Client;
-use crate::data::InternetRadioStation;
+use crate::client::take_list;
+use crate::data::{InternetRadioStation, RadioStationId};
 use crate::error::Error;
 
 impl Client {
@@ -9,13 +10,9 @@ impl Client {
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getinternetradiostations/>
     pub async fn get_internet_radio_stations(&self) -> Result<Vec<InternetRadioStation>, Error> {
-        let data = self.get_response("getInternetRadioStations", &[]).await?;
-        let stations = data
-            .get("internetRadioStations")
-            .and_then(|v| v.get("internetRadioStation"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(stations)?)
+        let mut data = self.get_response("getInternetRadioStations", &[]).await?;
+        let stations = take_list(&mut data, "internetRadioStations", "internetRadioStation");
+        self.decode_list(stations)
     }
 
     /// Create a new internet radio station.
@@ -41,12 +38,17 @@ impl Client {
     /// See <https://opensubsonic.netlify.app/docs/endpoints/updateinternetradiostation/>
     pub async fn update_internet_radio_station(
         &self,
-        id: &str,
+        id: impl Into<RadioStationId>,
         stream_url: &str,
         name: &str,
         home_page_url: Option<&str>,
     ) -> Result<(), Error> {
-        let mut params = vec![("id", id), ("streamUrl", stream_url), ("name", name)];
+        let id: RadioStationId = id.into();
+        let mut params = vec![
+            ("id", id.as_str()),
+            ("streamUrl", stream_url),
+            ("name", name),
+        ];
         if let Some(hp) = home_page_url {
             params.push(("homepageUrl", hp));
         }
@@ -58,8 +60,12 @@ impl Client {
     /// Delete an internet radio station.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/deleteinternetradiostation/>
-    pub async fn delete_internet_radio_station(&self, id: &str) -> Result<(), Error> {
-        self.get_response("deleteInternetRadioStation", &[("id", id)])
+    pub async fn delete_internet_radio_station(
+        &self,
+        id: impl Into<RadioStationId>,
+    ) -> Result<(), Error> {
+        let id: RadioStationId = id.into();
+        self.get_response("deleteInternetRadioStation", &[("id", &id)])
             .await?;
         Ok(())
     }
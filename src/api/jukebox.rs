@@ -2,6 +2,7 @@
 
 use crate::Client;
 use crate::data::{JukeboxPlaylist, JukeboxStatus};
+use crate::diff::ListDiff;
 use crate::error::Error;
 
 /// Jukebox control action.
@@ -73,23 +74,178 @@ impl Client {
             params.push(("gain", g.to_string()));
         }
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let data = self.get_response("jukeboxControl", &param_refs).await?;
+        let mut data = self.get_response("jukeboxControl", &param_refs).await?;
 
         // The "get" action returns jukeboxPlaylist; all others return jukeboxStatus.
         if action == JukeboxAction::Get {
             let playlist = data
-                .get("jukeboxPlaylist")
+                .remove("jukeboxPlaylist")
                 .ok_or_else(|| Error::Parse("Missing 'jukeboxPlaylist' in response".into()))?;
-            Ok(JukeboxResult::Playlist(serde_json::from_value(
-                playlist.clone(),
-            )?))
+            Ok(JukeboxResult::Playlist(self.decode(playlist)?))
         } else {
             let status = data
-                .get("jukeboxStatus")
+                .remove("jukeboxStatus")
                 .ok_or_else(|| Error::Parse("Missing 'jukeboxStatus' in response".into()))?;
-            Ok(JukeboxResult::Status(serde_json::from_value(
-                status.clone(),
-            )?))
+            Ok(JukeboxResult::Status(self.decode(status)?))
         }
     }
 }
+
+/// High-level jukebox helper built on top of [`Client::jukebox_control`].
+///
+/// Obtain one via [`Client::jukebox`].
+#[derive(Debug, Clone, Copy)]
+pub struct JukeboxController<'a> {
+    client: &'a Client,
+}
+
+impl Client {
+    /// Get a [`JukeboxController`] for higher-level jukebox operations.
+    pub fn jukebox(&self) -> JukeboxController<'_> {
+        JukeboxController { client: self }
+    }
+}
+
+impl JukeboxController<'_> {
+    /// Get the current jukebox playlist (the `get` action).
+    pub async fn get(&self) -> Result<JukeboxPlaylist, Error> {
+        match self
+            .client
+            .jukebox_control(JukeboxAction::Get, None, None, &[], None)
+            .await?
+        {
+            JukeboxResult::Playlist(playlist) => Ok(playlist),
+            JukeboxResult::Status(_) => Err(Error::Parse(
+                "Expected 'jukeboxPlaylist' for the get action".into(),
+            )),
+        }
+    }
+
+    /// Replace the jukebox queue with `ids`, touching as little of it as possible.
+    ///
+    /// The current queue is fetched and compared to the desired one. While a track is
+    /// playing and would survive the update, only the entries that differ are removed and
+    /// the missing tail is appended, so playback continues without the audible gap caused
+    /// by a naive clear-and-re-add. Otherwise a single `set` action is issued.
+    ///
+    /// Returns the jukebox status after the last applied action.
+    pub async fn set_queue(&self, ids: &[&str]) -> Result<JukeboxStatus, Error> {
+        let playlist = self.get().await?;
+        let current: Vec<&str> = playlist.entry.iter().map(|c| c.id.as_str()).collect();
+        let plan = QueuePlan::compute(
+            &current,
+            playlist.status.current_index,
+            playlist.status.playing,
+            ids,
+        );
+
+        let mut status = playlist.status;
+        match plan {
+            QueuePlan::Unchanged => {}
+            QueuePlan::Set => {
+                status = self.status_of(JukeboxAction::Set, None, ids).await?;
+            }
+            QueuePlan::Patch { remove, append } => {
+                for index in remove {
+                    status = self
+                        .status_of(JukeboxAction::Remove, Some(index as i32), &[])
+                        .await?;
+                }
+                if !append.is_empty() {
+                    status = self.status_of(JukeboxAction::Add, None, &append).await?;
+                }
+            }
+        }
+        Ok(status)
+    }
+
+    /// Run an action that is expected to return a [`JukeboxStatus`].
+    async fn status_of(
+        &self,
+        action: JukeboxAction,
+        index: Option<i32>,
+        ids: &[&str],
+    ) -> Result<JukeboxStatus, Error> {
+        match self
+            .client
+            .jukebox_control(action, index, None, ids, None)
+            .await?
+        {
+            JukeboxResult::Status(status) => Ok(status),
+            JukeboxResult::Playlist(playlist) => Ok(playlist.status),
+        }
+    }
+}
+
+/// The sequence of jukebox actions needed to turn one queue into another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum QueuePlan<'a> {
+    /// The queue already matches.
+    Unchanged,
+    /// Replace the whole queue with a single `set` action.
+    Set,
+    /// Remove entries (indexes in descending order), then append the remaining ids.
+    Patch {
+        remove: Vec<usize>,
+        append: Vec<&'a str>,
+    },
+}
+
+impl<'a> QueuePlan<'a> {
+    fn compute(current: &[&str], current_index: i32, playing: bool, desired: &[&'a str]) -> Self {
+        if current == desired {
+            return Self::Unchanged;
+        }
+        if !playing {
+            return Self::Set;
+        }
+
+        let diff = ListDiff::compute(current, desired);
+
+        // If the playing track would be dropped anyway there is no gap to avoid.
+        let playing_kept = usize::try_from(current_index).is_ok_and(|i| diff.keeps(i));
+        if !playing_kept {
+            return Self::Set;
+        }
+
+        Self::Patch {
+            remove: diff.removed().rev().collect(),
+            append: diff.append,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_queue_is_unchanged() {
+        let plan = QueuePlan::compute(&["a", "b"], 0, true, &["a", "b"]);
+        assert_eq!(plan, QueuePlan::Unchanged);
+    }
+
+    #[test]
+    fn stopped_jukebox_uses_set() {
+        let plan = QueuePlan::compute(&["a", "b"], 0, false, &["a", "c"]);
+        assert_eq!(plan, QueuePlan::Set);
+    }
+
+    #[test]
+    fn playing_track_is_preserved() {
+        let plan = QueuePlan::compute(&["x", "a", "y", "b"], 1, true, &["a", "b", "c", "d"]);
+        assert_eq!(
+            plan,
+            QueuePlan::Patch {
+                remove: vec![2, 0],
+                append: vec!["c", "d"],
+            }
+        );
+    }
+
+    #[test]
+    fn dropped_playing_track_falls_back_to_set() {
+        let plan = QueuePlan::compute(&["a", "b"], 1, true, &["a", "c"]);
+        assert_eq!(plan, QueuePlan::Set);
+    }
+}
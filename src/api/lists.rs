@@ -1,11 +1,29 @@
 //! Lists API endpoints.
 
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
+
+use futures_util::{Stream, TryStreamExt, stream};
+
 use crate::Client;
-use crate::data::{AlbumId3, ArtistId3, Child, NowPlayingEntry};
+use crate::client::take_list;
+use crate::data::{AlbumId3, Artist, ArtistId3, Child, NowPlayingEntry, SongId, Timestamp};
 use crate::error::Error;
 
+/// Page size used by [`Client::album_list2_stream`] when none is set, and by
+/// [`Client::songs_by_genre_stream`].
+const DEFAULT_PAGE_SIZE: i32 = 500;
+/// Songs fetched per `getRandomSongs` request by [`Client::random_songs_stream`] when no size
+/// is set.
+const DEFAULT_RANDOM_BATCH: i32 = 50;
+/// Number of recently emitted songs [`Client::random_songs_stream`] does not repeat.
+const RANDOM_HISTORY: usize = 1000;
+
 /// Album list ordering type.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// [`ByYear`](Self::ByYear) and [`ByGenre`](Self::ByGenre) carry the parameters the server
+/// requires for them.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AlbumListType {
     Random,
     Newest,
@@ -15,12 +33,19 @@ pub enum AlbumListType {
     AlphabeticalByName,
     AlphabeticalByArtist,
     Starred,
-    ByYear,
-    ByGenre,
+    /// Albums released in the range of years, in reverse order if `from` is after `to`.
+    ByYear {
+        from: i32,
+        to: i32,
+    },
+    /// Albums of the genre.
+    ByGenre {
+        genre: String,
+    },
 }
 
 impl AlbumListType {
-    fn as_str(self) -> &'static str {
+    fn as_str(&self) -> &'static str {
         match self {
             Self::Random => "random",
             Self::Newest => "newest",
@@ -30,9 +55,153 @@ impl AlbumListType {
             Self::AlphabeticalByName => "alphabeticalByName",
             Self::AlphabeticalByArtist => "alphabeticalByArtist",
             Self::Starred => "starred",
-            Self::ByYear => "byYear",
-            Self::ByGenre => "byGenre",
+            Self::ByYear { .. } => "byYear",
+            Self::ByGenre { .. } => "byGenre",
+        }
+    }
+
+    fn push_params(&self, params: &mut Vec<(&'static str, String)>) {
+        params.push(("type", self.as_str().to_string()));
+        match self {
+            Self::ByYear { from, to } => {
+                params.push(("fromYear", from.to_string()));
+                params.push(("toYear", to.to_string()));
+            }
+            Self::ByGenre { genre } => params.push(("genre", genre.clone())),
+            _ => {}
+        }
+    }
+}
+
+/// Optional parameters for `getAlbumList` and `getAlbumList2`, used by
+/// [`Client::get_album_list_with`], [`Client::get_album_list2_with`] and
+/// [`Client::album_list2_stream`].
+///
+/// ```
+/// use opensubsonic::AlbumListParams;
+///
+/// let params = AlbumListParams::new().size(50).music_folder_id("1");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AlbumListParams {
+    size: Option<i32>,
+    offset: Option<i32>,
+    music_folder_id: Option<String>,
+}
+
+impl AlbumListParams {
+    /// Create empty parameters (server defaults for everything).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of albums per request (the server caps this, usually at 500).
+    #[must_use]
+    pub fn size(mut self, size: i32) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Offset of the first album to return.
+    #[must_use]
+    pub fn offset(mut self, offset: i32) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Only return albums in the given music folder.
+    #[must_use]
+    pub fn music_folder_id(mut self, id: &str) -> Self {
+        self.music_folder_id = Some(id.to_owned());
+        self
+    }
+
+    fn to_params(&self, list_type: &AlbumListType) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        list_type.push_params(&mut params);
+        if let Some(s) = self.size {
+            params.push(("size", s.to_string()));
+        }
+        if let Some(o) = self.offset {
+            params.push(("offset", o.to_string()));
+        }
+        if let Some(id) = &self.music_folder_id {
+            params.push(("musicFolderId", id.clone()));
+        }
+        params
+    }
+}
+
+/// Filters for `getRandomSongs`, used by [`Client::get_random_songs_with`] and
+/// [`Client::random_songs_stream`].
+///
+/// ```
+/// use opensubsonic::RandomSongsParams;
+///
+/// let params = RandomSongsParams::new().genre("Rock").years(1970, 1979);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RandomSongsParams {
+    size: Option<i32>,
+    genre: Option<String>,
+    from_year: Option<i32>,
+    to_year: Option<i32>,
+    music_folder_id: Option<String>,
+}
+
+impl RandomSongsParams {
+    /// Create empty parameters (any song in the library).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of songs per request (the server caps this, usually at 500).
+    #[must_use]
+    pub fn size(mut self, size: i32) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Only return songs of the given genre.
+    #[must_use]
+    pub fn genre(mut self, genre: &str) -> Self {
+        self.genre = Some(genre.to_owned());
+        self
+    }
+
+    /// Only return songs released in the range of years.
+    #[must_use]
+    pub fn years(mut self, from: i32, to: i32) -> Self {
+        self.from_year = Some(from);
+        self.to_year = Some(to);
+        self
+    }
+
+    /// Only return songs in the given music folder.
+    #[must_use]
+    pub fn music_folder_id(mut self, id: &str) -> Self {
+        self.music_folder_id = Some(id.to_owned());
+        self
+    }
+
+    fn to_params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        if let Some(s) = self.size {
+            params.push(("size", s.to_string()));
         }
+        if let Some(g) = &self.genre {
+            params.push(("genre", g.clone()));
+        }
+        if let Some(y) = self.from_year {
+            params.push(("fromYear", y.to_string()));
+        }
+        if let Some(y) = self.to_year {
+            params.push(("toYear", y.to_string()));
+        }
+        if let Some(id) = &self.music_folder_id {
+            params.push(("musicFolderId", id.clone()));
+        }
+        params
     }
 }
 
@@ -40,87 +209,113 @@ impl Client {
     /// Get a list of albums (folder-based).
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getalbumlist/>
-    #[allow(clippy::too_many_arguments)]
     pub async fn get_album_list(
         &self,
         list_type: AlbumListType,
         size: Option<i32>,
         offset: Option<i32>,
-        from_year: Option<i32>,
-        to_year: Option<i32>,
-        genre: Option<&str>,
         music_folder_id: Option<&str>,
     ) -> Result<Vec<Child>, Error> {
-        let mut params = vec![("type", list_type.as_str().to_string())];
-        if let Some(s) = size {
-            params.push(("size", s.to_string()));
-        }
-        if let Some(o) = offset {
-            params.push(("offset", o.to_string()));
-        }
-        if let Some(y) = from_year {
-            params.push(("fromYear", y.to_string()));
-        }
-        if let Some(y) = to_year {
-            params.push(("toYear", y.to_string()));
-        }
-        if let Some(g) = genre {
-            params.push(("genre", g.to_string()));
-        }
-        if let Some(id) = music_folder_id {
-            params.push(("musicFolderId", id.to_string()));
-        }
+        let params = AlbumListParams {
+            size,
+            offset,
+            music_folder_id: music_folder_id.map(str::to_owned),
+        };
+        self.get_album_list_with(list_type, &params).await
+    }
+
+    /// Get a list of albums (folder-based) with the given [`AlbumListParams`].
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/getalbumlist/>
+    pub async fn get_album_list_with(
+        &self,
+        list_type: AlbumListType,
+        params: &AlbumListParams,
+    ) -> Result<Vec<Child>, Error> {
+        let params = params.to_params(&list_type);
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let data = self.get_response("getAlbumList", &param_refs).await?;
-        let albums = data
-            .get("albumList")
-            .and_then(|v| v.get("album"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(albums)?)
+        let mut data = self.get_response("getAlbumList", &param_refs).await?;
+        let albums = take_list(&mut data, "albumList", "album");
+        self.decode_list(albums)
     }
 
     /// Get a list of albums (ID3-based).
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getalbumlist2/>
-    #[allow(clippy::too_many_arguments)]
     pub async fn get_album_list2(
         &self,
         list_type: AlbumListType,
         size: Option<i32>,
         offset: Option<i32>,
-        from_year: Option<i32>,
-        to_year: Option<i32>,
-        genre: Option<&str>,
         music_folder_id: Option<&str>,
     ) -> Result<Vec<AlbumId3>, Error> {
-        let mut params = vec![("type", list_type.as_str().to_string())];
-        if let Some(s) = size {
-            params.push(("size", s.to_string()));
-        }
-        if let Some(o) = offset {
-            params.push(("offset", o.to_string()));
-        }
-        if let Some(y) = from_year {
-            params.push(("fromYear", y.to_string()));
-        }
-        if let Some(y) = to_year {
-            params.push(("toYear", y.to_string()));
-        }
-        if let Some(g) = genre {
-            params.push(("genre", g.to_string()));
-        }
-        if let Some(id) = music_folder_id {
-            params.push(("musicFolderId", id.to_string()));
-        }
+        let params = AlbumListParams {
+            size,
+            offset,
+            music_folder_id: music_folder_id.map(str::to_owned),
+        };
+        self.get_album_list2_with(list_type, &params).await
+    }
+
+    /// Get a list of albums (ID3-based) with the given [`AlbumListParams`].
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/getalbumlist2/>
+    pub async fn get_album_list2_with(
+        &self,
+        list_type: AlbumListType,
+        params: &AlbumListParams,
+    ) -> Result<Vec<AlbumId3>, Error> {
+        let params = params.to_params(&list_type);
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let data = self.get_response("getAlbumList2", &param_refs).await?;
-        let albums = data
-            .get("albumList2")
-            .and_then(|v| v.get("album"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(albums)?)
+        let mut data = self.get_response("getAlbumList2", &param_refs).await?;
+        let albums = take_list(&mut data, "albumList2", "album");
+        self.decode_list(albums)
+    }
+
+    /// Stream every album of an ID3-based album list, page by page.
+    ///
+    /// Successive `getAlbumList2` requests are issued lazily, starting at the offset in
+    /// `params` and using its `size` as the page size (500 by default), until the server
+    /// returns an empty page. Note that [`AlbumListType::Random`] never runs out of pages.
+    ///
+    /// ```no_run
+    /// # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+    /// use futures_util::TryStreamExt;
+    /// use opensubsonic::{AlbumListParams, AlbumListType};
+    ///
+    /// let albums: Vec<_> = client
+    ///     .album_list2_stream(AlbumListType::AlphabeticalByName, AlbumListParams::new())
+    ///     .try_collect()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn album_list2_stream(
+        &self,
+        list_type: AlbumListType,
+        params: AlbumListParams,
+    ) -> impl Stream<Item = Result<AlbumId3, Error>> + '_ {
+        let size = params.size.unwrap_or(DEFAULT_PAGE_SIZE);
+        let start = params.offset.unwrap_or(0);
+        stream::try_unfold(Some(start), move |offset| {
+            let page = AlbumListParams {
+                size: Some(size),
+                offset,
+                ..params.clone()
+            };
+            let list_type = list_type.clone();
+            async move {
+                let Some(offset) = page.offset else {
+                    return Ok::<_, Error>(None);
+                };
+                let albums = self.get_album_list2_with(list_type, &page).await?;
+                // Advance by what was returned: the server may cap the page size.
+                let next = (!albums.is_empty()).then(|| offset + albums.len() as i32);
+                Ok(Some((albums, next)))
+            }
+        })
+        .map_ok(|albums| stream::iter(albums.into_iter().map(Ok)))
+        .try_flatten()
     }
 
     /// Get random songs.
@@ -134,30 +329,85 @@ impl Client {
         to_year: Option<i32>,
         music_folder_id: Option<&str>,
     ) -> Result<Vec<Child>, Error> {
-        let mut params = Vec::new();
-        if let Some(s) = size {
-            params.push(("size", s.to_string()));
-        }
-        if let Some(g) = genre {
-            params.push(("genre", g.to_string()));
-        }
-        if let Some(y) = from_year {
-            params.push(("fromYear", y.to_string()));
-        }
-        if let Some(y) = to_year {
-            params.push(("toYear", y.to_string()));
-        }
-        if let Some(id) = music_folder_id {
-            params.push(("musicFolderId", id.to_string()));
-        }
+        let params = RandomSongsParams {
+            size,
+            genre: genre.map(str::to_owned),
+            from_year,
+            to_year,
+            music_folder_id: music_folder_id.map(str::to_owned),
+        };
+        self.get_random_songs_with(&params).await
+    }
+
+    /// Get random songs matching the given [`RandomSongsParams`].
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/getrandomsongs/>
+    pub async fn get_random_songs_with(
+        &self,
+        params: &RandomSongsParams,
+    ) -> Result<Vec<Child>, Error> {
+        let params = params.to_params();
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let data = self.get_response("getRandomSongs", &param_refs).await?;
-        let songs = data
-            .get("randomSongs")
-            .and_then(|v| v.get("song"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(songs)?)
+        let mut data = self.get_response("getRandomSongs", &param_refs).await?;
+        let songs = take_list(&mut data, "randomSongs", "song");
+        self.decode_list(songs)
+    }
+
+    /// Stream random songs without end, for "shuffle my library" playback.
+    ///
+    /// `getRandomSongs` is called again whenever the songs of the previous request have been
+    /// consumed, fetching `size` songs at a time (50 by default). Songs among the last 1000
+    /// emitted are skipped; once a request brings nothing new, the library (or the part of
+    /// it matching the filters) has been played through and songs may repeat. The stream
+    /// ends only if no song matches the filters, or after an error.
+    ///
+    /// ```no_run
+    /// # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+    /// use futures_util::TryStreamExt;
+    /// use opensubsonic::RandomSongsParams;
+    ///
+    /// let mut queue = std::pin::pin!(client.random_songs_stream(RandomSongsParams::new()));
+    /// while let Some(song) = queue.try_next().await? {
+    ///     println!("{song}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn random_songs_stream(
+        &self,
+        params: RandomSongsParams,
+    ) -> impl Stream<Item = Result<Child, Error>> + '_ {
+        let params = RandomSongsParams {
+            size: Some(params.size.unwrap_or(DEFAULT_RANDOM_BATCH)),
+            ..params
+        };
+        let recent = RecentIds::new(RANDOM_HISTORY);
+        stream::try_unfold(Some(recent), move |recent| {
+            let params = params.clone();
+            async move {
+                let Some(mut recent) = recent else {
+                    return Ok::<_, Error>(None);
+                };
+                let songs = self.get_random_songs_with(&params).await?;
+                if songs.is_empty() {
+                    return Ok(Some((Vec::new(), None)));
+                }
+                let mut fresh: Vec<Child> = songs
+                    .iter()
+                    .filter(|s| !recent.contains(&s.id))
+                    .cloned()
+                    .collect();
+                if fresh.is_empty() {
+                    // Everything was played recently: start over.
+                    recent.clear();
+                    fresh = songs;
+                }
+                fresh.retain(|song| recent.insert(song.id.clone()));
+                Ok(Some((fresh, Some(recent))))
+            }
+        })
+        .map_ok(|songs| stream::iter(songs.into_iter().map(Ok)))
+        .try_flatten()
     }
 
     /// Get songs by genre.
@@ -181,26 +431,109 @@ impl Client {
             params.push(("musicFolderId", id.to_string()));
         }
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let data = self.get_response("getSongsByGenre", &param_refs).await?;
-        let songs = data
-            .get("songsByGenre")
-            .and_then(|v| v.get("song"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(songs)?)
+        let mut data = self.get_response("getSongsByGenre", &param_refs).await?;
+        let songs = take_list(&mut data, "songsByGenre", "song");
+        self.decode_list(songs)
+    }
+
+    /// Stream every song of a genre, page by page.
+    ///
+    /// Successive `getSongsByGenre` requests of 500 songs are issued lazily until the server
+    /// returns an empty page.
+    ///
+    /// ```no_run
+    /// # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+    /// use futures_util::TryStreamExt;
+    ///
+    /// let songs: Vec<_> = client.songs_by_genre_stream("Jazz", None).try_collect().await?;
+    /// println!("{} jazz songs", songs.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn songs_by_genre_stream<'a>(
+        &'a self,
+        genre: &'a str,
+        music_folder_id: Option<&'a str>,
+    ) -> impl Stream<Item = Result<Child, Error>> + 'a {
+        stream::try_unfold(Some(0), move |offset| async move {
+            let Some(offset) = offset else {
+                return Ok::<_, Error>(None);
+            };
+            let songs = self
+                .get_songs_by_genre(
+                    genre,
+                    Some(DEFAULT_PAGE_SIZE),
+                    Some(offset),
+                    music_folder_id,
+                )
+                .await?;
+            // Advance by what was returned: the server may cap the page size.
+            let next = (!songs.is_empty()).then(|| offset + songs.len() as i32);
+            Ok(Some((songs, next)))
+        })
+        .map_ok(|songs| stream::iter(songs.into_iter().map(Ok)))
+        .try_flatten()
     }
 
     /// Get what is currently being played by all users.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getnowplaying/>
     pub async fn get_now_playing(&self) -> Result<Vec<NowPlayingEntry>, Error> {
-        let data = self.get_response("getNowPlaying", &[]).await?;
-        let entries = data
-            .get("nowPlaying")
-            .and_then(|v| v.get("entry"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(entries)?)
+        let mut data = self.get_response("getNowPlaying", &[]).await?;
+        let entries = take_list(&mut data, "nowPlaying", "entry");
+        self.decode_list(entries)
+    }
+
+    /// Poll `getNowPlaying` every `interval`, yielding only what changed.
+    ///
+    /// Entries are identified by username and song ID. The first poll reports everything
+    /// currently playing as [`NowPlayingChange::Started`]. Failed polls yield the error and
+    /// polling continues, so the stream never ends on its own; drop it to stop.
+    ///
+    /// ```no_run
+    /// # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+    /// use std::time::Duration;
+    ///
+    /// use futures_util::TryStreamExt;
+    /// use opensubsonic::NowPlayingChange;
+    ///
+    /// let mut changes = std::pin::pin!(client.now_playing_stream(Duration::from_secs(15)));
+    /// while let Some(change) = changes.try_next().await? {
+    ///     match change {
+    ///         NowPlayingChange::Started(e) => println!("{:?} started {}", e.username, e.child.title),
+    ///         NowPlayingChange::Stopped(e) => println!("{:?} stopped {}", e.username, e.child.title),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn now_playing_stream(
+        &self,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<NowPlayingChange, Error>> + '_ {
+        let state = NowPlayingState {
+            playing: HashMap::new(),
+            pending: VecDeque::new(),
+            polled: false,
+        };
+        stream::unfold(state, move |mut state| async move {
+            loop {
+                if let Some(item) = state.pending.pop_front() {
+                    return Some((item, state));
+                }
+                if state.polled {
+                    tokio::time::sleep(interval).await;
+                }
+                state.polled = true;
+                match self.get_now_playing().await {
+                    Ok(entries) => {
+                        let changes = diff_now_playing(&mut state.playing, entries);
+                        state.pending.extend(changes.into_iter().map(Ok));
+                    }
+                    Err(e) => state.pending.push_back(Err(e)),
+                }
+            }
+        })
     }
 
     /// Get starred songs, albums and artists (folder-based).
@@ -214,11 +547,11 @@ impl Client {
         if let Some(id) = music_folder_id {
             params.push(("musicFolderId", id));
         }
-        let data = self.get_response("getStarred", &params).await?;
+        let mut data = self.get_response("getStarred", &params).await?;
         let starred = data
-            .get("starred")
+            .remove("starred")
             .ok_or_else(|| Error::Parse("Missing 'starred' in response".into()))?;
-        Ok(serde_json::from_value(starred.clone())?)
+        self.decode(starred)
     }
 
     /// Get starred songs, albums and artists (ID3-based).
@@ -232,26 +565,70 @@ impl Client {
         if let Some(id) = music_folder_id {
             params.push(("musicFolderId", id));
         }
-        let data = self.get_response("getStarred2", &params).await?;
-        let starred = data
-            .get("starred2")
-            .ok_or_else(|| Error::Parse("Missing 'starred2' in response".into()))?;
-        Ok(serde_json::from_value(starred.clone())?)
+        self.get_typed("getStarred2", &params, "starred2")
+            .await?
+            .ok_or_else(|| Error::Parse("Missing 'starred2' in response".into()))
     }
 }
 
+/// A change reported by [`Client::now_playing_stream`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum NowPlayingChange {
+    /// A user started playing a song.
+    Started(NowPlayingEntry),
+    /// A user is no longer playing a song; the entry is the last one seen.
+    Stopped(NowPlayingEntry),
+}
+
+/// Identifies a now-playing entry: username and song ID.
+type NowPlayingKey = (Option<String>, SongId);
+
+struct NowPlayingState {
+    playing: HashMap<NowPlayingKey, NowPlayingEntry>,
+    pending: VecDeque<Result<NowPlayingChange, Error>>,
+    polled: bool,
+}
+
+/// Replace `playing` with `entries`, returning the stopped entries followed by the started
+/// ones.
+fn diff_now_playing(
+    playing: &mut HashMap<NowPlayingKey, NowPlayingEntry>,
+    entries: Vec<NowPlayingEntry>,
+) -> Vec<NowPlayingChange> {
+    let current: Vec<(NowPlayingKey, NowPlayingEntry)> = entries
+        .into_iter()
+        .map(|e| ((e.username.clone(), e.child.id.clone()), e))
+        .collect();
+    let mut changes = Vec::new();
+    playing.retain(|key, entry| {
+        let still_playing = current.iter().any(|(k, _)| k == key);
+        if !still_playing {
+            changes.push(NowPlayingChange::Stopped(entry.clone()));
+        }
+        still_playing
+    });
+    for (key, entry) in current {
+        if !playing.contains_key(&key) {
+            changes.push(NowPlayingChange::Started(entry.clone()));
+        }
+        // Keep the latest version (position, minutes ago) of every entry.
+        playing.insert(key, entry);
+    }
+    changes
+}
+
 /// Starred content (folder-based).
 #[derive(Debug, Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StarredContent {
     /// Starred artists.
-    #[serde(default)]
-    pub artist: Vec<crate::data::Artist>,
+    #[serde(default, deserialize_with = "crate::data::de::one_or_many")]
+    pub artist: Vec<Artist>,
     /// Starred albums (as Child).
-    #[serde(default)]
+    #[serde(default, deserialize_with = "crate::data::de::one_or_many")]
     pub album: Vec<Child>,
     /// Starred songs.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "crate::data::de::one_or_many")]
     pub song: Vec<Child>,
 }
 
@@ -260,12 +637,376 @@ pub struct StarredContent {
 #[serde(rename_all = "camelCase")]
 pub struct Starred2Content {
     /// Starred artists (ID3).
-    #[serde(default)]
+    #[serde(default, deserialize_with = "crate::data::de::one_or_many")]
     pub artist: Vec<ArtistId3>,
     /// Starred albums (ID3).
-    #[serde(default)]
+    #[serde(default, deserialize_with = "crate::data::de::one_or_many")]
     pub album: Vec<AlbumId3>,
     /// Starred songs.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "crate::data::de::one_or_many")]
     pub song: Vec<Child>,
 }
+
+/// One starred artist, album or song, as yielded by iterating over [`Starred2Content`]
+/// (ID3 types) or [`StarredContent`] (folder-based types, with albums as [`Child`]).
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub enum StarredItem<Ar = ArtistId3, Al = AlbumId3> {
+    /// A starred artist.
+    Artist(Ar),
+    /// A starred album.
+    Album(Al),
+    /// A starred song.
+    Song(Child),
+}
+
+impl<Ar: StarDate, Al: StarDate> StarredItem<Ar, Al> {
+    /// When the item was starred.
+    pub fn starred(&self) -> Option<&Timestamp> {
+        match self {
+            Self::Artist(a) => a.star_date(),
+            Self::Album(a) => a.star_date(),
+            Self::Song(s) => s.star_date(),
+        }
+    }
+}
+
+/// The starred items a [`StarredItem`] can hold: artists, albums and songs.
+pub trait StarDate {
+    /// When the item was starred.
+    fn star_date(&self) -> Option<&Timestamp>;
+}
+
+macro_rules! star_date {
+    ($($ty:ty),*) => {
+        $(
+            impl StarDate for $ty {
+                fn star_date(&self) -> Option<&Timestamp> {
+                    self.starred.as_ref()
+                }
+            }
+        )*
+    };
+}
+
+star_date!(Artist, ArtistId3, AlbumId3, Child);
+
+/// Iterator over starred content: the artists, then the albums, then the songs.
+#[derive(Debug)]
+pub struct StarredIter<Ar = ArtistId3, Al = AlbumId3> {
+    artists: std::vec::IntoIter<Ar>,
+    albums: std::vec::IntoIter<Al>,
+    songs: std::vec::IntoIter<Child>,
+}
+
+impl<Ar, Al> Iterator for StarredIter<Ar, Al> {
+    type Item = StarredItem<Ar, Al>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.artists
+            .next()
+            .map(StarredItem::Artist)
+            .or_else(|| self.albums.next().map(StarredItem::Album))
+            .or_else(|| self.songs.next().map(StarredItem::Song))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl<Ar, Al> ExactSizeIterator for StarredIter<Ar, Al> {
+    fn len(&self) -> usize {
+        self.artists.len() + self.albums.len() + self.songs.len()
+    }
+}
+
+impl IntoIterator for StarredContent {
+    type Item = StarredItem<Artist, Child>;
+    type IntoIter = StarredIter<Artist, Child>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        StarredIter {
+            artists: self.artist.into_iter(),
+            albums: self.album.into_iter(),
+            songs: self.song.into_iter(),
+        }
+    }
+}
+
+impl IntoIterator for Starred2Content {
+    type Item = StarredItem;
+    type IntoIter = StarredIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        StarredIter {
+            artists: self.artist.into_iter(),
+            albums: self.album.into_iter(),
+            songs: self.song.into_iter(),
+        }
+    }
+}
+
+impl StarredContent {
+    /// All starred items in one list, most recently starred first (see
+    /// [`Starred2Content::by_star_date`]).
+    pub fn by_star_date(self) -> Vec<StarredItem<Artist, Child>> {
+        by_star_date(self)
+    }
+}
+
+impl Starred2Content {
+    /// All starred items in one list, most recently starred first.
+    ///
+    /// Dates are compared by the time they denote; dates in an unrecognised format come
+    /// after the others, ordered by their text, and items without a date come last.
+    pub fn by_star_date(self) -> Vec<StarredItem> {
+        by_star_date(self)
+    }
+}
+
+fn by_star_date<Ar: StarDate, Al: StarDate>(
+    items: impl IntoIterator<Item = StarredItem<Ar, Al>>,
+) -> Vec<StarredItem<Ar, Al>> {
+    let mut items: Vec<_> = items.into_iter().collect();
+    items.sort_by(|a, b| match (a.starred(), b.starred()) {
+        (Some(a), Some(b)) => b.cmp_chronological(a),
+        (a, b) => a.is_none().cmp(&b.is_none()),
+    });
+    items
+}
+
+/// The IDs of the last songs emitted, oldest first, up to a limit.
+struct RecentIds {
+    order: VecDeque<SongId>,
+    ids: HashSet<SongId>,
+    limit: usize,
+}
+
+impl RecentIds {
+    fn new(limit: usize) -> Self {
+        Self {
+            order: VecDeque::new(),
+            ids: HashSet::new(),
+            limit,
+        }
+    }
+
+    fn contains(&self, id: &SongId) -> bool {
+        self.ids.contains(id)
+    }
+
+    /// Remember `id`, forgetting the oldest ID past the limit. Returns `false` if `id` was
+    /// already remembered.
+    fn insert(&mut self, id: SongId) -> bool {
+        if !self.ids.insert(id.clone()) {
+            return false;
+        }
+        self.order.push_back(id);
+        if self.order.len() > self.limit {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    fn clear(&mut self) {
+        self.order.clear();
+        self.ids.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn album_list_type_carries_required_params() {
+        let params = AlbumListParams::new()
+            .size(10)
+            .to_params(&AlbumListType::ByYear {
+                from: 1970,
+                to: 1979,
+            });
+        assert_eq!(
+            params,
+            [
+                ("type", "byYear".to_string()),
+                ("fromYear", "1970".to_string()),
+                ("toYear", "1979".to_string()),
+                ("size", "10".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn recent_ids_forget_the_oldest() {
+        let mut recent = RecentIds::new(2);
+        assert!(recent.insert("a".into()));
+        assert!(!recent.insert("a".into()));
+        assert!(recent.insert("b".into()));
+        assert!(recent.insert("c".into()));
+        assert!(!recent.contains(&"a".into()));
+        assert!(recent.contains(&"b".into()));
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn random_songs_stream_refills() {
+        use futures_util::StreamExt;
+
+        let server = crate::testing::MockServer::start().await;
+        let client = server.client();
+        let songs: Vec<_> = client
+            .random_songs_stream(RandomSongsParams::new().genre("Rock"))
+            .take(3)
+            .collect()
+            .await;
+        assert!(
+            songs
+                .iter()
+                .all(|s| s.as_ref().is_ok_and(|s| s.id == "tr-1"))
+        );
+        assert_eq!(server.calls("getRandomSongs").await, 3);
+
+        server
+            .respond_with("getRandomSongs", serde_json::json!({ "randomSongs": {} }))
+            .await;
+        let songs: Vec<_> = client
+            .random_songs_stream(RandomSongsParams::new())
+            .collect()
+            .await;
+        assert!(songs.is_empty());
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn songs_by_genre_stream_pages_until_empty() {
+        use futures_util::TryStreamExt;
+
+        let server = crate::testing::MockServer::start().await;
+        let client = server.client();
+        wiremock::Mock::given(wiremock::matchers::path("/rest/getSongsByGenre"))
+            .and(wiremock::matchers::query_param("offset", "1"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_json(
+                crate::testing::ok_envelope(serde_json::json!({ "songsByGenre": {} })),
+            ))
+            .mount(server.inner())
+            .await;
+        let songs: Vec<_> = client
+            .songs_by_genre_stream("Rock", None)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(songs.len(), 1);
+        assert_eq!(server.calls("getSongsByGenre").await, 2);
+    }
+
+    fn entry(username: &str, id: &str, minutes_ago: i64) -> NowPlayingEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "isDir": false,
+            "title": id,
+            "username": username,
+            "minutesAgo": minutes_ago,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn now_playing_diff_reports_only_changes() {
+        let mut playing = HashMap::new();
+        let changes = diff_now_playing(&mut playing, vec![entry("alice", "tr-1", 0)]);
+        assert_eq!(
+            changes,
+            [NowPlayingChange::Started(entry("alice", "tr-1", 0))]
+        );
+
+        // Same song still playing, another user starts the same song.
+        let changes = diff_now_playing(
+            &mut playing,
+            vec![entry("alice", "tr-1", 1), entry("bob", "tr-1", 0)],
+        );
+        assert_eq!(
+            changes,
+            [NowPlayingChange::Started(entry("bob", "tr-1", 0))]
+        );
+
+        let changes = diff_now_playing(&mut playing, vec![entry("bob", "tr-1", 1)]);
+        assert_eq!(
+            changes,
+            [NowPlayingChange::Stopped(entry("alice", "tr-1", 1))]
+        );
+    }
+
+    #[test]
+    fn starred_content_accepts_single_objects() {
+        let starred: Starred2Content = serde_json::from_value(serde_json::json!({
+            "artist": { "id": "ar-1", "name": "Artist" },
+            "album": { "id": "al-1", "name": "Album" },
+            "song": { "id": "tr-1", "isDir": false, "title": "Song" },
+        }))
+        .unwrap();
+        assert_eq!(
+            (
+                starred.artist.len(),
+                starred.album.len(),
+                starred.song.len()
+            ),
+            (1, 1, 1)
+        );
+        let starred: StarredContent = serde_json::from_value(serde_json::json!({
+            "artist": { "id": "ar-1", "name": "Artist" },
+            "song": [{ "id": "tr-1", "isDir": false, "title": "Song" }],
+        }))
+        .unwrap();
+        assert_eq!((starred.artist.len(), starred.song.len()), (1, 1));
+        assert!(starred.album.is_empty());
+    }
+
+    #[test]
+    fn starred_items_sort_by_star_date() {
+        let starred: Starred2Content = serde_json::from_value(serde_json::json!({
+            "artist": [{ "id": "ar-1", "name": "a", "starred": "2024-01-01T00:00:00Z" }],
+            "album": [{ "id": "al-1", "name": "b" }],
+            "song": [{ "id": "tr-1", "title": "c", "starred": "2024-06-01T00:00:00Z" }],
+        }))
+        .unwrap();
+        let kinds = |items: &[StarredItem]| {
+            items
+                .iter()
+                .map(|item| match item {
+                    StarredItem::Artist(_) => "artist",
+                    StarredItem::Album(_) => "album",
+                    StarredItem::Song(_) => "song",
+                })
+                .collect::<Vec<_>>()
+        };
+        let items: Vec<_> = starred.clone().into_iter().collect();
+        assert_eq!(kinds(&items), ["artist", "album", "song"]);
+        assert_eq!(kinds(&starred.by_star_date()), ["song", "artist", "album"]);
+
+        // Offsets and formats vary; the time they denote decides, not their text.
+        let starred: Starred2Content = serde_json::from_value(serde_json::json!({
+            "artist": [
+                { "id": "ar-1", "name": "a", "starred": "2024-01-01T10:00:00+02:00" },
+                { "id": "ar-2", "name": "b", "starred": "2024-01-01T09:00:00Z" },
+                { "id": "ar-3", "name": "c", "starred": "yesterday" },
+            ],
+            "song": [{ "id": "tr-1", "title": "c", "starred": "1704103200000" }],
+        }))
+        .unwrap();
+        let ids: Vec<String> = starred
+            .by_star_date()
+            .into_iter()
+            .map(|item| match item {
+                StarredItem::Artist(a) => a.id.to_string(),
+                StarredItem::Album(a) => a.id.to_string(),
+                StarredItem::Song(s) => s.id.to_string(),
+            })
+            .collect();
+        assert_eq!(ids, ["tr-1", "ar-2", "ar-1", "ar-3"]);
+    }
+}
@@ -1,9 +1,264 @@
 //! Media Annotation API endpoints.
 
+use std::collections::HashSet;
+
+use futures_util::{StreamExt, stream};
+
 use crate::Client;
+use crate::data::{AlbumId, ArtistId, SongId};
 use crate::error::Error;
 
+/// Default number of requests an [`Annotations`] batch sends at the same time.
+const DEFAULT_CONCURRENCY: usize = 4;
+/// Default number of items starred or unstarred per request, which keeps URLs short.
+const DEFAULT_BATCH_SIZE: usize = 50;
+
+/// A song, album or artist to star with [`Client::toggle_star`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum StarItem {
+    /// A song, video or directory (`id`).
+    Song(SongId),
+    /// An album (ID3, `albumId`).
+    Album(AlbumId),
+    /// An artist (ID3, `artistId`).
+    Artist(ArtistId),
+}
+
+impl From<SongId> for StarItem {
+    fn from(id: SongId) -> Self {
+        Self::Song(id)
+    }
+}
+
+impl From<AlbumId> for StarItem {
+    fn from(id: AlbumId) -> Self {
+        Self::Album(id)
+    }
+}
+
+impl From<ArtistId> for StarItem {
+    fn from(id: ArtistId) -> Self {
+        Self::Artist(id)
+    }
+}
+
+/// One operation of an [`Annotations`] batch.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Annotation {
+    /// Star an item.
+    Star(StarItem),
+    /// Unstar an item.
+    Unstar(StarItem),
+    /// Rate a song, album or artist from 1 to 5, or remove its rating with 0.
+    Rate {
+        /// ID of the rated item.
+        id: String,
+        /// The rating.
+        rating: i32,
+    },
+}
+
+impl Annotation {
+    /// What the operation applies to; later operations on the same target replace earlier
+    /// ones.
+    fn target(&self) -> Target<'_> {
+        match self {
+            Self::Star(item) | Self::Unstar(item) => Target::Star(item),
+            Self::Rate { id, .. } => Target::Rating(id),
+        }
+    }
+
+    /// Whether both operations can be sent in the same request.
+    fn same_request(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::Star(_), Self::Star(_)) | (Self::Unstar(_), Self::Unstar(_))
+        )
+    }
+}
+
+#[derive(PartialEq, Eq, Hash)]
+enum Target<'a> {
+    Star(&'a StarItem),
+    Rating(&'a str),
+}
+
+/// The outcome of [`Annotations::run`].
+#[derive(Debug, Default)]
+pub struct AnnotationReport {
+    /// Operations that were applied.
+    pub succeeded: Vec<Annotation>,
+    /// Operations that failed, with their error.
+    pub failed: Vec<(Annotation, Error)>,
+}
+
+impl AnnotationReport {
+    /// Whether every operation was applied.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Stars, unstars and ratings for many items, applied with few requests.
+///
+/// Obtained from [`Client::annotations`]. Stars and unstars are grouped into requests of up
+/// to [`batch_size`](Self::batch_size) items, ratings take one request each, and
+/// [`concurrency`](Self::concurrency) requests run at the same time. When a grouped request
+/// fails, its items are retried one by one, so the report says which ones failed. Only the
+/// last operation queued for an item is applied: starring then unstarring a song unstars it.
+///
+/// ```no_run
+/// # async fn example(client: &opensubsonic::Client) {
+/// use opensubsonic::data::SongId;
+///
+/// let report = client
+///     .annotations()
+///     .star(SongId::from("tr-1"))
+///     .star(SongId::from("tr-2"))
+///     .rate("al-1", 5)
+///     .run()
+///     .await;
+/// for (operation, error) in &report.failed {
+///     eprintln!("{operation:?}: {error}");
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Annotations<'a> {
+    client: &'a Client,
+    operations: Vec<Annotation>,
+    concurrency: usize,
+    batch_size: usize,
+}
+
+impl Annotations<'_> {
+    /// Star `item`.
+    #[must_use]
+    pub fn star(self, item: impl Into<StarItem>) -> Self {
+        self.push(Annotation::Star(item.into()))
+    }
+
+    /// Unstar `item`.
+    #[must_use]
+    pub fn unstar(self, item: impl Into<StarItem>) -> Self {
+        self.push(Annotation::Unstar(item.into()))
+    }
+
+    /// Rate the song, album or artist `id` (see [`Client::set_rating`]).
+    #[must_use]
+    pub fn rate(self, id: impl Into<String>, rating: i32) -> Self {
+        self.push(Annotation::Rate {
+            id: id.into(),
+            rating,
+        })
+    }
+
+    /// Queue an operation.
+    #[must_use]
+    pub fn push(mut self, operation: Annotation) -> Self {
+        self.operations.push(operation);
+        self
+    }
+
+    /// Number of requests sent at the same time (default 4, at least 1).
+    #[must_use]
+    pub fn concurrency(mut self, limit: usize) -> Self {
+        self.concurrency = limit.max(1);
+        self
+    }
+
+    /// Maximum number of items per `star` or `unstar` request (default 50, at least 1).
+    #[must_use]
+    pub fn batch_size(mut self, items: usize) -> Self {
+        self.batch_size = items.max(1);
+        self
+    }
+
+    /// Apply the queued operations, returning once every request has finished.
+    pub async fn run(&self) -> AnnotationReport {
+        let mut seen = HashSet::new();
+        let mut operations: Vec<&Annotation> = self
+            .operations
+            .iter()
+            .rev()
+            .filter(|op| seen.insert(op.target()))
+            .collect();
+        operations.reverse();
+
+        let mut requests: Vec<Vec<Annotation>> = Vec::new();
+        for op in operations {
+            match requests
+                .iter_mut()
+                .find(|r| r[0].same_request(op) && r.len() < self.batch_size)
+            {
+                Some(request) => request.push(op.clone()),
+                None => requests.push(vec![op.clone()]),
+            }
+        }
+
+        let mut report = AnnotationReport::default();
+        let mut outcomes = stream::iter(requests)
+            .map(|request| self.apply(request))
+            .buffer_unordered(self.concurrency);
+        while let Some(outcome) = outcomes.next().await {
+            for (op, result) in outcome {
+                match result {
+                    Ok(()) => report.succeeded.push(op),
+                    Err(e) => report.failed.push((op, e)),
+                }
+            }
+        }
+        report
+    }
+
+    /// Send one request, retrying its items one by one if it fails.
+    async fn apply(&self, request: Vec<Annotation>) -> Vec<(Annotation, Result<(), Error>)> {
+        match self.send(&request).await {
+            Ok(()) => return request.into_iter().map(|op| (op, Ok(()))).collect(),
+            Err(e) if request.len() == 1 => return request.into_iter().zip([Err(e)]).collect(),
+            Err(_) => {}
+        }
+        let mut outcome = Vec::with_capacity(request.len());
+        for op in request {
+            let result = self.send(std::slice::from_ref(&op)).await;
+            outcome.push((op, result));
+        }
+        outcome
+    }
+
+    /// Send operations that [`Annotation::same_request`] allows together.
+    async fn send(&self, request: &[Annotation]) -> Result<(), Error> {
+        let (mut ids, mut album_ids, mut artist_ids) = (Vec::new(), Vec::new(), Vec::new());
+        for op in request {
+            match op {
+                Annotation::Star(item) | Annotation::Unstar(item) => match item {
+                    StarItem::Song(id) => ids.push(id.as_str()),
+                    StarItem::Album(id) => album_ids.push(id.as_str()),
+                    StarItem::Artist(id) => artist_ids.push(id.as_str()),
+                },
+                Annotation::Rate { id, rating } => {
+                    return self.client.set_rating(id, *rating).await;
+                }
+            }
+        }
+        match request.first() {
+            Some(Annotation::Unstar(_)) => self.client.unstar(&ids, &album_ids, &artist_ids).await,
+            _ => self.client.star(&ids, &album_ids, &artist_ids).await,
+        }
+    }
+}
+
 impl Client {
+    /// Start an [`Annotations`] batch of stars, unstars and ratings.
+    pub fn annotations(&self) -> Annotations<'_> {
+        Annotations {
+            client: self,
+            operations: Vec::new(),
+            concurrency: DEFAULT_CONCURRENCY,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
     /// Star songs, albums, or artists.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/star/>
@@ -52,6 +307,60 @@ impl Client {
         Ok(())
     }
 
+    /// Star one song (or video or directory).
+    pub async fn star_song(&self, id: impl Into<SongId>) -> Result<(), Error> {
+        self.star(&[id.into().as_str()], &[], &[]).await
+    }
+
+    /// Star one album (ID3).
+    pub async fn star_album(&self, id: impl Into<AlbumId>) -> Result<(), Error> {
+        self.star(&[], &[id.into().as_str()], &[]).await
+    }
+
+    /// Star one artist (ID3).
+    pub async fn star_artist(&self, id: impl Into<ArtistId>) -> Result<(), Error> {
+        self.star(&[], &[], &[id.into().as_str()]).await
+    }
+
+    /// Unstar one song (or video or directory).
+    pub async fn unstar_song(&self, id: impl Into<SongId>) -> Result<(), Error> {
+        self.unstar(&[id.into().as_str()], &[], &[]).await
+    }
+
+    /// Unstar one album (ID3).
+    pub async fn unstar_album(&self, id: impl Into<AlbumId>) -> Result<(), Error> {
+        self.unstar(&[], &[id.into().as_str()], &[]).await
+    }
+
+    /// Unstar one artist (ID3).
+    pub async fn unstar_artist(&self, id: impl Into<ArtistId>) -> Result<(), Error> {
+        self.unstar(&[], &[], &[id.into().as_str()]).await
+    }
+
+    /// Star `item` if it is not starred and unstar it otherwise, returning whether it is now
+    /// starred.
+    ///
+    /// The current state is read with `getSong`, `getAlbum` or `getArtist` first.
+    pub async fn toggle_star(&self, item: impl Into<StarItem>) -> Result<bool, Error> {
+        let item = item.into();
+        let starred = match &item {
+            StarItem::Song(id) => self.get_song(id).await?.starred.is_some(),
+            StarItem::Album(id) => self.get_album(id).await?.starred.is_some(),
+            StarItem::Artist(id) => self.get_artist(id).await?.starred.is_some(),
+        };
+        let (ids, album_ids, artist_ids): (&[&str], &[&str], &[&str]) = match &item {
+            StarItem::Song(id) => (&[id.as_str()], &[], &[]),
+            StarItem::Album(id) => (&[], &[id.as_str()], &[]),
+            StarItem::Artist(id) => (&[], &[], &[id.as_str()]),
+        };
+        if starred {
+            self.unstar(ids, album_ids, artist_ids).await?;
+        } else {
+            self.star(ids, album_ids, artist_ids).await?;
+        }
+        Ok(!starred)
+    }
+
     /// Set the rating of a song, album, or artist.
     ///
     /// A rating of 0 removes the rating.
@@ -71,10 +380,11 @@ impl Client {
     /// See <https://opensubsonic.netlify.app/docs/endpoints/scrobble/>
     pub async fn scrobble(
         &self,
-        id: &str,
+        id: impl Into<SongId>,
         time: Option<i64>,
         submission: Option<bool>,
     ) -> Result<(), Error> {
+        let id: SongId = id.into();
         let mut params = vec![("id", id.to_string())];
         if let Some(t) = time {
             params.push(("time", t.to_string()));
@@ -92,13 +402,14 @@ impl Client {
     /// See <https://opensubsonic.netlify.app/docs/endpoints/reportplayback/>
     pub async fn report_playback(
         &self,
-        media_id: &str,
+        media_id: impl Into<SongId>,
         media_type: &str,
         position_ms: i64,
         state: &str,
         playback_rate: Option<f64>,
         ignore_scrobble: Option<bool>,
     ) -> Result<(), Error> {
+        let media_id: SongId = media_id.into();
         let position_str = position_ms.to_string();
         let mut params = vec![
             ("mediaId", media_id.to_string()),
@@ -117,3 +428,52 @@ impl Client {
         Ok(())
     }
 }
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::testing::MockServer;
+
+    #[tokio::test]
+    async fn toggles_stars() {
+        let server = MockServer::start().await;
+        let client = server.client();
+        assert!(client.toggle_star(SongId::from("tr-1")).await.unwrap());
+        assert_eq!(server.calls("star").await, 1);
+
+        let mut album = crate::testing::fixtures::album_json();
+        album["starred"] = "2024-01-01T00:00:00Z".into();
+        server
+            .respond_with("getAlbum", serde_json::json!({ "album": album }))
+            .await;
+        assert!(!client.toggle_star(AlbumId::from("al-1")).await.unwrap());
+        assert_eq!(server.calls("unstar").await, 1);
+    }
+
+    #[tokio::test]
+    async fn batches_annotations() {
+        let server = MockServer::start().await;
+        let client = server.client();
+        let report = client
+            .annotations()
+            .batch_size(2)
+            .star(SongId::from("tr-1"))
+            .star(AlbumId::from("al-1"))
+            .star(ArtistId::from("ar-1"))
+            .unstar(SongId::from("tr-2"))
+            .star(SongId::from("tr-2"))
+            .rate("tr-1", 5)
+            .run()
+            .await;
+        assert!(report.is_complete());
+        assert_eq!(report.succeeded.len(), 5);
+        // tr-1 and al-1, then ar-1 and tr-2.
+        assert_eq!(server.calls("star").await, 2);
+        assert_eq!(server.calls("unstar").await, 0);
+        assert_eq!(server.calls("setRating").await, 1);
+
+        server.fail_with("setRating", 70, "Not found").await;
+        let report = client.annotations().rate("missing", 1).run().await;
+        assert!(matches!(&report.failed[..], [(Annotation::Rate { .. }, _)]));
+    }
+}
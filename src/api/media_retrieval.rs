@@ -1,66 +1,503 @@
 //! Media Retrieval API endpoints.
 
+use std::time::Duration;
+
 use bytes::Bytes;
 use url::Url;
 
 use crate::Client;
-use crate::data::{Lyrics, LyricsList};
+use crate::api::system::KnownExtension;
+#[cfg(feature = "video")]
+use crate::captions::CaptionFormat;
+use crate::conditional::{Conditional, ValidatorStore};
+use crate::data::{Child, CoverArtId, Lyrics, LyricsList, SongId, StructuredLyrics};
 use crate::error::Error;
 
+/// Options for the `stream` endpoint, shared by [`Client::stream_with`] and
+/// [`Client::stream_url_with`].
+///
+/// ```
+/// use opensubsonic::StreamOptions;
+///
+/// let options = StreamOptions::new().max_bit_rate(192).format("opus").time_offset(30);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StreamOptions {
+    max_bit_rate: Option<i32>,
+    format: Option<String>,
+    time_offset: Option<i32>,
+    estimate_content_length: Option<bool>,
+    converted: Option<bool>,
+}
+
+impl StreamOptions {
+    /// Create empty options (server defaults for everything).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit the bitrate (kbps) of the returned stream; `0` means no limit.
+    #[must_use]
+    pub fn max_bit_rate(mut self, kbps: i32) -> Self {
+        self.max_bit_rate = Some(kbps);
+        self
+    }
+
+    /// Request a specific target format (e.g. `"mp3"`, `"opus"`).
+    #[must_use]
+    pub fn format(mut self, format: &str) -> Self {
+        self.format = Some(format.to_owned());
+        self
+    }
+
+    /// Request the original file without any transcoding (`format=raw`).
+    #[must_use]
+    pub fn raw(self) -> Self {
+        self.format("raw")
+    }
+
+    /// Start streaming at the given offset in seconds (transcoded streams only).
+    #[must_use]
+    pub fn time_offset(mut self, seconds: i32) -> Self {
+        self.time_offset = Some(seconds);
+        self
+    }
+
+    /// Ask the server to send an estimated `Content-Length` for transcoded streams.
+    #[must_use]
+    pub fn estimate_content_length(mut self, estimate: bool) -> Self {
+        self.estimate_content_length = Some(estimate);
+        self
+    }
+
+    /// Request the server's pre-converted version of a video (`converted=true`).
+    #[must_use]
+    pub fn converted(mut self, converted: bool) -> Self {
+        self.converted = Some(converted);
+        self
+    }
+
+    /// Build the query parameters for the given media `id`.
+    pub(crate) fn to_params(&self, id: &str) -> Vec<(&'static str, String)> {
+        let mut params = vec![("id", id.to_string())];
+        if let Some(br) = self.max_bit_rate {
+            params.push(("maxBitRate", br.to_string()));
+        }
+        if let Some(f) = &self.format {
+            params.push(("format", f.clone()));
+        }
+        if let Some(t) = self.time_offset {
+            params.push(("timeOffset", t.to_string()));
+        }
+        if let Some(e) = self.estimate_content_length {
+            params.push(("estimateContentLength", e.to_string()));
+        }
+        if let Some(c) = self.converted {
+            params.push(("converted", c.to_string()));
+        }
+        params
+    }
+}
+
+/// What a server reports about a media resource before it is fetched.
+///
+/// Returned by [`Client::probe_media`] and [`Client::probe_download`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaProbe {
+    /// The `Content-Type` the server will send.
+    pub content_type: Option<String>,
+    /// Total size of the resource in bytes, if known.
+    pub content_length: Option<u64>,
+    /// Whether the server honors `Range` requests (enables seeking and resuming).
+    pub accepts_ranges: bool,
+}
+
+impl MediaProbe {
+    /// Build a probe from a `HEAD` response. `accepts_ranges` is `None` when the server
+    /// did not say whether it supports ranges.
+    fn from_head(resp: &reqwest::Response) -> (Self, Option<bool>) {
+        let accepts_ranges = header_str(resp, reqwest::header::ACCEPT_RANGES).map(|v| {
+            v.split(',')
+                .any(|unit| unit.trim().eq_ignore_ascii_case("bytes"))
+        });
+        let probe = Self {
+            content_type: header_str(resp, reqwest::header::CONTENT_TYPE).map(str::to_owned),
+            content_length: header_str(resp, reqwest::header::CONTENT_LENGTH)
+                .and_then(|v| v.parse().ok()),
+            accepts_ranges: accepts_ranges.unwrap_or(false),
+        };
+        (probe, accepts_ranges)
+    }
+
+    /// Build a probe from the response to a `Range: bytes=0-0` GET.
+    fn from_ranged(resp: &reqwest::Response) -> Self {
+        let content_type = header_str(resp, reqwest::header::CONTENT_TYPE).map(str::to_owned);
+        if resp.status() == reqwest::StatusCode::PARTIAL_CONTENT {
+            Self {
+                content_type,
+                content_length: content_range_total(resp),
+                accepts_ranges: true,
+            }
+        } else {
+            Self {
+                content_type,
+                content_length: header_str(resp, reqwest::header::CONTENT_LENGTH)
+                    .and_then(|v| v.parse().ok()),
+                accepts_ranges: false,
+            }
+        }
+    }
+}
+
+/// A media file together with what the server reported about it.
+///
+/// Returned by [`Client::stream_media`], [`Client::download_media`] and
+/// [`Client::get_cover_art_media`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaResponse {
+    /// The file contents.
+    pub bytes: Bytes,
+    /// The `Content-Type` of the file (e.g. `audio/flac`).
+    pub content_type: Option<String>,
+    /// The size announced in `Content-Length`, if any.
+    pub content_length: Option<u64>,
+    /// The file name suggested in `Content-Disposition`, if any.
+    pub file_name: Option<String>,
+}
+
+impl MediaResponse {
+    async fn from_response(resp: reqwest::Response) -> Result<Self, Error> {
+        let content_type = header_str(&resp, reqwest::header::CONTENT_TYPE).map(str::to_owned);
+        let content_length = resp.content_length();
+        let file_name =
+            header_str(&resp, reqwest::header::CONTENT_DISPOSITION).and_then(disposition_file_name);
+        Ok(Self {
+            bytes: resp.bytes().await?,
+            content_type,
+            content_length,
+            file_name,
+        })
+    }
+}
+
+/// The file name in a `Content-Disposition` header, preferring the RFC 6266 `filename*`
+/// parameter over the plain `filename`.
+fn disposition_file_name(value: &str) -> Option<String> {
+    let mut plain = None;
+    for param in value.split(';').skip(1) {
+        let Some((name, value)) = param.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "filename*" => {
+                // charset'language'percent-encoded-name
+                let decoded = value.splitn(3, '\'').nth(2).and_then(percent_decode);
+                if decoded.is_some() {
+                    return decoded;
+                }
+            }
+            "filename" => {
+                plain = Some(value.trim_matches('"').replace("\\\"", "\""));
+            }
+            _ => {}
+        }
+    }
+    plain.filter(|name| !name.is_empty())
+}
+
+/// Decode `%XX` escapes as UTF-8.
+pub(crate) fn percent_decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        if b == b'%' && tail.len() >= 2 {
+            let hex = std::str::from_utf8(&tail[..2]).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(b);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Estimate the byte offset of `position` within the original file of `song`.
+///
+/// Assumes a constant bitrate: the offset is interpolated from `size` and `duration` when
+/// both are known, otherwise derived from `bit_rate`. Returns `None` when the song carries
+/// neither, and clamps the result to the last byte of the file when its size is known.
+pub fn estimate_byte_offset(song: &Child, position: Duration) -> Option<u64> {
+    let size = song.size.and_then(|s| u64::try_from(s).ok());
+    let duration = song.duration.filter(|d| *d > 0);
+    let offset = match (size, duration, song.bit_rate) {
+        (Some(size), Some(duration), _) => {
+            let ratio = position.as_secs_f64() / duration as f64;
+            (size as f64 * ratio) as u64
+        }
+        (_, _, Some(kbps)) if kbps > 0 => {
+            (position.as_secs_f64() * f64::from(kbps) * 1000.0 / 8.0) as u64
+        }
+        _ => return None,
+    };
+    Some(match size {
+        Some(size) => offset.min(size.saturating_sub(1)),
+        None => offset,
+    })
+}
+
+/// The total resource size from a `Content-Range: bytes 0-0/12345` header.
+pub(crate) fn content_range_total(resp: &reqwest::Response) -> Option<u64> {
+    header_str(resp, reqwest::header::CONTENT_RANGE)
+        .and_then(|v| v.rsplit_once('/'))
+        .and_then(|(_, total)| total.trim().parse().ok())
+}
+
+/// Parameters of an `hls` request.
+fn hls_params(
+    id: SongId,
+    bit_rate: Option<&str>,
+    audio_track: Option<&str>,
+) -> Vec<(&'static str, String)> {
+    let mut params = vec![("id", id.to_string())];
+    if let Some(br) = bit_rate {
+        params.push(("bitRate", br.to_string()));
+    }
+    if let Some(at) = audio_track {
+        params.push(("audioTrack", at.to_string()));
+    }
+    params
+}
+
+/// Parameters of a `getCaptions` request.
+#[cfg(feature = "video")]
+fn captions_params(id: SongId, format: Option<&str>) -> Vec<(&'static str, String)> {
+    let mut params = vec![("id", id.to_string())];
+    if let Some(f) = format {
+        params.push(("format", f.to_string()));
+    }
+    params
+}
+
+/// Parameters of a `getCoverArt` request.
+fn cover_art_params(id: CoverArtId, size: Option<i32>) -> Vec<(&'static str, String)> {
+    let mut params = vec![("id", id.to_string())];
+    if let Some(s) = size {
+        params.push(("size", s.to_string()));
+    }
+    params
+}
+
+/// Read a response header as a string, ignoring non-UTF-8 values.
+fn header_str(resp: &reqwest::Response, name: reqwest::header::HeaderName) -> Option<&str> {
+    resp.headers().get(name).and_then(|v| v.to_str().ok())
+}
+
 impl Client {
     /// Stream a song or video. Returns the raw bytes.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/stream/>
     pub async fn stream(
         &self,
-        id: &str,
+        id: impl Into<SongId>,
         max_bit_rate: Option<i32>,
         format: Option<&str>,
         time_offset: Option<i32>,
         estimated_content_length: Option<bool>,
     ) -> Result<Bytes, Error> {
-        let mut params = vec![("id", id.to_string())];
-        if let Some(br) = max_bit_rate {
-            params.push(("maxBitRate", br.to_string()));
-        }
-        if let Some(f) = format {
-            params.push(("format", f.to_string()));
-        }
-        if let Some(t) = time_offset {
-            params.push(("timeOffset", t.to_string()));
-        }
-        if let Some(e) = estimated_content_length {
-            params.push(("estimateContentLength", e.to_string()));
+        let id: SongId = id.into();
+        let options = StreamOptions {
+            max_bit_rate,
+            format: format.map(str::to_owned),
+            time_offset,
+            estimate_content_length: estimated_content_length,
+            ..StreamOptions::default()
+        };
+        self.stream_with(id, &options).await
+    }
+
+    /// Stream a song or video using [`StreamOptions`]. Returns the raw bytes.
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/stream/>
+    pub async fn stream_with(
+        &self,
+        id: impl Into<SongId>,
+        options: &StreamOptions,
+    ) -> Result<Bytes, Error> {
+        let id: SongId = id.into();
+        if options.time_offset.is_some()
+            && self.supports(KnownExtension::TranscodeOffset) == Some(false)
+        {
+            log::debug!("Server does not support transcodeOffset; timeOffset may be ignored");
         }
+        let params = options.to_params(&id);
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
         self.get_bytes("stream", &param_refs).await
     }
 
+    /// Stream a song or video using [`StreamOptions`], keeping the content type, size and
+    /// file name reported by the server.
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/stream/>
+    pub async fn stream_media(
+        &self,
+        id: impl Into<SongId>,
+        options: &StreamOptions,
+    ) -> Result<MediaResponse, Error> {
+        let id: SongId = id.into();
+        let params = options.to_params(&id);
+        let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        self.get_media("stream", &param_refs).await
+    }
+
+    /// Stream the original file of `song` starting at roughly `position`.
+    ///
+    /// Provides coarse seeking on servers that do not implement `timeOffset`: the byte
+    /// offset is computed with [`estimate_byte_offset`] and requested with a `Range` header
+    /// against the untranscoded (`format=raw`) stream. Returns the bytes from that offset
+    /// to the end of the file.
+    ///
+    /// # Errors
+    /// Returns [`Error::Other`] if the offset cannot be estimated or the server ignores the
+    /// `Range` request.
+    pub async fn stream_at(&self, song: &Child, position: Duration) -> Result<Bytes, Error> {
+        let offset = estimate_byte_offset(song, position).ok_or_else(|| {
+            Error::Other(format!(
+                "Cannot estimate a byte offset for '{}': no size, duration or bit rate",
+                song.id
+            ))
+        })?;
+        let params = StreamOptions::new().raw().to_params(&song.id);
+        let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        let request = self
+            .request(reqwest::Method::GET, "stream", &param_refs)?
+            .header(reqwest::header::RANGE, format!("bytes={offset}-"));
+        let resp = self.send_binary(request).await?;
+        if offset > 0 && resp.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+            return Err(Error::Other(
+                "Server ignored the Range request; seeking by byte offset is unsupported".into(),
+            ));
+        }
+        Ok(resp.bytes().await?)
+    }
+
     /// Build a streaming URL for a song without making an HTTP request.
     ///
     /// Useful for passing to external audio players or download managers.
     pub fn stream_url(
         &self,
-        id: &str,
+        id: impl Into<SongId>,
         max_bit_rate: Option<i32>,
         format: Option<&str>,
     ) -> Result<Url, Error> {
-        let mut params = vec![("id", id.to_string())];
-        if let Some(br) = max_bit_rate {
-            params.push(("maxBitRate", br.to_string()));
-        }
-        if let Some(f) = format {
-            params.push(("format", f.to_string()));
-        }
+        let id: SongId = id.into();
+        let options = StreamOptions {
+            max_bit_rate,
+            format: format.map(str::to_owned),
+            ..StreamOptions::default()
+        };
+        self.stream_url_with(id, &options)
+    }
+
+    /// Build a streaming URL using [`StreamOptions`] without making an HTTP request.
+    ///
+    /// Accepts exactly the same options as [`Client::stream_with`], so a URL handed to an
+    /// external player requests the same bitrate, format, and seek position.
+    pub fn stream_url_with(
+        &self,
+        id: impl Into<SongId>,
+        options: &StreamOptions,
+    ) -> Result<Url, Error> {
+        let id: SongId = id.into();
+        let params = options.to_params(&id);
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
         self.build_url("stream", &param_refs)
     }
 
+    /// Probe a `stream` resource without downloading it.
+    ///
+    /// Issues a `HEAD` request and, when the server does not report its range support (or
+    /// rejects `HEAD`), falls back to a ranged GET of a single byte. Useful for accurate size
+    /// estimates and for checking whether seeking will work before playback starts.
+    pub async fn probe_media(
+        &self,
+        id: impl Into<SongId>,
+        options: &StreamOptions,
+    ) -> Result<MediaProbe, Error> {
+        let id: SongId = id.into();
+        let params = options.to_params(&id);
+        let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        self.probe("stream", &param_refs).await
+    }
+
+    /// Probe a `download` resource without downloading it. See [`Client::probe_media`].
+    pub async fn probe_download(&self, id: impl Into<SongId>) -> Result<MediaProbe, Error> {
+        let id: SongId = id.into();
+        self.probe("download", &[("id", &id)]).await
+    }
+
+    /// Shared implementation of [`Client::probe_media`] and [`Client::probe_download`].
+    pub(crate) async fn probe(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+    ) -> Result<MediaProbe, Error> {
+        let head = self
+            .send(self.request(reqwest::Method::HEAD, endpoint, params)?)
+            .await?;
+        if head.status().is_success() && !crate::client::is_json_response(&head) {
+            let (probe, accepts_ranges) = MediaProbe::from_head(&head);
+            if accepts_ranges.is_some() && probe.content_length.is_some() {
+                return Ok(probe);
+            }
+        }
+
+        let ranged = self
+            .request(reqwest::Method::GET, endpoint, params)?
+            .header(reqwest::header::RANGE, "bytes=0-0");
+        let resp = self.send_binary(ranged).await?;
+        Ok(MediaProbe::from_ranged(&resp))
+    }
+
     /// Download a song or video. Returns raw bytes.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/download/>
-    pub async fn download(&self, id: &str) -> Result<Bytes, Error> {
-        self.get_bytes("download", &[("id", id)]).await
+    pub async fn download(&self, id: impl Into<SongId>) -> Result<Bytes, Error> {
+        let id: SongId = id.into();
+        self.get_bytes("download", &[("id", &id)]).await
+    }
+
+    /// Download a song or video, keeping the content type, size and file name reported by
+    /// the server.
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/download/>
+    pub async fn download_media(&self, id: impl Into<SongId>) -> Result<MediaResponse, Error> {
+        let id: SongId = id.into();
+        self.get_media("download", &[("id", &id)]).await
+    }
+
+    /// Build a download URL for the original file of a song or video without making an HTTP
+    /// request.
+    ///
+    /// Useful for handing original-quality files to external download managers.
+    pub fn download_url(&self, id: impl Into<SongId>) -> Result<Url, Error> {
+        let id: SongId = id.into();
+        self.build_url("download", &[("id", &id)])
+    }
+
+    /// Get an HLS playlist for a video or song. Returns the raw `m3u8` bytes.
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/hls/>
+    pub async fn hls(
+        &self,
+        id: impl Into<SongId>,
+        bit_rate: Option<&str>,
+        audio_track: Option<&str>,
+    ) -> Result<Bytes, Error> {
+        let params = hls_params(id.into(), bit_rate, audio_track);
+        let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        self.get_bytes("hls.m3u8", &param_refs).await
     }
 
     /// Get an HLS playlist URL for a video or song.
@@ -68,55 +505,110 @@ impl Client {
     /// See <https://opensubsonic.netlify.app/docs/endpoints/hls/>
     pub fn hls_url(
         &self,
-        id: &str,
+        id: impl Into<SongId>,
         bit_rate: Option<&str>,
         audio_track: Option<&str>,
     ) -> Result<Url, Error> {
-        let mut params = vec![("id", id.to_string())];
-        if let Some(br) = bit_rate {
-            params.push(("bitRate", br.to_string()));
-        }
-        if let Some(at) = audio_track {
-            params.push(("audioTrack", at.to_string()));
-        }
+        let params = hls_params(id.into(), bit_rate, audio_track);
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
         self.build_url("hls.m3u8", &param_refs)
     }
 
     /// Get captions (subtitles) for a video. Returns raw bytes.
     ///
+    /// See [`Client::get_captions_with`] for a typed format and [`Client::get_caption_cues`]
+    /// for parsed captions.
+    ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getcaptions/>
-    pub async fn get_captions(&self, id: &str, format: Option<&str>) -> Result<Bytes, Error> {
-        let mut params = vec![("id", id.to_string())];
-        if let Some(f) = format {
-            params.push(("format", f.to_string()));
-        }
+    #[cfg(feature = "video")]
+    pub async fn get_captions(
+        &self,
+        id: impl Into<SongId>,
+        format: Option<&str>,
+    ) -> Result<Bytes, Error> {
+        let params = captions_params(id.into(), format);
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
         self.get_bytes("getCaptions", &param_refs).await
     }
 
+    /// Get captions (subtitles) for a video in a [`CaptionFormat`]. Returns raw bytes.
+    #[cfg(feature = "video")]
+    pub async fn get_captions_with(
+        &self,
+        id: impl Into<SongId>,
+        format: Option<CaptionFormat>,
+    ) -> Result<Bytes, Error> {
+        self.get_captions(id, format.map(CaptionFormat::as_str))
+            .await
+    }
+
+    /// Build a captions URL without making an HTTP request.
+    #[cfg(feature = "video")]
+    pub fn captions_url(
+        &self,
+        id: impl Into<SongId>,
+        format: Option<CaptionFormat>,
+    ) -> Result<Url, Error> {
+        let params = captions_params(id.into(), format.map(CaptionFormat::as_str));
+        let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        self.build_url("getCaptions", &param_refs)
+    }
+
     /// Get cover art for an album or artist. Returns raw image bytes.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getcoverart/>
-    pub async fn get_cover_art(&self, id: &str, size: Option<i32>) -> Result<Bytes, Error> {
-        let mut params = vec![("id", id.to_string())];
-        if let Some(s) = size {
-            params.push(("size", s.to_string()));
-        }
+    pub async fn get_cover_art(
+        &self,
+        id: impl Into<CoverArtId>,
+        size: Option<i32>,
+    ) -> Result<Bytes, Error> {
+        let params = cover_art_params(id.into(), size);
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
         self.get_bytes("getCoverArt", &param_refs).await
     }
 
+    /// Get cover art for an album or artist, keeping the image type and size reported by the
+    /// server.
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/getcoverart/>
+    pub async fn get_cover_art_media(
+        &self,
+        id: impl Into<CoverArtId>,
+        size: Option<i32>,
+    ) -> Result<MediaResponse, Error> {
+        let params = cover_art_params(id.into(), size);
+        let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        self.get_media("getCoverArt", &param_refs).await
+    }
+
     /// Build a cover art URL without making an HTTP request.
-    pub fn cover_art_url(&self, id: &str, size: Option<i32>) -> Result<Url, Error> {
-        let mut params = vec![("id", id.to_string())];
-        if let Some(s) = size {
-            params.push(("size", s.to_string()));
-        }
+    pub fn cover_art_url(
+        &self,
+        id: impl Into<CoverArtId>,
+        size: Option<i32>,
+    ) -> Result<Url, Error> {
+        let params = cover_art_params(id.into(), size);
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
         self.build_url("getCoverArt", &param_refs)
     }
 
+    /// Get a cover art image unless the copy fetched earlier is still current.
+    ///
+    /// Sends the validators stored in `store` for this `id` and `size` and returns
+    /// [`Conditional::Cached`] when the server answers `304 Not Modified`. See
+    /// [`crate::conditional`].
+    pub async fn get_cover_art_conditional(
+        &self,
+        id: impl Into<CoverArtId>,
+        size: Option<i32>,
+        store: &dyn ValidatorStore,
+    ) -> Result<Conditional, Error> {
+        let params = cover_art_params(id.into(), size);
+        let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        self.get_bytes_conditional("getCoverArt", &param_refs, store)
+            .await
+    }
+
     /// Get lyrics for a song (legacy, unstructured).
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getlyrics/>
@@ -132,12 +624,11 @@ impl Client {
         if let Some(t) = title {
             params.push(("title", t));
         }
-        let data = self.get_response("getLyrics", &params).await?;
+        let mut data = self.get_response("getLyrics", &params).await?;
         let lyrics = data
-            .get("lyrics")
-            .cloned()
+            .remove("lyrics")
             .unwrap_or_else(|| serde_json::Value::Object(serde_json::Map::new()));
-        Ok(serde_json::from_value(lyrics)?)
+        self.decode(lyrics)
     }
 
     /// Get structured lyrics for a song by ID (OpenSubsonic extension).
@@ -145,20 +636,52 @@ impl Client {
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getlyricsbysongid/>
     pub async fn get_lyrics_by_song_id(
         &self,
-        id: &str,
+        id: impl Into<SongId>,
         enhanced: Option<bool>,
     ) -> Result<LyricsList, Error> {
+        if self.supports(KnownExtension::SongLyrics) == Some(false) {
+            return Err(Error::Other(
+                "Server does not support the songLyrics extension".into(),
+            ));
+        }
+        let id: SongId = id.into();
         let mut params = vec![("id", id.to_string())];
         if let Some(e) = enhanced {
             params.push(("enhanced", e.to_string()));
         }
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let data = self.get_response("getLyricsBySongId", &param_refs).await?;
+        let mut data = self.get_response("getLyricsBySongId", &param_refs).await?;
         let lyrics = data
-            .get("lyricsList")
-            .cloned()
+            .remove("lyricsList")
             .unwrap_or_else(|| serde_json::Value::Object(serde_json::Map::new()));
-        Ok(serde_json::from_value(lyrics)?)
+        self.decode(lyrics)
+    }
+
+    /// Get the lyrics of `song` from whichever endpoint the server offers.
+    ///
+    /// Uses `getLyricsBySongId` when the server advertises the `songLyrics` extension (see
+    /// [`Client::capabilities`]), and otherwise, or when that finds nothing, `getLyrics` with
+    /// the song's artist and title, converted to unsynced [`StructuredLyrics`]. Returns an
+    /// empty list when the song has no lyrics.
+    ///
+    /// A server whose extensions cannot be fetched, such as a plain Subsonic server, is
+    /// treated as one without `songLyrics`.
+    pub async fn get_lyrics_unified(&self, song: &Child) -> Result<Vec<StructuredLyrics>, Error> {
+        if self.capabilities().await.is_ok_and(|c| c.song_lyrics()) {
+            let list = self.get_lyrics_by_song_id(&song.id, None).await?;
+            if !list.structured_lyrics.is_empty() {
+                return Ok(list.structured_lyrics);
+            }
+        }
+        let lyrics = self
+            .get_lyrics(song.artist.as_deref(), Some(&song.title))
+            .await?;
+        let lyrics = StructuredLyrics::from(lyrics);
+        Ok(if lyrics.line.is_empty() {
+            Vec::new()
+        } else {
+            vec![lyrics]
+        })
     }
 
     /// Get a user's avatar image. Returns raw image bytes.
@@ -167,4 +690,172 @@ impl Client {
     pub async fn get_avatar(&self, username: &str) -> Result<Bytes, Error> {
         self.get_bytes("getAvatar", &[("username", username)]).await
     }
+
+    /// Build an avatar URL without making an HTTP request.
+    pub fn avatar_url(&self, username: &str) -> Result<Url, Error> {
+        self.build_url("getAvatar", &[("username", username)])
+    }
+
+    /// Get a user's avatar unless the copy fetched earlier is still current (see
+    /// [`Client::get_cover_art_conditional`]).
+    pub async fn get_avatar_conditional(
+        &self,
+        username: &str,
+        store: &dyn ValidatorStore,
+    ) -> Result<Conditional, Error> {
+        self.get_bytes_conditional("getAvatar", &[("username", username)], store)
+            .await
+    }
+
+    /// Perform a GET request to a binary endpoint, keeping the response metadata.
+    async fn get_media(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+    ) -> Result<MediaResponse, Error> {
+        let request = self.request(reqwest::Method::GET, endpoint, params)?;
+        let resp = self.send_binary(request).await?;
+        MediaResponse::from_response(resp).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_content_disposition_file_names() {
+        assert_eq!(
+            disposition_file_name(r#"attachment; filename="01 - Intro.flac""#).as_deref(),
+            Some("01 - Intro.flac")
+        );
+        assert_eq!(
+            disposition_file_name(
+                "attachment; filename=\"fallback.mp3\"; filename*=UTF-8''Bj%C3%B6rk%20-%20J%C3%B3ga.mp3"
+            )
+            .as_deref(),
+            Some("Björk - Jóga.mp3")
+        );
+        assert_eq!(disposition_file_name("inline"), None);
+    }
+
+    #[test]
+    fn stream_options_build_params() {
+        assert_eq!(
+            StreamOptions::new().to_params("tr-1"),
+            [("id", "tr-1".into())]
+        );
+        let options = StreamOptions::new()
+            .max_bit_rate(192)
+            .raw()
+            .time_offset(30)
+            .estimate_content_length(true)
+            .converted(false);
+        assert_eq!(
+            options.to_params("vi-1"),
+            [
+                ("id", "vi-1".to_owned()),
+                ("maxBitRate", "192".to_owned()),
+                ("format", "raw".to_owned()),
+                ("timeOffset", "30".to_owned()),
+                ("estimateContentLength", "true".to_owned()),
+                ("converted", "false".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn estimates_byte_offsets() {
+        let song = Child::new("tr-1", "t").with_size(1_000).with_duration(100);
+        assert_eq!(
+            estimate_byte_offset(&song, Duration::from_secs(25)),
+            Some(250)
+        );
+        assert_eq!(
+            estimate_byte_offset(&song, Duration::from_secs(500)),
+            Some(999)
+        );
+
+        // A zero duration says nothing; the bitrate is used instead.
+        let song = Child::new("tr-2", "t")
+            .with_size(1_000_000)
+            .with_duration(0);
+        assert_eq!(estimate_byte_offset(&song, Duration::from_secs(1)), None);
+        let song = song.with_bit_rate(128);
+        assert_eq!(
+            estimate_byte_offset(&song, Duration::from_secs(2)),
+            Some(32_000)
+        );
+        let song = Child::new("tr-3", "t").with_bit_rate(128);
+        assert_eq!(
+            estimate_byte_offset(&song, Duration::from_secs(60)),
+            Some(960_000)
+        );
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn probe_falls_back_to_a_ranged_get() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, ResponseTemplate};
+
+        let server = crate::testing::MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/rest/stream"))
+            .respond_with(ResponseTemplate::new(405))
+            .expect(1)
+            .mount(server.inner())
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/stream"))
+            .and(header("range", "bytes=0-0"))
+            .respond_with(
+                ResponseTemplate::new(206)
+                    .insert_header("content-range", "bytes 0-0/8520000")
+                    .set_body_raw(&b"I"[..], "audio/mpeg"),
+            )
+            .expect(1)
+            .mount(server.inner())
+            .await;
+
+        let probe = server
+            .client()
+            .probe_media("tr-1", &StreamOptions::new())
+            .await
+            .unwrap();
+        assert_eq!(
+            probe,
+            MediaProbe {
+                content_type: Some("audio/mpeg".into()),
+                content_length: Some(8_520_000),
+                accepts_ranges: true,
+            }
+        );
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn unified_lyrics_fall_back_to_get_lyrics() {
+        let server = crate::testing::MockServer::start().await;
+        let client = server.client();
+        let song = client.get_song("tr-1").await.unwrap();
+        let lyrics = client.get_lyrics_unified(&song).await.unwrap();
+        assert!(lyrics[0].synced);
+
+        server
+            .respond_with("getOpenSubsonicExtensions", serde_json::json!({}))
+            .await;
+        let client = server.client();
+        let lyrics = client.get_lyrics_unified(&song).await.unwrap();
+        assert_eq!((lyrics[0].synced, lyrics[0].line.len()), (false, 2));
+        assert_eq!(lyrics[0].display_artist.as_deref(), Some("Queen"));
+
+        let server = crate::testing::MockServer::start().await;
+        server
+            .fail_with("getOpenSubsonicExtensions", 70, "Unknown method")
+            .await;
+        let lyrics = server.client().get_lyrics_unified(&song).await.unwrap();
+        assert_eq!((lyrics[0].synced, lyrics[0].line.len()), (false, 2));
+        assert_eq!(server.calls("getLyricsBySongId").await, 0);
+    }
 }
@@ -1,22 +1,30 @@
 //! API endpoint implementations.
 //!
 //! Each sub-module corresponds to a section of the Subsonic REST API and provides
-//! methods on [`crate::Client`].
+//! methods on [`crate::Client`]. Optional sections are compiled only with their cargo
+//! feature (`chat`, `internet-radio`, `jukebox`, `podcast`, `sharing`, `user-management`,
+//! `video`), all enabled by default.
 
-mod bookmarks;
-mod browsing;
+pub mod bookmarks;
+pub mod browsing;
+#[cfg(feature = "chat")]
 mod chat;
+#[cfg(feature = "internet-radio")]
 mod internet_radio;
+#[cfg(feature = "jukebox")]
 pub mod jukebox;
 pub mod lists;
-mod media_annotation;
-mod media_retrieval;
-mod playlists;
+pub mod media_annotation;
+pub mod media_retrieval;
+pub mod playlists;
+#[cfg(feature = "podcast")]
 mod podcast;
 mod scanning;
-mod searching;
-mod sharing;
+pub mod searching;
+#[cfg(feature = "sharing")]
+pub mod sharing;
 mod sonic_similarity;
-mod system;
+pub mod system;
 mod transcoding;
-mod user_management;
+#[cfg(feature = "user-management")]
+pub mod user_management;
@@ -1,9 +1,25 @@
 //! Playlists API endpoints.
 
 use crate::Client;
-use crate::data::{Playlist, PlaylistWithSongs};
+use crate::client::take_list;
+use crate::data::{Playlist, PlaylistId, PlaylistWithSongs};
+use crate::diff::ListDiff;
 use crate::error::Error;
 
+/// Higher-level playlist editing built on `getPlaylist`, `updatePlaylist` and
+/// `createPlaylist`.
+///
+/// Every operation fetches the playlist, computes the desired entries and applies them with a
+/// single request: an `updatePlaylist` call with the minimal removals and additions when the
+/// change allows it, or a `createPlaylist` call replacing the entries otherwise. Changes made
+/// by someone else between the fetch and the update are overwritten.
+///
+/// Obtain one via [`Client::playlist_manager`].
+#[derive(Debug, Clone, Copy)]
+pub struct PlaylistManager<'a> {
+    client: &'a Client,
+}
+
 impl Client {
     /// Get all playlists.
     ///
@@ -13,24 +29,24 @@ impl Client {
         if let Some(u) = username {
             params.push(("username", u));
         }
-        let data = self.get_response("getPlaylists", &params).await?;
-        let playlists = data
-            .get("playlists")
-            .and_then(|v| v.get("playlist"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(playlists)?)
+        let mut data = self.get_response("getPlaylists", &params).await?;
+        let playlists = take_list(&mut data, "playlists", "playlist");
+        self.decode_list(playlists)
     }
 
     /// Get a playlist with its songs.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getplaylist/>
-    pub async fn get_playlist(&self, id: &str) -> Result<PlaylistWithSongs, Error> {
-        let data = self.get_response("getPlaylist", &[("id", id)]).await?;
+    pub async fn get_playlist(
+        &self,
+        id: impl Into<PlaylistId>,
+    ) -> Result<PlaylistWithSongs, Error> {
+        let id: PlaylistId = id.into();
+        let mut data = self.get_response("getPlaylist", &[("id", &id)]).await?;
         let playlist = data
-            .get("playlist")
+            .remove("playlist")
             .ok_or_else(|| Error::Parse("Missing 'playlist' in response".into()))?;
-        Ok(serde_json::from_value(playlist.clone())?)
+        self.decode(playlist)
     }
 
     /// Create or update a playlist.
@@ -41,7 +57,7 @@ impl Client {
     /// See <https://opensubsonic.netlify.app/docs/endpoints/createplaylist/>
     pub async fn create_playlist(
         &self,
-        playlist_id: Option<&str>,
+        playlist_id: Option<&PlaylistId>,
         name: Option<&str>,
         song_ids: &[&str],
     ) -> Result<PlaylistWithSongs, Error> {
@@ -56,11 +72,11 @@ impl Client {
             params.push(("songId", song_id.to_string()));
         }
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let data = self.get_response("createPlaylist", &param_refs).await?;
+        let mut data = self.get_response("createPlaylist", &param_refs).await?;
         let playlist = data
-            .get("playlist")
+            .remove("playlist")
             .ok_or_else(|| Error::Parse("Missing 'playlist' in response".into()))?;
-        Ok(serde_json::from_value(playlist.clone())?)
+        self.decode(playlist)
     }
 
     /// Update a playlist (name, comment, public status, add/remove songs).
@@ -68,13 +84,14 @@ impl Client {
     /// See <https://opensubsonic.netlify.app/docs/endpoints/updateplaylist/>
     pub async fn update_playlist(
         &self,
-        playlist_id: &str,
+        playlist_id: impl Into<PlaylistId>,
         name: Option<&str>,
         comment: Option<&str>,
         public: Option<bool>,
         song_ids_to_add: &[&str],
         song_indexes_to_remove: &[i32],
     ) -> Result<(), Error> {
+        let playlist_id: PlaylistId = playlist_id.into();
         let mut params = vec![("playlistId", playlist_id.to_string())];
         if let Some(n) = name {
             params.push(("name", n.to_string()));
@@ -99,8 +116,196 @@ impl Client {
     /// Delete a playlist.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/deleteplaylist/>
-    pub async fn delete_playlist(&self, id: &str) -> Result<(), Error> {
-        self.get_response("deletePlaylist", &[("id", id)]).await?;
+    pub async fn delete_playlist(&self, id: impl Into<PlaylistId>) -> Result<(), Error> {
+        let id: PlaylistId = id.into();
+        self.get_response("deletePlaylist", &[("id", &id)]).await?;
         Ok(())
     }
 }
+
+impl Client {
+    /// Get a [`PlaylistManager`] for higher-level playlist editing.
+    pub fn playlist_manager(&self) -> PlaylistManager<'_> {
+        PlaylistManager { client: self }
+    }
+}
+
+impl PlaylistManager<'_> {
+    /// Replace the entries of the playlist with `song_ids`, in order.
+    pub async fn set_entries(
+        &self,
+        playlist_id: impl Into<PlaylistId>,
+        song_ids: &[&str],
+    ) -> Result<(), Error> {
+        let playlist_id: PlaylistId = playlist_id.into();
+        let playlist = self.client.get_playlist(&playlist_id).await?;
+        self.apply(&playlist, song_ids).await
+    }
+
+    /// Move the entry at index `from` to index `to`, shifting the entries in between.
+    ///
+    /// # Errors
+    /// Returns [`Error::Other`] if either index is out of range.
+    pub async fn move_entry(
+        &self,
+        playlist_id: impl Into<PlaylistId>,
+        from: usize,
+        to: usize,
+    ) -> Result<(), Error> {
+        let playlist_id: PlaylistId = playlist_id.into();
+        let playlist = self.client.get_playlist(&playlist_id).await?;
+        let mut ids = entry_ids(&playlist);
+        if from >= ids.len() || to >= ids.len() {
+            return Err(Error::Other(format!(
+                "Cannot move entry {from} to {to} in a playlist of {} entries",
+                ids.len()
+            )));
+        }
+        let id = ids.remove(from);
+        ids.insert(to, id);
+        self.apply(&playlist, &ids).await
+    }
+
+    /// Insert `song_ids` before the entry at `index` (at the end if `index` is the number of
+    /// entries).
+    ///
+    /// # Errors
+    /// Returns [`Error::Other`] if `index` is out of range.
+    pub async fn insert_at(
+        &self,
+        playlist_id: impl Into<PlaylistId>,
+        index: usize,
+        song_ids: &[&str],
+    ) -> Result<(), Error> {
+        let playlist_id: PlaylistId = playlist_id.into();
+        let playlist = self.client.get_playlist(&playlist_id).await?;
+        let mut ids = entry_ids(&playlist);
+        if index > ids.len() {
+            return Err(Error::Other(format!(
+                "Cannot insert at {index} in a playlist of {} entries",
+                ids.len()
+            )));
+        }
+        ids.splice(index..index, song_ids.iter().copied());
+        self.apply(&playlist, &ids).await
+    }
+
+    /// Remove repeated songs, keeping the first occurrence of each. Returns the number of
+    /// entries removed.
+    pub async fn deduplicate(&self, playlist_id: impl Into<PlaylistId>) -> Result<usize, Error> {
+        let playlist_id: PlaylistId = playlist_id.into();
+        let playlist = self.client.get_playlist(&playlist_id).await?;
+        let ids = entry_ids(&playlist);
+        let mut seen = std::collections::HashSet::new();
+        let unique: Vec<&str> = ids.iter().copied().filter(|id| seen.insert(*id)).collect();
+        self.apply(&playlist, &unique).await?;
+        Ok(ids.len() - unique.len())
+    }
+
+    /// Turn the entries of `playlist` into `desired` with a single request.
+    async fn apply(&self, playlist: &PlaylistWithSongs, desired: &[&str]) -> Result<(), Error> {
+        let current = entry_ids(playlist);
+        match PlaylistPlan::compute(&current, desired) {
+            PlaylistPlan::Unchanged => Ok(()),
+            PlaylistPlan::Update { remove, add } => {
+                self.client
+                    .update_playlist(&playlist.id, None, None, None, &add, &remove)
+                    .await
+            }
+            PlaylistPlan::Replace => {
+                self.client
+                    .create_playlist(Some(&playlist.id), None, desired)
+                    .await?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The song IDs of a playlist's entries, in order.
+fn entry_ids(playlist: &PlaylistWithSongs) -> Vec<&str> {
+    playlist.entry.iter().map(|c| c.id.as_str()).collect()
+}
+
+/// The request needed to turn one list of entries into another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PlaylistPlan<'a> {
+    /// The entries already match.
+    Unchanged,
+    /// One `updatePlaylist` call removing the entries at `remove` (indexes into the current
+    /// list) and appending `add`.
+    Update { remove: Vec<i32>, add: Vec<&'a str> },
+    /// One `createPlaylist` call replacing all entries.
+    Replace,
+}
+
+impl<'a> PlaylistPlan<'a> {
+    fn compute(current: &[&str], desired: &[&'a str]) -> Self {
+        if current == desired {
+            return Self::Unchanged;
+        }
+
+        let diff = ListDiff::compute(current, desired);
+        let remove: Vec<i32> = diff.removed().map(|i| i as i32).collect();
+        let add = diff.append;
+
+        // Replacing sends every desired ID; prefer it when the update would send as many.
+        if !desired.is_empty() && remove.len() + add.len() >= desired.len() {
+            return Self::Replace;
+        }
+        Self::Update { remove, add }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_entries_are_unchanged() {
+        let plan = PlaylistPlan::compute(&["a", "b"], &["a", "b"]);
+        assert_eq!(plan, PlaylistPlan::Unchanged);
+    }
+
+    #[test]
+    fn duplicates_are_removed_by_index() {
+        let plan = PlaylistPlan::compute(&["a", "b", "a", "c", "b"], &["a", "b", "c"]);
+        assert_eq!(
+            plan,
+            PlaylistPlan::Update {
+                remove: vec![2, 4],
+                add: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn appending_uses_update() {
+        let plan = PlaylistPlan::compute(&["a", "b", "c"], &["a", "c", "d"]);
+        assert_eq!(
+            plan,
+            PlaylistPlan::Update {
+                remove: vec![1],
+                add: vec!["d"],
+            }
+        );
+    }
+
+    #[test]
+    fn reordering_falls_back_to_replace() {
+        let plan = PlaylistPlan::compute(&["a", "b", "c", "d"], &["d", "a", "b", "c"]);
+        assert_eq!(plan, PlaylistPlan::Replace);
+    }
+
+    #[test]
+    fn clearing_uses_update() {
+        let plan = PlaylistPlan::compute(&["a", "b"], &[]);
+        assert_eq!(
+            plan,
+            PlaylistPlan::Update {
+                remove: vec![0, 1],
+                add: vec![],
+            }
+        );
+    }
+}
@@ -1,9 +1,16 @@
 //! Podcast API endpoints.
 
+use std::time::Duration;
+
 use crate::Client;
-use crate::data::{PodcastChannel, PodcastEpisode};
+use crate::client::take_list;
+use crate::data::{PodcastChannel, PodcastChannelId, PodcastEpisode, PodcastStatus, SongId};
 use crate::error::Error;
 
+/// Delay between the `getPodcastEpisode` polls of
+/// [`Client::download_podcast_episode_and_wait`].
+const EPISODE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
 impl Client {
     /// Get all podcast channels.
     ///
@@ -13,7 +20,7 @@ impl Client {
     pub async fn get_podcasts(
         &self,
         include_episodes: Option<bool>,
-        id: Option<&str>,
+        id: Option<&PodcastChannelId>,
     ) -> Result<Vec<PodcastChannel>, Error> {
         let mut params = Vec::new();
         if let Some(ie) = include_episodes {
@@ -23,13 +30,9 @@ impl Client {
             params.push(("id", id_val.to_string()));
         }
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let data = self.get_response("getPodcasts", &param_refs).await?;
-        let channels = data
-            .get("podcasts")
-            .and_then(|v| v.get("channel"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(channels)?)
+        let mut data = self.get_response("getPodcasts", &param_refs).await?;
+        let channels = take_list(&mut data, "podcasts", "channel");
+        self.decode_list(channels)
     }
 
     /// Get the newest podcast episodes.
@@ -44,26 +47,26 @@ impl Client {
             params.push(("count", c.to_string()));
         }
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let data = self.get_response("getNewestPodcasts", &param_refs).await?;
-        let episodes = data
-            .get("newestPodcasts")
-            .and_then(|v| v.get("episode"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(episodes)?)
+        let mut data = self.get_response("getNewestPodcasts", &param_refs).await?;
+        let episodes = take_list(&mut data, "newestPodcasts", "episode");
+        self.decode_list(episodes)
     }
 
     /// Get a specific podcast episode (OpenSubsonic extension).
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getpodcastepisode/>
-    pub async fn get_podcast_episode(&self, id: &str) -> Result<PodcastEpisode, Error> {
-        let data = self
-            .get_response("getPodcastEpisode", &[("id", id)])
+    pub async fn get_podcast_episode(
+        &self,
+        id: impl Into<SongId>,
+    ) -> Result<PodcastEpisode, Error> {
+        let id: SongId = id.into();
+        let mut data = self
+            .get_response("getPodcastEpisode", &[("id", &id)])
             .await?;
         let episode = data
-            .get("podcastEpisode")
+            .remove("podcastEpisode")
             .ok_or_else(|| Error::Parse("Missing 'podcastEpisode' in response".into()))?;
-        Ok(serde_json::from_value(episode.clone())?)
+        self.decode(episode)
     }
 
     /// Tell the server to check for new podcast episodes.
@@ -86,8 +89,12 @@ impl Client {
     /// Delete a podcast channel.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/deletepodcastchannel/>
-    pub async fn delete_podcast_channel(&self, id: &str) -> Result<(), Error> {
-        self.get_response("deletePodcastChannel", &[("id", id)])
+    pub async fn delete_podcast_channel(
+        &self,
+        id: impl Into<PodcastChannelId>,
+    ) -> Result<(), Error> {
+        let id: PodcastChannelId = id.into();
+        self.get_response("deletePodcastChannel", &[("id", &id)])
             .await?;
         Ok(())
     }
@@ -95,8 +102,9 @@ impl Client {
     /// Delete a podcast episode.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/deletepodcastepisode/>
-    pub async fn delete_podcast_episode(&self, id: &str) -> Result<(), Error> {
-        self.get_response("deletePodcastEpisode", &[("id", id)])
+    pub async fn delete_podcast_episode(&self, id: impl Into<SongId>) -> Result<(), Error> {
+        let id: SongId = id.into();
+        self.get_response("deletePodcastEpisode", &[("id", &id)])
             .await?;
         Ok(())
     }
@@ -104,9 +112,104 @@ impl Client {
     /// Tell the server to download a podcast episode.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/downloadpodcastepisode/>
-    pub async fn download_podcast_episode(&self, id: &str) -> Result<(), Error> {
-        self.get_response("downloadPodcastEpisode", &[("id", id)])
+    pub async fn download_podcast_episode(&self, id: impl Into<SongId>) -> Result<(), Error> {
+        let id: SongId = id.into();
+        self.get_response("downloadPodcastEpisode", &[("id", &id)])
             .await?;
         Ok(())
     }
+
+    /// Tell the server to download a podcast episode and wait until it has.
+    ///
+    /// After [`Client::download_podcast_episode`], the episode is polled with
+    /// `getPodcastEpisode` (OpenSubsonic) every two seconds, calling `on_status` with the
+    /// episode whenever its status changes, until it is
+    /// [`Completed`](PodcastStatus::Completed). The returned episode can be streamed
+    /// through its [`stream_id`](PodcastEpisode::stream_id).
+    ///
+    /// # Errors
+    /// Returns [`Error::Other`] if the server reports a download error, or if the download
+    /// does not complete within `timeout`.
+    ///
+    /// ```no_run
+    /// # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+    /// use std::time::Duration;
+    ///
+    /// let episode = client
+    ///     .download_podcast_episode_and_wait("ep-1", Duration::from_secs(600), |episode| {
+    ///         println!("{:?}", episode.status);
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_podcast_episode_and_wait(
+        &self,
+        id: impl Into<SongId>,
+        timeout: Duration,
+        mut on_status: impl FnMut(&PodcastEpisode),
+    ) -> Result<PodcastEpisode, Error> {
+        let id: SongId = id.into();
+        let wait = async {
+            self.download_podcast_episode(&id).await?;
+            let mut last = None;
+            loop {
+                let episode = self.get_podcast_episode(&id).await?;
+                if last.as_ref() != Some(&episode.status) {
+                    on_status(&episode);
+                    last = Some(episode.status.clone());
+                }
+                match episode.status {
+                    PodcastStatus::Completed => return Ok(episode),
+                    PodcastStatus::Error => {
+                        return Err(Error::Other(format!(
+                            "Server failed to download podcast episode {id}"
+                        )));
+                    }
+                    _ => tokio::time::sleep(EPISODE_POLL_INTERVAL).await,
+                }
+            }
+        };
+        tokio::time::timeout(timeout, wait).await.map_err(|_| {
+            Error::Other(format!(
+                "Podcast episode {id} was not downloaded within {timeout:?}"
+            ))
+        })?
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::testing::MockServer;
+    use crate::testing::fixtures::episode_json;
+
+    #[tokio::test]
+    async fn waits_for_episode_downloads() {
+        let server = MockServer::start().await;
+        let client = server.client();
+        let mut statuses = Vec::new();
+        let episode = client
+            .download_podcast_episode_and_wait("ep-1", Duration::from_secs(5), |e| {
+                statuses.push(e.status.clone())
+            })
+            .await
+            .unwrap();
+        assert_eq!(episode.stream_id.as_deref(), Some("tr-ep-1"));
+        assert_eq!(statuses, [PodcastStatus::Completed]);
+        assert_eq!(server.calls("downloadPodcastEpisode").await, 1);
+
+        let mut failed = episode_json();
+        failed["status"] = "error".into();
+        server
+            .respond_with(
+                "getPodcastEpisode",
+                serde_json::json!({ "podcastEpisode": failed }),
+            )
+            .await;
+        let result = client
+            .download_podcast_episode_and_wait("ep-1", Duration::from_secs(5), |_| {})
+            .await;
+        assert!(matches!(result, Err(Error::Other(_))));
+    }
 }
@@ -1,5 +1,9 @@
 //! Media Library Scanning API endpoints.
 
+use std::time::Duration;
+
+use futures_util::{Stream, stream};
+
 use crate::Client;
 use crate::data::ScanStatus;
 use crate::error::Error;
@@ -9,21 +13,68 @@ impl Client {
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getscanstatus/>
     pub async fn get_scan_status(&self) -> Result<ScanStatus, Error> {
-        let data = self.get_response("getScanStatus", &[]).await?;
+        let mut data = self.get_response("getScanStatus", &[]).await?;
         let status = data
-            .get("scanStatus")
+            .remove("scanStatus")
             .ok_or_else(|| Error::Parse("Missing 'scanStatus' in response".into()))?;
-        Ok(serde_json::from_value(status.clone())?)
+        self.decode(status)
     }
 
     /// Start a media library scan.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/startscan/>
     pub async fn start_scan(&self) -> Result<ScanStatus, Error> {
-        let data = self.get_response("startScan", &[]).await?;
+        let mut data = self.get_response("startScan", &[]).await?;
         let status = data
-            .get("scanStatus")
+            .remove("scanStatus")
             .ok_or_else(|| Error::Parse("Missing 'scanStatus' in response".into()))?;
-        Ok(serde_json::from_value(status.clone())?)
+        self.decode(status)
+    }
+
+    /// Poll `getScanStatus` until the scan finishes, yielding every status.
+    ///
+    /// The first status is fetched immediately. While the item count keeps growing the
+    /// server is polled every `poll_interval`; when it stalls the interval doubles, up to
+    /// eight times `poll_interval`. The stream ends after yielding a status with `scanning`
+    /// false, or after yielding an error.
+    ///
+    /// ```no_run
+    /// # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+    /// use std::time::Duration;
+    ///
+    /// use futures_util::TryStreamExt;
+    ///
+    /// client.start_scan().await?;
+    /// let mut statuses = std::pin::pin!(client.watch_scan(Duration::from_secs(1)));
+    /// while let Some(status) = statuses.try_next().await? {
+    ///     println!("scanned {} items", status.count.unwrap_or(0));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn watch_scan(
+        &self,
+        poll_interval: Duration,
+    ) -> impl Stream<Item = Result<ScanStatus, Error>> + '_ {
+        let max_delay = poll_interval * 8;
+        // State: the delay before the next poll and the last count, or `None` once finished.
+        stream::try_unfold(Some((Duration::ZERO, None)), move |state| async move {
+            let Some((delay, last_count)) = state else {
+                return Ok(None);
+            };
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+            let status = self.get_scan_status().await?;
+            let next = status.scanning.then(|| {
+                let delay = if delay.is_zero() || status.count != last_count {
+                    poll_interval
+                } else {
+                    (delay * 2).min(max_delay)
+                };
+                (delay, status.count)
+            });
+            Ok(Some((status, next)))
+        })
     }
 }
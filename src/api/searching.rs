@@ -1,12 +1,252 @@
 //! Searching API endpoints.
 
+use futures_util::{Stream, TryStreamExt, stream};
+
 use crate::Client;
-use crate::data::{SearchResult, SearchResult2, SearchResult3};
+use crate::data::{AlbumId3, ArtistId3, Child, SearchResult, SearchResult2, SearchResult3};
 use crate::error::Error;
 
+/// Page size used by the `iter_all_*` streams.
+const ALL_PAGE_SIZE: i32 = 500;
+
+/// Parameters for the legacy `search` endpoint, used by [`Client::search_with`].
+///
+/// ```
+/// use opensubsonic::SearchParams;
+///
+/// let params = SearchParams::new().artist("Beatles").count(20);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchParams {
+    artist: Option<String>,
+    album: Option<String>,
+    title: Option<String>,
+    any: Option<String>,
+    count: Option<i32>,
+    offset: Option<i32>,
+    newer_than: Option<i64>,
+}
+
+impl SearchParams {
+    /// Create empty search parameters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Artist to search for.
+    #[must_use]
+    pub fn artist(mut self, artist: &str) -> Self {
+        self.artist = Some(artist.to_owned());
+        self
+    }
+
+    /// Album to search for.
+    #[must_use]
+    pub fn album(mut self, album: &str) -> Self {
+        self.album = Some(album.to_owned());
+        self
+    }
+
+    /// Song title to search for.
+    #[must_use]
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_owned());
+        self
+    }
+
+    /// Search all fields.
+    #[must_use]
+    pub fn any(mut self, any: &str) -> Self {
+        self.any = Some(any.to_owned());
+        self
+    }
+
+    /// Maximum number of results to return.
+    #[must_use]
+    pub fn count(mut self, count: i32) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Search result offset, for paging.
+    #[must_use]
+    pub fn offset(mut self, offset: i32) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Only return matches newer than this time (milliseconds since the epoch).
+    #[must_use]
+    pub fn newer_than(mut self, millis: i64) -> Self {
+        self.newer_than = Some(millis);
+        self
+    }
+
+    fn to_params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        if let Some(v) = &self.artist {
+            params.push(("artist", v.clone()));
+        }
+        if let Some(v) = &self.album {
+            params.push(("album", v.clone()));
+        }
+        if let Some(v) = &self.title {
+            params.push(("title", v.clone()));
+        }
+        if let Some(v) = &self.any {
+            params.push(("any", v.clone()));
+        }
+        if let Some(v) = self.count {
+            params.push(("count", v.to_string()));
+        }
+        if let Some(v) = self.offset {
+            params.push(("offset", v.to_string()));
+        }
+        if let Some(v) = self.newer_than {
+            params.push(("newerThan", v.to_string()));
+        }
+        params
+    }
+}
+
+/// Parameters for the `search3` endpoint, used by [`Client::search3_with`].
+///
+/// ```
+/// use opensubsonic::Search3Params;
+///
+/// let params = Search3Params::new("love").artist_count(0).album_count(0).song_count(50);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Search3Params {
+    query: String,
+    artist_count: Option<i32>,
+    artist_offset: Option<i32>,
+    album_count: Option<i32>,
+    album_offset: Option<i32>,
+    song_count: Option<i32>,
+    song_offset: Option<i32>,
+    music_folder_id: Option<String>,
+}
+
+/// Parameters for the `search2` endpoint, used by [`Client::search2_with`].
+///
+/// `search2` accepts the same parameters as `search3`.
+pub type Search2Params = Search3Params;
+
+impl Search3Params {
+    /// Search for `query` (an empty query matches everything on most servers).
+    pub fn new(query: &str) -> Self {
+        Self {
+            query: query.to_owned(),
+            ..Self::default()
+        }
+    }
+
+    /// Maximum number of artists to return.
+    #[must_use]
+    pub fn artist_count(mut self, count: i32) -> Self {
+        self.artist_count = Some(count);
+        self
+    }
+
+    /// Artist result offset, for paging.
+    #[must_use]
+    pub fn artist_offset(mut self, offset: i32) -> Self {
+        self.artist_offset = Some(offset);
+        self
+    }
+
+    /// Maximum number of albums to return.
+    #[must_use]
+    pub fn album_count(mut self, count: i32) -> Self {
+        self.album_count = Some(count);
+        self
+    }
+
+    /// Album result offset, for paging.
+    #[must_use]
+    pub fn album_offset(mut self, offset: i32) -> Self {
+        self.album_offset = Some(offset);
+        self
+    }
+
+    /// Maximum number of songs to return.
+    #[must_use]
+    pub fn song_count(mut self, count: i32) -> Self {
+        self.song_count = Some(count);
+        self
+    }
+
+    /// Song result offset, for paging.
+    #[must_use]
+    pub fn song_offset(mut self, offset: i32) -> Self {
+        self.song_offset = Some(offset);
+        self
+    }
+
+    /// Only search within the given music folder.
+    #[must_use]
+    pub fn music_folder_id(mut self, id: &str) -> Self {
+        self.music_folder_id = Some(id.to_owned());
+        self
+    }
+
+    fn to_params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![("query", self.query.clone())];
+        if let Some(v) = self.artist_count {
+            params.push(("artistCount", v.to_string()));
+        }
+        if let Some(v) = self.artist_offset {
+            params.push(("artistOffset", v.to_string()));
+        }
+        if let Some(v) = self.album_count {
+            params.push(("albumCount", v.to_string()));
+        }
+        if let Some(v) = self.album_offset {
+            params.push(("albumOffset", v.to_string()));
+        }
+        if let Some(v) = self.song_count {
+            params.push(("songCount", v.to_string()));
+        }
+        if let Some(v) = self.song_offset {
+            params.push(("songOffset", v.to_string()));
+        }
+        if let Some(id) = &self.music_folder_id {
+            params.push(("musicFolderId", id.clone()));
+        }
+        params
+    }
+
+    /// Build parameters from the positional arguments of [`Client::search2`] / [`Client::search3`].
+    #[allow(clippy::too_many_arguments)]
+    fn from_positional(
+        query: &str,
+        artist_count: Option<i32>,
+        artist_offset: Option<i32>,
+        album_count: Option<i32>,
+        album_offset: Option<i32>,
+        song_count: Option<i32>,
+        song_offset: Option<i32>,
+        music_folder_id: Option<&str>,
+    ) -> Self {
+        Self {
+            query: query.to_owned(),
+            artist_count,
+            artist_offset,
+            album_count,
+            album_offset,
+            song_count,
+            song_offset,
+            music_folder_id: music_folder_id.map(str::to_owned),
+        }
+    }
+}
+
 impl Client {
     /// Search (legacy, pre-1.4.0).
     ///
+    /// Prefer [`Client::search_with`], which takes a [`SearchParams`] builder.
+    ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/search/>
     #[allow(clippy::too_many_arguments)]
     pub async fn search(
@@ -19,39 +259,35 @@ impl Client {
         offset: Option<i32>,
         newer_than: Option<i64>,
     ) -> Result<SearchResult, Error> {
-        let mut params = Vec::new();
-        if let Some(v) = artist {
-            params.push(("artist", v.to_string()));
-        }
-        if let Some(v) = album {
-            params.push(("album", v.to_string()));
-        }
-        if let Some(v) = title {
-            params.push(("title", v.to_string()));
-        }
-        if let Some(v) = any {
-            params.push(("any", v.to_string()));
-        }
-        if let Some(v) = count {
-            params.push(("count", v.to_string()));
-        }
-        if let Some(v) = offset {
-            params.push(("offset", v.to_string()));
-        }
-        if let Some(v) = newer_than {
-            params.push(("newerThan", v.to_string()));
-        }
+        let params = SearchParams {
+            artist: artist.map(str::to_owned),
+            album: album.map(str::to_owned),
+            title: title.map(str::to_owned),
+            any: any.map(str::to_owned),
+            count,
+            offset,
+            newer_than,
+        };
+        self.search_with(&params).await
+    }
+
+    /// Search (legacy, pre-1.4.0) with the given [`SearchParams`].
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/search/>
+    pub async fn search_with(&self, params: &SearchParams) -> Result<SearchResult, Error> {
+        let params = params.to_params();
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let data = self.get_response("search", &param_refs).await?;
+        let mut data = self.get_response("search", &param_refs).await?;
         let result = data
-            .get("searchResult")
-            .cloned()
+            .remove("searchResult")
             .unwrap_or_else(|| serde_json::Value::Object(serde_json::Map::new()));
-        Ok(serde_json::from_value(result)?)
+        self.decode(result)
     }
 
     /// Search (folder-based, search2).
     ///
+    /// Prefer [`Client::search2_with`], which takes a [`Search2Params`] builder.
+    ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/search2/>
     #[allow(clippy::too_many_arguments)]
     pub async fn search2(
@@ -65,38 +301,36 @@ impl Client {
         song_offset: Option<i32>,
         music_folder_id: Option<&str>,
     ) -> Result<SearchResult2, Error> {
-        let mut params = vec![("query", query.to_string())];
-        if let Some(v) = artist_count {
-            params.push(("artistCount", v.to_string()));
-        }
-        if let Some(v) = artist_offset {
-            params.push(("artistOffset", v.to_string()));
-        }
-        if let Some(v) = album_count {
-            params.push(("albumCount", v.to_string()));
-        }
-        if let Some(v) = album_offset {
-            params.push(("albumOffset", v.to_string()));
-        }
-        if let Some(v) = song_count {
-            params.push(("songCount", v.to_string()));
-        }
-        if let Some(v) = song_offset {
-            params.push(("songOffset", v.to_string()));
-        }
-        if let Some(id) = music_folder_id {
-            params.push(("musicFolderId", id.to_string()));
-        }
+        let params = Search2Params::from_positional(
+            query,
+            artist_count,
+            artist_offset,
+            album_count,
+            album_offset,
+            song_count,
+            song_offset,
+            music_folder_id,
+        );
+        self.search2_with(&params).await
+    }
+
+    /// Search (folder-based, search2) with the given [`Search2Params`].
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/search2/>
+    pub async fn search2_with(&self, params: &Search2Params) -> Result<SearchResult2, Error> {
+        let params = params.to_params();
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let data = self.get_response("search2", &param_refs).await?;
+        let mut data = self.get_response("search2", &param_refs).await?;
         let result = data
-            .get("searchResult2")
+            .remove("searchResult2")
             .ok_or_else(|| Error::Parse("Missing 'searchResult2' in response".into()))?;
-        Ok(serde_json::from_value(result.clone())?)
+        self.decode(result)
     }
 
     /// Search (ID3-based, search3).
     ///
+    /// Prefer [`Client::search3_with`], which takes a [`Search3Params`] builder.
+    ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/search3/>
     #[allow(clippy::too_many_arguments)]
     pub async fn search3(
@@ -110,33 +344,149 @@ impl Client {
         song_offset: Option<i32>,
         music_folder_id: Option<&str>,
     ) -> Result<SearchResult3, Error> {
-        let mut params = vec![("query", query.to_string())];
-        if let Some(v) = artist_count {
-            params.push(("artistCount", v.to_string()));
-        }
-        if let Some(v) = artist_offset {
-            params.push(("artistOffset", v.to_string()));
-        }
-        if let Some(v) = album_count {
-            params.push(("albumCount", v.to_string()));
-        }
-        if let Some(v) = album_offset {
-            params.push(("albumOffset", v.to_string()));
-        }
-        if let Some(v) = song_count {
-            params.push(("songCount", v.to_string()));
-        }
-        if let Some(v) = song_offset {
-            params.push(("songOffset", v.to_string()));
-        }
-        if let Some(id) = music_folder_id {
-            params.push(("musicFolderId", id.to_string()));
-        }
+        let params = Search3Params::from_positional(
+            query,
+            artist_count,
+            artist_offset,
+            album_count,
+            album_offset,
+            song_count,
+            song_offset,
+            music_folder_id,
+        );
+        self.search3_with(&params).await
+    }
+
+    /// Search (ID3-based, search3) with the given [`Search3Params`].
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/search3/>
+    pub async fn search3_with(&self, params: &Search3Params) -> Result<SearchResult3, Error> {
+        let params = params.to_params();
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let data = self.get_response("search3", &param_refs).await?;
+        let mut data = self.get_response("search3", &param_refs).await?;
         let result = data
-            .get("searchResult3")
+            .remove("searchResult3")
             .ok_or_else(|| Error::Parse("Missing 'searchResult3' in response".into()))?;
-        Ok(serde_json::from_value(result.clone())?)
+        self.decode(result)
+    }
+
+    /// Stream every artist in the library, paging through `search3` with an empty query.
+    ///
+    /// See [`Client::iter_all_songs`] for how paging works and how unsupported servers are
+    /// detected.
+    pub fn iter_all_artists(&self) -> impl Stream<Item = Result<ArtistId3, Error>> + '_ {
+        self.search3_all(
+            |params, offset| params.artist_count(ALL_PAGE_SIZE).artist_offset(offset),
+            |result| result.artist,
+        )
+    }
+
+    /// Stream every album in the library, paging through `search3` with an empty query.
+    ///
+    /// See [`Client::iter_all_songs`] for how paging works and how unsupported servers are
+    /// detected.
+    pub fn iter_all_albums(&self) -> impl Stream<Item = Result<AlbumId3, Error>> + '_ {
+        self.search3_all(
+            |params, offset| params.album_count(ALL_PAGE_SIZE).album_offset(offset),
+            |result| result.album,
+        )
+    }
+
+    /// Stream every song in the library, paging through `search3` with an empty query.
+    ///
+    /// OpenSubsonic servers answer an empty `search3` query with the whole library.
+    /// Successive requests of 500 items are issued lazily until the server returns an empty
+    /// page. If the very first page is empty although `getScanStatus` reports scanned
+    /// files, the server does not support empty queries and the stream yields
+    /// [`Error::Other`].
+    ///
+    /// ```no_run
+    /// # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+    /// use futures_util::TryStreamExt;
+    ///
+    /// let songs: Vec<_> = client.iter_all_songs().try_collect().await?;
+    /// println!("{} songs", songs.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_all_songs(&self) -> impl Stream<Item = Result<Child, Error>> + '_ {
+        self.search3_all(
+            |params, offset| params.song_count(ALL_PAGE_SIZE).song_offset(offset),
+            |result| result.song,
+        )
+    }
+
+    /// Page through an empty `search3` query. `page` requests one kind of result at
+    /// `offset` (the others have a count of zero) and `take` extracts it.
+    fn search3_all<T: 'static>(
+        &self,
+        page: fn(Search3Params, i32) -> Search3Params,
+        take: fn(SearchResult3) -> Vec<T>,
+    ) -> impl Stream<Item = Result<T, Error>> + '_ {
+        stream::try_unfold(Some(0), move |offset| async move {
+            let Some(offset) = offset else {
+                return Ok::<_, Error>(None);
+            };
+            let none = Search3Params::new("")
+                .artist_count(0)
+                .album_count(0)
+                .song_count(0);
+            let items = take(self.search3_with(&page(none, offset)).await?);
+            if items.is_empty() && offset == 0 {
+                self.check_library_empty().await?;
+            }
+            // Advance by what was returned: the server may cap the page size.
+            let next = (!items.is_empty()).then(|| offset + items.len() as i32);
+            Ok(Some((items, next)))
+        })
+        .map_ok(|items| stream::iter(items.into_iter().map(Ok)))
+        .try_flatten()
+    }
+
+    /// Tell an empty library from a server that returns nothing for an empty query.
+    async fn check_library_empty(&self) -> Result<(), Error> {
+        // Servers without a usable scan status get the benefit of the doubt.
+        let scanned = self
+            .get_scan_status()
+            .await
+            .ok()
+            .and_then(|status| status.count)
+            .unwrap_or(0);
+        if scanned > 0 {
+            return Err(Error::Other(format!(
+                "search3 with an empty query returned nothing although {scanned} files are \
+                 scanned; the server does not support listing the library this way"
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use futures_util::TryStreamExt;
+    use serde_json::json;
+
+    use crate::testing::MockServer;
+
+    #[tokio::test]
+    async fn iter_all_detects_unsupported_servers() {
+        let server = MockServer::start().await;
+        let client = server.client();
+        let songs: Vec<_> = client.iter_all_songs().try_collect().await.unwrap();
+        assert_eq!(songs.len(), 1);
+        assert_eq!(server.calls("search3").await, 2);
+
+        server
+            .respond_with("search3", json!({ "searchResult3": {} }))
+            .await;
+        server
+            .respond_with(
+                "getScanStatus",
+                json!({ "scanStatus": { "scanning": false, "count": 3 } }),
+            )
+            .await;
+        let err = client.iter_all_albums().try_collect::<Vec<_>>().await;
+        assert!(matches!(err, Err(crate::Error::Other(_))));
     }
 }
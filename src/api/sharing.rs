@@ -1,21 +1,77 @@
 //! Sharing API endpoints.
 
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 use crate::Client;
-use crate::data::Share;
+use crate::client::take_list;
+use crate::data::{Share, ShareId};
 use crate::error::Error;
 
+/// Description and expiration of a share, for [`Client::create_share_with`] and
+/// [`Client::update_share_with`].
+///
+/// ```
+/// use std::time::Duration;
+/// use opensubsonic::ShareOptions;
+///
+/// let options = ShareOptions::new()
+///     .description("Party mix")
+///     .expires_in(Duration::from_secs(7 * 24 * 3600));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShareOptions {
+    description: Option<String>,
+    expires: Option<SystemTime>,
+}
+
+impl ShareOptions {
+    /// Create empty options (no description, and the share never expires).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Description of the share.
+    #[must_use]
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_owned());
+        self
+    }
+
+    /// Expire the share at the given time.
+    #[must_use]
+    pub fn expires_at(mut self, time: SystemTime) -> Self {
+        self.expires = Some(time);
+        self
+    }
+
+    /// Expire the share after `duration`, counted from now.
+    #[must_use]
+    pub fn expires_in(self, duration: Duration) -> Self {
+        self.expires_at(SystemTime::now() + duration)
+    }
+
+    fn to_params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        if let Some(d) = &self.description {
+            params.push(("description", d.clone()));
+        }
+        if let Some(t) = self.expires {
+            // Milliseconds since the epoch; times before it are clamped to it.
+            let millis = t.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis());
+            params.push(("expires", millis.to_string()));
+        }
+        params
+    }
+}
+
 impl Client {
     /// Get all shares.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getshares/>
     pub async fn get_shares(&self) -> Result<Vec<Share>, Error> {
-        let data = self.get_response("getShares", &[]).await?;
-        let shares = data
-            .get("shares")
-            .and_then(|v| v.get("share"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(shares)?)
+        let mut data = self.get_response("getShares", &[]).await?;
+        let shares = take_list(&mut data, "shares", "share");
+        self.decode_list(shares)
     }
 
     /// Create a new share.
@@ -38,13 +94,25 @@ impl Client {
             params.push(("expires", e.to_string()));
         }
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let data = self.get_response("createShare", &param_refs).await?;
-        let shares = data
-            .get("shares")
-            .and_then(|v| v.get("share"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(shares)?)
+        let mut data = self.get_response("createShare", &param_refs).await?;
+        let shares = take_list(&mut data, "shares", "share");
+        self.decode_list(shares)
+    }
+
+    /// Create a new share with the given [`ShareOptions`].
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/createshare/>
+    pub async fn create_share_with(
+        &self,
+        ids: &[&str],
+        options: &ShareOptions,
+    ) -> Result<Vec<Share>, Error> {
+        let mut params: Vec<(&str, String)> = ids.iter().map(|id| ("id", id.to_string())).collect();
+        params.extend(options.to_params());
+        let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        let mut data = self.get_response("createShare", &param_refs).await?;
+        let shares = take_list(&mut data, "shares", "share");
+        self.decode_list(shares)
     }
 
     /// Update an existing share.
@@ -52,10 +120,11 @@ impl Client {
     /// See <https://opensubsonic.netlify.app/docs/endpoints/updateshare/>
     pub async fn update_share(
         &self,
-        id: &str,
+        id: impl Into<ShareId>,
         description: Option<&str>,
         expires: Option<i64>,
     ) -> Result<(), Error> {
+        let id: ShareId = id.into();
         let mut params = vec![("id", id.to_string())];
         if let Some(d) = description {
             params.push(("description", d.to_string()));
@@ -68,11 +137,58 @@ impl Client {
         Ok(())
     }
 
+    /// Update the description and expiration of a share with the given [`ShareOptions`].
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/updateshare/>
+    pub async fn update_share_with(
+        &self,
+        id: impl Into<ShareId>,
+        options: &ShareOptions,
+    ) -> Result<(), Error> {
+        let id: ShareId = id.into();
+        let mut params = vec![("id", id.to_string())];
+        params.extend(options.to_params());
+        let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        self.get_response("updateShare", &param_refs).await?;
+        Ok(())
+    }
+
     /// Delete an existing share.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/deleteshare/>
-    pub async fn delete_share(&self, id: &str) -> Result<(), Error> {
-        self.get_response("deleteShare", &[("id", id)]).await?;
+    pub async fn delete_share(&self, id: impl Into<ShareId>) -> Result<(), Error> {
+        let id: ShareId = id.into();
+        self.get_response("deleteShare", &[("id", &id)]).await?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Timestamp;
+
+    #[test]
+    fn share_expiry_is_typed() {
+        let options = ShareOptions::new().expires_at(UNIX_EPOCH + Duration::from_secs(90));
+        assert_eq!(options.to_params(), [("expires", "90000".to_owned())]);
+
+        let share = Share::new(
+            "sh-1",
+            "https://s/1",
+            "bob",
+            Timestamp::new("2024-01-01"),
+            0,
+        );
+        assert!(!share.is_expired());
+        let share = share.with_expires(Timestamp::new("2024-03-05T10:20:30Z"));
+        assert!(share.is_expired());
+        let later = SystemTime::now() + Duration::from_secs(3600);
+        let millis = later.duration_since(UNIX_EPOCH).unwrap().as_millis();
+        assert!(
+            !share
+                .with_expires(Timestamp::new(millis.to_string()))
+                .is_expired()
+        );
+    }
+}
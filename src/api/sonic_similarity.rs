@@ -1,7 +1,8 @@
 //! Sonic Similarity API endpoints (OpenSubsonic extension).
 
 use crate::Client;
-use crate::data::SonicMatch;
+use crate::client::take_list;
+use crate::data::{SongId, SonicMatch};
 use crate::error::Error;
 
 impl Client {
@@ -10,23 +11,20 @@ impl Client {
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getsonicsimilartracks/>
     pub async fn get_sonic_similar_tracks(
         &self,
-        id: &str,
+        id: impl Into<SongId>,
         count: Option<i32>,
     ) -> Result<Vec<SonicMatch>, Error> {
+        let id: SongId = id.into();
         let mut params = vec![("id", id.to_string())];
         if let Some(c) = count {
             params.push(("count", c.to_string()));
         }
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let data = self
+        let mut data = self
             .get_response("getSonicSimilarTracks", &param_refs)
             .await?;
-        let matches = data
-            .get("sonicSimilarTracks")
-            .and_then(|v| v.get("sonicMatch"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(matches)?)
+        let matches = take_list(&mut data, "sonicSimilarTracks", "sonicMatch");
+        self.decode_list(matches)
     }
 
     /// Find a path of sonically similar tracks between two songs
@@ -35,10 +33,12 @@ impl Client {
     /// See <https://opensubsonic.netlify.app/docs/endpoints/findsonicpath/>
     pub async fn find_sonic_path(
         &self,
-        start_song_id: &str,
-        end_song_id: &str,
+        start_song_id: impl Into<SongId>,
+        end_song_id: impl Into<SongId>,
         count: Option<i32>,
     ) -> Result<Vec<SonicMatch>, Error> {
+        let end_song_id: SongId = end_song_id.into();
+        let start_song_id: SongId = start_song_id.into();
         let mut params = vec![
             ("startSongId", start_song_id.to_string()),
             ("endSongId", end_song_id.to_string()),
@@ -47,12 +47,8 @@ impl Client {
             params.push(("count", c.to_string()));
         }
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let data = self.get_response("findSonicPath", &param_refs).await?;
-        let matches = data
-            .get("sonicPath")
-            .and_then(|v| v.get("sonicMatch"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(matches)?)
+        let mut data = self.get_response("findSonicPath", &param_refs).await?;
+        let matches = take_list(&mut data, "sonicPath", "sonicMatch");
+        self.decode_list(matches)
     }
 }
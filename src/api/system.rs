@@ -1,8 +1,185 @@
 //! System API endpoints: `ping`, `getLicense`, `getOpenSubsonicExtensions`, `tokenInfo`.
 
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::Ordering;
+
+use serde::{Deserialize, Serialize};
+
 use crate::Client;
+use crate::ServerProfile;
 use crate::data::{License, OpenSubsonicExtension, TokenInfo};
-use crate::error::Error;
+use crate::error::{Error, SubsonicApiError, SubsonicErrorCode};
+
+/// The OpenSubsonic extensions this crate takes advantage of.
+///
+/// Parse an extension name with [`str::parse`], and check whether a server supports one with
+/// [`Capabilities::supports`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum KnownExtension {
+    /// `apiKeyAuthentication`: [`Auth::api_key`](crate::Auth::api_key) authentication.
+    ApiKeyAuthentication,
+    /// `formPost`: parameters sent as a POST form body (see [`Client::with_form_post`]).
+    FormPost,
+    /// `getPodcastEpisode`: fetching a single podcast episode.
+    GetPodcastEpisode,
+    /// `indexBasedQueue`: [`Client::get_play_queue_by_index`] and
+    /// [`Client::save_play_queue_by_index`].
+    IndexBasedQueue,
+    /// `playbackReport`: [`Client::report_playback`].
+    PlaybackReport,
+    /// `songLyrics`: [`Client::get_lyrics_by_song_id`].
+    SongLyrics,
+    /// `transcodeOffset`: `stream` honours `timeOffset` for audio as well as video.
+    TranscodeOffset,
+    /// `transcoding`: [`Client::get_transcode_decision`] and `getTranscodeStream`.
+    Transcoding,
+}
+
+impl KnownExtension {
+    /// Every known extension.
+    pub const ALL: [Self; 8] = [
+        Self::ApiKeyAuthentication,
+        Self::FormPost,
+        Self::GetPodcastEpisode,
+        Self::IndexBasedQueue,
+        Self::PlaybackReport,
+        Self::SongLyrics,
+        Self::TranscodeOffset,
+        Self::Transcoding,
+    ];
+
+    /// The extension name, as listed by `getOpenSubsonicExtensions`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ApiKeyAuthentication => "apiKeyAuthentication",
+            Self::FormPost => "formPost",
+            Self::GetPodcastEpisode => "getPodcastEpisode",
+            Self::IndexBasedQueue => "indexBasedQueue",
+            Self::PlaybackReport => "playbackReport",
+            Self::SongLyrics => "songLyrics",
+            Self::TranscodeOffset => "transcodeOffset",
+            Self::Transcoding => "transcoding",
+        }
+    }
+
+    /// The version of the extension this crate implements.
+    pub fn version(self) -> i32 {
+        1
+    }
+}
+
+impl fmt::Display for KnownExtension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for KnownExtension {
+    type Err = Error;
+
+    /// Parse an extension name such as `songLyrics`.
+    ///
+    /// # Errors
+    /// Returns [`Error::Other`] for extensions this crate does not know.
+    fn from_str(name: &str) -> Result<Self, Error> {
+        Self::ALL
+            .into_iter()
+            .find(|e| e.name() == name)
+            .ok_or_else(|| Error::Other(format!("Unknown OpenSubsonic extension: {name}")))
+    }
+}
+
+/// The OpenSubsonic extensions a server supports, as returned by [`Client::capabilities`].
+///
+/// Plain Subsonic servers support none.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Capabilities {
+    extensions: Vec<OpenSubsonicExtension>,
+}
+
+impl Capabilities {
+    /// Capabilities made of the given extensions.
+    pub fn new(extensions: Vec<OpenSubsonicExtension>) -> Self {
+        Self { extensions }
+    }
+
+    /// All supported extensions.
+    pub fn extensions(&self) -> &[OpenSubsonicExtension] {
+        &self.extensions
+    }
+
+    /// Whether the server supports version `version` of the extension `name`.
+    pub fn has_extension(&self, name: &str, version: i32) -> bool {
+        self.extensions
+            .iter()
+            .any(|e| e.name == name && e.versions.contains(&version))
+    }
+
+    /// Whether the server supports the version of `extension` this crate implements.
+    pub fn supports(&self, extension: KnownExtension) -> bool {
+        self.has_extension(extension.name(), extension.version())
+    }
+
+    /// The supported extensions this crate takes advantage of.
+    pub fn known_extensions(&self) -> Vec<KnownExtension> {
+        KnownExtension::ALL
+            .into_iter()
+            .filter(|e| self.supports(*e))
+            .collect()
+    }
+
+    /// Whether API calls may send their parameters as a POST form body (`formPost`).
+    pub fn form_post(&self) -> bool {
+        self.supports(KnownExtension::FormPost)
+    }
+
+    /// Whether [`Auth::api_key`](crate::Auth::api_key) authentication is accepted
+    /// (`apiKeyAuthentication`).
+    pub fn api_key_authentication(&self) -> bool {
+        self.supports(KnownExtension::ApiKeyAuthentication)
+    }
+
+    /// Whether `stream` honours `timeOffset` for audio as well as video (`transcodeOffset`).
+    pub fn transcode_offset(&self) -> bool {
+        self.supports(KnownExtension::TranscodeOffset)
+    }
+
+    /// Whether [`Client::get_lyrics_by_song_id`] is available (`songLyrics`).
+    pub fn song_lyrics(&self) -> bool {
+        self.supports(KnownExtension::SongLyrics)
+    }
+
+    /// Whether [`Client::get_play_queue_by_index`] and [`Client::save_play_queue_by_index`]
+    /// are available (`indexBasedQueue`).
+    pub fn index_based_queue(&self) -> bool {
+        self.supports(KnownExtension::IndexBasedQueue)
+    }
+}
+
+/// Details of a server, as returned by [`Client::ping_info`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerInfo {
+    /// Protocol version implemented by the server (e.g. `"1.16.1"`).
+    pub api_version: String,
+    /// Server implementation (OpenSubsonic, e.g. `"navidrome"`).
+    pub server_type: Option<String>,
+    /// Server software version (OpenSubsonic, e.g. `"0.52.0"`).
+    pub server_version: Option<String>,
+    /// Whether the server supports OpenSubsonic extensions.
+    pub open_subsonic: bool,
+}
+
+impl ServerInfo {
+    /// The [`ServerProfile`] matching [`server_type`](Self::server_type).
+    pub fn profile(&self) -> ServerProfile {
+        self.server_type
+            .as_deref()
+            .map_or(ServerProfile::Generic, ServerProfile::from_server_type)
+    }
+}
 
 impl Client {
     /// Test connectivity with the server. Returns `Ok(())` on success.
@@ -13,37 +190,220 @@ impl Client {
         Ok(())
     }
 
+    /// Test connectivity with the server and return the details from its response
+    /// envelope: protocol version, server type and version, and OpenSubsonic support.
+    ///
+    /// ```no_run
+    /// # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+    /// let info = client.ping_info().await?;
+    /// let server = info.server_type.as_deref().unwrap_or("subsonic");
+    /// println!("Connected to {server} (API {})", info.api_version);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/ping/>
+    ///
+    /// # Errors
+    /// Returns [`Error::Parse`] if the response does not carry the protocol version.
+    pub async fn ping_info(&self) -> Result<ServerInfo, Error> {
+        let (_, meta) = self.get_response_meta("ping", &[]).await?;
+        let api_version = meta
+            .version
+            .ok_or_else(|| Error::Parse("Missing 'version' in response".into()))?;
+        Ok(ServerInfo {
+            api_version,
+            server_type: meta.server_type,
+            server_version: meta.server_version,
+            open_subsonic: meta.open_subsonic,
+        })
+    }
+
     /// Get details about the software license.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getlicense/>
     pub async fn get_license(&self) -> Result<License, Error> {
-        let data = self.get_response("getLicense", &[]).await?;
+        let mut data = self.get_response("getLicense", &[]).await?;
         let license = data
-            .get("license")
+            .remove("license")
             .ok_or_else(|| Error::Parse("Missing 'license' in response".into()))?;
-        Ok(serde_json::from_value(license.clone())?)
+        self.decode(license)
     }
 
     /// Get the list of OpenSubsonic API extensions supported by the server.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getopensubsonicextensions/>
     pub async fn get_open_subsonic_extensions(&self) -> Result<Vec<OpenSubsonicExtension>, Error> {
-        let data = self.get_response("getOpenSubsonicExtensions", &[]).await?;
-        let extensions = data
-            .get("openSubsonicExtensions")
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(extensions)?)
+        let mut data = self.get_response("getOpenSubsonicExtensions", &[]).await?;
+        let extensions = data.remove("openSubsonicExtensions");
+        self.decode_list(extensions)
+    }
+
+    /// The server's OpenSubsonic extensions, fetched with `getOpenSubsonicExtensions` on the
+    /// first call and cached for the lifetime of the client (and its clones).
+    ///
+    /// Once cached, the client consults them automatically: [`Client::negotiate_form_post`]
+    /// reuses them and [`Client::get_lyrics_by_song_id`] fails without a request when
+    /// `songLyrics` is not supported.
+    ///
+    /// A server that does not know `getOpenSubsonicExtensions` (a plain Subsonic server) has
+    /// no extensions. Any other error, such as wrong credentials, is returned and not cached.
+    pub async fn capabilities(&self) -> Result<Capabilities, Error> {
+        if let Some(cached) = self.cached_capabilities() {
+            return Ok(cached);
+        }
+        let capabilities = match self.get_open_subsonic_extensions().await {
+            Ok(extensions) => Capabilities::new(extensions),
+            Err(e) if e.api_error().is_some_and(is_unknown_endpoint) => Capabilities::default(),
+            Err(e) => return Err(e),
+        };
+        *self.capabilities.lock().unwrap_or_else(|e| e.into_inner()) = Some(capabilities.clone());
+        Ok(capabilities)
+    }
+
+    /// The cached [`Client::capabilities`], if they have been fetched.
+    pub fn cached_capabilities(&self) -> Option<Capabilities> {
+        self.capabilities
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Whether the cached capabilities include `extension`, or `None` if they have not been
+    /// fetched.
+    pub(crate) fn supports(&self, extension: KnownExtension) -> Option<bool> {
+        let cached = self.capabilities.lock().unwrap_or_else(|e| e.into_inner());
+        cached.as_ref().map(|c| c.supports(extension))
+    }
+
+    /// Enable the `formPost` extension if the server advertises it, returning whether it is
+    /// now in use.
+    ///
+    /// See [`Client::with_form_post`]. Servers without OpenSubsonic support leave it disabled.
+    pub async fn negotiate_form_post(&self) -> Result<bool, Error> {
+        let supported = self.capabilities().await?.form_post();
+        self.form_post.store(supported, Ordering::Relaxed);
+        Ok(supported)
     }
 
     /// Get information about the API token (OpenSubsonic extension).
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/tokeninfo/>
     pub async fn token_info(&self) -> Result<TokenInfo, Error> {
-        let data = self.get_response("tokenInfo", &[]).await?;
+        let mut data = self.get_response("tokenInfo", &[]).await?;
         let info = data
-            .get("tokenInfo")
+            .remove("tokenInfo")
             .ok_or_else(|| Error::Parse("Missing 'tokenInfo' in response".into()))?;
-        Ok(serde_json::from_value(info.clone())?)
+        self.decode(info)
+    }
+}
+
+/// Whether `error` is how plain Subsonic servers answer an endpoint they do not implement:
+/// "not found", or a generic error naming the unknown request.
+fn is_unknown_endpoint(error: &SubsonicApiError) -> bool {
+    match error.error_code() {
+        SubsonicErrorCode::NotFound => true,
+        SubsonicErrorCode::Generic => {
+            let message = error.message.to_lowercase();
+            ["unknown", "not found", "not implemented", "not supported"]
+                .iter()
+                .any(|needle| message.contains(needle))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::KnownExtension;
+    use crate::error::Error;
+    use crate::testing::MockServer;
+
+    #[tokio::test]
+    async fn capabilities_are_fetched_once() {
+        let server = MockServer::start().await;
+        let client = server.client();
+
+        let capabilities = client.capabilities().await.unwrap();
+        assert!(capabilities.has_extension("songLyrics", 1));
+        assert!(!capabilities.has_extension("songLyrics", 2));
+        assert!(!capabilities.api_key_authentication());
+        assert_eq!(
+            capabilities.known_extensions(),
+            [
+                KnownExtension::FormPost,
+                KnownExtension::SongLyrics,
+                KnownExtension::TranscodeOffset,
+            ]
+        );
+        assert_eq!(
+            "songLyrics".parse::<KnownExtension>().unwrap(),
+            KnownExtension::SongLyrics
+        );
+        assert!("sonicSimilarity".parse::<KnownExtension>().is_err());
+        assert!(client.negotiate_form_post().await.unwrap());
+        assert_eq!(server.calls("getOpenSubsonicExtensions").await, 1);
+    }
+
+    #[tokio::test]
+    async fn plain_subsonic_servers_have_no_extensions() {
+        let server = MockServer::start().await;
+        server
+            .fail_with("getOpenSubsonicExtensions", 70, "Not found")
+            .await;
+        let client = server.client();
+
+        let capabilities = client.capabilities().await.unwrap();
+        assert!(capabilities.known_extensions().is_empty());
+        assert!(!client.negotiate_form_post().await.unwrap());
+        assert_eq!(server.calls("getOpenSubsonicExtensions").await, 1);
+    }
+
+    #[tokio::test]
+    async fn other_errors_are_not_cached() {
+        let server = MockServer::start().await;
+        server
+            .fail_with(
+                "getOpenSubsonicExtensions",
+                40,
+                "Wrong username or password",
+            )
+            .await;
+        let client = server.client();
+
+        let error = client.capabilities().await.unwrap_err();
+        assert_eq!(error.api_error().map(|e| e.code), Some(40));
+        assert!(client.cached_capabilities().is_none());
+        assert!(client.negotiate_form_post().await.is_err());
+        assert_eq!(server.calls("getOpenSubsonicExtensions").await, 2);
+
+        let server = MockServer::start().await;
+        server
+            .fail_with(
+                "getOpenSubsonicExtensions",
+                0,
+                "Unknown request: getOpenSubsonicExtensions",
+            )
+            .await;
+        let capabilities = server.client().capabilities().await.unwrap();
+        assert!(capabilities.known_extensions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn ping_info_reads_the_envelope() {
+        let server = MockServer::start().await;
+        let info = server.client().ping_info().await.unwrap();
+        assert_eq!(info.api_version, "1.16.1");
+        assert_eq!(info.server_type.as_deref(), Some("opensubsonic-mock"));
+        assert!(info.open_subsonic);
+
+        let server = MockServer::start().await;
+        let envelope = serde_json::json!({ "subsonic-response": { "status": "ok" } });
+        wiremock::Mock::given(wiremock::matchers::path("/rest/ping"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_json(envelope))
+            .mount(server.inner())
+            .await;
+        let err = server.client().ping_info().await.unwrap_err();
+        assert!(matches!(err.inner(), Error::Parse(_)));
     }
 }
@@ -4,7 +4,9 @@ use bytes::Bytes;
 use url::Url;
 
 use crate::Client;
-use crate::data::TranscodeDecision;
+use crate::api::media_retrieval::StreamOptions;
+use crate::auth::redact_url;
+use crate::data::{SongId, TranscodeDecision};
 use crate::error::Error;
 
 impl Client {
@@ -15,11 +17,12 @@ impl Client {
     /// See <https://opensubsonic.netlify.app/docs/endpoints/gettranscodedecision/>
     pub async fn get_transcode_decision(
         &self,
-        id: &str,
+        id: impl Into<SongId>,
         max_bit_rate: Option<i32>,
         format: Option<&str>,
         client_info: Option<&crate::data::ClientInfo>,
     ) -> Result<TranscodeDecision, Error> {
+        let id: SongId = id.into();
         // This is a POST endpoint with query params for id/maxBitRate/format
         // and JSON body for clientInfo. For simplicity, we use GET params when no body.
         let mut params = vec![("id", id.to_string())];
@@ -35,19 +38,16 @@ impl Client {
             let param_refs: Vec<(&str, &str)> =
                 params.iter().map(|(k, v)| (*k, v.as_str())).collect();
             let url = self.build_url("getTranscodeDecision", &param_refs)?;
-            log::debug!("POST {url}");
+            log::debug!("POST {}", redact_url(&url));
             let resp = self
-                .http
-                .post(url)
-                .json(info)
-                .send()
+                .send(self.http.post(url).json(info))
                 .await?
                 .error_for_status()?;
             let text = resp.text().await?;
-            let wrapper: serde_json::Value =
+            let mut wrapper: serde_json::Value =
                 serde_json::from_str(&text).map_err(|e| Error::Parse(format!("{e}: {text}")))?;
             let inner = wrapper
-                .get("subsonic-response")
+                .get_mut("subsonic-response")
                 .ok_or_else(|| Error::Parse("Missing subsonic-response".into()))?;
             let status = inner.get("status").and_then(|s| s.as_str()).unwrap_or("");
             if status != "ok" {
@@ -69,19 +69,20 @@ impl Client {
                 }));
             }
             let decision = inner
-                .get("transcodeDecision")
+                .get_mut("transcodeDecision")
+                .map(serde_json::Value::take)
                 .ok_or_else(|| Error::Parse("Missing 'transcodeDecision' in response".into()))?;
-            Ok(serde_json::from_value(decision.clone())?)
+            self.decode(decision)
         } else {
             let param_refs: Vec<(&str, &str)> =
                 params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let data = self
+            let mut data = self
                 .get_response("getTranscodeDecision", &param_refs)
                 .await?;
             let decision = data
-                .get("transcodeDecision")
+                .remove("transcodeDecision")
                 .ok_or_else(|| Error::Parse("Missing 'transcodeDecision' in response".into()))?;
-            Ok(serde_json::from_value(decision.clone())?)
+            self.decode(decision)
         }
     }
 
@@ -92,17 +93,25 @@ impl Client {
     /// See <https://opensubsonic.netlify.app/docs/endpoints/gettranscodestream/>
     pub fn get_transcode_stream_url(
         &self,
-        id: &str,
+        id: impl Into<SongId>,
         max_bit_rate: Option<i32>,
         format: Option<&str>,
     ) -> Result<Url, Error> {
-        let mut params = vec![("id", id.to_string())];
-        if let Some(br) = max_bit_rate {
-            params.push(("maxBitRate", br.to_string()));
-        }
-        if let Some(f) = format {
-            params.push(("format", f.to_string()));
-        }
+        let id: SongId = id.into();
+        self.get_transcode_stream_url_with(id, &transcode_options(max_bit_rate, format))
+    }
+
+    /// Get a transcoded stream URL using [`StreamOptions`] (OpenSubsonic extension).
+    ///
+    /// Accepts the same options as [`Client::get_transcode_stream_with`], including
+    /// `timeOffset` and `estimateContentLength`, so external players can seek.
+    pub fn get_transcode_stream_url_with(
+        &self,
+        id: impl Into<SongId>,
+        options: &StreamOptions,
+    ) -> Result<Url, Error> {
+        let id: SongId = id.into();
+        let params = options.to_params(&id);
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
         self.build_url("getTranscodeStream", &param_refs)
     }
@@ -112,18 +121,38 @@ impl Client {
     /// See <https://opensubsonic.netlify.app/docs/endpoints/gettranscodestream/>
     pub async fn get_transcode_stream(
         &self,
-        id: &str,
+        id: impl Into<SongId>,
         max_bit_rate: Option<i32>,
         format: Option<&str>,
     ) -> Result<Bytes, Error> {
-        let mut params = vec![("id", id.to_string())];
-        if let Some(br) = max_bit_rate {
-            params.push(("maxBitRate", br.to_string()));
-        }
-        if let Some(f) = format {
-            params.push(("format", f.to_string()));
-        }
+        let id: SongId = id.into();
+        self.get_transcode_stream_with(id, &transcode_options(max_bit_rate, format))
+            .await
+    }
+
+    /// Get a transcoded stream as raw bytes using [`StreamOptions`] (OpenSubsonic extension).
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/gettranscodestream/>
+    pub async fn get_transcode_stream_with(
+        &self,
+        id: impl Into<SongId>,
+        options: &StreamOptions,
+    ) -> Result<Bytes, Error> {
+        let id: SongId = id.into();
+        let params = options.to_params(&id);
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
         self.get_bytes("getTranscodeStream", &param_refs).await
     }
 }
+
+/// Map the legacy positional transcode-stream arguments onto [`StreamOptions`].
+fn transcode_options(max_bit_rate: Option<i32>, format: Option<&str>) -> StreamOptions {
+    let mut options = StreamOptions::new();
+    if let Some(br) = max_bit_rate {
+        options = options.max_bit_rate(br);
+    }
+    if let Some(f) = format {
+        options = options.format(f);
+    }
+    options
+}
@@ -1,39 +1,265 @@
 //! User Management API endpoints.
 
 use crate::Client;
+use crate::auth::Secret;
+use crate::client::take_list;
 use crate::data::User;
 use crate::error::Error;
 
+/// Generate setters for the role flags stored in a builder's `settings`.
+macro_rules! role_setters {
+    ($($name:ident => $doc:literal,)*) => {$(
+        #[doc = $doc]
+        #[must_use]
+        pub fn $name(mut self, enabled: bool) -> Self {
+            self.settings.$name = Some(enabled);
+            self
+        }
+    )*};
+}
+
+/// Settings shared by `createUser` and `updateUser`.
+#[derive(Debug, Clone, Default)]
+struct UserSettings {
+    ldap_authenticated: Option<bool>,
+    admin_role: Option<bool>,
+    settings_role: Option<bool>,
+    stream_role: Option<bool>,
+    jukebox_role: Option<bool>,
+    download_role: Option<bool>,
+    upload_role: Option<bool>,
+    playlist_role: Option<bool>,
+    cover_art_role: Option<bool>,
+    comment_role: Option<bool>,
+    podcast_role: Option<bool>,
+    share_role: Option<bool>,
+    video_conversion_role: Option<bool>,
+    music_folder_ids: Vec<i64>,
+}
+
+impl UserSettings {
+    fn push_params(&self, params: &mut Vec<(&'static str, String)>) {
+        let flags = [
+            ("ldapAuthenticated", self.ldap_authenticated),
+            ("adminRole", self.admin_role),
+            ("settingsRole", self.settings_role),
+            ("streamRole", self.stream_role),
+            ("jukeboxRole", self.jukebox_role),
+            ("downloadRole", self.download_role),
+            ("uploadRole", self.upload_role),
+            ("playlistRole", self.playlist_role),
+            ("coverArtRole", self.cover_art_role),
+            ("commentRole", self.comment_role),
+            ("podcastRole", self.podcast_role),
+            ("shareRole", self.share_role),
+            ("videoConversionRole", self.video_conversion_role),
+        ];
+        for (name, value) in flags {
+            if let Some(v) = value {
+                params.push((name, v.to_string()));
+            }
+        }
+        for folder_id in &self.music_folder_ids {
+            params.push(("musicFolderId", folder_id.to_string()));
+        }
+    }
+}
+
+/// Parameters for [`Client::create_user_with`].
+///
+/// Roles that are not set are left to the server's defaults.
+///
+/// ```
+/// use opensubsonic::CreateUserParams;
+///
+/// let params = CreateUserParams::new("alice", "sesame", "alice@example.com")
+///     .stream_role(true)
+///     .download_role(true)
+///     .music_folder_ids(&[1, 2]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CreateUserParams {
+    username: String,
+    password: Secret,
+    email: String,
+    settings: UserSettings,
+}
+
+impl CreateUserParams {
+    /// Parameters for a user with the given name, password and email address.
+    pub fn new(username: &str, password: &str, email: &str) -> Self {
+        Self {
+            username: username.to_owned(),
+            password: Secret::new(password),
+            email: email.to_owned(),
+            settings: UserSettings::default(),
+        }
+    }
+
+    role_setters! {
+        ldap_authenticated => "Whether the user is authenticated in LDAP.",
+        admin_role => "Whether the user is an administrator.",
+        settings_role => "Whether the user may change personal settings and password.",
+        stream_role => "Whether the user may play files.",
+        jukebox_role => "Whether the user may play files in jukebox mode.",
+        download_role => "Whether the user may download files.",
+        upload_role => "Whether the user may upload files.",
+        playlist_role => "Whether the user may create and delete playlists.",
+        cover_art_role => "Whether the user may change cover art and tags.",
+        comment_role => "Whether the user may create and edit comments and ratings.",
+        podcast_role => "Whether the user may administrate Podcasts.",
+        share_role => "Whether the user may share files with anyone.",
+        video_conversion_role => "Whether the user may start video conversions.",
+    }
+
+    /// Music folders the user may access (all of them if not set).
+    #[must_use]
+    pub fn music_folder_ids(mut self, ids: &[i64]) -> Self {
+        self.settings.music_folder_ids = ids.to_vec();
+        self
+    }
+
+    fn to_params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![
+            ("username", self.username.clone()),
+            ("password", self.password.expose().to_owned()),
+            ("email", self.email.clone()),
+        ];
+        self.settings.push_params(&mut params);
+        params
+    }
+}
+
+/// Parameters for [`Client::update_user_with`].
+///
+/// Only the settings that are set are changed.
+///
+/// ```
+/// use opensubsonic::UpdateUserParams;
+///
+/// let params = UpdateUserParams::new("alice").admin_role(false).max_bit_rate(320);
+/// ```
+#[derive(Debug, Clone)]
+pub struct UpdateUserParams {
+    username: String,
+    password: Option<Secret>,
+    email: Option<String>,
+    max_bit_rate: Option<i32>,
+    settings: UserSettings,
+}
+
+impl UpdateUserParams {
+    /// Parameters changing nothing yet for the user `username`.
+    pub fn new(username: &str) -> Self {
+        Self {
+            username: username.to_owned(),
+            password: None,
+            email: None,
+            max_bit_rate: None,
+            settings: UserSettings::default(),
+        }
+    }
+
+    /// New password.
+    #[must_use]
+    pub fn password(mut self, password: &str) -> Self {
+        self.password = Some(Secret::new(password));
+        self
+    }
+
+    /// New email address.
+    #[must_use]
+    pub fn email(mut self, email: &str) -> Self {
+        self.email = Some(email.to_owned());
+        self
+    }
+
+    /// Maximum bit rate (in Kbps) for the user, `0` for no limit.
+    #[must_use]
+    pub fn max_bit_rate(mut self, kbps: i32) -> Self {
+        self.max_bit_rate = Some(kbps);
+        self
+    }
+
+    role_setters! {
+        ldap_authenticated => "Whether the user is authenticated in LDAP.",
+        admin_role => "Whether the user is an administrator.",
+        settings_role => "Whether the user may change personal settings and password.",
+        stream_role => "Whether the user may play files.",
+        jukebox_role => "Whether the user may play files in jukebox mode.",
+        download_role => "Whether the user may download files.",
+        upload_role => "Whether the user may upload files.",
+        playlist_role => "Whether the user may create and delete playlists.",
+        cover_art_role => "Whether the user may change cover art and tags.",
+        comment_role => "Whether the user may create and edit comments and ratings.",
+        podcast_role => "Whether the user may administrate Podcasts.",
+        share_role => "Whether the user may share files with anyone.",
+        video_conversion_role => "Whether the user may start video conversions.",
+    }
+
+    /// Music folders the user may access.
+    #[must_use]
+    pub fn music_folder_ids(mut self, ids: &[i64]) -> Self {
+        self.settings.music_folder_ids = ids.to_vec();
+        self
+    }
+
+    fn to_params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![("username", self.username.clone())];
+        if let Some(v) = &self.password {
+            params.push(("password", v.expose().to_owned()));
+        }
+        if let Some(v) = &self.email {
+            params.push(("email", v.clone()));
+        }
+        if let Some(v) = self.max_bit_rate {
+            params.push(("maxBitRate", v.to_string()));
+        }
+        self.settings.push_params(&mut params);
+        params
+    }
+}
+
 impl Client {
     /// Get details about a specific user.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getuser/>
     pub async fn get_user(&self, username: &str) -> Result<User, Error> {
-        let data = self
+        let mut data = self
             .get_response("getUser", &[("username", username)])
             .await?;
         let user = data
-            .get("user")
+            .remove("user")
             .ok_or_else(|| Error::Parse("Missing 'user' in response".into()))?;
-        Ok(serde_json::from_value(user.clone())?)
+        self.decode(user)
     }
 
     /// Get details about all users (admin only).
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/getusers/>
     pub async fn get_users(&self) -> Result<Vec<User>, Error> {
-        let data = self.get_response("getUsers", &[]).await?;
-        let users = data
-            .get("users")
-            .and_then(|v| v.get("user"))
-            .cloned()
-            .unwrap_or_else(|| serde_json::Value::Array(vec![]));
-        Ok(serde_json::from_value(users)?)
+        let mut data = self.get_response("getUsers", &[]).await?;
+        let users = take_list(&mut data, "users", "user");
+        self.decode_list(users)
     }
 
     /// Create a new user (admin only).
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/createuser/>
+    pub async fn create_user_with(&self, params: &CreateUserParams) -> Result<(), Error> {
+        let params = params.to_params();
+        let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        self.get_response("createUser", &param_refs).await?;
+        Ok(())
+    }
+
+    /// Create a new user (admin only), with positional parameters.
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/createuser/>
+    #[deprecated(
+        since = "0.5.0",
+        note = "use `create_user_with` and `CreateUserParams`"
+    )]
     #[allow(clippy::too_many_arguments)]
     pub async fn create_user(
         &self,
@@ -55,61 +281,45 @@ impl Client {
         video_conversion_role: Option<bool>,
         music_folder_ids: &[i64],
     ) -> Result<(), Error> {
-        let mut params = vec![
-            ("username", username.to_string()),
-            ("password", password.to_string()),
-            ("email", email.to_string()),
-        ];
-        if let Some(v) = ldap_authenticated {
-            params.push(("ldapAuthenticated", v.to_string()));
-        }
-        if let Some(v) = admin_role {
-            params.push(("adminRole", v.to_string()));
-        }
-        if let Some(v) = settings_role {
-            params.push(("settingsRole", v.to_string()));
-        }
-        if let Some(v) = stream_role {
-            params.push(("streamRole", v.to_string()));
-        }
-        if let Some(v) = jukebox_role {
-            params.push(("jukeboxRole", v.to_string()));
-        }
-        if let Some(v) = download_role {
-            params.push(("downloadRole", v.to_string()));
-        }
-        if let Some(v) = upload_role {
-            params.push(("uploadRole", v.to_string()));
-        }
-        if let Some(v) = playlist_role {
-            params.push(("playlistRole", v.to_string()));
-        }
-        if let Some(v) = cover_art_role {
-            params.push(("coverArtRole", v.to_string()));
-        }
-        if let Some(v) = comment_role {
-            params.push(("commentRole", v.to_string()));
-        }
-        if let Some(v) = podcast_role {
-            params.push(("podcastRole", v.to_string()));
-        }
-        if let Some(v) = share_role {
-            params.push(("shareRole", v.to_string()));
-        }
-        if let Some(v) = video_conversion_role {
-            params.push(("videoConversionRole", v.to_string()));
-        }
-        for folder_id in music_folder_ids {
-            params.push(("musicFolderId", folder_id.to_string()));
-        }
+        let params = CreateUserParams {
+            settings: UserSettings {
+                ldap_authenticated,
+                admin_role,
+                settings_role,
+                stream_role,
+                jukebox_role,
+                download_role,
+                upload_role,
+                playlist_role,
+                cover_art_role,
+                comment_role,
+                podcast_role,
+                share_role,
+                video_conversion_role,
+                music_folder_ids: music_folder_ids.to_vec(),
+            },
+            ..CreateUserParams::new(username, password, email)
+        };
+        self.create_user_with(&params).await
+    }
+
+    /// Update an existing user (admin only).
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/updateuser/>
+    pub async fn update_user_with(&self, params: &UpdateUserParams) -> Result<(), Error> {
+        let params = params.to_params();
         let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        self.get_response("createUser", &param_refs).await?;
+        self.get_response("updateUser", &param_refs).await?;
         Ok(())
     }
 
-    /// Update an existing user (admin only).
+    /// Update an existing user (admin only), with positional parameters.
     ///
     /// See <https://opensubsonic.netlify.app/docs/endpoints/updateuser/>
+    #[deprecated(
+        since = "0.5.0",
+        note = "use `update_user_with` and `UpdateUserParams`"
+    )]
     #[allow(clippy::too_many_arguments)]
     pub async fn update_user(
         &self,
@@ -132,61 +342,29 @@ impl Client {
         max_bit_rate: Option<i32>,
         music_folder_ids: &[i64],
     ) -> Result<(), Error> {
-        let mut params = vec![("username", username.to_string())];
-        if let Some(v) = password {
-            params.push(("password", v.to_string()));
-        }
-        if let Some(v) = email {
-            params.push(("email", v.to_string()));
-        }
-        if let Some(v) = ldap_authenticated {
-            params.push(("ldapAuthenticated", v.to_string()));
-        }
-        if let Some(v) = admin_role {
-            params.push(("adminRole", v.to_string()));
-        }
-        if let Some(v) = settings_role {
-            params.push(("settingsRole", v.to_string()));
-        }
-        if let Some(v) = stream_role {
-            params.push(("streamRole", v.to_string()));
-        }
-        if let Some(v) = jukebox_role {
-            params.push(("jukeboxRole", v.to_string()));
-        }
-        if let Some(v) = download_role {
-            params.push(("downloadRole", v.to_string()));
-        }
-        if let Some(v) = upload_role {
-            params.push(("uploadRole", v.to_string()));
-        }
-        if let Some(v) = playlist_role {
-            params.push(("playlistRole", v.to_string()));
-        }
-        if let Some(v) = cover_art_role {
-            params.push(("coverArtRole", v.to_string()));
-        }
-        if let Some(v) = comment_role {
-            params.push(("commentRole", v.to_string()));
-        }
-        if let Some(v) = podcast_role {
-            params.push(("podcastRole", v.to_string()));
-        }
-        if let Some(v) = share_role {
-            params.push(("shareRole", v.to_string()));
-        }
-        if let Some(v) = video_conversion_role {
-            params.push(("videoConversionRole", v.to_string()));
-        }
-        if let Some(v) = max_bit_rate {
-            params.push(("maxBitRate", v.to_string()));
-        }
-        for folder_id in music_folder_ids {
-            params.push(("musicFolderId", folder_id.to_string()));
-        }
-        let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        self.get_response("updateUser", &param_refs).await?;
-        Ok(())
+        let params = UpdateUserParams {
+            username: username.to_owned(),
+            password: password.map(Secret::new),
+            email: email.map(str::to_owned),
+            max_bit_rate,
+            settings: UserSettings {
+                ldap_authenticated,
+                admin_role,
+                settings_role,
+                stream_role,
+                jukebox_role,
+                download_role,
+                upload_role,
+                playlist_role,
+                cover_art_role,
+                comment_role,
+                podcast_role,
+                share_role,
+                video_conversion_role,
+                music_folder_ids: music_folder_ids.to_vec(),
+            },
+        };
+        self.update_user_with(&params).await
     }
 
     /// Delete a user (admin only).
@@ -210,3 +388,59 @@ impl Client {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Auth;
+
+    #[test]
+    fn user_params_carry_roles_and_password() {
+        let params = CreateUserParams::new("alice", "p&ss=wörd", "alice@example.com")
+            .admin_role(false)
+            .stream_role(true)
+            .video_conversion_role(true)
+            .music_folder_ids(&[1, 2]);
+        let params = params.to_params();
+        assert_eq!(
+            params,
+            [
+                ("username", "alice".to_owned()),
+                ("password", "p&ss=wörd".to_owned()),
+                ("email", "alice@example.com".to_owned()),
+                ("adminRole", "false".to_owned()),
+                ("streamRole", "true".to_owned()),
+                ("videoConversionRole", "true".to_owned()),
+                ("musicFolderId", "1".to_owned()),
+                ("musicFolderId", "2".to_owned()),
+            ]
+        );
+
+        // The password is sent as given, escaped in the query string.
+        let client =
+            Client::new("https://music.example.com", Auth::token("admin", "pass")).unwrap();
+        let refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        let url = client.build_url("createUser", &refs).unwrap();
+        assert!(
+            url.query()
+                .unwrap()
+                .contains("&password=p%26ss%3Dw%C3%B6rd&")
+        );
+
+        let params = UpdateUserParams::new("alice")
+            .password("sesame")
+            .max_bit_rate(320)
+            .ldap_authenticated(true);
+        assert_eq!(
+            params.to_params(),
+            [
+                ("username", "alice".to_owned()),
+                ("password", "sesame".to_owned()),
+                ("maxBitRate", "320".to_owned()),
+                ("ldapAuthenticated", "true".to_owned()),
+            ]
+        );
+        assert!(!format!("{params:?}").contains("sesame"));
+        assert_eq!(UpdateUserParams::new("bob").to_params().len(), 1);
+    }
+}
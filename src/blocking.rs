@@ -0,0 +1,1088 @@
+//! Synchronous client (requires the `blocking` feature).
+//!
+//! [`Client`] offers the API of [`crate::Client`] with blocking methods, for CLI tools and
+//! GUI applications that do not run an async runtime. It drives the async client on a
+//! private single-threaded Tokio runtime shared by all of its clones.
+//!
+//! The blocking client must not be used from within an async runtime; calling it from an
+//! async context panics, like any nested `block_on`.
+//!
+//! ```no_run
+//! use opensubsonic::Auth;
+//! use opensubsonic::blocking::Client;
+//!
+//! let client = Client::new("https://music.example.com", Auth::token("admin", "pass"))?;
+//! client.ping()?;
+//! for index in client.get_artists(None)?.index {
+//!     for artist in index.artist {
+//!         println!("{}", artist.name);
+//!     }
+//! }
+//! # Ok::<(), opensubsonic::Error>(())
+//! ```
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures_util::StreamExt;
+use tokio::runtime::Runtime;
+use url::Url;
+
+#[cfg(feature = "sharing")]
+use crate::ShareOptions;
+use crate::auth::AuthProvider;
+#[cfg(feature = "video")]
+use crate::captions::{CaptionCue, CaptionFormat};
+use crate::conditional::{Conditional, ValidatorStore};
+use crate::data::*;
+use crate::error::Error;
+use crate::hls::HlsPlaylist;
+use crate::playlist_io::{EntryLocation, ImportReport, PlaylistFormat};
+#[cfg(feature = "podcast")]
+use crate::podcast_io::OpmlImportReport;
+#[cfg(feature = "internet-radio")]
+use crate::radio_io::{StationFormat, StationImportReport};
+use crate::stats::LibraryStats;
+use crate::{
+    AlbumListParams, AlbumListType, Annotation, AnnotationReport, BatchReport, CancelToken,
+    Capabilities, Discography, DownloadEvent, DownloadHandle, DownloadItem, DownloadOptions,
+    DownloadReport, MediaProbe, MediaResponse, NowPlayingChange, PlayQueueSession,
+    RandomSongsParams, ResponseMeta, Search2Params, Search3Params, SearchParams, ServerInfo,
+    ServerProfile, StarItem, Starred2Content, StarredContent, StreamOptions,
+};
+#[cfg(feature = "user-management")]
+use crate::{CreateUserParams, UpdateUserParams};
+#[cfg(feature = "jukebox")]
+use crate::{JukeboxAction, JukeboxResult};
+
+/// Blocking OpenSubsonic API client.
+///
+/// Cloning is cheap: clones share the underlying connection pool and runtime.
+#[derive(Debug, Clone)]
+pub struct Client {
+    inner: crate::Client,
+    runtime: Arc<Runtime>,
+}
+
+impl Client {
+    /// Create a new blocking client (see [`crate::Client::new`]).
+    ///
+    /// # Errors
+    /// Returns [`Error::Url`] if the base URL cannot be parsed, or [`Error::Io`] if the
+    /// runtime cannot be started.
+    pub fn new(base_url: &str, auth: impl AuthProvider) -> Result<Self, Error> {
+        Self::from_async(crate::Client::new(base_url, auth)?)
+    }
+
+    /// Wrap an already configured async client, e.g. one built with
+    /// [`crate::Client::builder`].
+    ///
+    /// # Errors
+    /// Returns [`Error::Io`] if the runtime cannot be started.
+    pub fn from_async(client: crate::Client) -> Result<Self, Error> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(Self {
+            inner: client,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// The wrapped async client.
+    pub fn async_client(&self) -> &crate::Client {
+        &self.inner
+    }
+
+    /// Time out every request after `timeout` (see [`crate::Client::with_timeout`]).
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.inner = self.inner.with_timeout(timeout);
+        self
+    }
+
+    /// Whether API calls are sent as POST form bodies (see [`crate::Client::uses_form_post`]).
+    pub fn uses_form_post(&self) -> bool {
+        self.inner.uses_form_post()
+    }
+
+    /// The server profile in use (see [`crate::Client::server_profile`]).
+    pub fn server_profile(&self) -> ServerProfile {
+        self.inner.server_profile()
+    }
+
+    /// The cached server capabilities (see [`crate::Client::cached_capabilities`]).
+    pub fn cached_capabilities(&self) -> Option<Capabilities> {
+        self.inner.cached_capabilities()
+    }
+
+    /// Envelope metadata of the last successful response (see
+    /// [`crate::Client::last_response_meta`]).
+    pub fn last_response_meta(&self) -> Option<ResponseMeta> {
+        self.inner.last_response_meta()
+    }
+
+    /// Build a streaming URL (see [`crate::Client::stream_url`]).
+    pub fn stream_url(
+        &self,
+        id: impl Into<SongId>,
+        max_bit_rate: Option<i32>,
+        format: Option<&str>,
+    ) -> Result<Url, Error> {
+        self.inner.stream_url(id, max_bit_rate, format)
+    }
+
+    /// Build a streaming URL from [`StreamOptions`] (see [`crate::Client::stream_url_with`]).
+    pub fn stream_url_with(
+        &self,
+        id: impl Into<SongId>,
+        options: &StreamOptions,
+    ) -> Result<Url, Error> {
+        self.inner.stream_url_with(id, options)
+    }
+
+    /// Build a download URL (see [`crate::Client::download_url`]).
+    pub fn download_url(&self, id: impl Into<SongId>) -> Result<Url, Error> {
+        self.inner.download_url(id)
+    }
+
+    /// Build an HLS playlist URL (see [`crate::Client::hls_url`]).
+    pub fn hls_url(
+        &self,
+        id: impl Into<SongId>,
+        bit_rate: Option<&str>,
+        audio_track: Option<&str>,
+    ) -> Result<Url, Error> {
+        self.inner.hls_url(id, bit_rate, audio_track)
+    }
+
+    /// Build a captions URL (see [`crate::Client::captions_url`]).
+    #[cfg(feature = "video")]
+    pub fn captions_url(
+        &self,
+        id: impl Into<SongId>,
+        format: Option<CaptionFormat>,
+    ) -> Result<Url, Error> {
+        self.inner.captions_url(id, format)
+    }
+
+    /// Build a cover art URL (see [`crate::Client::cover_art_url`]).
+    pub fn cover_art_url(
+        &self,
+        id: impl Into<CoverArtId>,
+        size: Option<i32>,
+    ) -> Result<Url, Error> {
+        self.inner.cover_art_url(id, size)
+    }
+
+    /// Build an avatar URL (see [`crate::Client::avatar_url`]).
+    pub fn avatar_url(&self, username: &str) -> Result<Url, Error> {
+        self.inner.avatar_url(username)
+    }
+
+    /// Build a transcoded stream URL (see [`crate::Client::get_transcode_stream_url`]).
+    pub fn get_transcode_stream_url(
+        &self,
+        id: impl Into<SongId>,
+        max_bit_rate: Option<i32>,
+        format: Option<&str>,
+    ) -> Result<Url, Error> {
+        self.inner
+            .get_transcode_stream_url(id, max_bit_rate, format)
+    }
+
+    /// Build a transcoded stream URL from [`StreamOptions`] (see
+    /// [`crate::Client::get_transcode_stream_url_with`]).
+    pub fn get_transcode_stream_url_with(
+        &self,
+        id: impl Into<SongId>,
+        options: &StreamOptions,
+    ) -> Result<Url, Error> {
+        self.inner.get_transcode_stream_url_with(id, options)
+    }
+
+    /// Render a playlist as M3U, M3U8 or XSPF (see [`crate::Client::export_playlist`]).
+    pub fn export_playlist(
+        &self,
+        playlist: &PlaylistWithSongs,
+        format: PlaylistFormat,
+        location: &EntryLocation,
+    ) -> Result<String, Error> {
+        self.inner.export_playlist(playlist, format, location)
+    }
+
+    /// Iterate over every album of an album list, fetching pages as needed (see
+    /// [`crate::Client::album_list2_stream`]).
+    pub fn album_list2_iter(
+        &self,
+        list_type: AlbumListType,
+        params: AlbumListParams,
+    ) -> impl Iterator<Item = Result<AlbumId3, Error>> + '_ {
+        let mut stream = Box::pin(self.inner.album_list2_stream(list_type, params));
+        std::iter::from_fn(move || self.block_on(stream.next()))
+    }
+
+    /// Iterate over random songs without end (see [`crate::Client::random_songs_stream`]).
+    pub fn random_songs_iter(
+        &self,
+        params: RandomSongsParams,
+    ) -> impl Iterator<Item = Result<Child, Error>> + '_ {
+        let mut stream = Box::pin(self.inner.random_songs_stream(params));
+        std::iter::from_fn(move || self.block_on(stream.next()))
+    }
+
+    /// Iterate over every song of a genre (see [`crate::Client::songs_by_genre_stream`]).
+    pub fn songs_by_genre_iter<'a>(
+        &'a self,
+        genre: &'a str,
+        music_folder_id: Option<&'a str>,
+    ) -> impl Iterator<Item = Result<Child, Error>> + 'a {
+        let mut stream = Box::pin(self.inner.songs_by_genre_stream(genre, music_folder_id));
+        std::iter::from_fn(move || self.block_on(stream.next()))
+    }
+
+    /// Iterate over every artist in the library (see [`crate::Client::iter_all_artists`]).
+    pub fn iter_all_artists(&self) -> impl Iterator<Item = Result<ArtistId3, Error>> + '_ {
+        let mut stream = Box::pin(self.inner.iter_all_artists());
+        std::iter::from_fn(move || self.block_on(stream.next()))
+    }
+
+    /// Iterate over every album in the library (see [`crate::Client::iter_all_albums`]).
+    pub fn iter_all_albums(&self) -> impl Iterator<Item = Result<AlbumId3, Error>> + '_ {
+        let mut stream = Box::pin(self.inner.iter_all_albums());
+        std::iter::from_fn(move || self.block_on(stream.next()))
+    }
+
+    /// Iterate over every song in the library (see [`crate::Client::iter_all_songs`]).
+    pub fn iter_all_songs(&self) -> impl Iterator<Item = Result<Child, Error>> + '_ {
+        let mut stream = Box::pin(self.inner.iter_all_songs());
+        std::iter::from_fn(move || self.block_on(stream.next()))
+    }
+
+    /// Iterate over every song in the library (see [`crate::Client::walk_library`]).
+    pub fn walk_library(
+        &self,
+        music_folder_id: Option<&str>,
+    ) -> impl Iterator<Item = Result<Child, Error>> + '_ {
+        let mut stream = Box::pin(self.inner.walk_library(music_folder_id));
+        std::iter::from_fn(move || self.block_on(stream.next()))
+    }
+
+    /// Poll the scan status until the scan finishes (see [`crate::Client::watch_scan`]).
+    pub fn watch_scan_iter(
+        &self,
+        poll_interval: Duration,
+    ) -> impl Iterator<Item = Result<ScanStatus, Error>> + '_ {
+        let mut stream = Box::pin(self.inner.watch_scan(poll_interval));
+        std::iter::from_fn(move || self.block_on(stream.next()))
+    }
+
+    /// Watch the chat, yielding each new message (see [`crate::Client::chat_stream`]).
+    #[cfg(feature = "chat")]
+    pub fn chat_iter(
+        &self,
+        poll_interval: Duration,
+    ) -> impl Iterator<Item = Result<ChatMessage, Error>> + '_ {
+        let mut stream = Box::pin(self.inner.chat_stream(poll_interval));
+        std::iter::from_fn(move || self.block_on(stream.next()))
+    }
+
+    /// Poll the now-playing list, yielding only changes (see
+    /// [`crate::Client::now_playing_stream`]).
+    pub fn now_playing_iter(
+        &self,
+        interval: Duration,
+    ) -> impl Iterator<Item = Result<NowPlayingChange, Error>> + '_ {
+        let mut stream = Box::pin(self.inner.now_playing_stream(interval));
+        std::iter::from_fn(move || self.block_on(stream.next()))
+    }
+
+    /// Get a [`JukeboxController`] for higher-level jukebox operations.
+    #[cfg(feature = "jukebox")]
+    pub fn jukebox(&self) -> JukeboxController<'_> {
+        JukeboxController { client: self }
+    }
+
+    /// Get a [`PlaylistManager`] for higher-level playlist editing.
+    pub fn playlist_manager(&self) -> PlaylistManager<'_> {
+        PlaylistManager { client: self }
+    }
+
+    /// Start an [`Annotations`] batch (see [`crate::Client::annotations`]).
+    pub fn annotations(&self) -> Annotations<'_> {
+        Annotations {
+            client: self,
+            inner: self.inner.annotations(),
+        }
+    }
+
+    /// Get a [`Downloader`] writing into `dir` (see [`crate::Client::downloader`]).
+    pub fn downloader(&self, dir: impl Into<PathBuf>) -> Downloader<'_> {
+        Downloader {
+            client: self,
+            inner: self.inner.downloader(dir),
+        }
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+}
+
+impl From<crate::Client> for Client {
+    /// Wrap an async client.
+    ///
+    /// # Panics
+    /// Panics if the runtime cannot be started; use [`Client::from_async`] to handle that.
+    fn from(client: crate::Client) -> Self {
+        Self::from_async(client).expect("failed to start the blocking client runtime")
+    }
+}
+
+/// Blocking counterpart of [`crate::JukeboxController`].
+///
+/// Obtain one via [`Client::jukebox`].
+#[cfg(feature = "jukebox")]
+#[derive(Debug, Clone, Copy)]
+pub struct JukeboxController<'a> {
+    client: &'a Client,
+}
+
+#[cfg(feature = "jukebox")]
+impl JukeboxController<'_> {
+    /// Get the current jukebox playlist (see [`crate::JukeboxController::get`]).
+    pub fn get(&self) -> Result<JukeboxPlaylist, Error> {
+        self.client.block_on(self.client.inner.jukebox().get())
+    }
+
+    /// Replace the jukebox queue with `ids` (see [`crate::JukeboxController::set_queue`]).
+    pub fn set_queue(&self, ids: &[&str]) -> Result<JukeboxStatus, Error> {
+        self.client
+            .block_on(self.client.inner.jukebox().set_queue(ids))
+    }
+}
+
+/// Blocking counterpart of [`crate::PlaylistManager`].
+///
+/// Obtain one via [`Client::playlist_manager`].
+#[derive(Debug, Clone, Copy)]
+pub struct PlaylistManager<'a> {
+    client: &'a Client,
+}
+
+impl PlaylistManager<'_> {
+    /// Replace the entries of a playlist (see [`crate::PlaylistManager::set_entries`]).
+    pub fn set_entries(
+        &self,
+        playlist_id: impl Into<PlaylistId>,
+        song_ids: &[&str],
+    ) -> Result<(), Error> {
+        let manager = self.client.inner.playlist_manager();
+        self.client
+            .block_on(manager.set_entries(playlist_id, song_ids))
+    }
+
+    /// Move an entry (see [`crate::PlaylistManager::move_entry`]).
+    pub fn move_entry(
+        &self,
+        playlist_id: impl Into<PlaylistId>,
+        from: usize,
+        to: usize,
+    ) -> Result<(), Error> {
+        let manager = self.client.inner.playlist_manager();
+        self.client
+            .block_on(manager.move_entry(playlist_id, from, to))
+    }
+
+    /// Insert songs at an index (see [`crate::PlaylistManager::insert_at`]).
+    pub fn insert_at(
+        &self,
+        playlist_id: impl Into<PlaylistId>,
+        index: usize,
+        song_ids: &[&str],
+    ) -> Result<(), Error> {
+        let manager = self.client.inner.playlist_manager();
+        self.client
+            .block_on(manager.insert_at(playlist_id, index, song_ids))
+    }
+
+    /// Remove repeated songs (see [`crate::PlaylistManager::deduplicate`]).
+    pub fn deduplicate(&self, playlist_id: impl Into<PlaylistId>) -> Result<usize, Error> {
+        let manager = self.client.inner.playlist_manager();
+        self.client.block_on(manager.deduplicate(playlist_id))
+    }
+}
+
+/// Blocking counterpart of [`crate::Annotations`].
+///
+/// Obtain one via [`Client::annotations`].
+#[derive(Debug, Clone)]
+pub struct Annotations<'a> {
+    client: &'a Client,
+    inner: crate::Annotations<'a>,
+}
+
+impl Annotations<'_> {
+    /// See [`crate::Annotations::star`].
+    #[must_use]
+    pub fn star(mut self, item: impl Into<StarItem>) -> Self {
+        self.inner = self.inner.star(item);
+        self
+    }
+
+    /// See [`crate::Annotations::unstar`].
+    #[must_use]
+    pub fn unstar(mut self, item: impl Into<StarItem>) -> Self {
+        self.inner = self.inner.unstar(item);
+        self
+    }
+
+    /// See [`crate::Annotations::rate`].
+    #[must_use]
+    pub fn rate(mut self, id: impl Into<String>, rating: i32) -> Self {
+        self.inner = self.inner.rate(id, rating);
+        self
+    }
+
+    /// See [`crate::Annotations::push`].
+    #[must_use]
+    pub fn push(mut self, operation: Annotation) -> Self {
+        self.inner = self.inner.push(operation);
+        self
+    }
+
+    /// See [`crate::Annotations::concurrency`].
+    #[must_use]
+    pub fn concurrency(mut self, limit: usize) -> Self {
+        self.inner = self.inner.concurrency(limit);
+        self
+    }
+
+    /// See [`crate::Annotations::batch_size`].
+    #[must_use]
+    pub fn batch_size(mut self, items: usize) -> Self {
+        self.inner = self.inner.batch_size(items);
+        self
+    }
+
+    /// Apply the queued operations (see [`crate::Annotations::run`]).
+    pub fn run(&self) -> AnnotationReport {
+        self.client.block_on(self.inner.run())
+    }
+}
+
+/// Blocking counterpart of [`crate::Downloader`].
+///
+/// Obtain one via [`Client::downloader`]. [`run`](Self::run) blocks until the batch is done;
+/// pause or cancel it from another thread with a [`DownloadHandle`].
+#[derive(Debug, Clone)]
+pub struct Downloader<'a> {
+    client: &'a Client,
+    inner: crate::Downloader<'a>,
+}
+
+impl Downloader<'_> {
+    /// See [`crate::Downloader::concurrency`].
+    #[must_use]
+    pub fn concurrency(mut self, limit: usize) -> Self {
+        self.inner = self.inner.concurrency(limit);
+        self
+    }
+
+    /// See [`crate::Downloader::retries`].
+    #[must_use]
+    pub fn retries(mut self, times: u32) -> Self {
+        self.inner = self.inner.retries(times);
+        self
+    }
+
+    /// See [`crate::Downloader::retry_delay`].
+    #[must_use]
+    pub fn retry_delay(mut self, delay: Duration) -> Self {
+        self.inner = self.inner.retry_delay(delay);
+        self
+    }
+
+    /// See [`crate::Downloader::options`].
+    #[must_use]
+    pub fn options(mut self, options: DownloadOptions) -> Self {
+        self.inner = self.inner.options(options);
+        self
+    }
+
+    /// See [`crate::Downloader::file_name`].
+    #[must_use]
+    pub fn file_name(mut self, name: impl Fn(&Child) -> PathBuf + Send + Sync + 'static) -> Self {
+        self.inner = self.inner.file_name(name);
+        self
+    }
+
+    /// A channel receiving progress events (see [`crate::Downloader::subscribe`]); read it
+    /// from another thread with `blocking_recv`.
+    pub fn subscribe(&mut self) -> tokio::sync::mpsc::UnboundedReceiver<DownloadEvent> {
+        self.inner.subscribe()
+    }
+
+    /// See [`crate::Downloader::cancel_token`].
+    #[must_use]
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.inner = self.inner.cancel_token(token);
+        self
+    }
+
+    /// A handle to pause, resume or cancel this downloader.
+    pub fn handle(&self) -> DownloadHandle {
+        self.inner.handle()
+    }
+
+    /// Download `items` (see [`crate::Downloader::run`]).
+    pub fn run(&self, items: &[DownloadItem]) -> BatchReport {
+        self.client.block_on(self.inner.run(items))
+    }
+}
+
+/// Generate blocking wrappers that run the async method of the same name to completion.
+macro_rules! blocking_methods {
+    ($(
+        $(#[$attr:meta])*
+        fn $name:ident(&self $(, $arg:ident: $ty:ty)* $(,)?) -> $ret:ty;
+    )*) => {
+        impl Client {$(
+            #[doc = concat!("Blocking version of [`crate::Client::", stringify!($name), "`].")]
+            $(#[$attr])*
+            pub fn $name(&self $(, $arg: $ty)*) -> $ret {
+                self.block_on(self.inner.$name($($arg),*))
+            }
+        )*}
+    };
+}
+
+blocking_methods! {
+    // ── System ──────────────────────────────────────────────────────────────
+    fn ping(&self) -> Result<(), Error>;
+    fn ping_info(&self) -> Result<ServerInfo, Error>;
+    fn get_license(&self) -> Result<License, Error>;
+    fn get_open_subsonic_extensions(&self) -> Result<Vec<OpenSubsonicExtension>, Error>;
+    fn capabilities(&self) -> Result<Capabilities, Error>;
+    fn negotiate_form_post(&self) -> Result<bool, Error>;
+    fn token_info(&self) -> Result<TokenInfo, Error>;
+    fn get_raw(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+    ) -> Result<serde_json::Map<String, serde_json::Value>, Error>;
+    fn get_raw_bytes(&self, endpoint: &str, params: &[(&str, &str)]) -> Result<Bytes, Error>;
+
+    // ── Browsing ────────────────────────────────────────────────────────────
+    fn get_music_folders(&self) -> Result<Vec<MusicFolder>, Error>;
+    fn get_indexes(
+        &self,
+        music_folder_id: Option<&str>,
+        if_modified_since: Option<i64>,
+    ) -> Result<Indexes, Error>;
+    fn get_music_directory(&self, id: &str) -> Result<Directory, Error>;
+    fn get_genres(&self) -> Result<Vec<Genre>, Error>;
+    fn get_artists(&self, music_folder_id: Option<&str>) -> Result<ArtistsId3, Error>;
+    fn get_artist(&self, id: impl Into<ArtistId>) -> Result<ArtistWithAlbumsId3, Error>;
+    fn get_album(&self, id: impl Into<AlbumId>) -> Result<AlbumWithSongsId3, Error>;
+    fn get_artist_discography(&self, id: impl Into<ArtistId>) -> Result<Discography, Error>;
+    fn get_song(&self, id: impl Into<SongId>) -> Result<Child, Error>;
+    #[cfg(feature = "video")]
+    fn get_videos(&self) -> Result<Vec<Child>, Error>;
+    #[cfg(feature = "video")]
+    fn get_video_info(&self, id: impl Into<SongId>) -> Result<VideoInfo, Error>;
+    fn get_artist_info(
+        &self,
+        id: impl Into<ArtistId>,
+        count: Option<i32>,
+        include_not_present: Option<bool>,
+    ) -> Result<ArtistInfo, Error>;
+    fn get_artist_info2(
+        &self,
+        id: impl Into<ArtistId>,
+        count: Option<i32>,
+        include_not_present: Option<bool>,
+    ) -> Result<ArtistInfo2, Error>;
+    fn get_album_info(&self, id: impl Into<AlbumId>) -> Result<AlbumInfo, Error>;
+    fn get_album_info2(&self, id: impl Into<AlbumId>) -> Result<AlbumInfo, Error>;
+    fn get_similar_songs(&self, id: &str, count: Option<i32>) -> Result<Vec<Child>, Error>;
+    fn get_similar_songs2(&self, id: &str, count: Option<i32>) -> Result<Vec<Child>, Error>;
+    fn get_top_songs(&self, artist: &str, count: Option<i32>) -> Result<Vec<Child>, Error>;
+
+    // ── Lists ───────────────────────────────────────────────────────────────
+    fn get_album_list(
+        &self,
+        list_type: AlbumListType,
+        size: Option<i32>,
+        offset: Option<i32>,
+        music_folder_id: Option<&str>,
+    ) -> Result<Vec<Child>, Error>;
+    fn get_album_list_with(
+        &self,
+        list_type: AlbumListType,
+        params: &AlbumListParams,
+    ) -> Result<Vec<Child>, Error>;
+    fn get_album_list2(
+        &self,
+        list_type: AlbumListType,
+        size: Option<i32>,
+        offset: Option<i32>,
+        music_folder_id: Option<&str>,
+    ) -> Result<Vec<AlbumId3>, Error>;
+    fn get_album_list2_with(
+        &self,
+        list_type: AlbumListType,
+        params: &AlbumListParams,
+    ) -> Result<Vec<AlbumId3>, Error>;
+    fn get_random_songs(
+        &self,
+        size: Option<i32>,
+        genre: Option<&str>,
+        from_year: Option<i32>,
+        to_year: Option<i32>,
+        music_folder_id: Option<&str>,
+    ) -> Result<Vec<Child>, Error>;
+    fn get_random_songs_with(&self, params: &RandomSongsParams) -> Result<Vec<Child>, Error>;
+    fn get_songs_by_genre(
+        &self,
+        genre: &str,
+        count: Option<i32>,
+        offset: Option<i32>,
+        music_folder_id: Option<&str>,
+    ) -> Result<Vec<Child>, Error>;
+    fn get_now_playing(&self) -> Result<Vec<NowPlayingEntry>, Error>;
+    fn get_starred(&self, music_folder_id: Option<&str>) -> Result<StarredContent, Error>;
+    fn get_starred2(&self, music_folder_id: Option<&str>) -> Result<Starred2Content, Error>;
+
+    // ── Searching ───────────────────────────────────────────────────────────
+    #[allow(clippy::too_many_arguments)]
+    fn search(
+        &self,
+        artist: Option<&str>,
+        album: Option<&str>,
+        title: Option<&str>,
+        any: Option<&str>,
+        count: Option<i32>,
+        offset: Option<i32>,
+        newer_than: Option<i64>,
+    ) -> Result<SearchResult, Error>;
+    fn search_with(&self, params: &SearchParams) -> Result<SearchResult, Error>;
+    #[allow(clippy::too_many_arguments)]
+    fn search2(
+        &self,
+        query: &str,
+        artist_count: Option<i32>,
+        artist_offset: Option<i32>,
+        album_count: Option<i32>,
+        album_offset: Option<i32>,
+        song_count: Option<i32>,
+        song_offset: Option<i32>,
+        music_folder_id: Option<&str>,
+    ) -> Result<SearchResult2, Error>;
+    fn search2_with(&self, params: &Search2Params) -> Result<SearchResult2, Error>;
+    #[allow(clippy::too_many_arguments)]
+    fn search3(
+        &self,
+        query: &str,
+        artist_count: Option<i32>,
+        artist_offset: Option<i32>,
+        album_count: Option<i32>,
+        album_offset: Option<i32>,
+        song_count: Option<i32>,
+        song_offset: Option<i32>,
+        music_folder_id: Option<&str>,
+    ) -> Result<SearchResult3, Error>;
+    fn search3_with(&self, params: &Search3Params) -> Result<SearchResult3, Error>;
+
+    // ── Playlists ───────────────────────────────────────────────────────────
+    fn get_playlists(&self, username: Option<&str>) -> Result<Vec<Playlist>, Error>;
+    fn get_playlist(&self, id: impl Into<PlaylistId>) -> Result<PlaylistWithSongs, Error>;
+    fn create_playlist(
+        &self,
+        playlist_id: Option<&PlaylistId>,
+        name: Option<&str>,
+        song_ids: &[&str],
+    ) -> Result<PlaylistWithSongs, Error>;
+    fn update_playlist(
+        &self,
+        playlist_id: impl Into<PlaylistId>,
+        name: Option<&str>,
+        comment: Option<&str>,
+        public: Option<bool>,
+        song_ids_to_add: &[&str],
+        song_indexes_to_remove: &[i32],
+    ) -> Result<(), Error>;
+    fn delete_playlist(&self, id: impl Into<PlaylistId>) -> Result<(), Error>;
+    fn import_playlist(&self, text: &str, format: PlaylistFormat) -> Result<ImportReport, Error>;
+
+    // ── Media retrieval ─────────────────────────────────────────────────────
+    fn stream(
+        &self,
+        id: impl Into<SongId>,
+        max_bit_rate: Option<i32>,
+        format: Option<&str>,
+        time_offset: Option<i32>,
+        estimated_content_length: Option<bool>,
+    ) -> Result<Bytes, Error>;
+    fn stream_with(&self, id: impl Into<SongId>, options: &StreamOptions) -> Result<Bytes, Error>;
+    fn stream_media(
+        &self,
+        id: impl Into<SongId>,
+        options: &StreamOptions,
+    ) -> Result<MediaResponse, Error>;
+    fn stream_at(&self, song: &Child, position: Duration) -> Result<Bytes, Error>;
+    fn probe_media(&self, id: impl Into<SongId>, options: &StreamOptions) -> Result<MediaProbe, Error>;
+    fn probe_download(&self, id: impl Into<SongId>) -> Result<MediaProbe, Error>;
+    fn download(&self, id: impl Into<SongId>) -> Result<Bytes, Error>;
+    fn download_media(&self, id: impl Into<SongId>) -> Result<MediaResponse, Error>;
+    fn hls(
+        &self,
+        id: impl Into<SongId>,
+        bit_rate: Option<&str>,
+        audio_track: Option<&str>,
+    ) -> Result<Bytes, Error>;
+    fn hls_playlist(
+        &self,
+        id: impl Into<SongId>,
+        bit_rate: Option<&str>,
+        audio_track: Option<&str>,
+    ) -> Result<HlsPlaylist, Error>;
+    fn download_to_file(
+        &self,
+        id: impl Into<SongId>,
+        path: impl AsRef<Path>,
+        options: &DownloadOptions,
+    ) -> Result<DownloadReport, Error>;
+    fn download_song_to_file(
+        &self,
+        song: &Child,
+        path: impl AsRef<Path>,
+        options: &DownloadOptions,
+    ) -> Result<DownloadReport, Error>;
+    #[cfg(feature = "video")]
+    fn get_captions(&self, id: impl Into<SongId>, format: Option<&str>) -> Result<Bytes, Error>;
+    #[cfg(feature = "video")]
+    fn get_captions_with(
+        &self,
+        id: impl Into<SongId>,
+        format: Option<CaptionFormat>,
+    ) -> Result<Bytes, Error>;
+    #[cfg(feature = "video")]
+    fn get_caption_cues(
+        &self,
+        id: impl Into<SongId>,
+        format: Option<CaptionFormat>,
+    ) -> Result<Vec<CaptionCue>, Error>;
+    fn get_cover_art(&self, id: impl Into<CoverArtId>, size: Option<i32>) -> Result<Bytes, Error>;
+    fn get_cover_art_media(
+        &self,
+        id: impl Into<CoverArtId>,
+        size: Option<i32>,
+    ) -> Result<MediaResponse, Error>;
+    fn get_cover_art_conditional(
+        &self,
+        id: impl Into<CoverArtId>,
+        size: Option<i32>,
+        store: &dyn ValidatorStore,
+    ) -> Result<Conditional, Error>;
+    fn get_lyrics(&self, artist: Option<&str>, title: Option<&str>) -> Result<Lyrics, Error>;
+    fn get_lyrics_by_song_id(&self, id: impl Into<SongId>, enhanced: Option<bool>) -> Result<LyricsList, Error>;
+    fn get_lyrics_unified(&self, song: &Child) -> Result<Vec<StructuredLyrics>, Error>;
+    fn get_avatar(&self, username: &str) -> Result<Bytes, Error>;
+    fn get_avatar_conditional(&self, username: &str, store: &dyn ValidatorStore) -> Result<Conditional, Error>;
+
+    // ── Media annotation ────────────────────────────────────────────────────
+    fn star(&self, ids: &[&str], album_ids: &[&str], artist_ids: &[&str]) -> Result<(), Error>;
+    fn unstar(&self, ids: &[&str], album_ids: &[&str], artist_ids: &[&str]) -> Result<(), Error>;
+    fn star_song(&self, id: impl Into<SongId>) -> Result<(), Error>;
+    fn star_album(&self, id: impl Into<AlbumId>) -> Result<(), Error>;
+    fn star_artist(&self, id: impl Into<ArtistId>) -> Result<(), Error>;
+    fn unstar_song(&self, id: impl Into<SongId>) -> Result<(), Error>;
+    fn unstar_album(&self, id: impl Into<AlbumId>) -> Result<(), Error>;
+    fn unstar_artist(&self, id: impl Into<ArtistId>) -> Result<(), Error>;
+    fn toggle_star(&self, item: impl Into<StarItem>) -> Result<bool, Error>;
+    fn set_rating(&self, id: &str, rating: i32) -> Result<(), Error>;
+    fn scrobble(&self, id: impl Into<SongId>, time: Option<i64>, submission: Option<bool>) -> Result<(), Error>;
+    fn report_playback(
+        &self,
+        media_id: impl Into<SongId>,
+        media_type: &str,
+        position_ms: i64,
+        state: &str,
+        playback_rate: Option<f64>,
+        ignore_scrobble: Option<bool>,
+    ) -> Result<(), Error>;
+
+    // ── Sharing ─────────────────────────────────────────────────────────────
+    #[cfg(feature = "sharing")]
+    fn get_shares(&self) -> Result<Vec<Share>, Error>;
+    #[cfg(feature = "sharing")]
+    fn create_share(
+        &self,
+        ids: &[&str],
+        description: Option<&str>,
+        expires: Option<i64>,
+    ) -> Result<Vec<Share>, Error>;
+    #[cfg(feature = "sharing")]
+    fn create_share_with(&self, ids: &[&str], options: &ShareOptions) -> Result<Vec<Share>, Error>;
+    #[cfg(feature = "sharing")]
+    fn update_share(
+        &self,
+        id: impl Into<ShareId>,
+        description: Option<&str>,
+        expires: Option<i64>,
+    ) -> Result<(), Error>;
+    #[cfg(feature = "sharing")]
+    fn update_share_with(&self, id: impl Into<ShareId>, options: &ShareOptions) -> Result<(), Error>;
+    #[cfg(feature = "sharing")]
+    fn delete_share(&self, id: impl Into<ShareId>) -> Result<(), Error>;
+
+    // ── Podcast ─────────────────────────────────────────────────────────────
+    #[cfg(feature = "podcast")]
+    fn get_podcasts(
+        &self,
+        include_episodes: Option<bool>,
+        id: Option<&PodcastChannelId>,
+    ) -> Result<Vec<PodcastChannel>, Error>;
+    #[cfg(feature = "podcast")]
+    fn get_newest_podcasts(&self, count: Option<i32>) -> Result<Vec<PodcastEpisode>, Error>;
+    #[cfg(feature = "podcast")]
+    fn get_podcast_episode(&self, id: impl Into<SongId>) -> Result<PodcastEpisode, Error>;
+    #[cfg(feature = "podcast")]
+    fn refresh_podcasts(&self) -> Result<(), Error>;
+    #[cfg(feature = "podcast")]
+    fn create_podcast_channel(&self, url: &str) -> Result<(), Error>;
+    #[cfg(feature = "podcast")]
+    fn delete_podcast_channel(&self, id: impl Into<PodcastChannelId>) -> Result<(), Error>;
+    #[cfg(feature = "podcast")]
+    fn delete_podcast_episode(&self, id: impl Into<SongId>) -> Result<(), Error>;
+    #[cfg(feature = "podcast")]
+    fn download_podcast_episode(&self, id: impl Into<SongId>) -> Result<(), Error>;
+    #[cfg(feature = "podcast")]
+    fn download_podcast_episode_and_wait(
+        &self,
+        id: impl Into<SongId>,
+        timeout: Duration,
+        on_status: impl FnMut(&PodcastEpisode),
+    ) -> Result<PodcastEpisode, Error>;
+    #[cfg(feature = "podcast")]
+    fn export_podcasts_opml(&self) -> Result<String, Error>;
+    #[cfg(feature = "podcast")]
+    fn import_podcasts_opml(&self, text: &str) -> Result<OpmlImportReport, Error>;
+
+    // ── Jukebox ─────────────────────────────────────────────────────────────
+    #[cfg(feature = "jukebox")]
+    fn jukebox_control(
+        &self,
+        action: JukeboxAction,
+        index: Option<i32>,
+        offset: Option<i32>,
+        ids: &[&str],
+        gain: Option<f64>,
+    ) -> Result<JukeboxResult, Error>;
+
+    // ── Internet radio ──────────────────────────────────────────────────────
+    #[cfg(feature = "internet-radio")]
+    fn get_internet_radio_stations(&self) -> Result<Vec<InternetRadioStation>, Error>;
+    #[cfg(feature = "internet-radio")]
+    fn create_internet_radio_station(
+        &self,
+        stream_url: &str,
+        name: &str,
+        home_page_url: Option<&str>,
+    ) -> Result<(), Error>;
+    #[cfg(feature = "internet-radio")]
+    fn update_internet_radio_station(
+        &self,
+        id: impl Into<RadioStationId>,
+        stream_url: &str,
+        name: &str,
+        home_page_url: Option<&str>,
+    ) -> Result<(), Error>;
+    #[cfg(feature = "internet-radio")]
+    fn delete_internet_radio_station(&self, id: impl Into<RadioStationId>) -> Result<(), Error>;
+    #[cfg(feature = "internet-radio")]
+    fn export_internet_radio_stations(&self, format: StationFormat) -> Result<String, Error>;
+    #[cfg(feature = "internet-radio")]
+    fn import_internet_radio_stations(
+        &self,
+        text: &str,
+        format: StationFormat,
+    ) -> Result<StationImportReport, Error>;
+
+    // ── Chat ────────────────────────────────────────────────────────────────
+    #[cfg(feature = "chat")]
+    fn get_chat_messages(&self, since: Option<i64>) -> Result<Vec<ChatMessage>, Error>;
+    #[cfg(feature = "chat")]
+    fn add_chat_message(&self, message: &str) -> Result<(), Error>;
+
+    // ── User management ─────────────────────────────────────────────────────
+    #[cfg(feature = "user-management")]
+    fn get_user(&self, username: &str) -> Result<User, Error>;
+    #[cfg(feature = "user-management")]
+    fn get_users(&self) -> Result<Vec<User>, Error>;
+    #[cfg(feature = "user-management")]
+    fn create_user_with(&self, params: &CreateUserParams) -> Result<(), Error>;
+    #[cfg(feature = "user-management")]
+    fn update_user_with(&self, params: &UpdateUserParams) -> Result<(), Error>;
+    #[cfg(feature = "user-management")]
+    #[deprecated(since = "0.5.0", note = "use `create_user_with` and `CreateUserParams`")]
+    #[allow(deprecated, clippy::too_many_arguments)]
+    fn create_user(
+        &self,
+        username: &str,
+        password: &str,
+        email: &str,
+        ldap_authenticated: Option<bool>,
+        admin_role: Option<bool>,
+        settings_role: Option<bool>,
+        stream_role: Option<bool>,
+        jukebox_role: Option<bool>,
+        download_role: Option<bool>,
+        upload_role: Option<bool>,
+        playlist_role: Option<bool>,
+        cover_art_role: Option<bool>,
+        comment_role: Option<bool>,
+        podcast_role: Option<bool>,
+        share_role: Option<bool>,
+        video_conversion_role: Option<bool>,
+        music_folder_ids: &[i64],
+    ) -> Result<(), Error>;
+    #[cfg(feature = "user-management")]
+    #[deprecated(since = "0.5.0", note = "use `update_user_with` and `UpdateUserParams`")]
+    #[allow(deprecated, clippy::too_many_arguments)]
+    fn update_user(
+        &self,
+        username: &str,
+        password: Option<&str>,
+        email: Option<&str>,
+        ldap_authenticated: Option<bool>,
+        admin_role: Option<bool>,
+        settings_role: Option<bool>,
+        stream_role: Option<bool>,
+        jukebox_role: Option<bool>,
+        download_role: Option<bool>,
+        upload_role: Option<bool>,
+        playlist_role: Option<bool>,
+        cover_art_role: Option<bool>,
+        comment_role: Option<bool>,
+        podcast_role: Option<bool>,
+        share_role: Option<bool>,
+        video_conversion_role: Option<bool>,
+        max_bit_rate: Option<i32>,
+        music_folder_ids: &[i64],
+    ) -> Result<(), Error>;
+    #[cfg(feature = "user-management")]
+    fn delete_user(&self, username: &str) -> Result<(), Error>;
+    #[cfg(feature = "user-management")]
+    fn change_password(&self, username: &str, password: &str) -> Result<(), Error>;
+
+    // ── Bookmarks ───────────────────────────────────────────────────────────
+    fn get_bookmarks(&self) -> Result<Vec<Bookmark>, Error>;
+    fn create_bookmark(&self, id: impl Into<SongId>, position: i64, comment: Option<&str>) -> Result<(), Error>;
+    fn create_bookmark_at(&self, id: impl Into<SongId>, position: Duration, comment: Option<&str>) -> Result<(), Error>;
+    fn delete_bookmark(&self, id: impl Into<SongId>) -> Result<(), Error>;
+    fn get_play_queue(&self) -> Result<PlayQueue, Error>;
+    fn save_play_queue(
+        &self,
+        ids: &[&str],
+        current: Option<&SongId>,
+        position: Option<i64>,
+    ) -> Result<(), Error>;
+    fn save_play_queue_at(
+        &self,
+        ids: &[&str],
+        current: Option<&SongId>,
+        position: Duration,
+    ) -> Result<(), Error>;
+    fn get_play_queue_by_index(&self) -> Result<PlayQueueByIndex, Error>;
+    fn save_play_queue_by_index(
+        &self,
+        ids: &[&str],
+        current_index: Option<i32>,
+        position: Option<i64>,
+    ) -> Result<(), Error>;
+    fn save_play_queue_by_index_at(
+        &self,
+        ids: &[&str],
+        current_index: Option<i32>,
+        position: Duration,
+    ) -> Result<(), Error>;
+    fn play_queue_session(&self) -> Result<PlayQueueSession, Error>;
+    fn save_play_queue_session(&self, session: &mut PlayQueueSession) -> Result<(), Error>;
+
+    // ── Scanning ────────────────────────────────────────────────────────────
+    fn get_scan_status(&self) -> Result<ScanStatus, Error>;
+    fn start_scan(&self) -> Result<ScanStatus, Error>;
+
+    // ── Transcoding ─────────────────────────────────────────────────────────
+    fn get_transcode_decision(
+        &self,
+        id: impl Into<SongId>,
+        max_bit_rate: Option<i32>,
+        format: Option<&str>,
+        client_info: Option<&ClientInfo>,
+    ) -> Result<TranscodeDecision, Error>;
+    fn get_transcode_stream(
+        &self,
+        id: impl Into<SongId>,
+        max_bit_rate: Option<i32>,
+        format: Option<&str>,
+    ) -> Result<Bytes, Error>;
+    fn get_transcode_stream_with(&self, id: impl Into<SongId>, options: &StreamOptions) -> Result<Bytes, Error>;
+
+    // ── Sonic similarity ────────────────────────────────────────────────────
+    fn get_sonic_similar_tracks(
+        &self,
+        id: impl Into<SongId>,
+        count: Option<i32>,
+    ) -> Result<Vec<SonicMatch>, Error>;
+    fn find_sonic_path(
+        &self,
+        start_song_id: impl Into<SongId>,
+        end_song_id: impl Into<SongId>,
+        count: Option<i32>,
+    ) -> Result<Vec<SonicMatch>, Error>;
+
+    // ── Statistics ──────────────────────────────────────────────────────────
+    fn library_stats(&self, music_folder_id: Option<&str>) -> Result<LibraryStats, Error>;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    use super::*;
+    use crate::Auth;
+
+    #[test]
+    fn ping_without_runtime() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            let mut request = [0; 4096];
+            let _ = socket.read(&mut request).unwrap();
+            let body = r#"{"subsonic-response":{"status":"ok","version":"1.16.1"}}"#;
+            write!(
+                socket,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        });
+
+        let client = Client::new(&format!("http://{addr}"), Auth::token("u", "p")).unwrap();
+        client.ping().unwrap();
+        assert_eq!(
+            client.last_response_meta().unwrap().version.as_deref(),
+            Some("1.16.1")
+        );
+        server.join().unwrap();
+    }
+}
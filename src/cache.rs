@@ -0,0 +1,352 @@
+//! Local library cache backed by SQLite (requires the `cache` feature).
+//!
+//! [`LibraryCache`] mirrors the server's artists, albums and songs into a local database using
+//! the crate's own data types, so applications can answer lookups synchronously and offline.
+//! Call [`LibraryCache::refresh`] to re-sync with the server.
+//!
+//! ```no_run
+//! # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+//! use opensubsonic::cache::LibraryCache;
+//!
+//! let cache = LibraryCache::open("library.sqlite")?;
+//! cache.refresh(client).await?;
+//! for song in cache.search_songs("love")? {
+//!     println!("{}", song.title);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+use futures_util::{StreamExt, TryStreamExt};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::Client;
+use crate::api::lists::{AlbumListParams, AlbumListType};
+use crate::data::{AlbumId, AlbumWithSongsId3, ArtistId3, Child};
+use crate::error::Error;
+use crate::stats::LibraryStats;
+
+/// Number of `getAlbum` requests in flight during a refresh.
+const REFRESH_CONCURRENCY: usize = 8;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS artists (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS albums (
+        id TEXT PRIMARY KEY,
+        artist_id TEXT,
+        name TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS songs (
+        id TEXT PRIMARY KEY,
+        album_id TEXT,
+        title TEXT NOT NULL,
+        disc INTEGER,
+        track INTEGER,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS albums_by_artist ON albums (artist_id);
+    CREATE INDEX IF NOT EXISTS songs_by_album ON songs (album_id);
+";
+
+/// Number of items stored in a [`LibraryCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of artists.
+    pub artists: u64,
+    /// Number of albums.
+    pub albums: u64,
+    /// Number of songs.
+    pub songs: u64,
+}
+
+/// A local SQLite mirror of the server's ID3 library.
+#[derive(Debug)]
+pub struct LibraryCache {
+    conn: Mutex<Connection>,
+}
+
+impl LibraryCache {
+    /// Open (or create) a cache database at `path`.
+    ///
+    /// # Errors
+    /// Returns [`Error::Other`] if the database cannot be opened or initialised.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::init(Connection::open(path).map_err(cache_error)?)
+    }
+
+    /// Create an empty cache that lives only in memory.
+    ///
+    /// # Errors
+    /// Returns [`Error::Other`] if the database cannot be initialised.
+    pub fn open_in_memory() -> Result<Self, Error> {
+        Self::init(Connection::open_in_memory().map_err(cache_error)?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, Error> {
+        conn.execute_batch(SCHEMA).map_err(cache_error)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Re-sync the cache with the server, replacing its previous contents.
+    ///
+    /// Artists come from `getArtists`, album IDs from `getAlbumList2`, and each album with its
+    /// songs from `getAlbum`. The cache is only modified once everything has been fetched, so
+    /// a failed refresh leaves the previous contents intact.
+    pub async fn refresh(&self, client: &Client) -> Result<CacheStats, Error> {
+        let artists: Vec<ArtistId3> = client
+            .get_artists(None)
+            .await?
+            .index
+            .into_iter()
+            .flat_map(|index| index.artist)
+            .collect();
+        let album_ids: Vec<AlbumId> = client
+            .album_list2_stream(AlbumListType::AlphabeticalByName, AlbumListParams::new())
+            .map_ok(|album| album.id)
+            .try_collect()
+            .await?;
+        let albums: Vec<AlbumWithSongsId3> = futures_util::stream::iter(album_ids)
+            .map(|id| async move { client.get_album(&id).await })
+            .buffer_unordered(REFRESH_CONCURRENCY)
+            .try_collect()
+            .await?;
+
+        let mut conn = self.lock();
+        let tx = conn.transaction().map_err(cache_error)?;
+        tx.execute_batch("DELETE FROM artists; DELETE FROM albums; DELETE FROM songs;")
+            .map_err(cache_error)?;
+        for artist in &artists {
+            tx.execute(
+                "INSERT OR REPLACE INTO artists (id, name, data) VALUES (?1, ?2, ?3)",
+                params![artist.id.as_str(), artist.name, to_json(artist)?],
+            )
+            .map_err(cache_error)?;
+        }
+        let mut songs = 0;
+        for album in &albums {
+            tx.execute(
+                "INSERT OR REPLACE INTO albums (id, artist_id, name, data) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    album.id.as_str(),
+                    album.artist_id.as_deref(),
+                    album.name,
+                    to_json(album)?
+                ],
+            )
+            .map_err(cache_error)?;
+            for song in &album.song {
+                tx.execute(
+                    "INSERT OR REPLACE INTO songs (id, album_id, title, disc, track, data)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![
+                        song.id.as_str(),
+                        album.id.as_str(),
+                        song.title,
+                        song.disc_number,
+                        song.track,
+                        to_json(song)?
+                    ],
+                )
+                .map_err(cache_error)?;
+                songs += 1;
+            }
+        }
+        tx.commit().map_err(cache_error)?;
+
+        Ok(CacheStats {
+            artists: artists.len() as u64,
+            albums: albums.len() as u64,
+            songs,
+        })
+    }
+
+    /// Number of artists, albums and songs currently cached.
+    pub fn stats(&self) -> Result<CacheStats, Error> {
+        let conn = self.lock();
+        let count = |table: &str| -> Result<u64, Error> {
+            conn.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .map(|n| n as u64)
+            .map_err(cache_error)
+        };
+        Ok(CacheStats {
+            artists: count("artists")?,
+            albums: count("albums")?,
+            songs: count("songs")?,
+        })
+    }
+
+    /// Statistics over all cached songs (see [`LibraryStats`]).
+    pub fn library_stats(&self) -> Result<LibraryStats, Error> {
+        let songs: Vec<Child> = self.query_all("SELECT data FROM songs", [])?;
+        Ok(LibraryStats::from_songs(&songs))
+    }
+
+    /// All cached artists, ordered by name.
+    pub fn artists(&self) -> Result<Vec<ArtistId3>, Error> {
+        self.query_all("SELECT data FROM artists ORDER BY name COLLATE NOCASE", [])
+    }
+
+    /// The cached artist with the given ID.
+    pub fn artist(&self, id: &str) -> Result<Option<ArtistId3>, Error> {
+        self.query_one("SELECT data FROM artists WHERE id = ?1", [id])
+    }
+
+    /// The cached album (with its songs) with the given ID.
+    pub fn album(&self, id: &str) -> Result<Option<AlbumWithSongsId3>, Error> {
+        self.query_one("SELECT data FROM albums WHERE id = ?1", [id])
+    }
+
+    /// All cached albums by the given artist, ordered by name.
+    pub fn albums_by_artist(&self, artist_id: &str) -> Result<Vec<AlbumWithSongsId3>, Error> {
+        self.query_all(
+            "SELECT data FROM albums WHERE artist_id = ?1 ORDER BY name COLLATE NOCASE",
+            [artist_id],
+        )
+    }
+
+    /// The cached song with the given ID.
+    pub fn song(&self, id: &str) -> Result<Option<Child>, Error> {
+        self.query_one("SELECT data FROM songs WHERE id = ?1", [id])
+    }
+
+    /// All cached songs of the given album, in disc and track order.
+    pub fn songs_in_album(&self, album_id: &str) -> Result<Vec<Child>, Error> {
+        self.query_all(
+            "SELECT data FROM songs WHERE album_id = ?1 ORDER BY disc, track",
+            [album_id],
+        )
+    }
+
+    /// Cached songs whose title contains `text` (case-insensitive for ASCII).
+    pub fn search_songs(&self, text: &str) -> Result<Vec<Child>, Error> {
+        let pattern = format!(
+            "%{}%",
+            text.replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        self.query_all(
+            "SELECT data FROM songs WHERE title LIKE ?1 ESCAPE '\\' ORDER BY title COLLATE NOCASE",
+            [pattern],
+        )
+    }
+
+    fn query_one<T: DeserializeOwned>(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Option<T>, Error> {
+        let data: Option<String> = self
+            .lock()
+            .query_row(sql, params, |row| row.get(0))
+            .optional()
+            .map_err(cache_error)?;
+        data.map(|json| Ok(serde_json::from_str(&json)?))
+            .transpose()
+    }
+
+    fn query_all<T: DeserializeOwned>(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<T>, Error> {
+        let conn = self.lock();
+        let mut stmt = conn.prepare(sql).map_err(cache_error)?;
+        let rows = stmt
+            .query_map(params, |row| row.get::<_, String>(0))
+            .map_err(cache_error)?;
+        rows.map(|json| Ok(serde_json::from_str(&json.map_err(cache_error)?)?))
+            .collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn to_json(value: &impl Serialize) -> Result<String, Error> {
+    Ok(serde_json::to_string(value)?)
+}
+
+fn cache_error(e: rusqlite::Error) -> Error {
+    Error::Other(format!("Library cache error: {e}"))
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::testing::MockServer;
+
+    #[tokio::test]
+    async fn lookups_are_served_locally() {
+        let server = MockServer::start().await;
+        let cache = LibraryCache::open_in_memory().unwrap();
+        let stats = cache.refresh(&server.client()).await.unwrap();
+        assert_eq!(
+            stats,
+            CacheStats {
+                artists: 1,
+                albums: 1,
+                songs: 1
+            }
+        );
+        let requests = server.inner().received_requests().await.unwrap().len();
+
+        assert_eq!(cache.artists().unwrap()[0].id.as_str(), "ar-1");
+        assert_eq!(
+            cache.song("tr-1").unwrap().unwrap().title,
+            "Bohemian Rhapsody"
+        );
+        assert_eq!(cache.songs_in_album("al-1").unwrap().len(), 1);
+        assert_eq!(cache.search_songs("rhapsody").unwrap().len(), 1);
+        assert!(cache.song("tr-2").unwrap().is_none());
+        let after = server.inner().received_requests().await.unwrap().len();
+        assert_eq!(after, requests);
+        assert_eq!(server.calls("getAlbum").await, 1);
+    }
+
+    #[tokio::test]
+    async fn refresh_replaces_contents_and_keeps_them_on_failure() {
+        let server = MockServer::start().await;
+        let client = server.client();
+        let cache = LibraryCache::open_in_memory().unwrap();
+        cache.refresh(&client).await.unwrap();
+
+        // Artists removed on the server are gone from the cache after the next refresh.
+        server
+            .respond_with("getArtists", json!({ "artists": { "index": [] } }))
+            .await;
+        cache.refresh(&client).await.unwrap();
+        assert!(cache.artists().unwrap().is_empty());
+        assert!(cache.song("tr-1").unwrap().is_some());
+
+        // A failed refresh is not stored: the previous contents stay.
+        server.fail_with("getAlbum", 70, "Album not found").await;
+        assert!(cache.refresh(&client).await.is_err());
+        assert_eq!(
+            cache.stats().unwrap(),
+            CacheStats {
+                artists: 0,
+                albums: 1,
+                songs: 1
+            }
+        );
+        assert_eq!(server.calls("getAlbum").await, 3);
+    }
+}
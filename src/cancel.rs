@@ -0,0 +1,132 @@
+//! Cooperative cancellation of long operations.
+
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+
+use futures_util::future::{self, Either};
+use futures_util::{Stream, StreamExt, stream};
+use tokio::sync::watch;
+
+use crate::error::Error;
+
+/// Cancels operations from another task, e.g. when the user navigates away from a view
+/// whose data is still loading.
+///
+/// Wrap a call with [`run`](Self::run) or a stream with [`run_stream`](Self::run_stream), or
+/// pass the token to a [`Downloader`](crate::Downloader) with
+/// [`cancel_token`](crate::Downloader::cancel_token). Cancelling drops the operation, which
+/// aborts its requests in flight. Clones cancel the same operations; a cancelled token
+/// stays cancelled.
+///
+/// ```no_run
+/// # async fn example(client: opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+/// use futures_util::TryStreamExt;
+/// use opensubsonic::CancelToken;
+///
+/// let token = CancelToken::new();
+/// let walk = tokio::spawn({
+///     let token = token.clone();
+///     async move {
+///         let songs = token.run_stream(client.walk_library(None));
+///         songs.try_collect::<Vec<_>>().await
+///     }
+/// });
+/// // Later, from the UI:
+/// token.cancel();
+/// // `Err(Error::Cancelled)`, unless the walk finished first.
+/// let songs = walk.await.unwrap();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CancelToken {
+    cancelled: Arc<watch::Sender<bool>>,
+}
+
+impl Default for CancelToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CancelToken {
+    /// Create a token that is not cancelled.
+    pub fn new() -> Self {
+        Self {
+            cancelled: Arc::new(watch::Sender::new(false)),
+        }
+    }
+
+    /// Cancel every operation run with this token, now and later.
+    pub fn cancel(&self) {
+        self.cancelled.send_replace(true);
+    }
+
+    /// Whether the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        *self.cancelled.borrow()
+    }
+
+    /// Resolve once the token is cancelled.
+    pub async fn cancelled(&self) {
+        let mut cancelled = self.cancelled.subscribe();
+        // The sender lives as long as `self`, so waiting cannot fail.
+        let _ = cancelled.wait_for(|c| *c).await;
+    }
+
+    /// Run `operation` until it completes or the token is cancelled.
+    ///
+    /// # Errors
+    /// Returns [`Error::Cancelled`] if the token is cancelled first, and the errors of
+    /// `operation` otherwise.
+    pub async fn run<T>(
+        &self,
+        operation: impl Future<Output = Result<T, Error>>,
+    ) -> Result<T, Error> {
+        if self.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        match future::select(pin!(operation), pin!(self.cancelled())).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(Error::Cancelled),
+        }
+    }
+
+    /// Forward the items of `items` until it ends or the token is cancelled, in which case
+    /// the stream yields a final [`Error::Cancelled`].
+    pub fn run_stream<S, T>(&self, items: S) -> impl Stream<Item = Result<T, Error>> + use<S, T>
+    where
+        S: Stream<Item = Result<T, Error>>,
+    {
+        let token = self.clone();
+        let check = self.clone();
+        items
+            .take_until(async move { token.cancelled().await })
+            .chain(
+                stream::once(async move { check.is_cancelled() }).filter_map(
+                    |cancelled| async move { cancelled.then_some(Err(Error::Cancelled)) },
+                ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::TryStreamExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn cancels_operations_in_flight() {
+        let token = CancelToken::new();
+        let pending = token.run(future::pending::<Result<(), Error>>());
+        let (result, ()) = futures_util::join!(pending, async { token.cancel() });
+        assert!(matches!(result, Err(Error::Cancelled)));
+
+        let items = stream::iter([Ok(1), Ok(2)]).chain(stream::pending());
+        let collected: Result<Vec<i32>, _> = token.run_stream(items).try_collect().await;
+        assert!(matches!(collected, Err(Error::Cancelled)));
+        assert!(CancelToken::new().run(async { Ok(1) }).await.is_ok());
+    }
+}
@@ -0,0 +1,167 @@
+//! Video captions (subtitles).
+//!
+//! [`Client::get_captions_with`] returns a caption file as served; [`Client::get_caption_cues`]
+//! parses it into [`CaptionCue`]s, and [`parse_captions`] does the same for a file obtained
+//! elsewhere. SubRip and WebVTT are both understood.
+//!
+//! ```no_run
+//! # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+//! use opensubsonic::captions::CaptionFormat;
+//!
+//! for cue in client.get_caption_cues("vi-1", Some(CaptionFormat::Vtt)).await? {
+//!     println!("{:?}-{:?}: {}", cue.start, cue.end, cue.text);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::time::Duration;
+
+use crate::Client;
+use crate::data::SongId;
+use crate::error::Error;
+
+/// A caption file format accepted by `getCaptions`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CaptionFormat {
+    /// SubRip (`.srt`).
+    Srt,
+    /// WebVTT (`.vtt`).
+    Vtt,
+}
+
+impl CaptionFormat {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Srt => "srt",
+            Self::Vtt => "vtt",
+        }
+    }
+}
+
+/// One caption: text shown between two points of the video.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptionCue {
+    /// The cue identifier (the SubRip sequence number or WebVTT cue ID), if any.
+    pub identifier: Option<String>,
+    /// When the text appears.
+    pub start: Duration,
+    /// When the text disappears.
+    pub end: Duration,
+    /// The text, with lines separated by `\n`.
+    pub text: String,
+}
+
+/// Parse a SubRip or WebVTT file into its cues.
+///
+/// WebVTT cue settings, `NOTE`, `STYLE` and `REGION` blocks are ignored; markup inside the
+/// text is kept as is.
+///
+/// # Errors
+/// Returns [`Error::Parse`] if a cue has an invalid timestamp.
+pub fn parse_captions(text: &str) -> Result<Vec<CaptionCue>, Error> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut cues = Vec::new();
+    let mut block = Vec::new();
+    for line in text.lines().chain(std::iter::once("")) {
+        if !line.trim().is_empty() {
+            block.push(line);
+            continue;
+        }
+        if let Some(cue) = parse_cue(&block)? {
+            cues.push(cue);
+        }
+        block.clear();
+    }
+    Ok(cues)
+}
+
+/// Parse one blank-line-separated block, or `None` if it is not a cue.
+fn parse_cue(block: &[&str]) -> Result<Option<CaptionCue>, Error> {
+    let Some(timing) = block.iter().position(|line| line.contains("-->")) else {
+        return Ok(None);
+    };
+    let (start, rest) = block[timing].split_once("-->").unwrap_or_default();
+    // WebVTT cue settings follow the end time.
+    let end = rest.split_whitespace().next().unwrap_or_default();
+    Ok(Some(CaptionCue {
+        identifier: timing.checked_sub(1).map(|i| block[i].trim().to_owned()),
+        start: parse_timestamp(start)?,
+        end: parse_timestamp(end)?,
+        text: block[timing + 1..].join("\n"),
+    }))
+}
+
+/// Parse `[hh:]mm:ss[.,]mmm`.
+fn parse_timestamp(value: &str) -> Result<Duration, Error> {
+    let invalid = || Error::Parse(format!("Invalid caption timestamp '{}'", value.trim()));
+    let value = value.trim();
+    let (clock, millis) = value.split_once(['.', ',']).unwrap_or((value, "0"));
+    let mut seconds = 0u64;
+    for part in clock.split(':') {
+        let part = part.parse::<u64>().map_err(|_| invalid())?;
+        seconds = seconds
+            .checked_mul(60)
+            .and_then(|s| s.checked_add(part))
+            .ok_or_else(invalid)?;
+    }
+    let millis: u64 = millis.parse().map_err(|_| invalid())?;
+    Duration::from_secs(seconds)
+        .checked_add(Duration::from_millis(millis))
+        .ok_or_else(invalid)
+}
+
+impl Client {
+    /// Get the captions of a video, parsed into cues.
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/getcaptions/>
+    pub async fn get_caption_cues(
+        &self,
+        id: impl Into<SongId>,
+        format: Option<CaptionFormat>,
+    ) -> Result<Vec<CaptionCue>, Error> {
+        let bytes = self.get_captions_with(id, format).await?;
+        let text = std::str::from_utf8(&bytes)
+            .map_err(|e| Error::Parse(format!("Captions are not UTF-8: {e}")))?;
+        parse_captions(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_srt() {
+        let cues = parse_captions(
+            "1\r\n00:00:01,500 --> 00:00:04,000\r\nHello\r\nworld\r\n\r\n\
+             2\r\n01:00:00,000 --> 01:00:02,250\r\nBye\r\n",
+        )
+        .unwrap();
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].identifier.as_deref(), Some("1"));
+        assert_eq!(cues[0].start, Duration::from_millis(1500));
+        assert_eq!(cues[0].text, "Hello\nworld");
+        assert_eq!(cues[1].end, Duration::from_millis(3_602_250));
+    }
+
+    #[test]
+    fn parses_vtt() {
+        let cues = parse_captions(
+            "WEBVTT\n\nNOTE a comment\n\n00:00.000 --> 00:02.000 align:start\nHello\n\n\
+             intro\n00:02.000 --> 00:03.500\n<i>there</i>\n",
+        )
+        .unwrap();
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].identifier, None);
+        assert_eq!(cues[0].end, Duration::from_secs(2));
+        assert_eq!(cues[1].identifier.as_deref(), Some("intro"));
+        assert_eq!(cues[1].text, "<i>there</i>");
+        assert!(parse_captions("00:xx --> 00:01.000\nBad\n").is_err());
+
+        let overflow = "99999999999999999999:00 --> 00:01.000\nBad\n";
+        assert!(matches!(parse_captions(overflow), Err(Error::Parse(_))));
+        let overflow = "307445734561825860:00:00.000 --> 00:01.000\nBad\n";
+        assert!(matches!(parse_captions(overflow), Err(Error::Parse(_))));
+    }
+}
@@ -1,35 +1,112 @@
 //! Core HTTP client for the Subsonic / OpenSubsonic REST API.
 
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use std::marker::PhantomData;
+
 use serde::Deserialize;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, Visitor,
+};
 use url::Url;
 
-use crate::auth::Auth;
+use crate::api::system::Capabilities;
+use crate::auth::{AuthProvider, basic_auth_header, redact_url};
+use crate::builder::{ClientBuilder, accept_invalid_certs};
 use crate::error::{Error, SubsonicApiError};
+use crate::layer::{Layer, Layers};
+use crate::metrics::{Metrics, MetricsSink, RequestStats};
+use crate::profile::ServerProfile;
+use crate::rate_limit::RateLimiter;
+use crate::trace;
+use crate::version;
 
 /// Default Subsonic REST API protocol version.
-const DEFAULT_API_VERSION: &str = "1.16.1";
+pub(crate) const DEFAULT_API_VERSION: &str = "1.16.1";
 /// Default client identifier sent with every request.
-const DEFAULT_CLIENT_NAME: &str = "opensubsonic-rs";
+pub(crate) const DEFAULT_CLIENT_NAME: &str = "opensubsonic-rs";
+
+/// Response format requested from the server (the `f` parameter).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    /// JSON responses (the default).
+    #[default]
+    Json,
+    /// XML responses, for older servers whose JSON output is broken.
+    #[cfg(feature = "xml")]
+    Xml,
+}
+
+impl Format {
+    fn as_param(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            #[cfg(feature = "xml")]
+            Self::Xml => "xml",
+        }
+    }
+}
+
+/// Metadata from the `subsonic-response` envelope, returned by
+/// [`Client::last_response_meta`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResponseMeta {
+    /// Protocol version implemented by the server (e.g. `"1.16.1"`).
+    pub version: Option<String>,
+    /// Server implementation (OpenSubsonic, e.g. `"navidrome"`).
+    pub server_type: Option<String>,
+    /// Server software version (OpenSubsonic, e.g. `"0.52.0"`).
+    pub server_version: Option<String>,
+    /// Whether the server supports OpenSubsonic extensions.
+    pub open_subsonic: bool,
+}
 
 /// An async client for the Subsonic / OpenSubsonic REST API.
 ///
 /// Construct via [`Client::new`] and optionally customise with the builder methods
-/// ([`Client::with_client_name`], [`Client::with_api_version`], [`Client::with_http_client`]).
+/// ([`Client::with_client_name`], [`Client::with_api_version`], [`Client::with_http_client`]),
+/// or configure everything up front with [`Client::builder`].
 ///
 /// API endpoint methods are provided by the [`crate::api`] module and are available as methods
 /// on this struct via extension traits.
 #[derive(Debug, Clone)]
 pub struct Client {
     /// Server base URL (e.g. `https://music.example.com`).
-    base_url: Url,
-    /// Authentication configuration (includes username when applicable).
-    auth: Auth,
+    pub(crate) base_url: Url,
+    /// Authentication provider (includes username when applicable).
+    pub(crate) auth: Arc<dyn AuthProvider>,
+    /// `Authorization: Basic` header for a reverse proxy in front of the server.
+    pub(crate) basic_auth: Option<reqwest::header::HeaderValue>,
+    /// Extra headers sent on every request.
+    pub(crate) default_headers: reqwest::header::HeaderMap,
     /// Client application identifier sent as the `c` parameter.
-    client_name: String,
+    pub(crate) client_name: String,
     /// Subsonic REST protocol version sent as the `v` parameter.
-    api_version: String,
+    pub(crate) api_version: String,
+    /// Response format sent as the `f` parameter.
+    pub(crate) format: Format,
     /// Underlying HTTP client (reused across requests for connection pooling).
     pub(crate) http: reqwest::Client,
+    /// Envelope metadata of the most recent API response (shared between clones).
+    pub(crate) last_meta: Arc<Mutex<Option<ResponseMeta>>>,
+    /// Whether API calls send their parameters as a POST form body (shared between clones).
+    pub(crate) form_post: Arc<AtomicBool>,
+    /// OpenSubsonic extensions supported by the server, once fetched (shared between clones).
+    pub(crate) capabilities: Arc<Mutex<Option<Capabilities>>>,
+    /// Middleware run around every request.
+    pub(crate) layers: Layers,
+    /// Optional request rate limit (shared between clones).
+    pub(crate) rate_limiter: Option<Arc<RateLimiter>>,
+    /// Telemetry sink notified of every request.
+    pub(crate) metrics: Metrics,
+    /// Server profile fixed by the user instead of detected from responses.
+    pub(crate) server_profile: Option<ServerProfile>,
+    /// Whether API calls are checked against the server's API version.
+    pub(crate) version_negotiation: bool,
+    /// Timeout of every request, overriding the HTTP client's.
+    pub(crate) timeout: Option<Duration>,
 }
 
 // ── Constructor & builders ──────────────────────────────────────────────────
@@ -39,20 +116,19 @@ impl Client {
     ///
     /// # Arguments
     /// * `base_url` — The server base URL, e.g. `"https://music.example.com"`.
-    /// * `auth` — Authentication method (see [`Auth::token`], [`Auth::plain`],
-    ///   [`Auth::api_key`]).
+    /// * `auth` — Authentication method (see [`Auth::token`](crate::Auth::token),
+    ///   [`Auth::plain`](crate::Auth::plain), [`Auth::api_key`](crate::Auth::api_key)), or
+    ///   any other [`AuthProvider`].
     ///
     /// # Errors
     /// Returns [`Error::Url`] if `base_url` cannot be parsed.
-    pub fn new(base_url: &str, auth: Auth) -> Result<Self, Error> {
-        let base_url = Url::parse(base_url)?;
-        Ok(Self {
-            base_url,
-            auth,
-            client_name: DEFAULT_CLIENT_NAME.to_owned(),
-            api_version: DEFAULT_API_VERSION.to_owned(),
-            http: reqwest::Client::new(),
-        })
+    pub fn new(base_url: &str, auth: impl AuthProvider) -> Result<Self, Error> {
+        ClientBuilder::new(base_url, auth).build()
+    }
+
+    /// Start building a client with a [`ClientBuilder`].
+    pub fn builder(base_url: &str, auth: impl AuthProvider) -> ClientBuilder {
+        ClientBuilder::new(base_url, auth)
     }
 
     /// Override the client application name sent as the `c` parameter.
@@ -84,11 +160,259 @@ impl Client {
     /// # Errors
     /// Returns [`Error::Http`] if the HTTP client cannot be built.
     pub fn with_danger_accept_invalid_certs(mut self) -> Result<Self, Error> {
+        self.http = accept_invalid_certs(reqwest::Client::builder()).build()?;
+        Ok(self)
+    }
+
+    /// Trust the CA certificates in `pem` besides the system ones, e.g. the private CA of a
+    /// homelab, instead of disabling verification with
+    /// [`Client::with_danger_accept_invalid_certs`].
+    ///
+    /// Like that method, this replaces the HTTP client with a new one; use
+    /// [`ClientBuilder::root_certificate`] to combine it with other HTTP settings.
+    ///
+    /// ```no_run
+    /// # fn example(client: opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+    /// let pem = std::fs::read("/etc/ssl/homelab-ca.pem")?;
+    /// let client = client.with_root_certificate(&pem)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`Error::Other`] if `pem` holds no valid certificate, or [`Error::Http`] if the
+    /// HTTP client cannot be built.
+    #[cfg(any(feature = "rustls", feature = "native-tls"))]
+    pub fn with_root_certificate(mut self, pem: &[u8]) -> Result<Self, Error> {
+        let certificates = crate::builder::parse_certificates(pem)?;
         self.http = reqwest::Client::builder()
-            .danger_accept_invalid_certs(true)
+            .tls_certs_merge(certificates)
             .build()?;
         Ok(self)
     }
+
+    /// Send API call parameters as an `application/x-www-form-urlencoded` POST body instead
+    /// of the URL query (OpenSubsonic `formPost` extension).
+    ///
+    /// Avoids URL length limits for calls with many IDs. Only enable this for servers that
+    /// advertise the extension; [`Client::negotiate_form_post`] checks that automatically.
+    /// Binary endpoints (`stream`, `download`, …) always use GET. Clients this one was cloned
+    /// from are not affected; clones of the returned client share its setting.
+    #[must_use]
+    pub fn with_form_post(mut self, enabled: bool) -> Self {
+        self.form_post = Arc::new(AtomicBool::new(enabled));
+        self
+    }
+
+    /// Use the workarounds of `profile` instead of detecting the server from its responses.
+    ///
+    /// Pass [`ServerProfile::Generic`] to disable all workarounds.
+    #[must_use]
+    pub fn with_server_profile(mut self, profile: ServerProfile) -> Self {
+        self.server_profile = Some(profile);
+        self
+    }
+
+    /// Check API calls against the protocol version the server reported in its last
+    /// response (disabled by default).
+    ///
+    /// A call to an endpoint newer than the server's version is sent to an older equivalent
+    /// when there is one (`search3` → `search2`, `getAlbumList2` → `getAlbumList`), with the
+    /// results converted, and otherwise fails with [`Error::UnsupportedByServer`] without a
+    /// request being made. Binary endpoints (`getAvatar`, `getCaptions`, `hls`, …) have no
+    /// equivalents and can only fail. Nothing is checked before the first response, nor for
+    /// servers that report no version. Leave this off for servers that implement endpoints
+    /// beyond the version they report.
+    #[must_use]
+    pub fn with_version_negotiation(mut self, enabled: bool) -> Self {
+        self.version_negotiation = enabled;
+        self
+    }
+
+    /// Send an `Authorization: Basic` header with `username` and `password` on every request,
+    /// for reverse proxies that require HTTP Basic authentication in front of the server.
+    ///
+    /// These credentials are separate from the Subsonic ones, which are still sent as query
+    /// parameters. The header is added to API calls and binary endpoints alike, but it cannot
+    /// be part of the URLs returned by the URL builders (e.g. [`Client::stream_url`]).
+    #[must_use]
+    pub fn with_basic_auth(mut self, username: &str, password: &str) -> Self {
+        self.basic_auth = Some(basic_auth_header(username, password));
+        self
+    }
+
+    /// Send the header `name: value` on every request, API calls and binary endpoints alike,
+    /// replacing any earlier value of the same header.
+    ///
+    /// For deployments behind an authenticating proxy (Authelia, oauth2-proxy, …) that
+    /// expects e.g. `Remote-User` or a bearer token besides the Subsonic credentials. Values
+    /// are treated as secrets and redacted from `Debug` output.
+    ///
+    /// ```
+    /// # fn example(client: opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+    /// let client = client.with_default_header("Authorization", "Bearer eyJhbGciOi...")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`Error::Other`] if `name` or `value` is not a valid header name or value.
+    pub fn with_default_header(mut self, name: &str, value: &str) -> Result<Self, Error> {
+        let (name, value) = parse_header(name, value)?;
+        self.default_headers.insert(name, value);
+        Ok(self)
+    }
+
+    /// Add a [`Layer`] run around every request, after the layers already added.
+    #[must_use]
+    pub fn with_layer(mut self, layer: impl Layer) -> Self {
+        self.layers.push(layer);
+        self
+    }
+
+    /// Limit requests to `requests_per_second` on average, allowing bursts of up to `burst`
+    /// requests. Requests over the limit wait their turn.
+    ///
+    /// The limit applies to every request, API calls and media alike, and is shared with
+    /// clones made after this call.
+    ///
+    /// # Errors
+    /// Returns [`Error::Other`] if `requests_per_second` is not positive, as
+    /// [`ClientBuilder::build`] does.
+    pub fn with_rate_limit(mut self, requests_per_second: f64, burst: u32) -> Result<Self, Error> {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(requests_per_second, burst)?));
+        Ok(self)
+    }
+
+    /// Time out every request after `timeout`, overriding the timeout of the HTTP client.
+    ///
+    /// The timeout covers the whole request, from connecting to reading the last byte of
+    /// the body. Clones are cheap and share everything else, so a clone with its own
+    /// timeout makes a handle for calls that need a longer or shorter one:
+    ///
+    /// ```no_run
+    /// # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+    /// use std::time::Duration;
+    ///
+    /// client.clone().with_timeout(Duration::from_secs(5)).ping().await?;
+    /// let slow = client.clone().with_timeout(Duration::from_secs(30 * 60));
+    /// let bytes = slow.download("song-id").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Report every request to `sink`, replacing the previous sink (by default
+    /// [`NoopMetrics`](crate::metrics::NoopMetrics)).
+    ///
+    /// Pass an `Arc` to keep a handle on the sink, e.g. to read
+    /// [`CountingMetrics`](crate::metrics::CountingMetrics) totals.
+    #[must_use]
+    pub fn with_metrics(mut self, sink: impl MetricsSink) -> Self {
+        self.metrics = Metrics::new(sink);
+        self
+    }
+
+    /// Whether API calls are currently sent as POST form bodies.
+    pub fn uses_form_post(&self) -> bool {
+        self.form_post.load(Ordering::Relaxed)
+    }
+}
+
+// ── Response metadata ───────────────────────────────────────────────────────
+
+impl Client {
+    /// Envelope metadata (protocol version, server type and version) of the most recent
+    /// API response, or `None` if no response has been received yet.
+    ///
+    /// Any call works, e.g. [`Client::ping`]:
+    ///
+    /// ```no_run
+    /// # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+    /// client.ping().await?;
+    /// if let Some(meta) = client.last_response_meta() {
+    ///     let server = meta.server_type.as_deref().unwrap_or("subsonic");
+    ///     let version = meta.server_version.as_deref().unwrap_or("?");
+    ///     println!("Connected to {server} {version}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn last_response_meta(&self) -> Option<ResponseMeta> {
+        self.last_meta
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// The [`ServerProfile`] whose workarounds are applied: the one set with
+    /// [`Client::with_server_profile`], or the one matching the server type of the last
+    /// response.
+    pub fn server_profile(&self) -> ServerProfile {
+        if let Some(profile) = self.server_profile {
+            return profile;
+        }
+        let meta = self.last_meta.lock().unwrap_or_else(|e| e.into_inner());
+        meta.as_ref()
+            .and_then(|m| m.server_type.as_deref())
+            .map_or(ServerProfile::Generic, ServerProfile::from_server_type)
+    }
+
+    /// Remember the envelope fields of a response, returning them.
+    fn record_meta(&self, inner: &SubsonicResponseInner) -> ResponseMeta {
+        let meta = ResponseMeta {
+            version: inner.version.clone(),
+            server_type: inner.server_type.clone(),
+            server_version: inner.server_version.clone(),
+            open_subsonic: inner.open_subsonic.unwrap_or(false),
+        };
+        *self.last_meta.lock().unwrap_or_else(|e| e.into_inner()) = Some(meta.clone());
+        meta
+    }
+}
+
+// ── Raw access ──────────────────────────────────────────────────────────────
+
+impl Client {
+    /// Call an arbitrary endpoint and return the untyped response data.
+    ///
+    /// An escape hatch for server-specific or new endpoints that this crate does not wrap
+    /// yet. Authentication, the envelope and API errors are handled as for every other
+    /// call; the returned map holds all fields of `subsonic-response` except the envelope
+    /// ones (`status`, `version`, `type`, `serverVersion`, `openSubsonic`, `error`).
+    /// Repeat a key in `params` to send a multi-valued parameter.
+    ///
+    /// ```no_run
+    /// # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+    /// let data = client.get_raw("getArtists", &[("musicFolderId", "1")]).await?;
+    /// println!("{}", data["artists"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_raw(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+    ) -> Result<serde_json::Map<String, serde_json::Value>, Error> {
+        self.get_response(endpoint, params).await
+    }
+
+    /// Call an arbitrary binary endpoint and return the response body.
+    ///
+    /// Like [`Client::get_raw`], but for endpoints returning media or other non-envelope
+    /// data. An error envelope sent instead of the data is returned as an
+    /// [`Error::Api`], wrapped in [`Error::Request`] like every error of an API call.
+    pub async fn get_raw_bytes(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+    ) -> Result<bytes::Bytes, Error> {
+        self.get_bytes(endpoint, params).await
+    }
 }
 
 // ── Internal transport helpers ──────────────────────────────────────────────
@@ -132,8 +456,8 @@ impl Client {
             // Protocol version & client id.
             query.append_pair("v", &self.api_version);
             query.append_pair("c", &self.client_name);
-            // Always request JSON.
-            query.append_pair("f", "json");
+            // Response format (JSON unless XML was selected).
+            query.append_pair("f", self.format.as_param());
             // Endpoint-specific params.
             for &(k, v) in params {
                 query.append_pair(k, v);
@@ -154,33 +478,266 @@ impl Client {
         endpoint: &str,
         params: &[(&str, &str)],
     ) -> Result<serde_json::Map<String, serde_json::Value>, Error> {
-        let url = self.build_url(endpoint, params)?;
-        log::debug!("GET {url}");
+        trace::call(endpoint, self.fetch_response(endpoint, params)).await
+    }
 
-        let resp = self.http.get(url).send().await?.error_for_status()?;
-        let text = resp.text().await?;
+    async fn fetch_response(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+    ) -> Result<serde_json::Map<String, serde_json::Value>, Error> {
+        let Some(fallback) = self.negotiate(endpoint)? else {
+            return Ok(self.fetch_endpoint(endpoint, params).await?.0);
+        };
+        let (mut data, _) = self.fetch_endpoint(fallback.replacement, params).await?;
+        fallback.convert(&mut data);
+        Ok(data)
+    }
 
-        let wrapper: SubsonicResponseWrapper =
-            serde_json::from_str(&text).map_err(|e| Error::Parse(format!("{e}: {text}")))?;
-        let inner = wrapper.response;
+    /// The older endpoint to call in place of `endpoint`, if the server's API version calls
+    /// for one (see [`Client::with_version_negotiation`]).
+    fn negotiate(&self, endpoint: &str) -> Result<Option<version::Fallback>, Error> {
+        if !self.version_negotiation {
+            return Ok(None);
+        }
+        let meta = self.last_meta.lock().unwrap_or_else(|e| e.into_inner());
+        match meta.as_ref().and_then(|m| m.version.as_deref()) {
+            Some(has) => {
+                version::negotiate(endpoint, has).map_err(|e| e.in_request(endpoint, None, None))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Fail with [`Error::UnsupportedByServer`] if the server's API version is too old for
+    /// the binary `endpoint`.
+    pub(crate) fn negotiate_binary(&self, endpoint: &str) -> Result<(), Error> {
+        let name = endpoint.strip_suffix(".m3u8").unwrap_or(endpoint);
+        self.negotiate(name).map(|_| ())
+    }
+
+    /// Perform an API call like [`Client::get_response`], also returning the envelope fields
+    /// of this very response. No fallback endpoint is called in place of `endpoint`.
+    pub(crate) async fn get_response_meta(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+    ) -> Result<(serde_json::Map<String, serde_json::Value>, ResponseMeta), Error> {
+        trace::call(endpoint, self.fetch_endpoint(endpoint, params)).await
+    }
+
+    async fn fetch_endpoint(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+    ) -> Result<(serde_json::Map<String, serde_json::Value>, ResponseMeta), Error> {
+        let substitute = self.server_profile().substitute(endpoint);
+        let called = substitute.map_or(endpoint, |(replacement, _, _)| replacement);
+        let request = self
+            .api_request(called, params)
+            .map_err(|e| e.in_request(called, None, None))?;
+        let resp = self.send(request).await?;
+        let url = resp.url().clone();
+        let status = resp.status().as_u16();
+        self.read_response(resp, substitute)
+            .await
+            .map_err(|e| e.in_request(called, Some(&url), Some(status)))
+    }
+
+    /// Check the status and envelope of an API response and return its data map and
+    /// envelope fields.
+    async fn read_response(
+        &self,
+        resp: reqwest::Response,
+        substitute: Option<(&str, &str, &str)>,
+    ) -> Result<(serde_json::Map<String, serde_json::Value>, ResponseMeta), Error> {
+        let text = resp.error_for_status()?.text().await?;
+
+        let inner = parse_envelope(text, self.format)?;
+        let meta = self.record_meta(&inner);
 
         if inner.status != "ok" {
-            let api_err = inner.error.map_or_else(
-                || SubsonicApiError {
-                    code: 0,
-                    message: "Unknown API error (status != ok but no error object)".into(),
-                    help_url: None,
-                },
-                |e| SubsonicApiError {
-                    code: e.code,
-                    message: e.message.unwrap_or_default(),
-                    help_url: e.help_url,
+            return Err(api_error(inner.error, NO_ERROR_OBJECT));
+        }
+
+        let mut data = inner.data;
+        if let Some((_, from, to)) = substitute {
+            if let Some(value) = data.remove(from) {
+                data.insert(to.to_owned(), value);
+            }
+        }
+        // Detected from this response's envelope if not known before.
+        self.server_profile().normalize(&mut data);
+        Ok((data, meta))
+    }
+
+    /// Perform an API call and deserialize the data under `key` straight from the response
+    /// body into `T`, or `None` if the response has no `key`.
+    ///
+    /// Unlike [`Client::get_response`], no `serde_json::Value` of the whole response is
+    /// built first, which matters for endpoints like `getArtists` whose responses run to
+    /// megabytes on large libraries. XML responses and calls sent to a fallback endpoint
+    /// take the [`Client::get_response`] path.
+    pub(crate) async fn get_typed<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+        key: &str,
+    ) -> Result<Option<T>, Error> {
+        trace::call(endpoint, self.fetch_typed(endpoint, params, key)).await
+    }
+
+    async fn fetch_typed<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+        key: &str,
+    ) -> Result<Option<T>, Error> {
+        // Fallback results are converted on the data map.
+        let untyped = self.negotiate(endpoint)?.is_some();
+        #[cfg(feature = "xml")]
+        let untyped = untyped || self.format == Format::Xml;
+        if untyped {
+            let mut data = self.fetch_response(endpoint, params).await?;
+            return data.remove(key).map(|value| self.decode(value)).transpose();
+        }
+        let substitute = self.server_profile().substitute(endpoint);
+        let (called, key) = match substitute {
+            Some((replacement, from, to)) if to == key => (replacement, from),
+            Some((replacement, _, _)) => (replacement, key),
+            None => (endpoint, key),
+        };
+        let request = self
+            .api_request(called, params)
+            .map_err(|e| e.in_request(called, None, None))?;
+        let resp = self.send(request).await?;
+        let url = resp.url().clone();
+        let status = resp.status().as_u16();
+        self.read_typed(resp, key)
+            .await
+            .map_err(|e| e.in_request(called, Some(&url), Some(status)))
+    }
+
+    /// Check the status and envelope of an API response and deserialize its `key`.
+    async fn read_typed<T: DeserializeOwned>(
+        &self,
+        resp: reqwest::Response,
+        key: &str,
+    ) -> Result<Option<T>, Error> {
+        let body = resp.error_for_status()?.bytes().await?;
+        let (inner, value) = parse_typed(body, key)?;
+        self.record_meta(&inner);
+        if inner.status != "ok" {
+            return Err(api_error(inner.error, NO_ERROR_OBJECT));
+        }
+        Ok(value)
+    }
+
+    /// Build the request for an API call: a GET with the parameters in the URL, or a POST
+    /// with the parameters as a form body when `formPost` is enabled.
+    fn api_request(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+    ) -> Result<reqwest::RequestBuilder, Error> {
+        if !self.uses_form_post() {
+            return self.request(reqwest::Method::GET, endpoint, params);
+        }
+        let mut url = self.build_url(endpoint, params)?;
+        let body = url.query().unwrap_or_default().to_owned();
+        url.set_query(None);
+        log::debug!("POST {}", redact_url(&url));
+        Ok(self
+            .http
+            .post(url)
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                "application/x-www-form-urlencoded",
+            )
+            .body(body))
+    }
+
+    /// Start building a request with the given HTTP `method` for `endpoint`.
+    ///
+    /// The URL carries the same authentication and endpoint parameters as [`Client::build_url`].
+    pub(crate) fn request(
+        &self,
+        method: reqwest::Method,
+        endpoint: &str,
+        params: &[(&str, &str)],
+    ) -> Result<reqwest::RequestBuilder, Error> {
+        let url = self.build_url(endpoint, params)?;
+        log::debug!("{method} {}", redact_url(&url));
+        Ok(self.http.request(method, url))
+    }
+
+    /// Send a request through the client's [`Layer`]s, reporting it to its [`MetricsSink`].
+    ///
+    /// Every HTTP request goes through here.
+    pub(crate) async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, Error> {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire().await;
+        }
+        let request = match self.timeout {
+            Some(timeout) => request.timeout(timeout),
+            None => request,
+        }
+        .build()?;
+        let endpoint = endpoint_name(request.url()).to_owned();
+        let url = request.url().clone();
+        let method = request.method().clone();
+        let result = trace::http(&endpoint, &method, async {
+            let metrics = self.metrics.sink();
+            metrics.request_started(&endpoint);
+            let start = std::time::Instant::now();
+            let (resp, bytes_sent) = match self.execute(&endpoint, request).await {
+                Ok(sent) => sent,
+                Err(e) => {
+                    metrics.request_failed(&endpoint, start.elapsed(), &e);
+                    return Err(e);
+                }
+            };
+            metrics.request_completed(
+                &endpoint,
+                &RequestStats {
+                    status: resp.status().as_u16(),
+                    duration: start.elapsed(),
+                    bytes_sent,
+                    bytes_received: resp.content_length(),
                 },
             );
-            return Err(Error::Api(api_err));
-        }
+            self.layers.after_response(&endpoint, &resp)?;
+            Ok(resp)
+        })
+        .await;
+        result.map_err(|e| e.in_request(&endpoint, Some(&url), None))
+    }
 
-        Ok(inner.data)
+    /// Add the default and authentication headers, run the `before_request` layers and
+    /// execute `request`, returning the response and the size of the request body.
+    async fn execute(
+        &self,
+        endpoint: &str,
+        mut request: reqwest::Request,
+    ) -> Result<(reqwest::Response, u64), Error> {
+        for (name, value) in &self.default_headers {
+            request.headers_mut().insert(name, value.clone());
+        }
+        if let Some(basic_auth) = &self.basic_auth {
+            request
+                .headers_mut()
+                .insert(reqwest::header::AUTHORIZATION, basic_auth.clone());
+        }
+        self.auth.headers(request.headers_mut());
+        self.layers.before_request(endpoint, &mut request)?;
+        let bytes_sent = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map_or(0, |body| body.len() as u64);
+        Ok((self.http.execute(request).await?, bytes_sent))
     }
 
     /// Perform a GET request and return the raw response bytes.
@@ -194,50 +751,199 @@ impl Client {
         endpoint: &str,
         params: &[(&str, &str)],
     ) -> Result<bytes::Bytes, Error> {
-        let url = self.build_url(endpoint, params)?;
-        log::debug!("GET (bytes) {url}");
+        self.negotiate_binary(endpoint)?;
+        let request = self.request(reqwest::Method::GET, endpoint, params)?;
+        let resp = self.send_binary(request).await?;
+        Ok(resp.bytes().await?)
+    }
 
-        let resp = self.http.get(url).send().await?.error_for_status()?;
+    /// Send a request to a binary endpoint and return the response with its body unread.
+    ///
+    /// Fails on HTTP error statuses and on JSON error bodies (see [`Client::get_bytes`]).
+    pub(crate) async fn send_binary(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, Error> {
+        // Media is already compressed, and its length matters for progress and ranges.
+        let request = request.header(reqwest::header::ACCEPT_ENCODING, "identity");
+        // The endpoint is recorded on the span once the request is built.
+        trace::call("", self.fetch_binary(request)).await
+    }
 
-        // Some servers return a JSON error even on binary endpoints.
-        let content_type = resp
-            .headers()
-            .get(reqwest::header::CONTENT_TYPE)
-            .and_then(|v| v.to_str().ok())
-            .unwrap_or("")
-            .to_lowercase();
+    async fn fetch_binary(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, Error> {
+        let resp = self.send(request).await?;
+        let url = resp.url().clone();
+        let status = resp.status().as_u16();
+        self.check_binary(resp)
+            .await
+            .map_err(|e| e.in_request(endpoint_name(&url), Some(&url), Some(status)))
+    }
 
-        if content_type.contains("application/json") || content_type.contains("text/json") {
-            // Likely an error response — try to parse it.
-            let text = resp.text().await?;
-            let wrapper: SubsonicResponseWrapper =
-                serde_json::from_str(&text).map_err(|e| Error::Parse(format!("{e}: {text}")))?;
-            let inner = wrapper.response;
-            if inner.status != "ok" {
-                let api_err = inner.error.map_or_else(
-                    || SubsonicApiError {
-                        code: 0,
-                        message: "Unknown API error on binary endpoint".into(),
-                        help_url: None,
-                    },
-                    |e| SubsonicApiError {
-                        code: e.code,
-                        message: e.message.unwrap_or_default(),
-                        help_url: e.help_url,
-                    },
-                );
-                return Err(Error::Api(api_err));
+    /// Pass a binary response through, or turn an HTTP error status or error envelope into
+    /// an error.
+    async fn check_binary(&self, resp: reqwest::Response) -> Result<reqwest::Response, Error> {
+        let resp = resp.error_for_status()?;
+
+        // Some servers return a JSON (or XML) error even on binary endpoints.
+        let format = if is_json_response(&resp) {
+            Format::Json
+        } else {
+            match self.format {
+                #[cfg(feature = "xml")]
+                Format::Xml if is_xml_response(&resp) => Format::Xml,
+                _ => return Ok(resp),
             }
-            // If status is ok but content-type is JSON, something unexpected happened.
-            return Err(Error::Parse(
-                "Expected binary response but got JSON with status=ok".into(),
+        };
+
+        // Likely an error response — try to parse it.
+        let text = resp.text().await?;
+        let inner = parse_envelope(text, format)?;
+        self.record_meta(&inner);
+        if inner.status != "ok" {
+            return Err(api_error(
+                inner.error,
+                "Unknown API error on binary endpoint",
             ));
         }
+        // If status is ok but content-type is JSON, something unexpected happened.
+        Err(Error::Parse(
+            "Expected binary response but got JSON with status=ok".into(),
+        ))
+    }
 
-        Ok(resp.bytes().await?)
+    /// Deserialize a list taken from the response data map.
+    ///
+    /// A missing or `null` value is an empty list, and a single object a one-element list.
+    pub(crate) fn decode_list<T: DeserializeOwned>(
+        &self,
+        value: Option<serde_json::Value>,
+    ) -> Result<Vec<T>, Error> {
+        match value {
+            None | Some(serde_json::Value::Null) => Ok(Vec::new()),
+            Some(list @ serde_json::Value::Array(_)) => self.decode(list),
+            Some(one) => self.decode(serde_json::Value::Array(vec![one])),
+        }
+    }
+
+    /// Deserialize a value taken from the response data map.
+    ///
+    /// XML responses carry no type information, so they are decoded leniently.
+    pub(crate) fn decode<T: DeserializeOwned>(&self, value: serde_json::Value) -> Result<T, Error> {
+        match self.format {
+            Format::Json => serde_path_to_error::deserialize(value).map_err(path_error),
+            #[cfg(feature = "xml")]
+            Format::Xml => {
+                serde_path_to_error::deserialize(crate::xml::Lenient(value)).map_err(path_error)
+            }
+        }
     }
 }
 
+/// Take the list `outer.inner` (e.g. `albumList2.album`) out of a response data map.
+pub(crate) fn take_list(
+    data: &mut serde_json::Map<String, serde_json::Value>,
+    outer: &str,
+    inner: &str,
+) -> Option<serde_json::Value> {
+    data.remove(outer)
+        .and_then(|mut v| v.get_mut(inner).map(serde_json::Value::take))
+}
+
+/// The endpoint name of an API URL (`…/rest/getAlbum.view` → `getAlbum`).
+fn endpoint_name(url: &Url) -> &str {
+    let name = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .unwrap_or_default();
+    name.strip_suffix(".view").unwrap_or(name)
+}
+
+/// Whether the response declares a JSON body (used to detect errors on binary endpoints).
+pub(crate) fn is_json_response(resp: &reqwest::Response) -> bool {
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_lowercase();
+    content_type.contains("application/json") || content_type.contains("text/json")
+}
+
+/// Whether the response declares an XML body.
+#[cfg(feature = "xml")]
+fn is_xml_response(resp: &reqwest::Response) -> bool {
+    resp.headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.to_lowercase().contains("xml"))
+}
+
+/// Parse a response body in the given format into the contents of `subsonic-response`.
+///
+/// With the `simd-json` feature, JSON is parsed in place in the body's buffer.
+fn parse_envelope(text: String, format: Format) -> Result<SubsonicResponseInner, Error> {
+    match format {
+        #[cfg(not(feature = "simd-json"))]
+        Format::Json => {
+            let wrapper: SubsonicResponseWrapper =
+                serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_str(&text))
+                    .map_err(path_error)?;
+            Ok(wrapper.response)
+        }
+        #[cfg(feature = "simd-json")]
+        Format::Json => {
+            let mut bytes = text.into_bytes();
+            let mut de = simd_json::Deserializer::from_slice(&mut bytes)
+                .map_err(|e| Error::Parse(e.to_string()))?;
+            let wrapper: SubsonicResponseWrapper =
+                serde_path_to_error::deserialize(&mut de).map_err(path_error)?;
+            Ok(wrapper.response)
+        }
+        #[cfg(feature = "xml")]
+        Format::Xml => {
+            let root = crate::xml::parse_response(&text)?;
+            serde_path_to_error::deserialize(root).map_err(path_error)
+        }
+    }
+}
+
+/// Parse a JSON response body, deserializing only the envelope fields and `key`.
+///
+/// The returned envelope has an empty data map; the value of `key` is returned next to it.
+fn parse_typed<T: DeserializeOwned>(
+    body: bytes::Bytes,
+    key: &str,
+) -> Result<(SubsonicResponseInner, Option<T>), Error> {
+    let seed = TypedSeed {
+        key,
+        wrapper: true,
+        marker: PhantomData,
+    };
+    let mut track = serde_path_to_error::Track::new();
+    #[cfg(not(feature = "simd-json"))]
+    let result = {
+        let mut de = serde_json::Deserializer::from_slice(&body);
+        seed.deserialize(serde_path_to_error::Deserializer::new(&mut de, &mut track))
+    };
+    #[cfg(feature = "simd-json")]
+    let result = {
+        let mut bytes = Vec::from(body);
+        let mut de = simd_json::Deserializer::from_slice(&mut bytes)
+            .map_err(|e| Error::Parse(e.to_string()))?;
+        seed.deserialize(serde_path_to_error::Deserializer::new(&mut de, &mut track))
+    };
+    result.map_err(|e| path_error(serde_path_to_error::Error::new(track.path(), e)))
+}
+
+/// A parse error naming the path of the offending field, e.g.
+/// `song[3].replayGain.trackGain: invalid type: string "x", expected f64`.
+fn path_error<E: std::fmt::Display>(err: serde_path_to_error::Error<E>) -> Error {
+    Error::Parse(err.to_string())
+}
+
 // ── Response deserialization helpers ────────────────────────────────────────
 
 /// Top-level JSON wrapper returned by all Subsonic REST API endpoints.
@@ -254,19 +960,15 @@ struct SubsonicResponseInner {
     status: String,
     /// Protocol version echoed by the server.
     #[serde(default)]
-    #[allow(dead_code)]
     version: Option<String>,
     /// Server implementation type (OpenSubsonic extension, e.g. `"navidrome"`).
     #[serde(rename = "type", default)]
-    #[allow(dead_code)]
     server_type: Option<String>,
     /// Server software version (OpenSubsonic extension).
     #[serde(rename = "serverVersion", default)]
-    #[allow(dead_code)]
     server_version: Option<String>,
     /// Whether the server supports OpenSubsonic extensions.
     #[serde(rename = "openSubsonic", default)]
-    #[allow(dead_code)]
     open_subsonic: Option<bool>,
     /// Present only when `status == "failed"`.
     error: Option<ApiErrorResponse>,
@@ -275,6 +977,94 @@ struct SubsonicResponseInner {
     data: serde_json::Map<String, serde_json::Value>,
 }
 
+/// Deserializes `{"subsonic-response": {…}}` (`wrapper`) or its contents, keeping the
+/// envelope fields and `key` and skipping everything else.
+struct TypedSeed<'a, T> {
+    key: &'a str,
+    wrapper: bool,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeSeed<'de> for TypedSeed<'_, T> {
+    type Value = (SubsonicResponseInner, Option<T>);
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for TypedSeed<'_, T> {
+    type Value = (SubsonicResponseInner, Option<T>);
+
+    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("a subsonic-response object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        if self.wrapper {
+            let mut response = None;
+            while let Some(name) = map.next_key::<String>()? {
+                if name == "subsonic-response" {
+                    response = Some(map.next_value_seed(TypedSeed {
+                        wrapper: false,
+                        ..self
+                    })?);
+                } else {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+            return response.ok_or_else(|| de::Error::missing_field("subsonic-response"));
+        }
+
+        let mut inner = SubsonicResponseInner {
+            status: String::new(),
+            version: None,
+            server_type: None,
+            server_version: None,
+            open_subsonic: None,
+            error: None,
+            data: serde_json::Map::new(),
+        };
+        let mut status = None;
+        let mut value = None;
+        while let Some(name) = map.next_key::<String>()? {
+            match name.as_str() {
+                "status" => status = Some(map.next_value()?),
+                "version" => inner.version = map.next_value()?,
+                "type" => inner.server_type = map.next_value()?,
+                "serverVersion" => inner.server_version = map.next_value()?,
+                "openSubsonic" => inner.open_subsonic = map.next_value()?,
+                "error" => inner.error = map.next_value()?,
+                name if name == self.key => value = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        inner.status = status.ok_or_else(|| de::Error::missing_field("status"))?;
+        Ok((inner, value))
+    }
+}
+
+/// Message of the error reported when a failed response has no error object.
+const NO_ERROR_OBJECT: &str = "Unknown API error (status != ok but no error object)";
+
+/// The [`Error::Api`] for a failed response, from its error object if it has one.
+fn api_error(error: Option<ApiErrorResponse>, fallback: &str) -> Error {
+    Error::Api(error.map_or_else(
+        || SubsonicApiError {
+            code: 0,
+            message: fallback.into(),
+            help_url: None,
+        },
+        |e| SubsonicApiError {
+            code: e.code,
+            message: e.message.unwrap_or_default(),
+            help_url: e.help_url,
+        },
+    ))
+}
+
 /// Subsonic API error object embedded in the response.
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -284,6 +1074,19 @@ struct ApiErrorResponse {
     help_url: Option<String>,
 }
 
+/// Parse a header given as strings, marking the value sensitive.
+pub(crate) fn parse_header(
+    name: &str,
+    value: &str,
+) -> Result<(reqwest::header::HeaderName, reqwest::header::HeaderValue), Error> {
+    let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+        .map_err(|_| Error::Other(format!("Invalid header name '{name}'")))?;
+    let mut value = reqwest::header::HeaderValue::from_str(value)
+        .map_err(|_| Error::Other(format!("Invalid value for header '{name}'")))?;
+    value.set_sensitive(true);
+    Ok((name, value))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -306,6 +1109,132 @@ mod tests {
         assert!(query.contains("s="));
     }
 
+    #[test]
+    fn form_post_moves_params_into_body() {
+        let client = Client::new("https://music.example.com", Auth::token("admin", "pass"))
+            .unwrap()
+            .with_form_post(true);
+        let request = client
+            .api_request("star", &[("id", "1"), ("id", "2")])
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert_eq!(request.method(), reqwest::Method::POST);
+        assert_eq!(
+            request.url().as_str(),
+            "https://music.example.com/rest/star"
+        );
+        let body = std::str::from_utf8(request.body().unwrap().as_bytes().unwrap()).unwrap();
+        assert!(body.starts_with("u=admin&"));
+        assert!(body.ends_with("&id=1&id=2"));
+
+        // Enabling it on a clone leaves the original alone.
+        let original =
+            Client::new("https://music.example.com", Auth::token("admin", "pass")).unwrap();
+        let clone = original.clone().with_form_post(true);
+        assert!(clone.uses_form_post());
+        assert!(!original.uses_form_post());
+    }
+
+    #[cfg(any(feature = "rustls", feature = "native-tls"))]
+    #[test]
+    fn root_certificates_are_parsed() {
+        const CA: &[u8] = b"-----BEGIN CERTIFICATE-----
+MIIBgTCCASegAwIBAgIUGohAfZaI+6nLcYksl3h0gfQPq/IwCgYIKoZIzj0EAwIw
+FTETMBEGA1UEAwwKSG9tZWxhYiBDQTAgFw0yNjEwMTYxNDM2NDZaGA8yMTI2MDky
+MjE0MzY0NlowFTETMBEGA1UEAwwKSG9tZWxhYiBDQTBZMBMGByqGSM49AgEGCCqG
+SM49AwEHA0IABPspYzkHq90KihIsMKA3DqM/x9nKjDHv5ScyUdZd/jnGcp0EoCYY
+/YNUxdF10m+EtpG/9p9VJVWYlj8+cDW7VhajUzBRMB0GA1UdDgQWBBSnzLoLXKRY
+5Zh8sqxMzcCw6FSeFDAfBgNVHSMEGDAWgBSnzLoLXKRY5Zh8sqxMzcCw6FSeFDAP
+BgNVHRMBAf8EBTADAQH/MAoGCCqGSM49BAMCA0gAMEUCICXB1enwKfhzPcrhJjDa
+h2hf4K59fBnoqiX4/XmseZuzAiEAhjOAHgrrWfItzR6uJuDm6rm8Ikq6cSwS6T1N
+HGIYRsM=
+-----END CERTIFICATE-----
+";
+        let client =
+            Client::new("https://music.example.com", Auth::token("admin", "pass")).unwrap();
+        assert!(client.clone().with_root_certificate(CA).is_ok());
+        let built = Client::builder("https://music.example.com", Auth::token("admin", "pass"))
+            .root_certificate(CA)
+            .build();
+        assert!(built.is_ok());
+        assert!(client.with_root_certificate(b"not a certificate").is_err());
+    }
+
+    #[test]
+    fn default_headers_are_validated_and_redacted() {
+        let client = Client::new("https://music.example.com", Auth::token("admin", "pass"))
+            .unwrap()
+            .with_default_header("Remote-User", "alice")
+            .unwrap();
+        assert!(!format!("{client:?}").contains("alice"));
+        assert!(client.with_default_header("Remote User", "alice").is_err());
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn default_headers_are_sent_on_every_request() {
+        let server = crate::testing::MockServer::start().await;
+        let client = server
+            .client()
+            .with_default_header("Remote-User", "alice")
+            .unwrap();
+        client.ping().await.unwrap();
+        client.get_cover_art("al-1", None).await.unwrap();
+
+        let requests = server.inner().received_requests().await.unwrap();
+        let paths: Vec<&str> = requests.iter().map(|r| r.url.path()).collect();
+        assert_eq!(paths, ["/rest/ping", "/rest/getCoverArt"]);
+        for request in &requests {
+            assert_eq!(request.headers["remote-user"], "alice");
+        }
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn authorization_headers_reach_the_server() {
+        use wiremock::matchers::{header, path};
+        use wiremock::{Mock, ResponseTemplate};
+
+        let server = crate::testing::MockServer::start().await;
+        let ok = ResponseTemplate::new(200)
+            .set_body_json(crate::testing::ok_envelope(serde_json::json!({})));
+        Mock::given(path("/rest/ping"))
+            .and(header("authorization", "Basic cHJveHk6c2VjcmV0"))
+            .respond_with(ok.clone())
+            .expect(1)
+            .mount(server.inner())
+            .await;
+        Mock::given(path("/rest/ping"))
+            .and(header("authorization", "Bearer token-1"))
+            .respond_with(ok)
+            .expect(1)
+            .mount(server.inner())
+            .await;
+
+        let client = server.client().with_basic_auth("proxy", "secret");
+        client.ping().await.unwrap();
+        let client = server
+            .client()
+            .with_default_header("Authorization", "Bearer token-1")
+            .unwrap();
+        client.ping().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn timeout_applies_per_client() {
+        // Connections are accepted by the OS but never answered.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let client = Client::new(&url, Auth::token("admin", "pass"))
+            .unwrap()
+            .with_timeout(Duration::from_millis(50));
+
+        let err = client.ping().await.unwrap_err();
+        assert!(matches!(err.inner(), Error::Http(e) if e.is_timeout()));
+    }
+
     #[test]
     fn build_url_preserves_base_path() {
         // When the base URL has a sub-path (e.g. /music), it must be preserved.
@@ -360,6 +1289,31 @@ mod tests {
         assert!(query.contains("f=json"));
     }
 
+    #[test]
+    fn build_url_custom_auth_provider() {
+        #[derive(Debug)]
+        struct Precomputed;
+
+        impl AuthProvider for Precomputed {
+            fn username(&self) -> Option<&str> {
+                Some("admin")
+            }
+
+            fn params(&self) -> Vec<(&'static str, String)> {
+                vec![
+                    ("t", "26719a1196d2a940705a59634eb18eab".into()),
+                    ("s", "c19b2d".into()),
+                ]
+            }
+        }
+
+        let client = Client::new("https://music.example.com", Precomputed).unwrap();
+        let url = client.build_url("ping", &[]).unwrap();
+        let query = url.query().unwrap();
+
+        assert!(query.starts_with("u=admin&t=26719a1196d2a940705a59634eb18eab&s=c19b2d&"));
+    }
+
     #[test]
     fn builder_methods() {
         let client = Client::new("https://example.com", Auth::token("u", "p"))
@@ -407,4 +1361,89 @@ mod tests {
         assert_eq!(err.code, 40);
         assert_eq!(err.message.as_deref(), Some("Wrong username or password"));
     }
+
+    #[test]
+    fn parse_errors_name_the_field() {
+        let client = Client::new("https://music.example.com", Auth::token("u", "p")).unwrap();
+        let err = client
+            .decode::<crate::data::AlbumWithSongsId3>(serde_json::json!({
+                "id": "al-1",
+                "name": "x",
+                "song": [
+                    { "id": "tr-1", "title": "a" },
+                    { "id": "tr-2", "title": "b", "replayGain": { "trackGain": "loud" } }
+                ]
+            }))
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("song[1].replayGain.trackGain: invalid type"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn parse_typed_reads_only_the_requested_key() {
+        let body = bytes::Bytes::from_static(
+            br#"{"subsonic-response": {
+                "status": "ok",
+                "version": "1.16.1",
+                "type": "navidrome",
+                "artists": { "ignoredArticles": "The", "index": [] },
+                "other": [1, 2, { "x": null }]
+            }}"#,
+        );
+        let (inner, artists) =
+            parse_typed::<crate::data::ArtistsId3>(body.clone(), "artists").unwrap();
+        assert_eq!(inner.status, "ok");
+        assert_eq!(inner.server_type.as_deref(), Some("navidrome"));
+        assert!(inner.data.is_empty());
+        assert_eq!(artists.unwrap().ignored_articles.as_deref(), Some("The"));
+
+        let (_, missing) = parse_typed::<crate::data::ArtistsId3>(body, "indexes").unwrap();
+        assert!(missing.is_none());
+
+        let failed = bytes::Bytes::from_static(
+            br#"{"subsonic-response": {"status": "failed", "error": {"code": 70, "message": "x"}}}"#,
+        );
+        let (inner, _) = parse_typed::<crate::data::ArtistsId3>(failed, "artists").unwrap();
+        assert_eq!(inner.error.map(|e| e.code), Some(70));
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn version_negotiation_is_opt_in() {
+        use crate::api::searching::Search3Params;
+        use crate::testing::MockServer;
+
+        let server = MockServer::start().await;
+        server
+            .respond_with("ping", serde_json::json!({ "version": "1.4.0" }))
+            .await;
+        let params = Search3Params::new("queen");
+
+        let client = server.client();
+        client.ping().await.unwrap();
+        client.search3_with(&params).await.unwrap();
+        assert_eq!(server.calls("search3").await, 1);
+
+        let client = server.client().with_version_negotiation(true);
+        client.ping().await.unwrap();
+        let result = client.search3_with(&params).await.unwrap();
+        assert_eq!(result.artist[0].name, "Queen");
+        assert_eq!(server.calls("search3").await, 1);
+        assert_eq!(server.calls("search2").await, 1);
+
+        // The canned search2 reply reported a newer version; report the old one again.
+        client.ping().await.unwrap();
+        let err = client.get_avatar("admin").await.unwrap_err();
+        assert!(matches!(
+            err.inner(),
+            Error::UnsupportedByServer { endpoint, needs, .. }
+                if endpoint == "getAvatar" && needs == "1.8.0"
+        ));
+        assert!(client.get_scan_status().await.is_err());
+        assert_eq!(server.calls("getAvatar").await, 0);
+        assert_eq!(server.calls("getScanStatus").await, 0);
+    }
 }
@@ -0,0 +1,199 @@
+//! Conditional requests for images.
+//!
+//! [`Client::get_cover_art_conditional`] and [`Client::get_avatar_conditional`] remember the
+//! `ETag` and `Last-Modified` headers of each image in a [`ValidatorStore`] and send them back
+//! as `If-None-Match` / `If-Modified-Since`, so the server can answer `304 Not Modified`
+//! instead of sending the image again. The image bytes themselves stay in the caller's cache.
+//!
+//! ```no_run
+//! use opensubsonic::conditional::{Conditional, MemoryValidatorStore};
+//! use opensubsonic::{Auth, Client};
+//!
+//! # async fn example() -> Result<(), opensubsonic::Error> {
+//! let client = Client::new("https://music.example.com", Auth::token("admin", "pass"))?;
+//! let validators = MemoryValidatorStore::new();
+//! match client.get_cover_art_conditional("al-1", Some(300), &validators).await? {
+//!     Conditional::Fresh(image) => println!("new image, {} bytes", image.len()),
+//!     Conditional::Cached => println!("cached copy is still current"),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use bytes::Bytes;
+use reqwest::header::{self, HeaderMap};
+
+use crate::Client;
+use crate::error::Error;
+
+/// The result of a conditional request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conditional {
+    /// The server sent the resource.
+    Fresh(Bytes),
+    /// The server answered `304 Not Modified`: the previously fetched copy is current.
+    Cached,
+}
+
+/// The cache validators the server sent with a resource.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Validators {
+    /// `ETag` header, sent back as `If-None-Match`.
+    pub etag: Option<String>,
+    /// `Last-Modified` header, sent back as `If-Modified-Since`.
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let get = |name| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_owned)
+        };
+        Self {
+            etag: get(header::ETAG),
+            last_modified: get(header::LAST_MODIFIED),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// Storage for [`Validators`], keyed by request (e.g. `getCoverArt?id=al-1&size=300`).
+///
+/// Implement this to persist validators alongside an on-disk image cache.
+pub trait ValidatorStore: Send + Sync {
+    /// The validators stored for `key`.
+    fn get(&self, key: &str) -> Option<Validators>;
+
+    /// Store `validators` for `key`, replacing any previous ones.
+    fn put(&self, key: &str, validators: Validators);
+}
+
+/// An in-memory [`ValidatorStore`].
+#[derive(Debug, Default)]
+pub struct MemoryValidatorStore(Mutex<HashMap<String, Validators>>);
+
+impl MemoryValidatorStore {
+    /// An empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ValidatorStore for MemoryValidatorStore {
+    fn get(&self, key: &str) -> Option<Validators> {
+        let map = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        map.get(key).cloned()
+    }
+
+    fn put(&self, key: &str, validators: Validators) {
+        let mut map = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        map.insert(key.to_owned(), validators);
+    }
+}
+
+impl Client {
+    /// Perform a GET request to a binary endpoint, sending the validators stored in `store`
+    /// and updating them from the response.
+    pub(crate) async fn get_bytes_conditional(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+        store: &dyn ValidatorStore,
+    ) -> Result<Conditional, Error> {
+        self.negotiate_binary(endpoint)?;
+        let key = store_key(endpoint, params);
+        let mut request = self.request(reqwest::Method::GET, endpoint, params)?;
+        if let Some(validators) = store.get(&key) {
+            if let Some(etag) = validators.etag {
+                request = request.header(header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = validators.last_modified {
+                request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let resp = self.send_binary(request).await?;
+        if resp.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(Conditional::Cached);
+        }
+        let validators = Validators::from_headers(resp.headers());
+        if !validators.is_empty() {
+            store.put(&key, validators);
+        }
+        Ok(Conditional::Fresh(resp.bytes().await?))
+    }
+}
+
+/// The store key for a request: the endpoint and its parameters, without authentication.
+fn store_key(endpoint: &str, params: &[(&str, &str)]) -> String {
+    let query = url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(params)
+        .finish();
+    format!("{endpoint}?{query}")
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::Auth;
+
+    #[tokio::test]
+    async fn revalidates_with_stored_etag() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for response in [
+                "HTTP/1.1 200 OK\r\nContent-Type: image/png\r\nETag: \"v1\"\r\n\
+                 Content-Length: 3\r\nConnection: close\r\n\r\npng",
+                "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n",
+            ] {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0; 4096];
+                let n = socket.read(&mut buf).await.unwrap();
+                requests.push(String::from_utf8_lossy(&buf[..n]).to_lowercase());
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+
+        let client = Client::new(&format!("http://{addr}"), Auth::token("u", "p")).unwrap();
+        let store = MemoryValidatorStore::new();
+        assert_eq!(
+            client
+                .get_cover_art_conditional("al-1", Some(300), &store)
+                .await
+                .unwrap(),
+            Conditional::Fresh(Bytes::from_static(b"png"))
+        );
+        assert_eq!(
+            store
+                .get("getCoverArt?id=al-1&size=300")
+                .unwrap()
+                .etag
+                .as_deref(),
+            Some("\"v1\"")
+        );
+        assert_eq!(
+            client
+                .get_cover_art_conditional("al-1", Some(300), &store)
+                .await
+                .unwrap(),
+            Conditional::Cached
+        );
+
+        let requests = server.await.unwrap();
+        assert!(!requests[0].contains("if-none-match"));
+        assert!(requests[1].contains("if-none-match: \"v1\""));
+    }
+}
@@ -1,12 +1,19 @@
 //! Types for the Bookmarks API section.
 
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 use super::common::Child;
+use super::ids::SongId;
+use super::timestamp::Timestamp;
 
 /// A bookmark on a media file.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Bookmark {
     /// Position in milliseconds.
     pub position: i64,
@@ -16,37 +23,71 @@ pub struct Bookmark {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub comment: Option<String>,
     /// Date created (ISO 8601).
-    pub created: String,
+    pub created: Timestamp,
     /// Date last changed (ISO 8601).
-    pub changed: String,
+    pub changed: Timestamp,
     /// The bookmarked media item.
     pub entry: Child,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    Bookmark::new(position: i64, username: String, created: Timestamp, changed: Timestamp, entry: Child);
+    set {
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_comment => comment: String,
+    }
 }
 
 /// The play queue (current playlist with position).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct PlayQueue {
     /// ID of the currently playing track.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub current: Option<String>,
+    pub current: Option<SongId>,
     /// Position in milliseconds of the currently playing track.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub position: Option<i64>,
     /// Username.
     pub username: String,
     /// Date modified (ISO 8601).
-    pub changed: String,
+    pub changed: Timestamp,
     /// Client app name that last modified this queue.
     pub changed_by: String,
     /// Songs in the queue.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub entry: Vec<Child>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    PlayQueue::new(username: String, changed: Timestamp, changed_by: String);
+    set {
+        with_entry => entry: Vec<Child>,
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_current => current: SongId,
+        with_position => position: i64,
+    }
 }
 
 /// The play queue by index (OpenSubsonic extension).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct PlayQueueByIndex {
     /// Index of the currently playing track.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -57,10 +98,52 @@ pub struct PlayQueueByIndex {
     /// Username.
     pub username: String,
     /// Date modified (ISO 8601).
-    pub changed: String,
+    pub changed: Timestamp,
     /// Client app name.
     pub changed_by: String,
     /// Songs in the queue.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub entry: Vec<Child>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    PlayQueueByIndex::new(username: String, changed: Timestamp, changed_by: String);
+    set {
+        with_entry => entry: Vec<Child>,
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_current_index => current_index: i32,
+        with_position => position: i64,
+    }
+}
+
+impl Bookmark {
+    /// The bookmarked position.
+    pub fn position(&self) -> Duration {
+        millis(Some(self.position))
+    }
+}
+
+impl PlayQueue {
+    /// Position in the current track; zero if the server sent none.
+    pub fn position(&self) -> Duration {
+        millis(self.position)
+    }
+}
+
+impl PlayQueueByIndex {
+    /// Position in the current track; zero if the server sent none.
+    pub fn position(&self) -> Duration {
+        millis(self.position)
+    }
+}
+
+/// A position in milliseconds as a [`Duration`], clamping negative values to zero.
+fn millis(position: Option<i64>) -> Duration {
+    Duration::from_millis(position.and_then(|p| u64::try_from(p).ok()).unwrap_or(0))
 }
@@ -1,51 +1,91 @@
 //! Types for the Browsing API section.
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 use super::common::{Artist, Child};
+use super::timestamp::Timestamp;
 
 /// A directory in the music library (folder-based browsing).
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Directory {
     /// Directory ID.
+    #[serde(deserialize_with = "super::de::string_or_number")]
     pub id: String,
     /// Parent directory ID.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_string_or_number",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub parent: Option<String>,
     /// Directory name.
     pub name: String,
     /// Date starred (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub starred: Option<String>,
+    pub starred: Option<Timestamp>,
     /// User rating (1–5).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_rating: Option<i32>,
     /// Average rating (1.0–5.0).
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = super::generate::option_finite))]
     pub average_rating: Option<f64>,
     /// Play count.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub play_count: Option<i64>,
     /// Child entries in this directory.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub child: Vec<Child>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    Directory::new(id: String, name: String);
+    set {
+        with_child => child: Vec<Child>,
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_parent => parent: String,
+        with_starred => starred: Timestamp,
+        with_user_rating => user_rating: i32,
+        with_average_rating => average_rating: f64,
+        with_play_count => play_count: i64,
+    }
 }
 
 /// An index entry grouping artists by first letter (folder-based).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Index {
     /// Index name (e.g. "A", "B", "#").
     pub name: String,
     /// Artists in this index.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub artist: Vec<Artist>,
 }
 
+setters! {
+    Index::new(name: String);
+    set {
+        with_artist => artist: Vec<Artist>,
+    }
+}
+
 /// The full indexes response (folder-based artist listing).
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Indexes {
     /// Ignored articles (space-separated).
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -54,19 +94,34 @@ pub struct Indexes {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_modified: Option<i64>,
     /// Shortcut artists.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub shortcut: Vec<Artist>,
     /// Child entries.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub child: Vec<Child>,
     /// Index list.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub index: Vec<Index>,
 }
 
+setters! {
+    Indexes::new();
+    set {
+        with_shortcut => shortcut: Vec<Artist>,
+        with_child => child: Vec<Child>,
+        with_index => index: Vec<Index>,
+    }
+    optional {
+        with_ignored_articles => ignored_articles: String,
+        with_last_modified => last_modified: i64,
+    }
+}
+
 /// Album info (external metadata).
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct AlbumInfo {
     /// Album notes/biography.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -86,11 +141,32 @@ pub struct AlbumInfo {
     /// Large image URL.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub large_image_url: Option<String>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    AlbumInfo::new();
+    set {
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_notes => notes: String,
+        with_music_brainz_id => music_brainz_id: String,
+        with_last_fm_url => last_fm_url: String,
+        with_small_image_url => small_image_url: String,
+        with_medium_image_url => medium_image_url: String,
+        with_large_image_url => large_image_url: String,
+    }
 }
 
 /// Artist info with similar artists (folder-based).
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct ArtistInfo {
     /// Artist biography.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -111,13 +187,39 @@ pub struct ArtistInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub large_image_url: Option<String>,
     /// Similar artists.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub similar_artist: Vec<Artist>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    ArtistInfo::new();
+    set {
+        with_similar_artist => similar_artist: Vec<Artist>,
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_biography => biography: String,
+        with_music_brainz_id => music_brainz_id: String,
+        with_last_fm_url => last_fm_url: String,
+        with_small_image_url => small_image_url: String,
+        with_medium_image_url => medium_image_url: String,
+        with_large_image_url => large_image_url: String,
+    }
 }
 
 /// Artist info with similar artists (ID3-based).
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct ArtistInfo2 {
     /// Artist biography.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -138,6 +240,30 @@ pub struct ArtistInfo2 {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub large_image_url: Option<String>,
     /// Similar artists (ID3).
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub similar_artist: Vec<super::common::ArtistId3>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    ArtistInfo2::new();
+    set {
+        with_similar_artist => similar_artist: Vec<super::common::ArtistId3>,
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_biography => biography: String,
+        with_music_brainz_id => music_brainz_id: String,
+        with_last_fm_url => last_fm_url: String,
+        with_small_image_url => small_image_url: String,
+        with_medium_image_url => medium_image_url: String,
+        with_large_image_url => large_image_url: String,
+    }
 }
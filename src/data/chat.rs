@@ -1,10 +1,13 @@
 //! Types for the Chat API section.
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 /// A chat message.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct ChatMessage {
     /// Username of the sender.
     pub username: String,
@@ -12,4 +15,15 @@ pub struct ChatMessage {
     pub time: i64,
     /// Message text.
     pub message: String,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    ChatMessage::new(username: String, time: i64, message: String);
+    set {
+        with_extra => extra: Map<String, Value>,
+    }
 }
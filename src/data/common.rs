@@ -1,10 +1,127 @@
 //! Common/shared types used across multiple API sections.
 
-use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use super::ids::{AlbumId, ArtistId, CoverArtId, SongId};
+use super::name::Name;
+use super::timestamp::Timestamp;
+
+/// Define a string-valued enum that keeps unrecognized values in an `Unknown` variant.
+macro_rules! string_enum {
+    (
+        $(#[$attr:meta])*
+        pub enum $name:ident {
+            $($(#[$vattr:meta])* $variant:ident => $value:literal,)*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$vattr])* $variant,)*
+            /// A value this version of the crate does not know.
+            Unknown(String),
+        }
+
+        impl $name {
+            /// The value as sent by the server.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $value,)*
+                    Self::Unknown(value) => value,
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                match value {
+                    $($value => Self::$variant,)*
+                    other => Self::Unknown(other.to_owned()),
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = String::deserialize(deserializer)?;
+                Ok(Self::from(value.as_str()))
+            }
+        }
+
+        #[cfg(feature = "arbitrary")]
+        impl<'a> arbitrary::Arbitrary<'a> for $name {
+            fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+                // Going through `From` never yields `Unknown` holding a known value, which
+                // would deserialize to the known variant.
+                let value = if u.ratio(1, 4)? {
+                    <&str>::arbitrary(u)?
+                } else {
+                    u.choose(&[$($value),*])?
+                };
+                Ok(Self::from(value))
+            }
+        }
+    };
+}
+
+string_enum! {
+    /// Explicit content rating of a song or album (OpenSubsonic).
+    pub enum ExplicitStatus {
+        /// Contains explicit content.
+        Explicit => "explicit",
+        /// An edited, clean version.
+        Clean => "clean",
+        /// Not rated.
+        Unrated => "",
+    }
+}
+
+string_enum! {
+    /// What a [`Child`] is (OpenSubsonic `mediaType`).
+    pub enum MediaType {
+        /// A song.
+        Song => "song",
+        /// An album.
+        Album => "album",
+        /// An artist.
+        Artist => "artist",
+    }
+}
+
+string_enum! {
+    /// The kind of media a [`Child`] holds (its `type` field).
+    pub enum GenericMediaType {
+        /// Music.
+        Music => "music",
+        /// A podcast episode.
+        Podcast => "podcast",
+        /// An audiobook.
+        Audiobook => "audiobook",
+        /// A video.
+        Video => "video",
+    }
+}
 
 /// A genre.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Genre {
     /// Genre name.
     #[serde(rename = "value")]
@@ -13,19 +130,38 @@ pub struct Genre {
     pub song_count: i64,
     /// Number of albums in this genre.
     pub album_count: i64,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    Genre::new(name: String, song_count: i64, album_count: i64);
+    set {
+        with_extra => extra: Map<String, Value>,
+    }
 }
 
 /// A genre tag on a media item (simplified, just a name).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct ItemGenre {
     /// Genre name.
     pub name: String,
 }
 
+setters! {
+    ItemGenre::new(name: String);
+}
+
 /// A date for a media item that may be partial (year only, year-month, or full date).
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct ItemDate {
     /// The year.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -38,9 +174,20 @@ pub struct ItemDate {
     pub day: Option<i32>,
 }
 
+setters! {
+    ItemDate::new();
+    optional {
+        with_year => year: i32,
+        with_month => month: i32,
+        with_day => day: i32,
+    }
+}
+
 /// A disc title for an album.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct DiscTitle {
     /// The disc number.
     pub disc: i32,
@@ -48,44 +195,79 @@ pub struct DiscTitle {
     pub title: String,
     /// Cover art ID for the disc (OpenSubsonic).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub cover_art: Option<String>,
+    pub cover_art: Option<CoverArtId>,
+}
+
+setters! {
+    DiscTitle::new(disc: i32, title: String);
+    optional {
+        with_cover_art => cover_art: CoverArtId,
+    }
 }
 
 /// A record label for an album.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct RecordLabel {
     /// Label name.
     pub name: String,
 }
 
+setters! {
+    RecordLabel::new(name: String);
+}
+
 /// Replay gain data for a song.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct ReplayGain {
     /// Track replay gain value in dB.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = super::generate::option_finite))]
     pub track_gain: Option<f64>,
     /// Album replay gain value in dB.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = super::generate::option_finite))]
     pub album_gain: Option<f64>,
     /// Track peak value (positive).
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = super::generate::option_finite))]
     pub track_peak: Option<f64>,
     /// Album peak value (positive).
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = super::generate::option_finite))]
     pub album_peak: Option<f64>,
     /// Base gain value in dB (e.g. Ogg Opus output gain).
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = super::generate::option_finite))]
     pub base_gain: Option<f64>,
     /// Fallback gain for when track/album gain is missing.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = super::generate::option_finite))]
     pub fallback_gain: Option<f64>,
 }
 
+setters! {
+    ReplayGain::new();
+    optional {
+        with_track_gain => track_gain: f64,
+        with_album_gain => album_gain: f64,
+        with_track_peak => track_peak: f64,
+        with_album_peak => album_peak: f64,
+        with_base_gain => base_gain: f64,
+        with_fallback_gain => fallback_gain: f64,
+    }
+}
+
 /// A contributor artist for a song or album.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Contributor {
     /// The contributor role (e.g. "composer", "performer").
     pub role: String,
@@ -96,65 +278,121 @@ pub struct Contributor {
     pub artist: ArtistId3,
 }
 
+setters! {
+    Contributor::new(role: String, artist: ArtistId3);
+    optional {
+        with_sub_role => sub_role: String,
+    }
+}
+
 /// A music folder.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct MusicFolder {
     /// Folder ID.
     pub id: i64,
     /// Folder name.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    MusicFolder::new(id: i64);
+    set {
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_name => name: String,
+    }
 }
 
 /// A supported OpenSubsonic extension.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct OpenSubsonicExtension {
     /// Extension name.
     pub name: String,
     /// Supported version numbers of this extension.
+    #[serde(deserialize_with = "super::de::one_or_many")]
     pub versions: Vec<i32>,
 }
 
+setters! {
+    OpenSubsonicExtension::new(name: String);
+    set {
+        with_versions => versions: Vec<i32>,
+    }
+}
+
 /// License information.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct License {
     /// Whether the license is valid.
+    #[serde(deserialize_with = "super::de::lenient_bool")]
     pub valid: bool,
     /// User email.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub email: Option<String>,
     /// License expiration date (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub license_expires: Option<String>,
+    pub license_expires: Option<Timestamp>,
     /// Trial expiration date (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub trial_expires: Option<String>,
+    pub trial_expires: Option<Timestamp>,
+}
+
+setters! {
+    License::new();
+    set {
+        with_valid => valid: bool,
+    }
+    optional {
+        with_email => email: String,
+        with_license_expires => license_expires: Timestamp,
+        with_trial_expires => trial_expires: Timestamp,
+    }
 }
 
 /// Token info (OpenSubsonic extension).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct TokenInfo {
     /// Username associated with the token.
     pub username: String,
 }
 
+setters! {
+    TokenInfo::new(username: String);
+}
+
 // ── ID3-based artist ───────────────────────────────────────────────────────
 
 /// An artist from ID3 tags.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct ArtistId3 {
     /// Artist ID.
-    pub id: String,
+    pub id: ArtistId,
     /// Artist name.
     pub name: String,
     /// Cover art ID.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub cover_art: Option<String>,
+    pub cover_art: Option<CoverArtId>,
     /// External image URL.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub artist_image_url: Option<String>,
@@ -163,7 +401,7 @@ pub struct ArtistId3 {
     pub album_count: Option<i64>,
     /// Date the artist was starred (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub starred: Option<String>,
+    pub starred: Option<Timestamp>,
     /// MusicBrainz ID.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub music_brainz_id: Option<String>,
@@ -171,21 +409,54 @@ pub struct ArtistId3 {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sort_name: Option<String>,
     /// Roles this artist has in the library (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub roles: Option<Vec<String>>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    ArtistId3::new(id: ArtistId, name: String);
+    set {
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_cover_art => cover_art: CoverArtId,
+        with_artist_image_url => artist_image_url: String,
+        with_album_count => album_count: i64,
+        with_starred => starred: Timestamp,
+        with_music_brainz_id => music_brainz_id: String,
+        with_sort_name => sort_name: String,
+        with_roles => roles: Vec<String>,
+    }
+}
+
+/// The artist name.
+impl fmt::Display for ArtistId3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
 }
 
 /// An artist with its albums (ID3-based).
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct ArtistWithAlbumsId3 {
     /// Artist ID.
-    pub id: String,
+    pub id: ArtistId,
     /// Artist name.
     pub name: String,
     /// Cover art ID.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub cover_art: Option<String>,
+    pub cover_art: Option<CoverArtId>,
     /// External image URL.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub artist_image_url: Option<String>,
@@ -194,7 +465,7 @@ pub struct ArtistWithAlbumsId3 {
     pub album_count: Option<i64>,
     /// Date the artist was starred (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub starred: Option<String>,
+    pub starred: Option<Timestamp>,
     /// MusicBrainz ID.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub music_brainz_id: Option<String>,
@@ -202,58 +473,106 @@ pub struct ArtistWithAlbumsId3 {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sort_name: Option<String>,
     /// Roles this artist has in the library (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub roles: Option<Vec<String>>,
     /// The artist's albums.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub album: Vec<AlbumId3>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    ArtistWithAlbumsId3::new(id: ArtistId, name: String);
+    set {
+        with_album => album: Vec<AlbumId3>,
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_cover_art => cover_art: CoverArtId,
+        with_artist_image_url => artist_image_url: String,
+        with_album_count => album_count: i64,
+        with_starred => starred: Timestamp,
+        with_music_brainz_id => music_brainz_id: String,
+        with_sort_name => sort_name: String,
+        with_roles => roles: Vec<String>,
+    }
 }
 
 /// A list of indexed artists (ID3-based).
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct ArtistsId3 {
     /// Ignored articles (space-separated).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ignored_articles: Option<String>,
     /// Index list (each entry groups artists by first letter).
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub index: Vec<IndexId3>,
 }
 
+setters! {
+    ArtistsId3::new();
+    set {
+        with_index => index: Vec<IndexId3>,
+    }
+    optional {
+        with_ignored_articles => ignored_articles: String,
+    }
+}
+
 /// A single index entry in the artist list (ID3-based).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct IndexId3 {
     /// Index name (e.g. "A", "B", "#").
     pub name: String,
     /// Artists in this index.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub artist: Vec<ArtistId3>,
 }
 
+setters! {
+    IndexId3::new(name: String);
+    set {
+        with_artist => artist: Vec<ArtistId3>,
+    }
+}
+
 // ── ID3-based album ────────────────────────────────────────────────────────
 
 /// An album from ID3 tags.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct AlbumId3 {
     /// Album ID.
-    pub id: String,
+    pub id: AlbumId,
     /// Album name.
-    pub name: String,
+    pub name: Name,
     /// Album version (e.g. "Remastered", "Deluxe Edition").
     #[serde(skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
     /// Artist name.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub artist: Option<String>,
+    pub artist: Option<Name>,
     /// Artist ID.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub artist_id: Option<String>,
+    pub artist_id: Option<ArtistId>,
     /// Cover art ID.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub cover_art: Option<String>,
+    pub cover_art: Option<CoverArtId>,
     /// Number of songs.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub song_count: Option<i64>,
@@ -265,39 +584,55 @@ pub struct AlbumId3 {
     pub play_count: Option<i64>,
     /// Date added (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub created: Option<String>,
+    pub created: Option<Timestamp>,
     /// Date starred (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub starred: Option<String>,
+    pub starred: Option<Timestamp>,
     /// Album year.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub year: Option<i32>,
     /// Genre name.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub genre: Option<String>,
+    pub genre: Option<Name>,
     /// Date last played (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub played: Option<String>,
+    pub played: Option<Timestamp>,
     /// User rating (1–5).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_rating: Option<i32>,
     /// Record labels (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub record_labels: Option<Vec<RecordLabel>>,
     /// MusicBrainz ID.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub music_brainz_id: Option<String>,
     /// All genres (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub genres: Option<Vec<ItemGenre>>,
     /// All artists (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub artists: Option<Vec<ArtistId3>>,
     /// Display artist string (OpenSubsonic).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display_artist: Option<String>,
     /// Release types such as "Album", "Compilation", "EP" (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub release_types: Option<Vec<String>>,
     /// Release date (OpenSubsonic).
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -306,28 +641,96 @@ pub struct AlbumId3 {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub release_date: Option<ItemDate>,
     /// Whether this is a compilation (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub is_compilation: Option<bool>,
     /// Sort name (OpenSubsonic).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sort_name: Option<String>,
     /// Disc titles (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub disc_titles: Option<Vec<DiscTitle>>,
     /// Explicit status (OpenSubsonic).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub explicit_status: Option<String>,
+    pub explicit_status: Option<ExplicitStatus>,
     /// Moods (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub moods: Option<Vec<String>>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    AlbumId3::new(id: AlbumId, name: Name);
+    set {
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_version => version: String,
+        with_artist => artist: Name,
+        with_artist_id => artist_id: ArtistId,
+        with_cover_art => cover_art: CoverArtId,
+        with_song_count => song_count: i64,
+        with_duration => duration: i64,
+        with_play_count => play_count: i64,
+        with_created => created: Timestamp,
+        with_starred => starred: Timestamp,
+        with_year => year: i32,
+        with_genre => genre: Name,
+        with_played => played: Timestamp,
+        with_user_rating => user_rating: i32,
+        with_record_labels => record_labels: Vec<RecordLabel>,
+        with_music_brainz_id => music_brainz_id: String,
+        with_genres => genres: Vec<ItemGenre>,
+        with_artists => artists: Vec<ArtistId3>,
+        with_display_artist => display_artist: String,
+        with_release_types => release_types: Vec<String>,
+        with_original_release_date => original_release_date: ItemDate,
+        with_release_date => release_date: ItemDate,
+        with_is_compilation => is_compilation: bool,
+        with_sort_name => sort_name: String,
+        with_disc_titles => disc_titles: Vec<DiscTitle>,
+        with_explicit_status => explicit_status: ExplicitStatus,
+        with_moods => moods: Vec<String>,
+    }
+}
+
+/// `Artist – Album (Year)`, leaving out the artist and year when unknown.
+impl fmt::Display for AlbumId3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let artist = self.artist.as_deref().or(self.display_artist.as_deref());
+        if let Some(artist) = artist.filter(|a| !a.is_empty()) {
+            write!(f, "{artist} – ")?;
+        }
+        f.write_str(&self.name)?;
+        if let Some(year) = self.year {
+            write!(f, " ({year})")?;
+        }
+        Ok(())
+    }
 }
 
 /// An album with its songs (ID3-based).
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct AlbumWithSongsId3 {
     /// Album ID.
-    pub id: String,
+    pub id: AlbumId,
     /// Album name.
     pub name: String,
     /// Album version.
@@ -338,10 +741,10 @@ pub struct AlbumWithSongsId3 {
     pub artist: Option<String>,
     /// Artist ID.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub artist_id: Option<String>,
+    pub artist_id: Option<ArtistId>,
     /// Cover art ID.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub cover_art: Option<String>,
+    pub cover_art: Option<CoverArtId>,
     /// Number of songs.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub song_count: Option<i64>,
@@ -353,10 +756,10 @@ pub struct AlbumWithSongsId3 {
     pub play_count: Option<i64>,
     /// Date added (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub created: Option<String>,
+    pub created: Option<Timestamp>,
     /// Date starred (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub starred: Option<String>,
+    pub starred: Option<Timestamp>,
     /// Album year.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub year: Option<i32>,
@@ -365,27 +768,43 @@ pub struct AlbumWithSongsId3 {
     pub genre: Option<String>,
     /// Date last played (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub played: Option<String>,
+    pub played: Option<Timestamp>,
     /// User rating (1–5).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_rating: Option<i32>,
     /// Record labels (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub record_labels: Option<Vec<RecordLabel>>,
     /// MusicBrainz ID.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub music_brainz_id: Option<String>,
     /// All genres (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub genres: Option<Vec<ItemGenre>>,
     /// All artists (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub artists: Option<Vec<ArtistId3>>,
     /// Display artist string (OpenSubsonic).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display_artist: Option<String>,
     /// Release types such as "Album", "Compilation", "EP" (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub release_types: Option<Vec<String>>,
     /// Release date (OpenSubsonic).
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -394,32 +813,87 @@ pub struct AlbumWithSongsId3 {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub release_date: Option<ItemDate>,
     /// Whether this is a compilation (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub is_compilation: Option<bool>,
     /// Sort name (OpenSubsonic).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sort_name: Option<String>,
     /// Disc titles (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub disc_titles: Option<Vec<DiscTitle>>,
     /// Explicit status (OpenSubsonic).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub explicit_status: Option<String>,
+    pub explicit_status: Option<ExplicitStatus>,
     /// Moods (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub moods: Option<Vec<String>>,
     /// The songs in this album.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub song: Vec<Child>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    AlbumWithSongsId3::new(id: AlbumId, name: String);
+    set {
+        with_song => song: Vec<Child>,
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_version => version: String,
+        with_artist => artist: String,
+        with_artist_id => artist_id: ArtistId,
+        with_cover_art => cover_art: CoverArtId,
+        with_song_count => song_count: i64,
+        with_duration => duration: i64,
+        with_play_count => play_count: i64,
+        with_created => created: Timestamp,
+        with_starred => starred: Timestamp,
+        with_year => year: i32,
+        with_genre => genre: String,
+        with_played => played: Timestamp,
+        with_user_rating => user_rating: i32,
+        with_record_labels => record_labels: Vec<RecordLabel>,
+        with_music_brainz_id => music_brainz_id: String,
+        with_genres => genres: Vec<ItemGenre>,
+        with_artists => artists: Vec<ArtistId3>,
+        with_display_artist => display_artist: String,
+        with_release_types => release_types: Vec<String>,
+        with_original_release_date => original_release_date: ItemDate,
+        with_release_date => release_date: ItemDate,
+        with_is_compilation => is_compilation: bool,
+        with_sort_name => sort_name: String,
+        with_disc_titles => disc_titles: Vec<DiscTitle>,
+        with_explicit_status => explicit_status: ExplicitStatus,
+        with_moods => moods: Vec<String>,
+    }
 }
 
 // ── Folder-based artist (legacy) ───────────────────────────────────────────
 
 /// An artist (folder-based / legacy).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Artist {
     /// Artist ID.
+    #[serde(deserialize_with = "super::de::string_or_number")]
     pub id: String,
     /// Artist name.
     pub name: String,
@@ -428,27 +902,56 @@ pub struct Artist {
     pub artist_image_url: Option<String>,
     /// Date starred (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub starred: Option<String>,
+    pub starred: Option<Timestamp>,
     /// User rating (1–5).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_rating: Option<i32>,
     /// Average rating (1.0–5.0).
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = super::generate::option_finite))]
     pub average_rating: Option<f64>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    Artist::new(id: String, name: String);
+    set {
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_artist_image_url => artist_image_url: String,
+        with_starred => starred: Timestamp,
+        with_user_rating => user_rating: i32,
+        with_average_rating => average_rating: f64,
+    }
 }
 
 /// A musical work associated with a song (OpenSubsonic).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Work {
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub music_brainz_id: Option<String>,
 }
 
+setters! {
+    Work::new(name: String);
+    optional {
+        with_music_brainz_id => music_brainz_id: String,
+    }
+}
+
 /// A movement within a musical work (OpenSubsonic).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Movement {
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -457,29 +960,43 @@ pub struct Movement {
     pub count: Option<i32>,
 }
 
+setters! {
+    Movement::new(name: String);
+    optional {
+        with_number => number: i32,
+        with_count => count: i32,
+    }
+}
+
 // ── Child (song/media) ─────────────────────────────────────────────────────
 
 /// A media item (song, video, or directory entry). This is the fundamental type returned by
 /// most browsing, searching, and listing endpoints.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Child {
     /// Media ID.
-    pub id: String,
+    pub id: SongId,
     /// Parent folder/album ID.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_string_or_number",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub parent: Option<String>,
     /// Whether this entry is a directory.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::lenient_bool")]
     pub is_dir: bool,
     /// Media title.
     pub title: String,
     /// Album name.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub album: Option<String>,
+    pub album: Option<Name>,
     /// Artist name.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub artist: Option<String>,
+    pub artist: Option<Name>,
     /// Track number.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub track: Option<i32>,
@@ -488,10 +1005,10 @@ pub struct Child {
     pub year: Option<i32>,
     /// Genre name.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub genre: Option<String>,
+    pub genre: Option<Name>,
     /// Cover art ID.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub cover_art: Option<String>,
+    pub cover_art: Option<CoverArtId>,
     /// File size in bytes.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub size: Option<i64>,
@@ -526,13 +1043,18 @@ pub struct Child {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub path: Option<String>,
     /// Whether this is a video.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub is_video: Option<bool>,
     /// User rating (1–5).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_rating: Option<i32>,
     /// Average rating (1.0–5.0).
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = super::generate::option_finite))]
     pub average_rating: Option<f64>,
     /// Play count.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -542,22 +1064,22 @@ pub struct Child {
     pub disc_number: Option<i32>,
     /// Date created (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub created: Option<String>,
+    pub created: Option<Timestamp>,
     /// Date starred (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub starred: Option<String>,
+    pub starred: Option<Timestamp>,
     /// Album ID.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub album_id: Option<String>,
+    pub album_id: Option<AlbumId>,
     /// Artist ID.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub artist_id: Option<String>,
+    pub artist_id: Option<ArtistId>,
     /// Generic media type (music/podcast/audiobook/video).
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
-    pub media_type_generic: Option<String>,
+    pub media_type_generic: Option<GenericMediaType>,
     /// Media type (song/album/artist) — OpenSubsonic.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub media_type: Option<String>,
+    pub media_type: Option<MediaType>,
     /// Bookmark position in seconds.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bookmark_position: Option<i64>,
@@ -569,7 +1091,7 @@ pub struct Child {
     pub original_height: Option<i32>,
     /// Date last played (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub played: Option<String>,
+    pub played: Option<Timestamp>,
     /// BPM (OpenSubsonic).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bpm: Option<i32>,
@@ -583,52 +1105,166 @@ pub struct Child {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub music_brainz_id: Option<String>,
     /// ISRC codes (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub isrc: Option<Vec<String>>,
     /// All genres (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub genres: Option<Vec<ItemGenre>>,
     /// All song artists (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub artists: Option<Vec<ArtistId3>>,
     /// Display artist string (OpenSubsonic).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display_artist: Option<String>,
     /// Album artists (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub album_artists: Option<Vec<ArtistId3>>,
     /// Display album artist (OpenSubsonic).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display_album_artist: Option<String>,
     /// Contributors (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub contributors: Option<Vec<Contributor>>,
     /// Display composer (OpenSubsonic).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display_composer: Option<String>,
     /// Moods (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub moods: Option<Vec<String>>,
     /// Replay gain data (OpenSubsonic).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub replay_gain: Option<ReplayGain>,
-    /// Explicit status (OpenSubsonic): "explicit", "clean", or "".
+    /// Explicit status (OpenSubsonic).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub explicit_status: Option<String>,
+    pub explicit_status: Option<ExplicitStatus>,
     /// Works associated with the song (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub works: Option<Vec<Work>>,
     /// Movements associated with the song (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub movements: Option<Vec<Movement>>,
     /// Grouping tags associated with the song (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub groupings: Option<Vec<String>>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    Child::new(id: SongId, title: String);
+    set {
+        with_is_dir => is_dir: bool,
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_parent => parent: String,
+        with_album => album: Name,
+        with_artist => artist: Name,
+        with_track => track: i32,
+        with_year => year: i32,
+        with_genre => genre: Name,
+        with_cover_art => cover_art: CoverArtId,
+        with_size => size: i64,
+        with_content_type => content_type: String,
+        with_suffix => suffix: String,
+        with_transcoded_content_type => transcoded_content_type: String,
+        with_transcoded_suffix => transcoded_suffix: String,
+        with_duration => duration: i64,
+        with_bit_rate => bit_rate: i32,
+        with_bit_depth => bit_depth: i32,
+        with_sampling_rate => sampling_rate: i32,
+        with_channel_count => channel_count: i32,
+        with_path => path: String,
+        with_is_video => is_video: bool,
+        with_user_rating => user_rating: i32,
+        with_average_rating => average_rating: f64,
+        with_play_count => play_count: i64,
+        with_disc_number => disc_number: i32,
+        with_created => created: Timestamp,
+        with_starred => starred: Timestamp,
+        with_album_id => album_id: AlbumId,
+        with_artist_id => artist_id: ArtistId,
+        with_media_type_generic => media_type_generic: GenericMediaType,
+        with_media_type => media_type: MediaType,
+        with_bookmark_position => bookmark_position: i64,
+        with_original_width => original_width: i32,
+        with_original_height => original_height: i32,
+        with_played => played: Timestamp,
+        with_bpm => bpm: i32,
+        with_comment => comment: String,
+        with_sort_name => sort_name: String,
+        with_music_brainz_id => music_brainz_id: String,
+        with_isrc => isrc: Vec<String>,
+        with_genres => genres: Vec<ItemGenre>,
+        with_artists => artists: Vec<ArtistId3>,
+        with_display_artist => display_artist: String,
+        with_album_artists => album_artists: Vec<ArtistId3>,
+        with_display_album_artist => display_album_artist: String,
+        with_contributors => contributors: Vec<Contributor>,
+        with_display_composer => display_composer: String,
+        with_moods => moods: Vec<String>,
+        with_replay_gain => replay_gain: ReplayGain,
+        with_explicit_status => explicit_status: ExplicitStatus,
+        with_works => works: Vec<Work>,
+        with_movements => movements: Vec<Movement>,
+        with_groupings => groupings: Vec<String>,
+    }
+}
+
+/// `Artist – Title`, or the title alone when the artist is unknown.
+impl fmt::Display for Child {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let artist = self.artist.as_deref().or(self.display_artist.as_deref());
+        match artist.filter(|a| !a.is_empty()) {
+            Some(artist) => write!(f, "{artist} – {}", self.title),
+            None => f.write_str(&self.title),
+        }
+    }
 }
 
 /// A "now playing" entry — a [`Child`] with additional playback metadata.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct NowPlayingEntry {
     /// All fields from [`Child`].
     #[serde(flatten)]
@@ -653,5 +1289,49 @@ pub struct NowPlayingEntry {
     pub position_ms: Option<i64>,
     /// Playback rate multiplier (OpenSubsonic, playbackReport extension).
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = super::generate::option_finite))]
     pub playback_rate: Option<f64>,
 }
+
+setters! {
+    NowPlayingEntry::new(child: Child);
+    optional {
+        with_username => username: String,
+        with_minutes_ago => minutes_ago: i64,
+        with_player_id => player_id: i64,
+        with_player_name => player_name: String,
+        with_state => state: String,
+        with_position_ms => position_ms: i64,
+        with_playback_rate => playback_rate: f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn string_enums_keep_unknown_values() {
+        let status: ExplicitStatus = serde_json::from_str(r#""clean""#).unwrap();
+        assert_eq!(status, ExplicitStatus::Clean);
+        let kind: GenericMediaType = serde_json::from_str(r#""musicvideo""#).unwrap();
+        assert_eq!(kind, GenericMediaType::Unknown("musicvideo".into()));
+        assert_eq!(serde_json::to_string(&kind).unwrap(), r#""musicvideo""#);
+    }
+
+    #[test]
+    fn displays_models() {
+        let song = Child::new("tr-1", "Bohemian Rhapsody");
+        assert_eq!(song.to_string(), "Bohemian Rhapsody");
+        assert_eq!(
+            song.with_artist("Queen").to_string(),
+            "Queen – Bohemian Rhapsody"
+        );
+        let album = AlbumId3::new("al-1", "A Night at the Opera").with_artist("Queen");
+        assert_eq!(album.to_string(), "Queen – A Night at the Opera");
+        assert_eq!(
+            album.with_year(1975).to_string(),
+            "Queen – A Night at the Opera (1975)"
+        );
+    }
+}
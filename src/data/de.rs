@@ -0,0 +1,297 @@
+//! Lenient deserialization for fields that servers encode inconsistently.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::Deserialize;
+use serde::de::{self, Deserializer, IntoDeserializer, Visitor};
+
+/// Deserialize a string, or a number as its decimal string.
+///
+/// Used for ID fields, which some servers (older Subsonic, Ampache bridges) send as JSON
+/// numbers.
+pub(crate) fn string_or_number<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+    d.deserialize_any(StringOrNumber)
+}
+
+/// [`string_or_number`] for optional fields.
+pub(crate) fn option_string_or_number<'de, D: Deserializer<'de>>(
+    d: D,
+) -> Result<Option<String>, D::Error> {
+    d.deserialize_option(OptionVisitor)
+}
+
+struct StringOrNumber;
+
+impl Visitor<'_> for StringOrNumber {
+    type Value = String;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a string or an integer")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<String, E> {
+        Ok(v.to_owned())
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<String, E> {
+        Ok(v)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<String, E> {
+        Ok(v.to_string())
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<String, E> {
+        Ok(v.to_string())
+    }
+}
+
+struct OptionVisitor;
+
+impl<'de> Visitor<'de> for OptionVisitor {
+    type Value = Option<String>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a string, an integer or null")
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        string_or_number(d).map(Some)
+    }
+}
+
+/// Deserialize a boolean, also accepting `"true"`/`"false"` strings and `0`/`1`.
+///
+/// Used for flags such as `isDir` and the user roles, which Airsonic sometimes sends as
+/// strings or numbers.
+pub(crate) fn lenient_bool<'de, D: Deserializer<'de>>(d: D) -> Result<bool, D::Error> {
+    d.deserialize_any(LenientBool)
+}
+
+/// [`lenient_bool`] for optional fields.
+pub(crate) fn option_lenient_bool<'de, D: Deserializer<'de>>(
+    d: D,
+) -> Result<Option<bool>, D::Error> {
+    d.deserialize_option(OptionLenientBool)
+}
+
+struct LenientBool;
+
+impl Visitor<'_> for LenientBool {
+    type Value = bool;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a boolean, \"true\"/\"false\" or 0/1")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<bool, E> {
+        Ok(v)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<bool, E> {
+        Ok(v != 0)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<bool, E> {
+        Ok(v != 0)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<bool, E> {
+        match v.trim() {
+            s if s.eq_ignore_ascii_case("true") || s == "1" => Ok(true),
+            s if s.eq_ignore_ascii_case("false") || s == "0" || s.is_empty() => Ok(false),
+            _ => Err(E::invalid_value(de::Unexpected::Str(v), &self)),
+        }
+    }
+}
+
+struct OptionLenientBool;
+
+impl<'de> Visitor<'de> for OptionLenientBool {
+    type Value = Option<bool>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a boolean, \"true\"/\"false\", 0/1 or null")
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        lenient_bool(d).map(Some)
+    }
+}
+
+/// Deserialize a list, also accepting a single element in place of a one-element array.
+///
+/// Some servers, XML-converted backends especially, send a list holding one item as the
+/// bare item. `null` is read as an empty list.
+pub(crate) fn one_or_many<'de, D, T>(d: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    d.deserialize_any(OneOrMany(PhantomData))
+}
+
+/// [`one_or_many`] for optional fields.
+pub(crate) fn option_one_or_many<'de, D, T>(d: D) -> Result<Option<Vec<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    d.deserialize_option(OptionOneOrMany(PhantomData))
+}
+
+struct OneOrMany<T>(PhantomData<T>);
+
+impl<T> OneOrMany<T> {
+    fn one<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<T>, D::Error>
+    where
+        T: Deserialize<'de>,
+    {
+        T::deserialize(d).map(|item| vec![item])
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for OneOrMany<T> {
+    type Value = Vec<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a list or a single element")
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<T>, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(items)
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Vec<T>, A::Error> {
+        Self::one(de::value::MapAccessDeserializer::new(map))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Vec<T>, E> {
+        Self::one(v.into_deserializer())
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Vec<T>, E> {
+        Self::one(v.into_deserializer())
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Vec<T>, E> {
+        Self::one(v.into_deserializer())
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Vec<T>, E> {
+        Self::one(v.into_deserializer())
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Vec<T>, E> {
+        Self::one(v.into_deserializer())
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Vec<T>, E> {
+        Self::one(v.into_deserializer())
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Vec<T>, E> {
+        Ok(Vec::new())
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Vec<T>, E> {
+        Ok(Vec::new())
+    }
+}
+
+struct OptionOneOrMany<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for OptionOneOrMany<T> {
+    type Value = Option<Vec<T>>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a list, a single element or null")
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        one_or_many(d).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::{AlbumWithSongsId3, Child, Directory};
+
+    #[test]
+    fn accepts_numeric_ids() {
+        let song: Child =
+            serde_json::from_value(serde_json::json!({ "id": 42, "parent": 7, "title": "x" }))
+                .unwrap();
+        assert_eq!(song.id, "42");
+        assert_eq!(song.parent.as_deref(), Some("7"));
+
+        let dir: Directory =
+            serde_json::from_value(serde_json::json!({ "id": "d-1", "parent": null, "name": "x" }))
+                .unwrap();
+        assert_eq!(dir.id, "d-1");
+        assert_eq!(dir.parent, None);
+    }
+
+    #[test]
+    fn accepts_single_elements_as_lists() {
+        let album: AlbumWithSongsId3 = serde_json::from_value(serde_json::json!({
+            "id": "al-1",
+            "name": "x",
+            "song": { "id": "tr-1", "title": "y" },
+            "genres": { "name": "Rock" }
+        }))
+        .unwrap();
+        assert_eq!(album.song.len(), 1);
+        assert_eq!(album.genres.map(|g| g.len()), Some(1));
+    }
+
+    #[test]
+    fn keeps_unknown_fields() {
+        let json = serde_json::json!({ "id": "tr-1", "title": "x", "lovedBy": ["alice"] });
+        let song: Child = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(song.extra["lovedBy"][0], "alice");
+        assert_eq!(
+            serde_json::to_value(&song).unwrap()["lovedBy"],
+            json["lovedBy"]
+        );
+    }
+
+    #[test]
+    fn accepts_string_and_numeric_bools() {
+        let song: Child = serde_json::from_value(
+            serde_json::json!({ "id": "1", "isDir": "true", "isVideo": 0, "title": "x" }),
+        )
+        .unwrap();
+        assert!(song.is_dir);
+        assert_eq!(song.is_video, Some(false));
+    }
+}
@@ -0,0 +1,18 @@
+//! Generators for `Arbitrary` fields that need more than the field type's own impl.
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+/// A finite number; JSON has no NaN or infinities, so servers never send them.
+pub(crate) fn finite(u: &mut Unstructured<'_>) -> Result<f64> {
+    let value = f64::arbitrary(u)?;
+    Ok(if value.is_finite() { value } else { 0.0 })
+}
+
+/// [`finite`] for optional fields.
+pub(crate) fn option_finite(u: &mut Unstructured<'_>) -> Result<Option<f64>> {
+    if u.arbitrary()? {
+        finite(u).map(Some)
+    } else {
+        Ok(None)
+    }
+}
@@ -0,0 +1,97 @@
+//! Comparing models by their server ID.
+
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+
+use super::common::{AlbumId3, AlbumWithSongsId3, ArtistId3, ArtistWithAlbumsId3, Child};
+use super::ids::{AlbumId, ArtistId, PlaylistId, SongId};
+use super::playlists::{Playlist, PlaylistWithSongs};
+
+/// A model identified by a server ID.
+pub trait Identifiable {
+    /// The ID type.
+    type Id: Eq + Hash;
+
+    /// The server ID of this object.
+    fn id(&self) -> &Self::Id;
+}
+
+macro_rules! identifiable {
+    ($($ty:ty => $id:ty),* $(,)?) => {
+        $(
+            impl Identifiable for $ty {
+                type Id = $id;
+
+                fn id(&self) -> &$id {
+                    &self.id
+                }
+            }
+        )*
+    };
+}
+
+identifiable! {
+    Child => SongId,
+    AlbumId3 => AlbumId,
+    AlbumWithSongsId3 => AlbumId,
+    ArtistId3 => ArtistId,
+    ArtistWithAlbumsId3 => ArtistId,
+    Playlist => PlaylistId,
+    PlaylistWithSongs => PlaylistId,
+}
+
+/// A model compared and hashed by its ID alone, for `HashSet`s and `HashMap` keys.
+///
+/// The models themselves only implement [`PartialEq`] over all their fields, and their
+/// floating-point fields rule out [`Eq`].
+///
+/// ```
+/// use std::collections::HashSet;
+///
+/// use opensubsonic::data::{ById, Child};
+///
+/// let songs = [
+///     Child::new("tr-1", "Bohemian Rhapsody"),
+///     Child::new("tr-1", "Bohemian Rhapsody").with_play_count(3),
+///     Child::new("tr-2", "Love of My Life"),
+/// ];
+/// let unique: HashSet<_> = songs.into_iter().map(ById).collect();
+/// assert_eq!(unique.len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ById<T>(pub T);
+
+impl<T> ById<T> {
+    /// Unwrap the model.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Identifiable> PartialEq for ById<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.id() == other.0.id()
+    }
+}
+
+impl<T: Identifiable> Eq for ById<T> {}
+
+impl<T: Identifiable> Hash for ById<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.id().hash(state);
+    }
+}
+
+impl<T> Deref for ById<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> From<T> for ById<T> {
+    fn from(model: T) -> Self {
+        Self(model)
+    }
+}
@@ -0,0 +1,181 @@
+//! Typed identifiers.
+//!
+//! Each kind of server object has its own ID type, so passing an album ID where a song ID
+//! is expected is a compile error. Endpoints accept `impl Into<…Id>`, so plain `&str` and
+//! `String` values still work, and all ID types dereference to `str`.
+//!
+//! ```no_run
+//! # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+//! let album = client.get_album("al-1").await?;
+//! for song in &album.song {
+//!     let song = client.get_song(&song.id).await?;
+//!     println!("{}", song.title);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ```compile_fail
+//! # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+//! let album = client.get_album("al-1").await?;
+//! client.get_song(&album.id).await?; // an AlbumId is not a SongId
+//! # Ok(())
+//! # }
+//! ```
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::ops::Deref;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+macro_rules! define_id {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+        #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+        #[serde(transparent)]
+        pub struct $name(String);
+
+        impl<'de> Deserialize<'de> for $name {
+            /// Accepts a string or, as some servers send, an integer.
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                super::de::string_or_number(deserializer).map(Self)
+            }
+        }
+
+        impl $name {
+            /// Wrap a raw ID.
+            pub fn new(id: impl Into<String>) -> Self {
+                Self(id.into())
+            }
+
+            /// The raw ID.
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            /// Unwrap the raw ID.
+            pub fn into_string(self) -> String {
+                self.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(id: String) -> Self {
+                Self(id)
+            }
+        }
+
+        impl From<&String> for $name {
+            fn from(id: &String) -> Self {
+                Self(id.clone())
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(id: &str) -> Self {
+                Self(id.to_owned())
+            }
+        }
+
+        impl From<&$name> for $name {
+            fn from(id: &$name) -> Self {
+                id.clone()
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialEq<String> for $name {
+            fn eq(&self, other: &String) -> bool {
+                &self.0 == other
+            }
+        }
+    };
+}
+
+define_id! {
+    /// ID of a song or other media file (video, podcast episode), as in [`Child::id`](super::Child::id).
+    SongId
+}
+
+define_id! {
+    /// ID of an ID3 album.
+    AlbumId
+}
+
+define_id! {
+    /// ID of an ID3 artist.
+    ArtistId
+}
+
+define_id! {
+    /// ID of a playlist.
+    PlaylistId
+}
+
+#[cfg(feature = "podcast")]
+define_id! {
+    /// ID of a podcast channel.
+    PodcastChannelId
+}
+
+#[cfg(feature = "sharing")]
+define_id! {
+    /// ID of a share.
+    ShareId
+}
+
+#[cfg(feature = "internet-radio")]
+define_id! {
+    /// ID of an internet radio station.
+    RadioStationId
+}
+
+define_id! {
+    /// ID of a cover art image, as found in the `cover_art` fields.
+    CoverArtId
+}
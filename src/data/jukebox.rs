@@ -1,32 +1,61 @@
 //! Types for the Jukebox API section.
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 use super::common::Child;
 
 /// Jukebox playback status.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct JukeboxStatus {
     /// Index of the currently playing song in the playlist.
     pub current_index: i32,
     /// Whether the jukebox is currently playing.
+    #[serde(deserialize_with = "super::de::lenient_bool")]
     pub playing: bool,
     /// Volume level (0.0–1.0, encoded as integer by some servers).
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = super::generate::finite))]
     pub volume: f64,
     /// Current position in the track (seconds).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub position: Option<i64>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    JukeboxStatus::new(current_index: i32, volume: f64);
+    set {
+        with_playing => playing: bool,
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_position => position: i64,
+    }
 }
 
 /// Jukebox playlist (status + entries).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct JukeboxPlaylist {
     /// Jukebox status fields.
     #[serde(flatten)]
     pub status: JukeboxStatus,
     /// Songs currently enqueued.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub entry: Vec<Child>,
 }
+
+setters! {
+    JukeboxPlaylist::new(status: JukeboxStatus);
+    set {
+        with_entry => entry: Vec<Child>,
+    }
+}
@@ -1,23 +1,35 @@
 //! Types for structured lyrics (OpenSubsonic extension).
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 /// A singer/voice attribution in lyrics (OpenSubsonic, songLyrics v2).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Agent {
+    #[serde(deserialize_with = "super::de::string_or_number")]
     pub id: String,
     pub role: String,
     pub name: String,
 }
 
+setters! {
+    Agent::new(id: String, role: String, name: String);
+}
+
 /// An individual word/syllable timestamp within a cue line (OpenSubsonic, songLyrics v2).
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Cue {
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = super::generate::option_finite))]
     pub start: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = super::generate::option_finite))]
     pub end: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<String>,
@@ -27,44 +39,95 @@ pub struct Cue {
     pub byte_end: Option<i32>,
 }
 
+setters! {
+    Cue::new();
+    optional {
+        with_start => start: f64,
+        with_end => end: f64,
+        with_value => value: String,
+        with_byte_start => byte_start: i32,
+        with_byte_end => byte_end: i32,
+    }
+}
+
 /// A word/syllable-level timing line (OpenSubsonic, songLyrics v2).
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct CueLine {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub index: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_string_or_number",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub agent_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = super::generate::option_finite))]
     pub start: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = super::generate::option_finite))]
     pub end: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<String>,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub cue: Vec<Cue>,
 }
 
+setters! {
+    CueLine::new();
+    set {
+        with_cue => cue: Vec<Cue>,
+    }
+    optional {
+        with_index => index: i32,
+        with_agent_id => agent_id: String,
+        with_start => start: f64,
+        with_end => end: f64,
+        with_value => value: String,
+    }
+}
+
 /// A single line of lyrics.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Line {
     /// The text of this line.
     pub value: String,
     /// Start time in milliseconds (present only for synced lyrics).
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = super::generate::option_finite))]
     pub start: Option<f64>,
 }
 
+setters! {
+    Line::new(value: String);
+    optional {
+        with_start => start: f64,
+    }
+}
+
 /// Structured lyrics for a song.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct StructuredLyrics {
     /// Language code (ideally ISO 639; "und" or "xxx" for unknown).
     pub lang: String,
     /// Whether the lyrics are time-synced.
+    #[serde(deserialize_with = "super::de::lenient_bool")]
     pub synced: bool,
     /// The lyrics lines.
+    #[serde(deserialize_with = "super::de::one_or_many")]
     pub line: Vec<Line>,
     /// Display artist name (may be localized).
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -74,23 +137,80 @@ pub struct StructuredLyrics {
     pub display_title: Option<String>,
     /// Time offset in milliseconds to apply to all lines.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = super::generate::option_finite))]
     pub offset: Option<f64>,
     /// Lyrics kind: "main", "translation", or "pronunciation" (OpenSubsonic, songLyrics v2).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub kind: Option<String>,
     /// Singer/voice attributions (OpenSubsonic, songLyrics v2).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub agents: Option<Vec<Agent>>,
     /// Word/syllable-level timing lines (OpenSubsonic, songLyrics v2).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub cue_line: Option<Vec<CueLine>>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    StructuredLyrics::new(lang: String);
+    set {
+        with_synced => synced: bool,
+        with_line => line: Vec<Line>,
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_display_artist => display_artist: String,
+        with_display_title => display_title: String,
+        with_offset => offset: f64,
+        with_kind => kind: String,
+        with_agents => agents: Vec<Agent>,
+        with_cue_line => cue_line: Vec<CueLine>,
+    }
+}
+
+/// Legacy `getLyrics` lyrics as unsynced lyrics in an unknown language (`und`), one line
+/// per line of text, with the artist and title as display artist and title.
+impl From<super::Lyrics> for StructuredLyrics {
+    fn from(lyrics: super::Lyrics) -> Self {
+        let text = lyrics.value.unwrap_or_default();
+        let mut line: Vec<Line> = text.lines().map(|l| Line::new(l.trim_end())).collect();
+        while line.last().is_some_and(|l| l.value.is_empty()) {
+            line.pop();
+        }
+        Self {
+            display_artist: lyrics.artist,
+            display_title: lyrics.title,
+            extra: lyrics.extra,
+            ..Self::new("und").with_line(line)
+        }
+    }
 }
 
 /// A list of structured lyrics entries for a song.
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct LyricsList {
     /// Structured lyrics entries (may have multiple per language).
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub structured_lyrics: Vec<StructuredLyrics>,
 }
+
+setters! {
+    LyricsList::new();
+    set {
+        with_structured_lyrics => structured_lyrics: Vec<StructuredLyrics>,
+    }
+}
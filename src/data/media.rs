@@ -5,13 +5,16 @@
 //! (e.g. `getLyrics`).
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 // Note: `stream`, `download`, `getCoverArt`, `getAvatar`, `hls`, `getCaptions`
 // all return binary data — no data types needed beyond bytes::Bytes.
 
 /// Lyrics for a song (legacy, unstructured).
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Lyrics {
     /// The lyrics text.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -22,43 +25,106 @@ pub struct Lyrics {
     /// Song title.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    Lyrics::new();
+    set {
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_value => value: String,
+        with_artist => artist: String,
+        with_title => title: String,
+    }
 }
 
 /// Additional information about a video (captions, audio tracks, conversions).
 ///
 /// Returned by `getVideoInfo`.
+#[cfg(feature = "video")]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct VideoInfo {
     /// Video ID.
+    #[serde(deserialize_with = "super::de::string_or_number")]
     pub id: String,
     /// Available caption/subtitle tracks.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub captions: Vec<Captions>,
     /// Available audio tracks.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub audio_track: Vec<AudioTrack>,
     /// Available pre-computed conversions.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub conversion: Vec<VideoConversion>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+#[cfg(feature = "video")]
+setters! {
+    VideoInfo::new(id: String);
+    set {
+        with_captions => captions: Vec<Captions>,
+        with_audio_track => audio_track: Vec<AudioTrack>,
+        with_conversion => conversion: Vec<VideoConversion>,
+        with_extra => extra: Map<String, Value>,
+    }
 }
 
 /// A caption / subtitle track for a video.
+#[cfg(feature = "video")]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Captions {
     /// Caption track ID.
+    #[serde(deserialize_with = "super::de::string_or_number")]
     pub id: String,
     /// Caption track name.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 }
 
+#[cfg(feature = "video")]
+setters! {
+    Captions::new(id: String);
+    optional {
+        with_name => name: String,
+    }
+}
+
 /// An audio track for a video.
+#[cfg(feature = "video")]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct AudioTrack {
     /// Audio track ID.
+    #[serde(deserialize_with = "super::de::string_or_number")]
     pub id: String,
     /// Audio track name.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -68,16 +134,42 @@ pub struct AudioTrack {
     pub language_code: Option<String>,
 }
 
+#[cfg(feature = "video")]
+setters! {
+    AudioTrack::new(id: String);
+    optional {
+        with_name => name: String,
+        with_language_code => language_code: String,
+    }
+}
+
 /// A pre-computed video conversion.
+#[cfg(feature = "video")]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct VideoConversion {
     /// Conversion ID.
+    #[serde(deserialize_with = "super::de::string_or_number")]
     pub id: String,
     /// Bit rate in kbps.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bit_rate: Option<i32>,
     /// Associated audio track ID.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_string_or_number",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub audio_track_id: Option<String>,
 }
+
+#[cfg(feature = "video")]
+setters! {
+    VideoConversion::new(id: String);
+    optional {
+        with_bit_rate => bit_rate: i32,
+        with_audio_track_id => audio_track_id: String,
+    }
+}
@@ -0,0 +1,189 @@
+//! Artist, album and genre names.
+//!
+//! The same few names repeat across every song and album of a library. With the `intern`
+//! feature, [`Name`] holds a shared `Arc<str>` and deserialization hands out one allocation
+//! per distinct name, which keeps a full-library model small. Without it, `Name` is a plain
+//! owned string. Both dereference to `str`, so code using them compiles either way.
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::ops::Deref;
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "intern")]
+type Repr = std::sync::Arc<str>;
+#[cfg(not(feature = "intern"))]
+type Repr = String;
+
+/// An artist, album or genre name, shared between all copies when the `intern` feature is
+/// enabled.
+#[derive(Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Name(Repr);
+
+/// Every distinct name deserialized so far.
+#[cfg(feature = "intern")]
+fn names() -> std::sync::MutexGuard<'static, std::collections::HashSet<Repr>> {
+    use std::sync::{Mutex, OnceLock};
+
+    static NAMES: OnceLock<Mutex<std::collections::HashSet<Repr>>> = OnceLock::new();
+    NAMES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(feature = "intern")]
+fn intern(name: &str) -> Repr {
+    let mut names = names();
+    if let Some(shared) = names.get(name) {
+        return shared.clone();
+    }
+    let shared = Repr::from(name);
+    names.insert(shared.clone());
+    shared
+}
+
+#[cfg(not(feature = "intern"))]
+fn intern(name: &str) -> Repr {
+    name.to_owned()
+}
+
+impl Name {
+    /// Wrap a name, sharing it with equal names already interned.
+    pub fn new(name: &str) -> Self {
+        Self(intern(name))
+    }
+
+    /// The name.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Forget interned names no longer used by any `Name`, e.g. after dropping a library
+    /// model. Does nothing without the `intern` feature.
+    pub fn release_unused() {
+        #[cfg(feature = "intern")]
+        names().retain(|name| std::sync::Arc::strong_count(name) > 1);
+    }
+}
+
+impl Deref for Name {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Name {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Name {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for Name {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<String> for Name {
+    fn from(name: String) -> Self {
+        Self::new(&name)
+    }
+}
+
+impl From<Name> for String {
+    fn from(name: Name) -> Self {
+        name.as_str().to_owned()
+    }
+}
+
+impl PartialEq<str> for Name {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Name {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for Name {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl Serialize for Name {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Name {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(NameVisitor)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Name {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        <&str>::arbitrary(u).map(Self::new)
+    }
+}
+
+struct NameVisitor;
+
+impl Visitor<'_> for NameVisitor {
+    type Value = Name;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a string")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Name, E> {
+        Ok(Name::new(v))
+    }
+}
+
+#[cfg(all(test, feature = "intern"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equal_names_share_one_allocation() {
+        let names: Vec<Name> = serde_json::from_str(r#"["Queen", "Queen"]"#).unwrap();
+        assert!(std::sync::Arc::ptr_eq(&names[0].0, &names[1].0));
+        assert_eq!(names[0], "Queen");
+
+        let unused = Name::new("Unused Name");
+        drop(unused);
+        Name::release_unused();
+        assert!(!super::names().contains("Unused Name"));
+        assert!(super::names().contains("Queen"));
+    }
+}
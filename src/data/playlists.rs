@@ -1,15 +1,22 @@
 //! Types for the Playlists API section.
 
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 use super::common::Child;
+use super::ids::{CoverArtId, PlaylistId};
+use super::timestamp::Timestamp;
 
 /// A playlist (without songs).
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Playlist {
     /// Playlist ID.
-    pub id: String,
+    pub id: PlaylistId,
     /// Playlist name.
     pub name: String,
     /// Comment.
@@ -19,7 +26,11 @@ pub struct Playlist {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub owner: Option<String>,
     /// Whether the playlist is public.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub public: Option<bool>,
     /// Number of songs.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -29,30 +40,76 @@ pub struct Playlist {
     pub duration: Option<i64>,
     /// Date created (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub created: Option<String>,
+    pub created: Option<Timestamp>,
     /// Date last changed (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub changed: Option<String>,
+    pub changed: Option<Timestamp>,
     /// Cover art ID.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub cover_art: Option<String>,
+    pub cover_art: Option<CoverArtId>,
     /// Allowed users.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub allowed_user: Vec<String>,
     /// Whether the playlist is read-only for the current user (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub readonly: Option<bool>,
     /// Date until playlist contents are valid for caching (ISO 8601, OpenSubsonic).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub valid_until: Option<String>,
+    pub valid_until: Option<Timestamp>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    Playlist::new(id: PlaylistId, name: String);
+    set {
+        with_allowed_user => allowed_user: Vec<String>,
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_comment => comment: String,
+        with_owner => owner: String,
+        with_public => public: bool,
+        with_song_count => song_count: i64,
+        with_duration => duration: i64,
+        with_created => created: Timestamp,
+        with_changed => changed: Timestamp,
+        with_cover_art => cover_art: CoverArtId,
+        with_readonly => readonly: bool,
+        with_valid_until => valid_until: Timestamp,
+    }
+}
+
+/// `Name (N songs)`, or the name alone when the song count is unknown.
+impl fmt::Display for Playlist {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        match self.song_count {
+            Some(1) => f.write_str(" (1 song)"),
+            Some(count) => write!(f, " ({count} songs)"),
+            None => Ok(()),
+        }
+    }
 }
 
 /// A playlist with its songs.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct PlaylistWithSongs {
     /// Playlist ID.
-    pub id: String,
+    pub id: PlaylistId,
     /// Playlist name.
     pub name: String,
     /// Comment.
@@ -62,7 +119,11 @@ pub struct PlaylistWithSongs {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub owner: Option<String>,
     /// Whether the playlist is public.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub public: Option<bool>,
     /// Number of songs.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -72,23 +133,56 @@ pub struct PlaylistWithSongs {
     pub duration: Option<i64>,
     /// Date created (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub created: Option<String>,
+    pub created: Option<Timestamp>,
     /// Date last changed (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub changed: Option<String>,
+    pub changed: Option<Timestamp>,
     /// Cover art ID.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub cover_art: Option<String>,
+    pub cover_art: Option<CoverArtId>,
     /// Allowed users.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub allowed_user: Vec<String>,
     /// Whether the playlist is read-only (OpenSubsonic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub readonly: Option<bool>,
     /// Date until playlist contents are valid for caching (ISO 8601, OpenSubsonic).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub valid_until: Option<String>,
+    pub valid_until: Option<Timestamp>,
     /// The songs in this playlist.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub entry: Vec<Child>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    PlaylistWithSongs::new(id: PlaylistId, name: String);
+    set {
+        with_allowed_user => allowed_user: Vec<String>,
+        with_entry => entry: Vec<Child>,
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_comment => comment: String,
+        with_owner => owner: String,
+        with_public => public: bool,
+        with_song_count => song_count: i64,
+        with_duration => duration: i64,
+        with_created => created: Timestamp,
+        with_changed => changed: Timestamp,
+        with_cover_art => cover_art: CoverArtId,
+        with_readonly => readonly: bool,
+        with_valid_until => valid_until: Timestamp,
+    }
 }
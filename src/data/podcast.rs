@@ -1,11 +1,15 @@
 //! Types for the Podcast API section.
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 use super::common::Child;
+use super::ids::{CoverArtId, PodcastChannelId, SongId};
+use super::timestamp::Timestamp;
 
 /// Podcast episode status.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
 pub enum PodcastStatus {
     /// New episode.
@@ -24,10 +28,12 @@ pub enum PodcastStatus {
 
 /// A podcast channel.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct PodcastChannel {
     /// Channel ID.
-    pub id: String,
+    pub id: PodcastChannelId,
     /// Podcast feed URL.
     pub url: String,
     /// Channel title.
@@ -38,7 +44,7 @@ pub struct PodcastChannel {
     pub description: Option<String>,
     /// Cover art ID.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub cover_art: Option<String>,
+    pub cover_art: Option<CoverArtId>,
     /// Original image URL.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub original_image_url: Option<String>,
@@ -48,22 +54,43 @@ pub struct PodcastChannel {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_message: Option<String>,
     /// Episodes in this channel.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub episode: Vec<PodcastEpisode>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    PodcastChannel::new(id: PodcastChannelId, url: String, status: PodcastStatus);
+    set {
+        with_episode => episode: Vec<PodcastEpisode>,
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_title => title: String,
+        with_description => description: String,
+        with_cover_art => cover_art: CoverArtId,
+        with_original_image_url => original_image_url: String,
+        with_error_message => error_message: String,
+    }
 }
 
 /// A podcast episode (extends [`Child`]).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct PodcastEpisode {
     /// All media fields from [`Child`].
     #[serde(flatten)]
     pub child: Child,
     /// Stream ID for streaming this episode.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub stream_id: Option<String>,
+    pub stream_id: Option<SongId>,
     /// Channel ID this episode belongs to.
-    pub channel_id: String,
+    pub channel_id: PodcastChannelId,
     /// Episode description.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
@@ -71,5 +98,14 @@ pub struct PodcastEpisode {
     pub status: PodcastStatus,
     /// Publish date (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub publish_date: Option<String>,
+    pub publish_date: Option<Timestamp>,
+}
+
+setters! {
+    PodcastEpisode::new(child: Child, channel_id: PodcastChannelId, status: PodcastStatus);
+    optional {
+        with_stream_id => stream_id: SongId,
+        with_description => description: String,
+        with_publish_date => publish_date: Timestamp,
+    }
 }
@@ -1,13 +1,18 @@
 //! Types for the Internet Radio API section.
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::ids::RadioStationId;
 
 /// An internet radio station.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct InternetRadioStation {
     /// Station ID.
-    pub id: String,
+    pub id: RadioStationId,
     /// Station name.
     pub name: String,
     /// Stream URL.
@@ -15,4 +20,18 @@ pub struct InternetRadioStation {
     /// Home page URL.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub home_page_url: Option<String>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    InternetRadioStation::new(id: RadioStationId, name: String, stream_url: String);
+    set {
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_home_page_url => home_page_url: String,
+    }
 }
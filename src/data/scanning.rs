@@ -1,14 +1,33 @@
 //! Types for the Media Library Scanning API section.
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 /// Library scan status.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct ScanStatus {
     /// Whether a scan is currently in progress.
+    #[serde(deserialize_with = "super::de::lenient_bool")]
     pub scanning: bool,
     /// Number of items scanned so far.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub count: Option<i64>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    ScanStatus::new();
+    set {
+        with_scanning => scanning: bool,
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_count => count: i64,
+    }
 }
@@ -6,10 +6,12 @@ use super::common::{AlbumId3, Artist, ArtistId3, Child};
 
 /// Legacy search result (search endpoint).
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct SearchResult {
     /// Matching entries.
-    #[serde(default, rename = "match")]
+    #[serde(default, rename = "match", deserialize_with = "super::de::one_or_many")]
     pub matches: Vec<Child>,
     /// Offset used.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -19,32 +21,65 @@ pub struct SearchResult {
     pub total_hits: Option<i64>,
 }
 
+setters! {
+    SearchResult::new();
+    set {
+        with_matches => matches: Vec<Child>,
+    }
+    optional {
+        with_offset => offset: i64,
+        with_total_hits => total_hits: i64,
+    }
+}
+
 /// Search result from `search2` (folder-based).
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct SearchResult2 {
     /// Matching artists.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub artist: Vec<Artist>,
     /// Matching albums (as Child entries).
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub album: Vec<Child>,
     /// Matching songs.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub song: Vec<Child>,
 }
 
+setters! {
+    SearchResult2::new();
+    set {
+        with_artist => artist: Vec<Artist>,
+        with_album => album: Vec<Child>,
+        with_song => song: Vec<Child>,
+    }
+}
+
 /// Search result from `search3` (ID3-based).
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct SearchResult3 {
     /// Matching artists (ID3).
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub artist: Vec<ArtistId3>,
     /// Matching albums (ID3).
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub album: Vec<AlbumId3>,
     /// Matching songs.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub song: Vec<Child>,
 }
+
+setters! {
+    SearchResult3::new();
+    set {
+        with_artist => artist: Vec<ArtistId3>,
+        with_album => album: Vec<AlbumId3>,
+        with_song => song: Vec<Child>,
+    }
+}
@@ -1,15 +1,22 @@
 //! Types for the Sharing API section.
 
+use std::time::SystemTime;
+
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 use super::common::Child;
+use super::ids::ShareId;
+use super::timestamp::Timestamp;
 
 /// A share (publicly accessible link).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Share {
     /// Share ID.
-    pub id: String,
+    pub id: ShareId,
     /// Share URL.
     pub url: String,
     /// Description.
@@ -18,16 +25,46 @@ pub struct Share {
     /// Username of the creator.
     pub username: String,
     /// Date created (ISO 8601).
-    pub created: String,
+    pub created: Timestamp,
     /// Expiration date (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub expires: Option<String>,
+    pub expires: Option<Timestamp>,
     /// Last visited date (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub last_visited: Option<String>,
+    pub last_visited: Option<Timestamp>,
     /// Visit count.
     pub visit_count: i64,
     /// Shared entries.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub entry: Vec<Child>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    Share::new(id: ShareId, url: String, username: String, created: Timestamp, visit_count: i64);
+    set {
+        with_entry => entry: Vec<Child>,
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_description => description: String,
+        with_expires => expires: Timestamp,
+        with_last_visited => last_visited: Timestamp,
+    }
+}
+
+impl Share {
+    /// When the share expires, or `None` if it never does (or the server's date is in an
+    /// unrecognised format).
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        self.expires.as_ref()?.to_system_time()
+    }
+
+    /// Whether the share has expired. Shares without an expiration date never do.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at().is_some_and(|t| t <= SystemTime::now())
+    }
 }
@@ -6,11 +6,18 @@ use super::common::Child;
 
 /// A sonic similarity match — a [`Child`] with a similarity score.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct SonicMatch {
     /// The matched track.
     #[serde(flatten)]
     pub entry: Child,
     /// Normalized similarity score (1.0 = exact, 0.0 = most different, -1 if unsupported).
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = super::generate::finite))]
     pub similarity: f64,
 }
+
+setters! {
+    SonicMatch::new(entry: Child, similarity: f64);
+}
@@ -0,0 +1,399 @@
+//! Date/time values.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::time::SystemTime;
+#[cfg(not(feature = "chrono"))]
+use std::time::{Duration, UNIX_EPOCH};
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A date/time as sent by the server (`created`, `starred`, `played`, `changed`, …).
+///
+/// Servers are expected to send ISO 8601 strings, but the exact shape varies (with or
+/// without fractional seconds or a timezone, date only, or milliseconds since the epoch).
+/// The raw value is always kept and available through [`Timestamp::as_str`] (or by
+/// dereferencing to `str`); with the `chrono` feature it is also parsed once, at
+/// deserialization, into a [`chrono::DateTime<Utc>`](chrono::DateTime) available through
+/// [`Timestamp::to_utc`].
+///
+/// Equality, hashing and serialization use the raw value.
+#[derive(Clone)]
+pub struct Timestamp {
+    raw: String,
+    #[cfg(feature = "chrono")]
+    utc: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Timestamp {
+    /// Wrap a raw date/time value.
+    pub fn new(raw: impl Into<String>) -> Self {
+        let raw = raw.into();
+        Self {
+            #[cfg(feature = "chrono")]
+            utc: parse(&raw),
+            raw,
+        }
+    }
+
+    /// The value exactly as sent by the server.
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    /// The value as a UTC date/time, or `None` if it is in an unrecognised format.
+    ///
+    /// Values without a timezone are taken to be UTC.
+    #[cfg(feature = "chrono")]
+    pub fn to_utc(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.utc
+    }
+
+    /// The value as a [`SystemTime`], or `None` if it is in an unrecognised format.
+    ///
+    /// Values without a timezone are taken to be UTC. Without the `chrono` feature, ISO 8601
+    /// date/times and milliseconds since the epoch are recognised.
+    pub fn to_system_time(&self) -> Option<SystemTime> {
+        #[cfg(feature = "chrono")]
+        {
+            self.utc.map(SystemTime::from)
+        }
+        #[cfg(not(feature = "chrono"))]
+        {
+            parse_system_time(&self.raw)
+        }
+    }
+
+    /// Order by the time the values denote, earliest first.
+    ///
+    /// Values in an unrecognised format come before all others and are ordered by their
+    /// text, as are equal times.
+    pub(crate) fn cmp_chronological(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.to_system_time(), other.to_system_time());
+        a.is_some()
+            .cmp(&b.is_some())
+            .then(a.cmp(&b))
+            .then_with(|| self.raw.cmp(&other.raw))
+    }
+}
+
+impl fmt::Debug for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.raw, f)
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl PartialEq for Timestamp {
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl Eq for Timestamp {}
+
+impl Hash for Timestamp {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.raw.hash(state);
+    }
+}
+
+impl Deref for Timestamp {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.raw
+    }
+}
+
+impl AsRef<str> for Timestamp {
+    fn as_ref(&self) -> &str {
+        &self.raw
+    }
+}
+
+impl From<String> for Timestamp {
+    fn from(raw: String) -> Self {
+        Self::new(raw)
+    }
+}
+
+impl From<&str> for Timestamp {
+    fn from(raw: &str) -> Self {
+        Self::new(raw)
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.raw)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TimestampVisitor;
+
+        impl Visitor<'_> for TimestampVisitor {
+            type Value = Timestamp;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a date/time string or milliseconds since the epoch")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Timestamp, E> {
+                Ok(Timestamp::new(v))
+            }
+
+            fn visit_string<E: de::Error>(self, v: String) -> Result<Timestamp, E> {
+                Ok(Timestamp::new(v))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Timestamp, E> {
+                Ok(Timestamp::new(v.to_string()))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Timestamp, E> {
+                Ok(Timestamp::new(v.to_string()))
+            }
+        }
+
+        deserializer.deserialize_any(TimestampVisitor)
+    }
+}
+
+/// Parse the date/time shapes emitted by Subsonic-compatible servers.
+#[cfg(feature = "chrono")]
+fn parse(raw: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+    let raw = raw.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(raw) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    for format in ["%Y-%m-%dT%H:%M:%S%.f%z", "%Y-%m-%d %H:%M:%S%.f%z"] {
+        if let Ok(dt) = DateTime::parse_from_str(raw, format) {
+            return Some(dt.with_timezone(&Utc));
+        }
+    }
+    for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(raw, format) {
+            return Some(dt.and_utc());
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+        return date.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc());
+    }
+    if !raw.is_empty() && raw.bytes().all(|b| b.is_ascii_digit()) {
+        return DateTime::from_timestamp_millis(raw.parse().ok()?);
+    }
+    None
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Timestamp {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        String::arbitrary(u).map(Self::new)
+    }
+}
+
+/// Parse `YYYY-MM-DD[(T| )HH:MM:SS[.f][Z|±HH[:MM]]]` or milliseconds since the epoch,
+/// rejecting the dates and times that do not exist, as `chrono` does.
+#[cfg(not(feature = "chrono"))]
+fn parse_system_time(raw: &str) -> Option<SystemTime> {
+    let raw = raw.trim();
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let number = |s: &str| digits(s).then(|| s.parse::<i64>().ok()).flatten();
+    if digits(raw) {
+        return Some(UNIX_EPOCH + Duration::from_millis(raw.parse().ok()?));
+    }
+    let (date, time) = match raw.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (raw, None),
+    };
+    let mut date = date.splitn(3, '-');
+    let year = number(date.next()?)?;
+    let month = number(date.next()?)?;
+    let day = number(date.next()?)?;
+    if year > 262_143
+        || !(1..=12).contains(&month)
+        || !(1..=days_in_month(year, month)).contains(&day)
+    {
+        return None;
+    }
+    let (mut seconds, mut nanos, mut offset) = (0, 0, 0);
+    if let Some(time) = time {
+        let clock = match time.find(['Z', '+', '-']) {
+            Some(i) => {
+                offset = zone_offset(&time[i..])?;
+                &time[..i]
+            }
+            None => time,
+        };
+        let (hms, fraction) = clock.split_once('.').unwrap_or((clock, ""));
+        let mut hms = hms.splitn(3, ':');
+        let (h, m, s) = (
+            number(hms.next()?)?,
+            number(hms.next()?)?,
+            number(hms.next()?)?,
+        );
+        if h > 23 || m > 59 || s > 59 {
+            return None;
+        }
+        seconds = h * 3600 + m * 60 + s;
+        if !fraction.is_empty() {
+            let fraction: f64 = format!("0.{}", digits(fraction).then_some(fraction)?)
+                .parse()
+                .ok()?;
+            nanos = (fraction * 1e9).round() as u64;
+        }
+    }
+    let total = days_from_civil(year, month, day) * 86_400 + seconds - offset;
+    let since = Duration::from_secs(total.unsigned_abs());
+    let time = if total >= 0 {
+        UNIX_EPOCH + since
+    } else {
+        UNIX_EPOCH - since
+    };
+    Some(time + Duration::from_nanos(nanos))
+}
+
+/// Parse `Z`, `±HH`, `±HHMM` or `±HH:MM` into seconds east of UTC.
+#[cfg(not(feature = "chrono"))]
+fn zone_offset(zone: &str) -> Option<i64> {
+    if zone == "Z" {
+        return Some(0);
+    }
+    let sign = if zone.starts_with('-') { -1 } else { 1 };
+    let value = zone[1..].replace(':', "");
+    if !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = match value.len() {
+        2 => (value.parse::<i64>().ok()?, 0),
+        4 => (
+            value[..2].parse::<i64>().ok()?,
+            value[2..].parse::<i64>().ok()?,
+        ),
+        _ => return None,
+    };
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Number of days in `month` of the proleptic Gregorian `year`.
+#[cfg(not(feature = "chrono"))]
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days from 1970-01-01 to the given proleptic Gregorian date.
+#[cfg(not(feature = "chrono"))]
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_to_system_time() {
+        let expected = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_709_634_030);
+        for raw in [
+            "2024-03-05T10:20:30Z",
+            "2024-03-05T11:20:30+01:00",
+            "2024-03-05T05:20:30.000-0500",
+            "2024-03-05 10:20:30",
+            "1709634030000",
+        ] {
+            assert_eq!(
+                Timestamp::new(raw).to_system_time(),
+                Some(expected),
+                "{raw}"
+            );
+        }
+        assert_eq!(Timestamp::new("yesterday").to_system_time(), None);
+
+        let midnight = |secs| Some(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs));
+        assert_eq!(
+            Timestamp::new("2024-02-29").to_system_time(),
+            midnight(1_709_164_800)
+        );
+        assert_eq!(
+            Timestamp::new("2000-02-29").to_system_time(),
+            midnight(951_782_400)
+        );
+        for raw in [
+            "2024-02-30",
+            "2024-02-31",
+            "2023-02-29",
+            "1900-02-29",
+            "2024-04-31",
+            "2024-13-01",
+            "2024-00-10",
+            "2024-03-05T",
+            "2024-03-05T24:00:00",
+            "2024-03-05T10:60:00",
+            "2024-03-05T10:20:61",
+            "2024-03-05T10:20:30+24:00",
+            "2024-03-05T10:20:30+01:60",
+        ] {
+            assert_eq!(Timestamp::new(raw).to_system_time(), None, "{raw}");
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn parses_server_formats() {
+        let expected = "2024-03-05T10:20:30Z"
+            .parse::<chrono::DateTime<chrono::Utc>>()
+            .unwrap();
+        for raw in [
+            "2024-03-05T10:20:30Z",
+            "2024-03-05T10:20:30.000Z",
+            "2024-03-05T11:20:30+01:00",
+            "2024-03-05T11:20:30.000+0100",
+            "2024-03-05T10:20:30",
+            "2024-03-05 10:20:30",
+            "1709634030000",
+        ] {
+            assert_eq!(Timestamp::new(raw).to_utc(), Some(expected), "{raw}");
+        }
+        assert!(Timestamp::new("2024-03-05").to_utc().is_some());
+        assert_eq!(Timestamp::new("yesterday").to_utc(), None);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn deserializes_strings_and_numbers() {
+        let ts: Timestamp = serde_json::from_str("1709634030000").unwrap();
+        assert_eq!(ts.as_str(), "1709634030000");
+        assert!(ts.to_utc().is_some());
+        let ts: Timestamp = serde_json::from_str(r#""2024-03-05T10:20:30Z""#).unwrap();
+        assert_eq!(
+            serde_json::to_string(&ts).unwrap(),
+            r#""2024-03-05T10:20:30Z""#
+        );
+    }
+}
@@ -1,10 +1,13 @@
 //! Types for the Transcoding API section (OpenSubsonic extension).
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 /// Stream details for a media file.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct StreamDetails {
     /// Protocol (e.g. "http", "hls").
     pub protocol: String,
@@ -29,16 +32,35 @@ pub struct StreamDetails {
     pub audio_bitdepth: Option<i32>,
 }
 
+setters! {
+    StreamDetails::new(protocol: String, container: String, codec: String);
+    optional {
+        with_audio_channels => audio_channels: i32,
+        with_audio_bitrate => audio_bitrate: i32,
+        with_audio_profile => audio_profile: String,
+        with_audio_samplerate => audio_samplerate: i32,
+        with_audio_bitdepth => audio_bitdepth: i32,
+    }
+}
+
 /// Transcode decision response.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct TranscodeDecision {
     /// Whether direct play is possible.
+    #[serde(deserialize_with = "super::de::lenient_bool")]
     pub can_direct_play: bool,
     /// Whether transcoding is possible.
+    #[serde(deserialize_with = "super::de::lenient_bool")]
     pub can_transcode: bool,
     /// Reasons for transcoding (if any).
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub transcode_reason: Vec<String>,
     /// Error reason (if any).
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -52,11 +74,33 @@ pub struct TranscodeDecision {
     /// Transcoded stream details.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub transcode_stream: Option<StreamDetails>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    TranscodeDecision::new();
+    set {
+        with_can_direct_play => can_direct_play: bool,
+        with_can_transcode => can_transcode: bool,
+        with_transcode_reason => transcode_reason: Vec<String>,
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_error_reason => error_reason: String,
+        with_transcode_params => transcode_params: String,
+        with_source_stream => source_stream: StreamDetails,
+        with_transcode_stream => transcode_stream: StreamDetails,
+    }
 }
 
 /// Client info for transcode decision request.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct ClientInfo {
     /// Client name.
     pub name: String,
@@ -69,37 +113,258 @@ pub struct ClientInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_transcoding_audio_bitrate: Option<i32>,
     /// Direct play profiles.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub direct_play_profiles: Vec<DirectPlayProfile>,
     /// Transcoding profiles.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub transcoding_profiles: Vec<TranscodingProfile>,
     /// Codec profiles.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub codec_profiles: Vec<CodecProfile>,
 }
 
+setters! {
+    ClientInfo::new(name: String, platform: String);
+    set {
+        with_direct_play_profiles => direct_play_profiles: Vec<DirectPlayProfile>,
+        with_transcoding_profiles => transcoding_profiles: Vec<TranscodingProfile>,
+        with_codec_profiles => codec_profiles: Vec<CodecProfile>,
+    }
+    optional {
+        with_max_audio_bitrate => max_audio_bitrate: i32,
+        with_max_transcoding_audio_bitrate => max_transcoding_audio_bitrate: i32,
+    }
+}
+
+impl ClientInfo {
+    /// Start building client info with a fluent [`ClientInfoBuilder`].
+    ///
+    /// The name defaults to `opensubsonic-rs` and the platform to the operating system.
+    ///
+    /// ```
+    /// use opensubsonic::data::ClientInfo;
+    ///
+    /// let info = ClientInfo::builder()
+    ///     .direct_play(["flac", "mp3"])
+    ///     .transcode_to("opus", 192)
+    ///     .max_channels(2)
+    ///     .build();
+    /// assert_eq!(info.transcoding_profiles[0].container, "ogg");
+    /// assert_eq!(info.max_transcoding_audio_bitrate, Some(192));
+    /// ```
+    pub fn builder() -> ClientInfoBuilder {
+        ClientInfoBuilder::default()
+    }
+
+    /// Start from the profiles suited to a class of device, to adjust further.
+    pub fn preset(device: DeviceClass) -> ClientInfoBuilder {
+        let builder = Self::builder();
+        match device {
+            DeviceClass::Desktop => builder
+                .direct_play(["flac", "mp3", "ogg", "opus", "m4a", "aac", "wav"])
+                .transcode_to("mp3", 320),
+            DeviceClass::Browser => builder
+                .direct_play(["flac", "mp3", "ogg", "opus", "m4a", "aac", "wav"])
+                .transcode_to("opus", 192)
+                .max_channels(2),
+            DeviceClass::Mobile => builder
+                .direct_play(["mp3", "ogg", "opus", "m4a", "aac"])
+                .transcode_to("opus", 128)
+                .max_bitrate(320)
+                .max_channels(2),
+            DeviceClass::LowBandwidth => builder
+                .direct_play(["mp3", "opus"])
+                .transcode_to("opus", 64)
+                .max_bitrate(128)
+                .max_channels(2),
+        }
+    }
+}
+
+/// A class of device, for [`ClientInfo::preset`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DeviceClass {
+    /// Desktop player: plays lossless and lossy files as they are, falls back to 320 kbps MP3.
+    Desktop,
+    /// Web browser: plays common formats in stereo, falls back to 192 kbps Opus.
+    Browser,
+    /// Phone or tablet: lossy files up to 320 kbps in stereo, falls back to 128 kbps Opus.
+    Mobile,
+    /// Metered or slow connections: MP3 and Opus up to 128 kbps, falls back to 64 kbps Opus.
+    LowBandwidth,
+}
+
+/// Fluent builder for [`ClientInfo`], created with [`ClientInfo::builder`] or
+/// [`ClientInfo::preset`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientInfoBuilder {
+    info: ClientInfo,
+    max_channels: Option<i32>,
+}
+
+impl Default for ClientInfoBuilder {
+    fn default() -> Self {
+        Self {
+            info: ClientInfo::new(
+                crate::client::DEFAULT_CLIENT_NAME.to_owned(),
+                std::env::consts::OS.to_owned(),
+            ),
+            max_channels: None,
+        }
+    }
+}
+
+impl ClientInfoBuilder {
+    /// Client name.
+    #[must_use]
+    pub fn name(mut self, name: &str) -> Self {
+        self.info.name = name.to_owned();
+        self
+    }
+
+    /// Client platform.
+    #[must_use]
+    pub fn platform(mut self, platform: &str) -> Self {
+        self.info.platform = platform.to_owned();
+        self
+    }
+
+    /// Play files in these containers (`flac`, `mp3`, `ogg`, …) directly over HTTP, whatever
+    /// their codec.
+    #[must_use]
+    pub fn direct_play<S: Into<String>>(mut self, containers: impl IntoIterator<Item = S>) -> Self {
+        let profile = DirectPlayProfile::new()
+            .with_containers(
+                containers
+                    .into_iter()
+                    .map(Into::into)
+                    .collect::<Vec<String>>(),
+            )
+            .with_protocols(vec!["http".to_owned()]);
+        self.info.direct_play_profiles.push(profile);
+        self
+    }
+
+    /// Add a direct play profile as is.
+    #[must_use]
+    pub fn direct_play_profile(mut self, profile: DirectPlayProfile) -> Self {
+        self.info.direct_play_profiles.push(profile);
+        self
+    }
+
+    /// Accept transcoding to `codec` over HTTP, at up to `max_bitrate` kbps.
+    ///
+    /// Opus and Vorbis are sent in an Ogg container, other codecs in the container of the same
+    /// name. The bitrate applies to all transcodes, so the last call sets it.
+    #[must_use]
+    pub fn transcode_to(mut self, codec: &str, max_bitrate: i32) -> Self {
+        let container = match codec {
+            "opus" | "vorbis" => "ogg",
+            other => other,
+        };
+        self.info.transcoding_profiles.push(TranscodingProfile::new(
+            container.to_owned(),
+            codec.to_owned(),
+            "http".to_owned(),
+        ));
+        self.info.max_transcoding_audio_bitrate = Some(max_bitrate);
+        self
+    }
+
+    /// Add a transcoding profile as is. Profiles are in order of preference.
+    #[must_use]
+    pub fn transcoding_profile(mut self, profile: TranscodingProfile) -> Self {
+        self.info.transcoding_profiles.push(profile);
+        self
+    }
+
+    /// Add a codec profile.
+    #[must_use]
+    pub fn codec_profile(mut self, profile: CodecProfile) -> Self {
+        self.info.codec_profiles.push(profile);
+        self
+    }
+
+    /// Maximum bitrate (kbps) of files played directly.
+    #[must_use]
+    pub fn max_bitrate(mut self, max_bitrate: i32) -> Self {
+        self.info.max_audio_bitrate = Some(max_bitrate);
+        self
+    }
+
+    /// Maximum number of audio channels, applied to every direct play and transcoding
+    /// profile that does not set its own.
+    #[must_use]
+    pub fn max_channels(mut self, channels: i32) -> Self {
+        self.max_channels = Some(channels);
+        self
+    }
+
+    /// Build the [`ClientInfo`].
+    pub fn build(self) -> ClientInfo {
+        let mut info = self.info;
+        if let Some(channels) = self.max_channels {
+            for profile in &mut info.direct_play_profiles {
+                profile.max_audio_channels.get_or_insert(channels);
+            }
+            for profile in &mut info.transcoding_profiles {
+                profile.max_audio_channels.get_or_insert(channels);
+            }
+        }
+        info
+    }
+}
+
 /// Direct play profile.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct DirectPlayProfile {
     /// Supported containers (empty = any).
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub containers: Vec<String>,
     /// Supported audio codecs (empty = any).
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub audio_codecs: Vec<String>,
     /// Supported protocols (empty = any).
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::de::one_or_many")]
     pub protocols: Vec<String>,
     /// Max audio channels.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_audio_channels: Option<i32>,
 }
 
+setters! {
+    DirectPlayProfile::new();
+    set {
+        with_containers => containers: Vec<String>,
+        with_audio_codecs => audio_codecs: Vec<String>,
+        with_protocols => protocols: Vec<String>,
+    }
+    optional {
+        with_max_audio_channels => max_audio_channels: i32,
+    }
+}
+
 /// Transcoding profile.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct TranscodingProfile {
     /// Container format.
     pub container: String,
@@ -112,9 +377,18 @@ pub struct TranscodingProfile {
     pub max_audio_channels: Option<i32>,
 }
 
+setters! {
+    TranscodingProfile::new(container: String, audio_codec: String, protocol: String);
+    optional {
+        with_max_audio_channels => max_audio_channels: i32,
+    }
+}
+
 /// Codec profile.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct CodecProfile {
     /// Type (e.g. "AudioCodec").
     #[serde(rename = "type")]
@@ -122,20 +396,43 @@ pub struct CodecProfile {
     /// Codec name.
     pub name: String,
     /// Limitations.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub limitations: Vec<Limitation>,
 }
 
+setters! {
+    CodecProfile::new(profile_type: String, name: String);
+    set {
+        with_limitations => limitations: Vec<Limitation>,
+    }
+}
+
 /// A limitation on a codec profile.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Limitation {
     /// Limitation name (e.g. "audioChannels", "audioBitrate").
     pub name: String,
     /// Comparison operator.
     pub comparison: String,
     /// Values to compare against.
+    #[serde(deserialize_with = "super::de::one_or_many")]
     pub values: Vec<String>,
     /// Whether this limitation is required.
+    #[serde(deserialize_with = "super::de::lenient_bool")]
     pub required: bool,
 }
+
+setters! {
+    Limitation::new(name: String, comparison: String);
+    set {
+        with_values => values: Vec<String>,
+        with_required => required: bool,
+    }
+}
@@ -1,62 +1,153 @@
 //! Types for the User Management API section.
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::timestamp::Timestamp;
 
 /// A Subsonic user.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct User {
     /// Username.
     pub username: String,
     /// Whether scrobbling is enabled.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub scrobbling_enabled: Option<bool>,
     /// Max bitrate (kbps).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_bit_rate: Option<i32>,
     /// Admin role.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub admin_role: Option<bool>,
     /// Settings role.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub settings_role: Option<bool>,
     /// Download role.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub download_role: Option<bool>,
     /// Upload role.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub upload_role: Option<bool>,
     /// Playlist role.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub playlist_role: Option<bool>,
     /// Cover art role.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub cover_art_role: Option<bool>,
     /// Comment role.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub comment_role: Option<bool>,
     /// Podcast role.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub podcast_role: Option<bool>,
     /// Stream role.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub stream_role: Option<bool>,
     /// Jukebox role.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub jukebox_role: Option<bool>,
     /// Share role.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub share_role: Option<bool>,
     /// Video conversion role.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::option_lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub video_conversion_role: Option<bool>,
     /// Date avatar was last changed (ISO 8601).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub avatar_last_changed: Option<String>,
+    pub avatar_last_changed: Option<Timestamp>,
     /// Accessible music folder IDs.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        deserialize_with = "super::de::one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub folder: Vec<i64>,
     /// Email address.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub email: Option<String>,
+    /// Fields sent by the server that this version of the crate does not know about.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: Map<String, Value>,
+}
+
+setters! {
+    User::new(username: String);
+    set {
+        with_folder => folder: Vec<i64>,
+        with_extra => extra: Map<String, Value>,
+    }
+    optional {
+        with_scrobbling_enabled => scrobbling_enabled: bool,
+        with_max_bit_rate => max_bit_rate: i32,
+        with_admin_role => admin_role: bool,
+        with_settings_role => settings_role: bool,
+        with_download_role => download_role: bool,
+        with_upload_role => upload_role: bool,
+        with_playlist_role => playlist_role: bool,
+        with_cover_art_role => cover_art_role: bool,
+        with_comment_role => comment_role: bool,
+        with_podcast_role => podcast_role: bool,
+        with_stream_role => stream_role: bool,
+        with_jukebox_role => jukebox_role: bool,
+        with_share_role => share_role: bool,
+        with_video_conversion_role => video_conversion_role: bool,
+        with_avatar_last_changed => avatar_last_changed: Timestamp,
+        with_email => email: String,
+    }
 }
@@ -0,0 +1,67 @@
+//! Turning one list of IDs into another by removing entries and appending new ones.
+//!
+//! Playlists (`updatePlaylist`) and the jukebox queue (`jukeboxControl`) can both only drop
+//! entries by index and append IDs at the end, so edits to them are planned the same way.
+
+use std::collections::HashSet;
+
+/// The entries to remove from a list, and the IDs to append afterwards, to turn it into
+/// another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ListDiff<'a> {
+    /// Indexes of the current entries that are kept.
+    kept: HashSet<usize>,
+    /// Number of current entries.
+    len: usize,
+    /// IDs to append after the kept entries.
+    pub(crate) append: Vec<&'a str>,
+}
+
+impl<'a> ListDiff<'a> {
+    /// Keep the longest prefix of `desired` that appears (in order) in `current`, remove the
+    /// other entries and append the rest of `desired`.
+    ///
+    /// Greedy leftmost matching yields the longest such prefix.
+    pub(crate) fn compute(current: &[&str], desired: &[&'a str]) -> Self {
+        let mut kept = HashSet::new();
+        let mut matched = 0;
+        for (index, id) in current.iter().enumerate() {
+            if matched < desired.len() && *id == desired[matched] {
+                kept.insert(index);
+                matched += 1;
+            }
+        }
+        Self {
+            kept,
+            len: current.len(),
+            append: desired[matched..].to_vec(),
+        }
+    }
+
+    /// Whether the current entry at `index` is kept.
+    pub(crate) fn keeps(&self, index: usize) -> bool {
+        self.kept.contains(&index)
+    }
+
+    /// Indexes of the current entries to remove, in ascending order.
+    pub(crate) fn removed(&self) -> impl DoubleEndedIterator<Item = usize> + '_ {
+        (0..self.len).filter(|i| !self.keeps(*i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_longest_matching_prefix() {
+        let diff = ListDiff::compute(&["x", "a", "y", "b"], &["a", "b", "c"]);
+        assert!(diff.keeps(1) && diff.keeps(3));
+        assert_eq!(diff.removed().collect::<Vec<_>>(), [0, 2]);
+        assert_eq!(diff.append, ["c"]);
+
+        let diff = ListDiff::compute(&["a", "b"], &[]);
+        assert_eq!(diff.removed().rev().collect::<Vec<_>>(), [1, 0]);
+        assert!(diff.append.is_empty());
+    }
+}
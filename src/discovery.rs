@@ -0,0 +1,350 @@
+//! Finding servers on the local network with mDNS / DNS-SD (requires the `discovery`
+//! feature).
+//!
+//! [`discover`] asks the LAN for Subsonic services and returns a [`DiscoveredServer`] for
+//! every answer, with a base URL ready for [`Client::new`](crate::Client::new). Servers
+//! advertised as `_subsonic._tcp` are always reported; generic `_http._tcp` services only
+//! when their name or TXT record identifies a known server implementation.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), opensubsonic::Error> {
+//! use std::time::Duration;
+//!
+//! for server in opensubsonic::discovery::discover(Duration::from_secs(2)).await? {
+//!     println!("{} at {} ({:?})", server.name, server.base_url, server.server_type);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Only IPv4 multicast is used. Servers must advertise themselves; most do not by default,
+//! but can be announced with Avahi or Bonjour service files.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use tokio::net::UdpSocket;
+use url::Url;
+
+use crate::error::Error;
+use crate::profile::ServerProfile;
+
+/// Service types browsed by [`discover`].
+pub const SERVICE_TYPES: [&str; 2] = ["_subsonic._tcp.local", "_http._tcp.local"];
+
+/// The mDNS multicast group and port.
+const MDNS_ADDR: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(224, 0, 0, 251)), 5353);
+
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const TYPE_TXT: u16 = 16;
+const TYPE_AAAA: u16 = 28;
+const TYPE_SRV: u16 = 33;
+/// Class IN with the "unicast response requested" bit.
+const CLASS_IN_QU: u16 = 0x8001;
+
+/// A server found by [`discover`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredServer {
+    /// The advertised instance name, e.g. `Navidrome on nas`.
+    pub name: String,
+    /// The server URL, built from the advertised address, port and `path` TXT entry.
+    pub base_url: Url,
+    /// The server implementation, from the `type` or `server` TXT entry or the instance
+    /// name, if recognised.
+    pub server_type: Option<String>,
+}
+
+impl DiscoveredServer {
+    /// The workarounds profile matching [`server_type`](Self::server_type).
+    pub fn profile(&self) -> ServerProfile {
+        self.server_type
+            .as_deref()
+            .map_or(ServerProfile::Generic, ServerProfile::from_server_type)
+    }
+}
+
+/// Browse the local network for [`SERVICE_TYPES`], collecting answers for `timeout`.
+///
+/// # Errors
+/// Returns [`Error::Io`] if the query cannot be sent.
+pub async fn discover(timeout: Duration) -> Result<Vec<DiscoveredServer>, Error> {
+    discover_services(&SERVICE_TYPES, timeout).await
+}
+
+/// Browse the local network for the given service types (e.g. `_subsonic._tcp.local`),
+/// collecting answers for `timeout`.
+///
+/// Services other than `_subsonic._tcp` are only reported when they identify a known server
+/// implementation.
+///
+/// # Errors
+/// Returns [`Error::Io`] if the query cannot be sent.
+pub async fn discover_services(
+    service_types: &[&str],
+    timeout: Duration,
+) -> Result<Vec<DiscoveredServer>, Error> {
+    // A query from a port other than 5353 is answered by unicast to that port.
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+    socket.send_to(&query(service_types), MDNS_ADDR).await?;
+
+    let mut records = Records::default();
+    let mut buf = [0; 9000];
+    let deadline = tokio::time::Instant::now() + timeout;
+    while let Ok(received) = tokio::time::timeout_at(deadline, socket.recv_from(&mut buf)).await {
+        let (len, _) = received?;
+        // Ignore packets that are not valid DNS messages.
+        let _ = records.read_message(&buf[..len]);
+    }
+    Ok(records.servers(service_types))
+}
+
+/// A DNS query with one PTR question per service type.
+fn query(service_types: &[&str]) -> Vec<u8> {
+    let mut packet = vec![0; 12];
+    packet[4..6].copy_from_slice(&(service_types.len() as u16).to_be_bytes());
+    for service in service_types {
+        encode_name(&mut packet, service);
+        packet.extend_from_slice(&TYPE_PTR.to_be_bytes());
+        packet.extend_from_slice(&CLASS_IN_QU.to_be_bytes());
+    }
+    packet
+}
+
+/// Append `name` as uncompressed DNS labels.
+fn encode_name(packet: &mut Vec<u8>, name: &str) {
+    for label in name.trim_end_matches('.').split('.') {
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+}
+
+/// Resource records gathered from all answers, keyed by lowercase owner name.
+#[derive(Debug, Default)]
+struct Records {
+    /// Service type → instance names.
+    ptr: HashMap<String, Vec<String>>,
+    /// Instance → (target host, port).
+    srv: HashMap<String, (String, u16)>,
+    /// Instance → TXT entries.
+    txt: HashMap<String, Vec<(String, String)>>,
+    /// Host → addresses.
+    addrs: HashMap<String, Vec<IpAddr>>,
+}
+
+impl Records {
+    /// Collect the records of every section of a DNS message.
+    fn read_message(&mut self, msg: &[u8]) -> Option<()> {
+        let count = |i: usize| Some(u16::from_be_bytes(msg.get(i..i + 2)?.try_into().ok()?));
+        let questions = count(4)?;
+        let records = count(6)? as usize + count(8)? as usize + count(10)? as usize;
+        let mut pos = 12;
+        for _ in 0..questions {
+            pos = read_name(msg, pos)?.1 + 4;
+        }
+        for _ in 0..records {
+            let (owner, next) = read_name(msg, pos)?;
+            let header = msg.get(next..next + 10)?;
+            let kind = u16::from_be_bytes([header[0], header[1]]);
+            let len = u16::from_be_bytes([header[8], header[9]]) as usize;
+            let start = next + 10;
+            let data = msg.get(start..start + len)?;
+            let owner = owner.to_ascii_lowercase();
+            match kind {
+                TYPE_PTR => {
+                    let instance = read_name(msg, start)?.0;
+                    self.ptr.entry(owner).or_default().push(instance);
+                }
+                TYPE_SRV if len >= 6 => {
+                    let port = u16::from_be_bytes([data[4], data[5]]);
+                    let target = read_name(msg, start + 6)?.0;
+                    self.srv.insert(owner, (target.to_ascii_lowercase(), port));
+                }
+                TYPE_TXT => {
+                    self.txt.insert(owner, read_txt(data));
+                }
+                TYPE_A if len == 4 => {
+                    let addr = Ipv4Addr::new(data[0], data[1], data[2], data[3]);
+                    self.addrs.entry(owner).or_default().push(addr.into());
+                }
+                TYPE_AAAA if len == 16 => {
+                    let octets: [u8; 16] = data.try_into().ok()?;
+                    let addr = Ipv6Addr::from(octets);
+                    self.addrs.entry(owner).or_default().push(addr.into());
+                }
+                _ => {}
+            }
+            pos = start + len;
+        }
+        Some(())
+    }
+
+    /// The servers advertised for `service_types`.
+    fn servers(&self, service_types: &[&str]) -> Vec<DiscoveredServer> {
+        let mut servers = Vec::new();
+        for service in service_types {
+            let service = service.trim_end_matches('.').to_ascii_lowercase();
+            for instance in self.ptr.get(&service).into_iter().flatten() {
+                let key = instance.to_ascii_lowercase();
+                let Some((host, port)) = self.srv.get(&key) else {
+                    continue;
+                };
+                let txt = self.txt.get(&key).map(Vec::as_slice).unwrap_or_default();
+                let entry = |name: &str| {
+                    txt.iter()
+                        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                        .map(|(_, v)| v.as_str())
+                };
+                let name = instance
+                    .len()
+                    .checked_sub(service.len() + 1)
+                    .map_or(instance.as_str(), |end| &instance[..end]);
+                let server_type = entry("type")
+                    .or_else(|| entry("server"))
+                    .map(str::to_owned)
+                    .or_else(|| known_server_in(name));
+                if !service.starts_with("_subsonic.") && server_type.is_none() {
+                    continue;
+                }
+                let scheme = if entry("scheme") == Some("https") || *port == 443 {
+                    "https"
+                } else {
+                    "http"
+                };
+                // Prefer an address over the `.local` name, which not every OS resolves.
+                let addrs = self.addrs.get(host).map(Vec::as_slice).unwrap_or_default();
+                let addr = addrs.iter().find(|a| a.is_ipv4()).or(addrs.first());
+                let host = match addr {
+                    Some(IpAddr::V6(addr)) => format!("[{addr}]"),
+                    Some(addr) => addr.to_string(),
+                    None => host.clone(),
+                };
+                let path = entry("path").unwrap_or("/");
+                let Ok(base_url) =
+                    Url::parse(&format!("{scheme}://{host}:{port}")).and_then(|url| url.join(path))
+                else {
+                    continue;
+                };
+                let server = DiscoveredServer {
+                    name: name.to_owned(),
+                    base_url,
+                    server_type,
+                };
+                if !servers.contains(&server) {
+                    servers.push(server);
+                }
+            }
+        }
+        servers
+    }
+}
+
+/// The name of a known server implementation mentioned in `name`, if any.
+fn known_server_in(name: &str) -> Option<String> {
+    name.split(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+        .find(|word| ServerProfile::from_server_type(word) != ServerProfile::Generic)
+        .map(str::to_owned)
+}
+
+/// Read a possibly compressed name at `pos`, returning it and the position after it.
+fn read_name(msg: &[u8], mut pos: usize) -> Option<(String, usize)> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    // Bound the number of pointers followed, against loops.
+    for _ in 0..128 {
+        let len = *msg.get(pos)? as usize;
+        if len & 0xc0 == 0xc0 {
+            let target = (len & 0x3f) << 8 | *msg.get(pos + 1)? as usize;
+            end.get_or_insert(pos + 2);
+            pos = target;
+        } else if len == 0 {
+            return Some((labels.join("."), end.unwrap_or(pos + 1)));
+        } else {
+            let label = msg.get(pos + 1..pos + 1 + len)?;
+            labels.push(String::from_utf8_lossy(label).into_owned());
+            pos += 1 + len;
+        }
+    }
+    None
+}
+
+/// Split TXT record data into `key=value` entries.
+fn read_txt(mut data: &[u8]) -> Vec<(String, String)> {
+    let mut entries = Vec::new();
+    while let Some((&len, rest)) = data.split_first() {
+        let Some(entry) = rest.get(..len as usize) else {
+            break;
+        };
+        let entry = String::from_utf8_lossy(entry);
+        let (key, value) = entry.split_once('=').unwrap_or((&entry, ""));
+        entries.push((key.to_owned(), value.to_owned()));
+        data = &rest[len as usize..];
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Append a resource record with an uncompressed owner name.
+    fn record(packet: &mut Vec<u8>, owner: &str, kind: u16, data: &[u8]) {
+        encode_name(packet, owner);
+        packet.extend_from_slice(&kind.to_be_bytes());
+        packet.extend_from_slice(&1u16.to_be_bytes());
+        packet.extend_from_slice(&120u32.to_be_bytes());
+        packet.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        packet.extend_from_slice(data);
+    }
+
+    #[test]
+    fn reads_advertisements() {
+        let mut packet = vec![0, 0, 0x84, 0, 0, 0, 0, 4, 0, 0, 0, 0];
+        let mut instance = Vec::new();
+        encode_name(&mut instance, "Navidrome on nas._http._tcp.local");
+        record(&mut packet, "_http._tcp.local", TYPE_PTR, &instance);
+        let mut srv = vec![0, 0, 0, 0, 0x11, 0xb5];
+        encode_name(&mut srv, "nas.local");
+        record(
+            &mut packet,
+            "Navidrome on nas._http._tcp.local",
+            TYPE_SRV,
+            &srv,
+        );
+        record(
+            &mut packet,
+            "Navidrome on nas._http._tcp.local",
+            TYPE_TXT,
+            b"\x0bpath=/music",
+        );
+        record(&mut packet, "nas.local", TYPE_A, &[192, 168, 1, 20]);
+
+        let mut records = Records::default();
+        records.read_message(&packet).unwrap();
+        let servers = records.servers(&SERVICE_TYPES);
+        assert_eq!(servers.len(), 1);
+        assert_eq!(servers[0].name, "Navidrome on nas");
+        assert_eq!(
+            servers[0].base_url.as_str(),
+            "http://192.168.1.20:4533/music"
+        );
+        assert_eq!(servers[0].profile(), ServerProfile::Navidrome);
+
+        // A plain web server is not reported.
+        let query = query(&SERVICE_TYPES);
+        let mut records = Records::default();
+        records.read_message(&query).unwrap();
+        assert!(records.servers(&SERVICE_TYPES).is_empty());
+    }
+
+    #[test]
+    fn follows_compression_pointers() {
+        let mut msg = Vec::new();
+        encode_name(&mut msg, "nas.local");
+        msg.extend_from_slice(&[3, b'w', b'w', b'w', 0xc0, 0]);
+        assert_eq!(read_name(&msg, 11), Some(("www.nas.local".to_owned(), 17)));
+        assert_eq!(read_name(&[0xc0, 0], 0), None);
+    }
+}
@@ -0,0 +1,602 @@
+//! Downloading batches of songs and albums concurrently.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::pin::pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::future::{self, Either};
+use futures_util::{StreamExt, stream};
+use tokio::sync::{mpsc, watch};
+
+use super::{DownloadOptions, DownloadReport};
+use crate::Client;
+use crate::cancel::CancelToken;
+use crate::data::{AlbumId, Child, SongId};
+use crate::error::Error;
+
+/// Default number of files downloaded at the same time.
+const DEFAULT_CONCURRENCY: usize = 4;
+/// Default delay before the first retry of a failed download; doubled for each further retry.
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Something to download with a [`Downloader`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DownloadItem {
+    /// A single song or video.
+    Song(SongId),
+    /// Every song of an album (ID3).
+    Album(AlbumId),
+}
+
+impl From<SongId> for DownloadItem {
+    fn from(id: SongId) -> Self {
+        Self::Song(id)
+    }
+}
+
+impl From<AlbumId> for DownloadItem {
+    fn from(id: AlbumId) -> Self {
+        Self::Album(id)
+    }
+}
+
+/// Progress of a [`Downloader`] run, sent to the channel returned by
+/// [`Downloader::subscribe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DownloadEvent {
+    /// A download attempt started.
+    Started {
+        /// The song being downloaded.
+        id: SongId,
+        /// Where it is written.
+        path: PathBuf,
+        /// Attempt number, starting at 1.
+        attempt: u32,
+    },
+    /// An attempt failed with a transport error and will be retried.
+    Retrying {
+        /// The song being downloaded.
+        id: SongId,
+        /// The attempt that failed.
+        attempt: u32,
+        /// Description of the error.
+        error: String,
+    },
+    /// A song was downloaded.
+    Completed(DownloadReport),
+    /// A song could not be downloaded; no further attempts are made.
+    Failed {
+        /// The song that failed.
+        id: SongId,
+        /// Description of the error.
+        error: String,
+    },
+    /// A song was not downloaded, or its download was interrupted, because the run was
+    /// cancelled.
+    Cancelled {
+        /// The song that was skipped.
+        id: SongId,
+    },
+}
+
+/// The outcome of [`Downloader::run`].
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    /// Songs that were downloaded.
+    pub completed: Vec<DownloadReport>,
+    /// Items that failed: songs whose download failed, and albums that could not be listed.
+    pub failed: Vec<(DownloadItem, Error)>,
+    /// Songs skipped or interrupted by [`DownloadHandle::cancel`].
+    pub cancelled: Vec<SongId>,
+}
+
+impl BatchReport {
+    /// Whether every item was downloaded.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty() && self.cancelled.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RunState {
+    Running,
+    Paused,
+    Cancelled,
+}
+
+/// Pauses, resumes or cancels a [`Downloader`] run from another task.
+///
+/// Obtained from [`Downloader::handle`]; clones control the same downloader.
+#[derive(Debug, Clone)]
+pub struct DownloadHandle {
+    state: Arc<watch::Sender<RunState>>,
+}
+
+impl DownloadHandle {
+    /// Stop starting new downloads; downloads already in progress finish.
+    pub fn pause(&self) {
+        self.state.send_if_modified(|state| {
+            let pause = *state == RunState::Running;
+            if pause {
+                *state = RunState::Paused;
+            }
+            pause
+        });
+    }
+
+    /// Start downloads again after [`pause`](Self::pause).
+    pub fn resume(&self) {
+        self.state.send_if_modified(|state| {
+            let resume = *state == RunState::Paused;
+            if resume {
+                *state = RunState::Running;
+            }
+            resume
+        });
+    }
+
+    /// Abort downloads in progress and skip the remaining ones.
+    ///
+    /// Interrupted files are left partially written; with a
+    /// [`DownloadManifest`](super::DownloadManifest) in the [`DownloadOptions`], a later run
+    /// resumes them. A cancelled downloader stays cancelled.
+    pub fn cancel(&self) {
+        self.state.send_replace(RunState::Cancelled);
+    }
+
+    /// Whether the downloader is paused.
+    pub fn is_paused(&self) -> bool {
+        *self.state.borrow() == RunState::Paused
+    }
+
+    /// Whether the downloader was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        *self.state.borrow() == RunState::Cancelled
+    }
+
+    /// Wait until downloads may start, returning `false` if the run was cancelled.
+    async fn runnable(&self) -> bool {
+        let mut state = self.state.subscribe();
+        match state.wait_for(|s| *s != RunState::Paused).await {
+            Ok(s) => *s == RunState::Running,
+            Err(_) => false,
+        }
+    }
+
+    /// Resolve once the run is cancelled.
+    async fn cancelled(&self) {
+        let mut state = self.state.subscribe();
+        let _ = state.wait_for(|s| *s == RunState::Cancelled).await;
+    }
+}
+
+/// Names the file a song is written to, relative to the download directory.
+type FileName = Arc<dyn Fn(&Child) -> PathBuf + Send + Sync>;
+
+/// Downloads batches of songs and albums into a directory.
+///
+/// Up to [`concurrency`](Self::concurrency) files are downloaded at the same time, each with
+/// [`Client::download_song_to_file`]. Attempts failing with a transport error are retried
+/// with exponential backoff; API errors (e.g. an unknown ID) are not. Albums are expanded
+/// into their songs, and a song requested several times is downloaded once.
+///
+/// Obtain one via [`Client::downloader`].
+///
+/// ```no_run
+/// # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+/// use opensubsonic::data::AlbumId;
+/// use opensubsonic::{DownloadEvent, DownloadItem};
+///
+/// let mut downloader = client.downloader("/music/offline").concurrency(2).retries(3);
+/// let mut events = downloader.subscribe();
+/// tokio::spawn(async move {
+///     while let Some(event) = events.recv().await {
+///         if let DownloadEvent::Completed(report) = event {
+///             println!("saved {}", report.path.display());
+///         }
+///     }
+/// });
+///
+/// let report = downloader
+///     .run(&[DownloadItem::Album(AlbumId::from("al-1"))])
+///     .await;
+/// println!("{} songs, {} failures", report.completed.len(), report.failed.len());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Downloader<'a> {
+    client: &'a Client,
+    dir: PathBuf,
+    options: DownloadOptions,
+    concurrency: usize,
+    retries: u32,
+    retry_delay: Duration,
+    file_name: Option<FileName>,
+    events: Option<mpsc::UnboundedSender<DownloadEvent>>,
+    handle: DownloadHandle,
+    cancel_token: Option<CancelToken>,
+}
+
+impl<'a> Downloader<'a> {
+    pub(crate) fn new(client: &'a Client, dir: PathBuf) -> Self {
+        Self {
+            client,
+            dir,
+            options: DownloadOptions::default(),
+            concurrency: DEFAULT_CONCURRENCY,
+            retries: 0,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            file_name: None,
+            events: None,
+            handle: DownloadHandle {
+                state: Arc::new(watch::Sender::new(RunState::Running)),
+            },
+            cancel_token: None,
+        }
+    }
+
+    /// Download up to `limit` files at the same time (default 4).
+    #[must_use]
+    pub fn concurrency(mut self, limit: usize) -> Self {
+        self.concurrency = limit.max(1);
+        self
+    }
+
+    /// Retry a download up to `times` more times after a transport error (default 0).
+    #[must_use]
+    pub fn retries(mut self, times: u32) -> Self {
+        self.retries = times;
+        self
+    }
+
+    /// Wait `delay` before the first retry (default 1 second), doubling it for each further
+    /// retry of the same file.
+    #[must_use]
+    pub fn retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Options for each file, e.g. segments, transcoding or a resume manifest.
+    #[must_use]
+    pub fn options(mut self, options: DownloadOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Name each file with `name`, relative to the download directory.
+    ///
+    /// By default files are named after the song ID and the suffix of the file fetched (e.g.
+    /// `tr-1.flac`, or `tr-1.mp3` when transcoding to MP3). Songs whose name is absolute or
+    /// contains `..` fail instead of being written outside the directory.
+    #[must_use]
+    pub fn file_name(mut self, name: impl Fn(&Child) -> PathBuf + Send + Sync + 'static) -> Self {
+        self.file_name = Some(Arc::new(name));
+        self
+    }
+
+    /// A channel receiving the progress of every following [`run`](Self::run).
+    ///
+    /// Subscribing again replaces the previous channel.
+    pub fn subscribe(&mut self) -> mpsc::UnboundedReceiver<DownloadEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.events = Some(tx);
+        rx
+    }
+
+    /// Cancel the downloader, like [`DownloadHandle::cancel`], when `token` is cancelled.
+    ///
+    /// Lets one [`CancelToken`] stop a download batch together with the other operations
+    /// started for the same view or task.
+    #[must_use]
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
+    /// A handle to pause, resume or cancel this downloader while it runs.
+    pub fn handle(&self) -> DownloadHandle {
+        self.handle.clone()
+    }
+
+    /// Download `items`, returning once every song has been downloaded, has failed, or was
+    /// cancelled.
+    pub async fn run(&self, items: &[DownloadItem]) -> BatchReport {
+        let mut report = BatchReport::default();
+        let mut songs = Vec::new();
+        let mut seen = HashSet::new();
+        for item in items {
+            match self.resolve(item).await {
+                Ok(resolved) => songs.extend(
+                    resolved
+                        .into_iter()
+                        .filter(|song| seen.insert(song.id.clone())),
+                ),
+                Err(e) => report.failed.push((item.clone(), e)),
+            }
+        }
+
+        let mut outcomes = stream::iter(songs)
+            .map(|song| self.download(song))
+            .buffer_unordered(self.concurrency);
+        while let Some((id, outcome)) = outcomes.next().await {
+            match outcome {
+                Outcome::Completed(done) => report.completed.push(done),
+                Outcome::Failed(e) => report.failed.push((DownloadItem::Song(id), e)),
+                Outcome::Cancelled => report.cancelled.push(id),
+            }
+        }
+        report
+    }
+
+    /// The songs an item stands for.
+    async fn resolve(&self, item: &DownloadItem) -> Result<Vec<Child>, Error> {
+        match item {
+            DownloadItem::Song(id) => Ok(vec![self.client.get_song(id).await?]),
+            DownloadItem::Album(id) => Ok(self.client.get_album(id).await?.song),
+        }
+    }
+
+    /// Download one song, retrying transport errors, until it completes, fails or the run is
+    /// cancelled.
+    async fn download(&self, song: Child) -> (SongId, Outcome) {
+        let outcome = match self.path(&song) {
+            Ok(path) => self.attempts(&song, path).await,
+            Err(e) => Outcome::Failed(e),
+        };
+        match &outcome {
+            Outcome::Completed(done) => self.emit(DownloadEvent::Completed(done.clone())),
+            Outcome::Failed(e) => self.emit(DownloadEvent::Failed {
+                id: song.id.clone(),
+                error: e.to_string(),
+            }),
+            Outcome::Cancelled => self.emit(DownloadEvent::Cancelled {
+                id: song.id.clone(),
+            }),
+        }
+        (song.id, outcome)
+    }
+
+    /// Where to write `song`, refusing names that would leave the download directory.
+    fn path(&self, song: &Child) -> Result<PathBuf, Error> {
+        let name = match &self.file_name {
+            Some(name) => name(song),
+            None => default_file_name(song, &self.options),
+        };
+        if name.as_os_str().is_empty()
+            || !name.components().all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(Error::Other(format!(
+                "File name {} of song {} is outside the download directory",
+                name.display(),
+                song.id
+            )));
+        }
+        Ok(self.dir.join(name))
+    }
+
+    /// Download `song` into `path`, retrying transport errors.
+    async fn attempts(&self, song: &Child, path: PathBuf) -> Outcome {
+        let mut delay = self.retry_delay;
+        let mut attempt = 0;
+        loop {
+            if !self.runnable().await {
+                break Outcome::Cancelled;
+            }
+            attempt += 1;
+            self.emit(DownloadEvent::Started {
+                id: song.id.clone(),
+                path: path.clone(),
+                attempt,
+            });
+            let fetch = pin!(self.fetch(song, &path));
+            let result = match future::select(fetch, pin!(self.cancelled())).await {
+                Either::Left((result, _)) => result,
+                Either::Right(_) => break Outcome::Cancelled,
+            };
+            match result {
+                Ok(done) => break Outcome::Completed(done),
+                Err(e) if matches!(e.inner(), Error::Http(_)) && attempt <= self.retries => {
+                    log::warn!("Download of {} failed, retrying: {e}", song.id);
+                    self.emit(DownloadEvent::Retrying {
+                        id: song.id.clone(),
+                        attempt,
+                        error: e.to_string(),
+                    });
+                    let sleep = pin!(tokio::time::sleep(delay));
+                    if let Either::Right(_) = future::select(sleep, pin!(self.cancelled())).await {
+                        break Outcome::Cancelled;
+                    }
+                    delay *= 2;
+                }
+                Err(e) => break Outcome::Failed(e),
+            }
+        }
+    }
+
+    /// Wait until downloads may start, returning `false` if the run was cancelled.
+    async fn runnable(&self) -> bool {
+        let Some(token) = &self.cancel_token else {
+            return self.handle.runnable().await;
+        };
+        match future::select(pin!(self.handle.runnable()), pin!(token.cancelled())).await {
+            Either::Left((runnable, _)) => runnable && !token.is_cancelled(),
+            Either::Right(_) => false,
+        }
+    }
+
+    /// Resolve once the run is cancelled, through the handle or the token.
+    async fn cancelled(&self) {
+        match &self.cancel_token {
+            Some(token) => {
+                future::select(pin!(self.handle.cancelled()), pin!(token.cancelled())).await;
+            }
+            None => self.handle.cancelled().await,
+        }
+    }
+
+    async fn fetch(&self, song: &Child, path: &Path) -> Result<DownloadReport, Error> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        self.client
+            .download_song_to_file(song, path, &self.options)
+            .await
+    }
+
+    fn emit(&self, event: DownloadEvent) {
+        if let Some(events) = &self.events {
+            // The receiver may have been dropped; progress is then simply not reported.
+            let _ = events.send(event);
+        }
+    }
+}
+
+impl fmt::Debug for Downloader<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Downloader")
+            .field("dir", &self.dir)
+            .field("options", &self.options)
+            .field("concurrency", &self.concurrency)
+            .field("retries", &self.retries)
+            .field("retry_delay", &self.retry_delay)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The result of downloading one song.
+enum Outcome {
+    Completed(DownloadReport),
+    Failed(Error),
+    Cancelled,
+}
+
+/// `<id>.<suffix>`, with the suffix of the file `options` fetch: the original one, or the
+/// requested or transcoded format when transcoding. Characters that could make the
+/// server-supplied name a path are replaced with `_`.
+fn default_file_name(song: &Child, options: &DownloadOptions) -> PathBuf {
+    let transcoded = options
+        .transcodes()
+        .then(|| {
+            options
+                .format
+                .as_deref()
+                .filter(|f| *f != "raw")
+                .or(song.transcoded_suffix.as_deref())
+        })
+        .flatten();
+    let suffix = transcoded
+        .or(song.suffix.as_deref())
+        .filter(|s| !s.is_empty())
+        .unwrap_or("bin");
+    let name: String = format!("{}.{suffix}", song.id)
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    PathBuf::from(name)
+}
+
+impl Client {
+    /// Get a [`Downloader`] writing into `dir`, for downloading many songs and albums
+    /// concurrently.
+    pub fn downloader(&self, dir: impl Into<PathBuf>) -> Downloader<'_> {
+        Downloader::new(self, dir.into())
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::testing::MockServer;
+
+    #[tokio::test]
+    async fn downloads_albums_and_songs_once() {
+        let server = MockServer::start().await;
+        let client = server.client();
+        let dir = std::env::temp_dir().join(format!("opensubsonic-batch-{}", std::process::id()));
+
+        let mut downloader = client.downloader(&dir).concurrency(2);
+        let mut events = downloader.subscribe();
+        let report = downloader
+            .run(&[
+                DownloadItem::Album(AlbumId::from("al-1")),
+                DownloadItem::Song(SongId::from("tr-1")),
+            ])
+            .await;
+
+        assert_eq!(report.completed.len(), 1);
+        assert!(report.is_complete());
+        assert_eq!(report.completed[0].path, dir.join("tr-1.mp3"));
+        assert_eq!(server.calls("download").await, 1);
+        assert!(matches!(
+            events.recv().await,
+            Some(DownloadEvent::Started { attempt: 1, .. })
+        ));
+        assert!(matches!(
+            events.recv().await,
+            Some(DownloadEvent::Completed(_))
+        ));
+
+        downloader.handle().cancel();
+        let report = downloader
+            .run(&[DownloadItem::Song(SongId::from("tr-1"))])
+            .await;
+        assert_eq!(report.cancelled, [SongId::from("tr-1")]);
+
+        let token = CancelToken::new();
+        token.cancel();
+        let report = client
+            .downloader(&dir)
+            .cancel_token(token)
+            .run(&[DownloadItem::Song(SongId::from("tr-1"))])
+            .await;
+        assert_eq!(report.cancelled, [SongId::from("tr-1")]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn names_files_after_the_fetched_format() {
+        let mut song = Child::new("tr-1", "Song");
+        song.suffix = Some("flac".into());
+        song.transcoded_suffix = Some("mp3".into());
+        let name = |options: &DownloadOptions| default_file_name(&song, options);
+        assert_eq!(name(&DownloadOptions::new()), Path::new("tr-1.flac"));
+        assert_eq!(
+            name(&DownloadOptions::new().format("opus")),
+            Path::new("tr-1.opus")
+        );
+        assert_eq!(
+            name(&DownloadOptions::new().max_bit_rate(128)),
+            Path::new("tr-1.mp3")
+        );
+
+        song.id = SongId::from("../../etc/passwd");
+        song.suffix = Some(String::new());
+        let name = default_file_name(&song, &DownloadOptions::new());
+        assert_eq!(name, Path::new(".._.._etc_passwd.bin"));
+    }
+
+    #[tokio::test]
+    async fn refuses_names_outside_the_directory() {
+        let server = MockServer::start().await;
+        let dir = std::env::temp_dir().join(format!("opensubsonic-escape-{}", std::process::id()));
+        let report = server
+            .client()
+            .downloader(&dir)
+            .file_name(|song| PathBuf::from(format!("../{}.mp3", song.id)))
+            .run(&[DownloadItem::Song(SongId::from("tr-1"))])
+            .await;
+        assert!(matches!(report.failed[0].1, Error::Other(_)));
+        assert_eq!(server.calls("download").await, 0);
+        assert!(!dir.exists());
+    }
+}
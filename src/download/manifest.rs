@@ -0,0 +1,312 @@
+//! Persistent record of in-progress downloads.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+/// Progress of one byte range of a file being downloaded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RangeProgress {
+    /// First byte of the range within the file.
+    pub start: u64,
+    /// Last byte of the range (inclusive); `None` means "until the end of the file".
+    pub end: Option<u64>,
+    /// Number of bytes of this range already safely on disk.
+    pub written: u64,
+}
+
+/// A file whose download has started but not finished.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestEntry {
+    /// The media ID being downloaded.
+    pub id: String,
+    /// The file being written.
+    pub path: PathBuf,
+    /// Requested transcoding format, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    /// Requested maximum bitrate (kbps), if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_bit_rate: Option<i32>,
+    /// Total file size announced by the server, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+    /// Progress of each byte range (a single open-ended range for sequential downloads).
+    pub ranges: Vec<RangeProgress>,
+}
+
+impl ManifestEntry {
+    /// Total number of bytes already on disk across all ranges.
+    pub fn bytes_written(&self) -> u64 {
+        self.ranges.iter().map(|r| r.written).sum()
+    }
+
+    /// Whether this entry was created for the same source and transcoding settings.
+    fn same_source(&self, id: &str, format: Option<&str>, max_bit_rate: Option<i32>) -> bool {
+        self.id == id && self.format.as_deref() == format && self.max_bit_rate == max_bit_rate
+    }
+}
+
+/// A manifest of in-progress downloads persisted as a JSON file.
+///
+/// Attach it with [`super::DownloadOptions::manifest`]. Progress is checkpointed to disk while
+/// files are written (only after the written bytes have been synced), so an interrupted
+/// session resumes each file exactly where it left off instead of restarting or leaving a
+/// corrupt file behind. Entries are removed once their download completes.
+///
+/// The handle is cheap to clone; clones share the same manifest.
+#[derive(Debug, Clone)]
+pub struct DownloadManifest {
+    inner: Arc<Mutex<ManifestFile>>,
+}
+
+#[derive(Debug)]
+struct ManifestFile {
+    location: PathBuf,
+    entries: Vec<ManifestEntry>,
+}
+
+impl DownloadManifest {
+    /// Load the manifest stored at `location`, or start an empty one if it does not exist.
+    ///
+    /// # Errors
+    /// Returns [`Error::Io`] if the file cannot be read or [`Error::Parse`] if it is corrupt.
+    pub fn open(location: impl Into<PathBuf>) -> Result<Self, Error> {
+        let location = location.into();
+        let entries = match std::fs::read(&location) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            inner: Arc::new(Mutex::new(ManifestFile { location, entries })),
+        })
+    }
+
+    /// All downloads that are still in progress.
+    pub fn entries(&self) -> Vec<ManifestEntry> {
+        self.lock().entries.clone()
+    }
+
+    /// The in-progress entry for `path`, if any.
+    pub fn entry(&self, path: &Path) -> Option<ManifestEntry> {
+        self.lock().entries.iter().find(|e| e.path == path).cloned()
+    }
+
+    /// The entry for `path` if it can be resumed for the given source and settings.
+    ///
+    /// Progress is clamped to what is actually on disk, in case the file was truncated.
+    pub(crate) fn resumable(
+        &self,
+        path: &Path,
+        id: &str,
+        format: Option<&str>,
+        max_bit_rate: Option<i32>,
+    ) -> Option<ManifestEntry> {
+        let mut entry = self
+            .entry(path)
+            .filter(|e| e.same_source(id, format, max_bit_rate))?;
+        let on_disk = std::fs::metadata(path).ok()?.len();
+        for range in &mut entry.ranges {
+            let available = on_disk.saturating_sub(range.start);
+            range.written = range.written.min(available);
+        }
+        Some(entry)
+    }
+
+    /// Record (or replace) the entry for a download that is starting.
+    pub(crate) fn begin(&self, entry: ManifestEntry) -> Result<(), Error> {
+        let mut file = self.lock();
+        file.entries.retain(|e| e.path != entry.path);
+        file.entries.push(entry);
+        file.save()
+    }
+
+    /// Update the number of bytes written for range `index` of the download into `path`.
+    pub(crate) fn progress(&self, path: &Path, index: usize, written: u64) -> Result<(), Error> {
+        let mut file = self.lock();
+        let range = file
+            .entries
+            .iter_mut()
+            .find(|e| e.path == path)
+            .and_then(|e| e.ranges.get_mut(index));
+        match range {
+            Some(range) => {
+                range.written = written;
+                file.save()
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Mark the download into `path` as not started, keeping its entry (it failed
+    /// verification and must be fetched again).
+    pub(crate) fn reset(&self, path: &Path) -> Result<(), Error> {
+        let mut file = self.lock();
+        let Some(entry) = file.entries.iter_mut().find(|e| e.path == path) else {
+            return Ok(());
+        };
+        entry.ranges.iter_mut().for_each(|range| range.written = 0);
+        file.save()
+    }
+
+    /// Forget the download into `path` (it completed or must restart from scratch).
+    pub(crate) fn finish(&self, path: &Path) -> Result<(), Error> {
+        let mut file = self.lock();
+        let before = file.entries.len();
+        file.entries.retain(|e| e.path != path);
+        if file.entries.len() == before {
+            return Ok(());
+        }
+        file.save()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ManifestFile> {
+        // A panic while holding the lock cannot leave the entry list half-updated.
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl ManifestFile {
+    /// Atomically replace the manifest file with the current entries.
+    fn save(&self) -> Result<(), Error> {
+        let json = serde_json::to_vec_pretty(&self.entries)?;
+        let mut tmp = self.location.clone().into_os_string();
+        tmp.push(".tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, &self.location)?;
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use wiremock::matchers::{header, header_exists, path};
+    use wiremock::{Mock, ResponseTemplate};
+
+    use super::*;
+    use crate::download::DownloadOptions;
+    use crate::testing::MockServer;
+
+    /// A partial download of `tr-1` (`01234` of 10 bytes) recorded in a fresh manifest.
+    fn partial(name: &str) -> (PathBuf, PathBuf, DownloadManifest) {
+        let dir = std::env::temp_dir().join(format!("opensubsonic-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("tr-1.mp3");
+        std::fs::write(&file, b"01234").unwrap();
+        let manifest = DownloadManifest::open(dir.join("manifest.json")).unwrap();
+        manifest
+            .begin(ManifestEntry {
+                id: "tr-1".into(),
+                path: file.clone(),
+                format: None,
+                max_bit_rate: None,
+                total: Some(10),
+                ranges: vec![RangeProgress {
+                    start: 0,
+                    end: None,
+                    written: 5,
+                }],
+            })
+            .unwrap();
+        (dir, file, manifest)
+    }
+
+    #[tokio::test]
+    async fn appends_to_the_partial_file() {
+        let server = MockServer::start().await;
+        Mock::given(path("/rest/download"))
+            .and(header("range", "bytes=5-"))
+            .respond_with(
+                ResponseTemplate::new(206)
+                    .insert_header("content-range", "bytes 5-9/10")
+                    .set_body_bytes(&b"56789"[..]),
+            )
+            .expect(1)
+            .mount(server.inner())
+            .await;
+        let (dir, file, _) = partial("manifest-append");
+
+        // Reopening picks up the entry written by the previous session.
+        let reopened = DownloadManifest::open(dir.join("manifest.json")).unwrap();
+        assert_eq!(reopened.entry(&file).unwrap().bytes_written(), 5);
+        let options = DownloadOptions::new().manifest(reopened);
+        let report = server
+            .client()
+            .download_to_file("tr-1", &file, &options)
+            .await
+            .unwrap();
+        assert_eq!(report.bytes_written, 10);
+        assert_eq!(std::fs::read(&file).unwrap(), b"0123456789");
+        assert!(
+            DownloadManifest::open(dir.join("manifest.json"))
+                .unwrap()
+                .entries()
+                .is_empty()
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn restarts_when_the_server_ignores_the_range() {
+        let server = MockServer::start().await;
+        Mock::given(path("/rest/download"))
+            .and(header("range", "bytes=5-"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(&b"abcdefghij"[..]))
+            .expect(1)
+            .mount(server.inner())
+            .await;
+        let (dir, file, manifest) = partial("manifest-full");
+
+        let options = DownloadOptions::new().manifest(manifest.clone());
+        let report = server
+            .client()
+            .download_to_file("tr-1", &file, &options)
+            .await
+            .unwrap();
+        assert!(report.is_verified());
+        assert_eq!(std::fs::read(&file).unwrap(), b"abcdefghij");
+        assert!(manifest.entries().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn does_not_resume_a_different_source() {
+        let server = MockServer::start().await;
+        Mock::given(path("/rest/stream"))
+            .and(header_exists("range"))
+            .respond_with(ResponseTemplate::new(416))
+            .expect(0)
+            .mount(server.inner())
+            .await;
+        let (dir, file, manifest) = partial("manifest-other");
+
+        // The partial file was an original download, not a transcode.
+        let options = DownloadOptions::new()
+            .format("opus")
+            .manifest(manifest.clone());
+        let report = server
+            .client()
+            .download_to_file("tr-1", &file, &options)
+            .await
+            .unwrap();
+        assert_eq!(report.bytes_written, report.content_length.unwrap());
+        assert_ne!(std::fs::read(&file).unwrap(), b"01234");
+        assert!(manifest.entries().is_empty());
+        assert_eq!(server.calls("stream").await, 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // Progress is clamped to what is on disk if the file was truncated since.
+        let (dir, file, manifest) = partial("manifest-truncated");
+        std::fs::write(&file, b"01").unwrap();
+        let entry = manifest.resumable(&file, "tr-1", None, None).unwrap();
+        assert_eq!(entry.bytes_written(), 2);
+        assert!(manifest.resumable(&file, "tr-2", None, None).is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,603 @@
+//! Downloading media files straight to disk.
+//!
+//! [`Client::download_to_file`] streams the original file for a song or video to disk
+//! instead of buffering it in memory like [`Client::download`]. On servers that honor
+//! `Range` requests, large files can be fetched as several byte ranges concurrently (see
+//! [`DownloadOptions::segments`]), which significantly improves throughput on
+//! high-latency links.
+//!
+//! Every download is verified against the size reported by the server (and, when known,
+//! the song's [`Child::size`]); the outcome is recorded in the returned [`DownloadReport`].
+//!
+//! Attaching a [`DownloadManifest`] makes downloads resumable across crashes and restarts.
+//!
+//! A [`Downloader`] fetches whole batches of songs and albums concurrently, with retries,
+//! progress events and pause/cancel.
+
+mod batch;
+mod manifest;
+mod segmented;
+
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+
+use futures_util::StreamExt;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+pub use batch::{BatchReport, DownloadEvent, DownloadHandle, DownloadItem, Downloader};
+pub use manifest::{DownloadManifest, ManifestEntry, RangeProgress};
+
+use crate::Client;
+use crate::api::media_retrieval::{StreamOptions, content_range_total};
+use crate::data::{Child, SongId};
+use crate::error::Error;
+
+/// Default minimum size of a single download segment (8 MiB).
+const DEFAULT_MIN_SEGMENT_SIZE: u64 = 8 * 1024 * 1024;
+/// Bytes written between two manifest checkpoints (4 MiB).
+const CHECKPOINT_INTERVAL: u64 = 4 * 1024 * 1024;
+
+/// Options for [`Client::download_to_file`].
+#[derive(Debug, Clone)]
+pub struct DownloadOptions {
+    segments: usize,
+    min_segment_size: u64,
+    expected_size: Option<u64>,
+    refetch_on_mismatch: u32,
+    format: Option<String>,
+    max_bit_rate: Option<i32>,
+    manifest: Option<DownloadManifest>,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            segments: 1,
+            min_segment_size: DEFAULT_MIN_SEGMENT_SIZE,
+            expected_size: None,
+            refetch_on_mismatch: 0,
+            format: None,
+            max_bit_rate: None,
+            manifest: None,
+        }
+    }
+}
+
+impl DownloadOptions {
+    /// Create default options (a single sequential request).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fetch up to `segments` byte ranges concurrently and stitch them together.
+    ///
+    /// Only used when the server reports the file size and honors `Range` requests;
+    /// otherwise the file is downloaded with a single request.
+    #[must_use]
+    pub fn segments(mut self, segments: usize) -> Self {
+        self.segments = segments.max(1);
+        self
+    }
+
+    /// Never split a file into segments smaller than `bytes` (default 8 MiB).
+    #[must_use]
+    pub fn min_segment_size(mut self, bytes: u64) -> Self {
+        self.min_segment_size = bytes.max(1);
+        self
+    }
+
+    /// Verify the downloaded file against a known size in bytes (e.g. [`Child::size`]).
+    #[must_use]
+    pub fn expected_size(mut self, bytes: u64) -> Self {
+        self.expected_size = Some(bytes);
+        self
+    }
+
+    /// Re-fetch the file up to `times` more times when verification finds a size mismatch.
+    #[must_use]
+    pub fn refetch_on_mismatch(mut self, times: u32) -> Self {
+        self.refetch_on_mismatch = times;
+        self
+    }
+
+    /// Download a transcoded copy in `format` (via `stream`) instead of the original file.
+    #[must_use]
+    pub fn format(mut self, format: &str) -> Self {
+        self.format = Some(format.to_owned());
+        self
+    }
+
+    /// Download a transcoded copy limited to `kbps` (via `stream`) instead of the original file.
+    #[must_use]
+    pub fn max_bit_rate(mut self, kbps: i32) -> Self {
+        self.max_bit_rate = Some(kbps);
+        self
+    }
+
+    /// Persist progress in `manifest` so interrupted downloads resume where they stopped.
+    #[must_use]
+    pub fn manifest(mut self, manifest: DownloadManifest) -> Self {
+        self.manifest = Some(manifest);
+        self
+    }
+
+    /// Whether files are fetched transcoded, through `stream`, rather than through `download`.
+    fn transcodes(&self) -> bool {
+        self.format.is_some() || self.max_bit_rate.is_some()
+    }
+
+    /// The endpoint and parameters to fetch `id` from.
+    fn source<'a>(&self, id: &'a str) -> Source<'a> {
+        if !self.transcodes() {
+            return Source {
+                id,
+                endpoint: "download",
+                params: vec![("id", id.to_owned())],
+            };
+        }
+        let mut stream = StreamOptions::new();
+        if let Some(f) = &self.format {
+            stream = stream.format(f);
+        }
+        if let Some(br) = self.max_bit_rate {
+            stream = stream.max_bit_rate(br);
+        }
+        Source {
+            id,
+            endpoint: "stream",
+            params: stream.to_params(id),
+        }
+    }
+
+    /// A fresh manifest entry for a download of `id` into `path`.
+    fn manifest_entry(
+        &self,
+        id: &str,
+        path: &Path,
+        total: Option<u64>,
+        ranges: Vec<RangeProgress>,
+    ) -> ManifestEntry {
+        ManifestEntry {
+            id: id.to_owned(),
+            path: path.to_owned(),
+            format: self.format.clone(),
+            max_bit_rate: self.max_bit_rate,
+            total,
+            ranges,
+        }
+    }
+}
+
+/// The endpoint and parameters a download is fetched from.
+struct Source<'a> {
+    id: &'a str,
+    endpoint: &'static str,
+    params: Vec<(&'static str, String)>,
+}
+
+impl Source<'_> {
+    fn param_refs(&self) -> Vec<(&str, &str)> {
+        self.params.iter().map(|(k, v)| (*k, v.as_str())).collect()
+    }
+}
+
+/// Where to record the progress of one byte range while it is written.
+struct Checkpoint<'a> {
+    manifest: &'a DownloadManifest,
+    path: &'a Path,
+    index: usize,
+}
+
+/// Outcome of the post-download integrity check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verification {
+    /// The byte count matched every known size.
+    Verified,
+    /// Neither the server nor the caller provided a size to compare against.
+    Unverifiable,
+    /// The byte count differs from a known size.
+    Mismatch {
+        /// The size the file should have had.
+        expected: u64,
+        /// The number of bytes actually written.
+        actual: u64,
+    },
+}
+
+impl Verification {
+    /// Compare `written` bytes against the server's `Content-Length` and an expected size.
+    fn check(written: u64, content_length: Option<u64>, expected_size: Option<u64>) -> Self {
+        let mut verified = false;
+        for expected in [content_length, expected_size].into_iter().flatten() {
+            if expected != written {
+                return Self::Mismatch {
+                    expected,
+                    actual: written,
+                };
+            }
+            verified = true;
+        }
+        if verified {
+            Self::Verified
+        } else {
+            Self::Unverifiable
+        }
+    }
+}
+
+/// Per-item result of a download.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadReport {
+    /// The media ID that was downloaded.
+    pub id: SongId,
+    /// Where the file was written.
+    pub path: PathBuf,
+    /// Number of bytes written to `path`.
+    pub bytes_written: u64,
+    /// The size announced by the server, if any.
+    pub content_length: Option<u64>,
+    /// Result of the integrity check.
+    pub verification: Verification,
+    /// Number of fetch attempts made (more than one when mismatches were re-fetched).
+    pub attempts: u32,
+}
+
+impl DownloadReport {
+    /// Whether the file is known to be complete.
+    pub fn is_verified(&self) -> bool {
+        self.verification == Verification::Verified
+    }
+}
+
+impl Client {
+    /// Download the original file for `id` into `path`.
+    ///
+    /// The file at `path` is created (or truncated), unless a [`DownloadManifest`] records a
+    /// partial download of the same source into it, in which case the download resumes.
+    /// See [`DownloadOptions`] for parallel segmented downloads and integrity checks.
+    ///
+    /// A size mismatch that persists after all re-fetch attempts is reported in
+    /// [`DownloadReport::verification`], not as an error. Such a file stays in the manifest
+    /// with its progress reset, so it is not mistaken for complete and the next attempt
+    /// fetches it from the start.
+    pub async fn download_to_file(
+        &self,
+        id: impl Into<SongId>,
+        path: impl AsRef<Path>,
+        options: &DownloadOptions,
+    ) -> Result<DownloadReport, Error> {
+        let id: SongId = id.into();
+        let path = path.as_ref();
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let (bytes_written, content_length) = self.fetch_to_file(&id, path, options).await?;
+            let verification =
+                Verification::check(bytes_written, content_length, options.expected_size);
+            let mismatch = matches!(verification, Verification::Mismatch { .. });
+            if let Some(manifest) = &options.manifest {
+                if mismatch {
+                    manifest.reset(path)?;
+                } else {
+                    manifest.finish(path)?;
+                }
+            }
+            if mismatch {
+                log::warn!("Download of {id} failed verification: {verification:?}");
+            }
+            if !mismatch || attempts > options.refetch_on_mismatch {
+                return Ok(DownloadReport {
+                    id,
+                    path: path.to_owned(),
+                    bytes_written,
+                    content_length,
+                    verification,
+                    attempts,
+                });
+            }
+        }
+    }
+
+    /// Download `song` into `path`, verifying the result against [`Child::size`] unless
+    /// `options` transcode it, since that is the size of the original file.
+    pub async fn download_song_to_file(
+        &self,
+        song: &Child,
+        path: impl AsRef<Path>,
+        options: &DownloadOptions,
+    ) -> Result<DownloadReport, Error> {
+        let mut options = options.clone();
+        let original_size = song.size.filter(|_| !options.transcodes());
+        if let Some(size) = original_size.and_then(|s| u64::try_from(s).ok()) {
+            options = options.expected_size(size);
+        }
+        self.download_to_file(&song.id, path, &options).await
+    }
+
+    /// Fetch the file once, returning the bytes written and the announced size.
+    async fn fetch_to_file(
+        &self,
+        id: &str,
+        path: &Path,
+        options: &DownloadOptions,
+    ) -> Result<(u64, Option<u64>), Error> {
+        let source = options.source(id);
+        let resume = options
+            .manifest
+            .as_ref()
+            .and_then(|m| m.resumable(path, id, options.format.as_deref(), options.max_bit_rate));
+
+        if options.segments > 1 {
+            let probe = self.probe(source.endpoint, &source.param_refs()).await?;
+            if let Some(total) = probe.content_length.filter(|_| probe.accepts_ranges) {
+                let ranges = match &resume {
+                    Some(entry) if entry.total == Some(total) && entry.ranges.len() > 1 => {
+                        entry.ranges.clone()
+                    }
+                    _ => segmented::split(total, options.segments, options.min_segment_size),
+                };
+                if ranges.len() > 1 {
+                    if let Some(manifest) = &options.manifest {
+                        manifest.begin(options.manifest_entry(
+                            id,
+                            path,
+                            Some(total),
+                            ranges.clone(),
+                        ))?;
+                    }
+                    let written = segmented::download(
+                        self,
+                        &source,
+                        path,
+                        total,
+                        &ranges,
+                        options.manifest.as_ref(),
+                    )
+                    .await?;
+                    return Ok((written, Some(total)));
+                }
+            }
+        }
+
+        self.fetch_sequential(&source, path, resume, options).await
+    }
+
+    /// Fetch the file with a single request, resuming from `resume` when the server allows.
+    async fn fetch_sequential(
+        &self,
+        source: &Source<'_>,
+        path: &Path,
+        resume: Option<ManifestEntry>,
+        options: &DownloadOptions,
+    ) -> Result<(u64, Option<u64>), Error> {
+        let offset = resume
+            .filter(|e| e.ranges.len() == 1)
+            .map_or(0, |e| e.ranges[0].written);
+        let mut request =
+            self.request(reqwest::Method::GET, source.endpoint, &source.param_refs())?;
+        if offset > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={offset}-"));
+        }
+        let resp = self.send_binary(request).await?;
+
+        let resumed = offset > 0 && resp.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+        let (mut file, offset, content_length) = if resumed {
+            let mut file = tokio::fs::OpenOptions::new().write(true).open(path).await?;
+            file.set_len(offset).await?;
+            file.seek(SeekFrom::Start(offset)).await?;
+            (file, offset, content_range_total(&resp))
+        } else {
+            (
+                tokio::fs::File::create(path).await?,
+                0,
+                resp.content_length(),
+            )
+        };
+
+        let checkpoint = match &options.manifest {
+            Some(manifest) => {
+                let range = RangeProgress {
+                    start: 0,
+                    end: None,
+                    written: offset,
+                };
+                manifest.begin(options.manifest_entry(
+                    source.id,
+                    path,
+                    content_length,
+                    vec![range],
+                ))?;
+                Some(Checkpoint {
+                    manifest,
+                    path,
+                    index: 0,
+                })
+            }
+            None => None,
+        };
+        let written = write_body(resp, &mut file, checkpoint.as_ref(), offset).await?;
+        Ok((written, content_length))
+    }
+}
+
+/// Stream a response body into `file` after `already` bytes of the same range, returning the
+/// total number of bytes of the range now written.
+///
+/// With a checkpoint, progress is recorded in the manifest every [`CHECKPOINT_INTERVAL`]
+/// bytes, but only after the data has been synced so the manifest never runs ahead of disk.
+async fn write_body(
+    resp: reqwest::Response,
+    file: &mut tokio::fs::File,
+    checkpoint: Option<&Checkpoint<'_>>,
+    already: u64,
+) -> Result<u64, Error> {
+    let mut written = already;
+    let mut unsynced = 0;
+    let mut body = resp.bytes_stream();
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk?;
+        file.write_all(&chunk).await?;
+        written += chunk.len() as u64;
+        unsynced += chunk.len() as u64;
+        if unsynced >= CHECKPOINT_INTERVAL {
+            if let Some(checkpoint) = checkpoint {
+                file.flush().await?;
+                file.sync_data().await?;
+                checkpoint
+                    .manifest
+                    .progress(checkpoint.path, checkpoint.index, written)?;
+            }
+            unsynced = 0;
+        }
+    }
+    file.flush().await?;
+    if let Some(checkpoint) = checkpoint {
+        file.sync_data().await?;
+        checkpoint
+            .manifest
+            .progress(checkpoint.path, checkpoint.index, written)?;
+    }
+    Ok(written)
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use wiremock::matchers::{header, path};
+    use wiremock::{Mock, ResponseTemplate};
+
+    use super::*;
+    use crate::testing::MockServer;
+
+    #[tokio::test]
+    async fn refetches_until_the_size_matches() {
+        let server = MockServer::start().await;
+        Mock::given(path("/rest/download"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(&b"short"[..]))
+            .up_to_n_times(1)
+            .mount(server.inner())
+            .await;
+        Mock::given(path("/rest/download"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(&b"0123456789"[..]))
+            .mount(server.inner())
+            .await;
+        let dir = std::env::temp_dir().join(format!("opensubsonic-refetch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("tr-1.mp3");
+        let manifest = DownloadManifest::open(dir.join("manifest.json")).unwrap();
+        let client = server.client();
+
+        let options = DownloadOptions::new()
+            .expected_size(10)
+            .refetch_on_mismatch(1)
+            .manifest(manifest.clone());
+        let report = client
+            .download_to_file("tr-1", &file, &options)
+            .await
+            .unwrap();
+        assert!(report.is_verified());
+        assert_eq!(report.attempts, 2);
+        assert_eq!(std::fs::read(&file).unwrap(), b"0123456789");
+        assert!(manifest.entries().is_empty());
+
+        // A mismatch that persists is reported, and the file is not recorded as complete.
+        let options = DownloadOptions::new()
+            .expected_size(4)
+            .refetch_on_mismatch(2)
+            .manifest(manifest.clone());
+        let report = client
+            .download_to_file("tr-1", &file, &options)
+            .await
+            .unwrap();
+        assert_eq!(
+            report.verification,
+            Verification::Mismatch {
+                expected: 4,
+                actual: 10
+            }
+        );
+        assert_eq!(report.attempts, 3);
+        assert_eq!(manifest.entry(&file).unwrap().bytes_written(), 0);
+        assert_eq!(server.calls("download").await, 5);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn song_size_is_only_checked_for_the_original() {
+        let server = MockServer::start().await;
+        let client = server.client();
+        let dir = std::env::temp_dir().join(format!("opensubsonic-song-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("tr-1.mp3");
+        let mut song = client.get_song("tr-1").await.unwrap();
+        song.size = Some(1);
+
+        let options = DownloadOptions::new().refetch_on_mismatch(1);
+        let report = client
+            .download_song_to_file(&song, &file, &options)
+            .await
+            .unwrap();
+        assert!(matches!(
+            report.verification,
+            Verification::Mismatch { expected: 1, .. }
+        ));
+
+        let report = client
+            .download_song_to_file(&song, &file, &options.format("mp3"))
+            .await
+            .unwrap();
+        assert!(!matches!(
+            report.verification,
+            Verification::Mismatch { .. }
+        ));
+        assert_eq!(report.attempts, 1);
+        assert_eq!(server.calls("stream").await, 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn resumes_from_the_manifest_with_a_range_request() {
+        let server = MockServer::start().await;
+        Mock::given(path("/rest/download"))
+            .and(header("range", "bytes=5-"))
+            .respond_with(
+                ResponseTemplate::new(206)
+                    .insert_header("content-range", "bytes 5-9/10")
+                    .set_body_bytes(&b"56789"[..]),
+            )
+            .expect(1)
+            .mount(server.inner())
+            .await;
+        let dir = std::env::temp_dir().join(format!("opensubsonic-resume-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("tr-1.mp3");
+        std::fs::write(&file, b"01234").unwrap();
+        let manifest = DownloadManifest::open(dir.join("manifest.json")).unwrap();
+        // Too small to split, so fetched with one request despite the segments.
+        let options = DownloadOptions::new()
+            .segments(4)
+            .manifest(manifest.clone());
+        manifest
+            .begin(options.manifest_entry(
+                "tr-1",
+                &file,
+                Some(10),
+                vec![RangeProgress {
+                    start: 0,
+                    end: None,
+                    written: 5,
+                }],
+            ))
+            .unwrap();
+
+        let report = server
+            .client()
+            .download_to_file("tr-1", &file, &options)
+            .await
+            .unwrap();
+        assert!(report.is_verified());
+        assert_eq!(report.content_length, Some(10));
+        assert_eq!(std::fs::read(&file).unwrap(), b"0123456789");
+        assert!(manifest.entries().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
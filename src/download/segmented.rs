@@ -0,0 +1,133 @@
+//! Parallel byte-range downloads.
+
+use std::io::SeekFrom;
+use std::path::Path;
+
+use tokio::io::AsyncSeekExt;
+
+use super::{Checkpoint, DownloadManifest, RangeProgress, Source, write_body};
+use crate::Client;
+use crate::error::Error;
+
+/// Split `total` bytes into at most `segments` byte ranges of at least `min_segment_size`
+/// bytes each.
+pub(super) fn split(total: u64, segments: usize, min_segment_size: u64) -> Vec<RangeProgress> {
+    if total == 0 {
+        return Vec::new();
+    }
+    let count = (segments as u64).min(total / min_segment_size).max(1);
+    let base = total / count;
+    (0..count)
+        .map(|i| {
+            let start = i * base;
+            let end = if i + 1 == count {
+                total - 1
+            } else {
+                start + base - 1
+            };
+            RangeProgress {
+                start,
+                end: Some(end),
+                written: 0,
+            }
+        })
+        .collect()
+}
+
+/// Download the unfinished parts of `ranges` concurrently into a file of `total` bytes at
+/// `path`, returning the number of bytes of the file now written.
+pub(super) async fn download(
+    client: &Client,
+    source: &Source<'_>,
+    path: &Path,
+    total: u64,
+    ranges: &[RangeProgress],
+    manifest: Option<&DownloadManifest>,
+) -> Result<u64, Error> {
+    // Keep existing content: ranges that were already written are not fetched again.
+    let file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .await?;
+    file.set_len(total).await?;
+    drop(file);
+
+    let tasks = ranges
+        .iter()
+        .enumerate()
+        .map(|(index, range)| fetch_range(client, source, path, index, range, manifest));
+    let written = futures_util::future::try_join_all(tasks).await?;
+    Ok(written.iter().sum())
+}
+
+/// Fetch the rest of `range` and write it at the same offset in `path`, returning the
+/// number of bytes of the range now written.
+async fn fetch_range(
+    client: &Client,
+    source: &Source<'_>,
+    path: &Path,
+    index: usize,
+    range: &RangeProgress,
+    manifest: Option<&DownloadManifest>,
+) -> Result<u64, Error> {
+    let end = range
+        .end
+        .ok_or_else(|| Error::Other("Segmented download range has no end".into()))?;
+    let length = end - range.start + 1;
+    if range.written >= length {
+        return Ok(length);
+    }
+
+    let start = range.start + range.written;
+    let request = client
+        .request(reqwest::Method::GET, source.endpoint, &source.param_refs())?
+        .header(reqwest::header::RANGE, format!("bytes={start}-{end}"));
+    let resp = client.send_binary(request).await?;
+    if resp.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+        return Err(Error::Other(format!(
+            "Server ignored the Range request for bytes {start}-{end}"
+        )));
+    }
+
+    let mut file = tokio::fs::OpenOptions::new().write(true).open(path).await?;
+    file.seek(SeekFrom::Start(start)).await?;
+    let checkpoint = manifest.map(|manifest| Checkpoint {
+        manifest,
+        path,
+        index,
+    });
+    let written = write_body(resp, &mut file, checkpoint.as_ref(), range.written).await?;
+
+    if written != length {
+        return Err(Error::Other(format!(
+            "Segment {}-{end} returned {written} bytes, expected {length}",
+            range.start
+        )));
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(ranges: &[RangeProgress]) -> Vec<(u64, u64)> {
+        ranges.iter().map(|r| (r.start, r.end.unwrap())).collect()
+    }
+
+    #[test]
+    fn split_covers_every_byte() {
+        let ranges = split(100, 3, 10);
+        assert_eq!(bounds(&ranges), vec![(0, 32), (33, 65), (66, 99)]);
+        assert!(ranges.iter().all(|r| r.written == 0));
+    }
+
+    #[test]
+    fn split_respects_min_segment_size() {
+        assert_eq!(bounds(&split(100, 8, 40)), vec![(0, 49), (50, 99)]);
+        assert_eq!(bounds(&split(100, 8, 200)), vec![(0, 99)]);
+        assert!(split(0, 4, 1).is_empty());
+    }
+}
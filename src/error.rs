@@ -2,64 +2,104 @@
 
 use std::fmt;
 
+use url::Url;
+
 /// Subsonic API error codes as defined in the protocol specification.
 ///
 /// See <https://www.subsonic.org/pages/api.jsp> for the full list.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Codes this crate does not know are kept as [`SubsonicErrorCode::Unknown`]; new variants may
+/// be added as the specification grows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum SubsonicErrorCode {
     /// A generic error (code 0).
-    Generic = 0,
+    Generic,
     /// Required parameter is missing (code 10).
-    MissingParameter = 10,
+    MissingParameter,
     /// Incompatible Subsonic REST protocol version. Client must upgrade (code 20).
-    ClientMustUpgrade = 20,
+    ClientMustUpgrade,
     /// Incompatible Subsonic REST protocol version. Server must upgrade (code 30).
-    ServerMustUpgrade = 30,
+    ServerMustUpgrade,
     /// Wrong username or password (code 40).
-    WrongCredentials = 40,
+    WrongCredentials,
     /// Token authentication not supported for LDAP users (code 41).
-    TokenAuthNotSupported = 41,
+    TokenAuthNotSupported,
     /// Provided authentication mechanism not supported (code 42, OpenSubsonic extension).
     ///
     /// Returned when the server does not support the authentication mechanism used by the client.
-    AuthMechanismNotSupported = 42,
+    AuthMechanismNotSupported,
     /// Conflicting authentication mechanisms (code 43, OpenSubsonic extension).
     ///
     /// Returned when both API key and username-based authentication are provided.
-    ConflictingAuthentication = 43,
+    ConflictingAuthentication,
     /// Invalid API key (code 44, OpenSubsonic extension).
     ///
     /// Returned when the provided API key is not valid or has been revoked.
-    InvalidApiKey = 44,
+    InvalidApiKey,
     /// User is not authorized for the given operation (code 50).
-    NotAuthorized = 50,
+    NotAuthorized,
     /// The trial period for the Subsonic server is over (code 60).
-    TrialExpired = 60,
+    TrialExpired,
     /// The requested data was not found (code 70).
-    NotFound = 70,
+    NotFound,
+    /// A code not listed above, e.g. one added by a newer specification or a
+    /// server-specific one.
+    Unknown(i32),
 }
 
 impl SubsonicErrorCode {
-    /// Try to convert a numeric error code to a known [`SubsonicErrorCode`] variant.
-    pub fn from_code(code: i32) -> Option<Self> {
+    /// Convert a numeric error code, mapping unlisted codes to [`SubsonicErrorCode::Unknown`].
+    pub fn from_code(code: i32) -> Self {
         match code {
-            0 => Some(Self::Generic),
-            10 => Some(Self::MissingParameter),
-            20 => Some(Self::ClientMustUpgrade),
-            30 => Some(Self::ServerMustUpgrade),
-            40 => Some(Self::WrongCredentials),
-            41 => Some(Self::TokenAuthNotSupported),
-            42 => Some(Self::AuthMechanismNotSupported),
-            43 => Some(Self::ConflictingAuthentication),
-            44 => Some(Self::InvalidApiKey),
-            50 => Some(Self::NotAuthorized),
-            60 => Some(Self::TrialExpired),
-            70 => Some(Self::NotFound),
-            _ => None,
+            0 => Self::Generic,
+            10 => Self::MissingParameter,
+            20 => Self::ClientMustUpgrade,
+            30 => Self::ServerMustUpgrade,
+            40 => Self::WrongCredentials,
+            41 => Self::TokenAuthNotSupported,
+            42 => Self::AuthMechanismNotSupported,
+            43 => Self::ConflictingAuthentication,
+            44 => Self::InvalidApiKey,
+            50 => Self::NotAuthorized,
+            60 => Self::TrialExpired,
+            70 => Self::NotFound,
+            other => Self::Unknown(other),
+        }
+    }
+
+    /// The numeric error code.
+    pub fn code(self) -> i32 {
+        match self {
+            Self::Generic => 0,
+            Self::MissingParameter => 10,
+            Self::ClientMustUpgrade => 20,
+            Self::ServerMustUpgrade => 30,
+            Self::WrongCredentials => 40,
+            Self::TokenAuthNotSupported => 41,
+            Self::AuthMechanismNotSupported => 42,
+            Self::ConflictingAuthentication => 43,
+            Self::InvalidApiKey => 44,
+            Self::NotAuthorized => 50,
+            Self::TrialExpired => 60,
+            Self::NotFound => 70,
+            Self::Unknown(code) => code,
         }
     }
 }
 
+impl From<i32> for SubsonicErrorCode {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<SubsonicErrorCode> for i32 {
+    fn from(code: SubsonicErrorCode) -> Self {
+        code.code()
+    }
+}
+
 impl fmt::Display for SubsonicErrorCode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -75,6 +115,7 @@ impl fmt::Display for SubsonicErrorCode {
             Self::NotAuthorized => write!(f, "Not authorized"),
             Self::TrialExpired => write!(f, "Trial expired"),
             Self::NotFound => write!(f, "Not found"),
+            Self::Unknown(code) => write!(f, "Unknown error code {code}"),
         }
     }
 }
@@ -91,22 +132,23 @@ pub struct SubsonicApiError {
 }
 
 impl SubsonicApiError {
-    /// Try to interpret the numeric code as a known [`SubsonicErrorCode`].
-    pub fn error_code(&self) -> Option<SubsonicErrorCode> {
+    /// Interpret the numeric code as a [`SubsonicErrorCode`].
+    pub fn error_code(&self) -> SubsonicErrorCode {
         SubsonicErrorCode::from_code(self.code)
     }
 }
 
 impl fmt::Display for SubsonicApiError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let Some(known) = self.error_code() {
-            write!(
+        match self.error_code() {
+            SubsonicErrorCode::Unknown(_) => {
+                write!(f, "Subsonic API error {}: {}", self.code, self.message)
+            }
+            known => write!(
                 f,
                 "Subsonic API error {}: {} ({})",
                 self.code, self.message, known
-            )
-        } else {
-            write!(f, "Subsonic API error {}: {}", self.code, self.message)
+            ),
         }
     }
 }
@@ -124,8 +166,110 @@ pub enum Error {
     Parse(String),
     /// URL construction failed.
     Url(url::ParseError),
+    /// A local I/O operation (e.g. writing a downloaded file) failed.
+    Io(std::io::Error),
     /// Any other error.
     Other(String),
+    /// The server implements an API version older than the one that introduced the
+    /// endpoint, which has no older equivalent. Returned without making a request.
+    UnsupportedByServer {
+        /// The endpoint that was called, e.g. `getScanStatus`.
+        endpoint: String,
+        /// The API version that introduced the endpoint.
+        needs: String,
+        /// The API version the server implements.
+        has: String,
+    },
+    /// The operation was cancelled with a [`CancelToken`](crate::CancelToken).
+    Cancelled,
+    /// A request to the server failed; wraps the underlying error with where it happened.
+    ///
+    /// Errors returned by API calls are wrapped in this variant. Use [`Error::inner`] or
+    /// [`Error::api_error`] to look at the underlying error.
+    Request {
+        /// The endpoint that was called, e.g. `getAlbum`.
+        endpoint: String,
+        /// The request URL with credentials redacted, if the request was built.
+        url: Option<Url>,
+        /// The HTTP status of the response, if one was received.
+        status: Option<u16>,
+        /// The underlying error.
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Wrap `self` with the endpoint, URL and HTTP status of the request it came from.
+    ///
+    /// The URL is stored redacted. An error that already carries request context keeps it,
+    /// gaining the URL and status if it had none.
+    pub(crate) fn in_request(self, endpoint: &str, url: Option<&Url>, status: Option<u16>) -> Self {
+        let redacted = || url.map(crate::auth::redact_url);
+        match self {
+            Error::Request {
+                endpoint,
+                url: known_url,
+                status: known_status,
+                source,
+            } => Error::Request {
+                endpoint,
+                url: known_url.or_else(redacted),
+                status: known_status.or(status),
+                source,
+            },
+            other => Error::Request {
+                endpoint: endpoint.to_owned(),
+                url: redacted(),
+                status,
+                source: Box::new(other),
+            },
+        }
+    }
+
+    /// The underlying error, without request context.
+    pub fn inner(&self) -> &Error {
+        match self {
+            Error::Request { source, .. } => source.inner(),
+            other => other,
+        }
+    }
+
+    /// The endpoint of the request that failed, if known.
+    pub fn endpoint(&self) -> Option<&str> {
+        match self {
+            Error::Request { endpoint, .. } => Some(endpoint),
+            _ => None,
+        }
+    }
+
+    /// The URL of the failed request, with credentials redacted, if known.
+    pub fn url(&self) -> Option<&Url> {
+        match self {
+            Error::Request { url, .. } => url.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// The HTTP status of the failed request, if a response was received.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::Request {
+                status: Some(status),
+                ..
+            } => Some(*status),
+            Error::Request { source, .. } => source.status(),
+            Error::Http(e) => e.status().map(|s| s.as_u16()),
+            _ => None,
+        }
+    }
+
+    /// The error returned by the API, if this is one.
+    pub fn api_error(&self) -> Option<&SubsonicApiError> {
+        match self.inner() {
+            Error::Api(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for Error {
@@ -135,7 +279,26 @@ impl fmt::Display for Error {
             Error::Api(e) => write!(f, "{e}"),
             Error::Parse(msg) => write!(f, "Parse error: {msg}"),
             Error::Url(e) => write!(f, "URL error: {e}"),
+            Error::Io(e) => write!(f, "I/O error: {e}"),
             Error::Other(msg) => write!(f, "{msg}"),
+            Error::UnsupportedByServer {
+                endpoint,
+                needs,
+                has,
+            } => write!(
+                f,
+                "{endpoint} needs API version {needs}, but the server implements {has}"
+            ),
+            Error::Cancelled => f.write_str("Operation cancelled"),
+            Error::Request {
+                endpoint,
+                status: Some(status),
+                source,
+                ..
+            } => write!(f, "{endpoint} (HTTP {status}): {source}"),
+            Error::Request {
+                endpoint, source, ..
+            } => write!(f, "{endpoint}: {source}"),
         }
     }
 }
@@ -146,14 +309,24 @@ impl std::error::Error for Error {
             Error::Http(e) => Some(e),
             Error::Api(e) => Some(e),
             Error::Url(e) => Some(e),
-            Error::Parse(_) | Error::Other(_) => None,
+            Error::Io(e) => Some(e),
+            Error::Request { source, .. } => Some(source.as_ref()),
+            Error::Parse(_)
+            | Error::Other(_)
+            | Error::UnsupportedByServer { .. }
+            | Error::Cancelled => None,
         }
     }
 }
 
 impl From<reqwest::Error> for Error {
+    /// Wraps `err`, redacting credentials from the URL it reports.
     fn from(err: reqwest::Error) -> Self {
-        Error::Http(err)
+        let redacted = err.url().map(crate::auth::redact_url);
+        match redacted {
+            Some(url) => Error::Http(err.with_url(url)),
+            None => Error::Http(err),
+        }
     }
 }
 
@@ -163,8 +336,54 @@ impl From<url::ParseError> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
 impl From<serde_json::Error> for Error {
     fn from(err: serde_json::Error) -> Self {
         Error::Parse(err.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_codes_round_trip() {
+        for code in [0, 10, 20, 30, 40, 41, 42, 43, 44, 50, 60, 70, 99] {
+            assert_eq!(SubsonicErrorCode::from_code(code).code(), code);
+        }
+        assert_eq!(
+            SubsonicErrorCode::from_code(44),
+            SubsonicErrorCode::InvalidApiKey
+        );
+        assert_eq!(
+            SubsonicErrorCode::from_code(99),
+            SubsonicErrorCode::Unknown(99)
+        );
+    }
+
+    #[test]
+    fn request_context_wraps_once() {
+        let url = Url::parse("https://music.example.com/rest/getAlbum?u=admin&t=abc&s=salt&id=1")
+            .unwrap();
+        let err = Error::Parse("Missing 'album' in response".into())
+            .in_request("getAlbum", None, None)
+            .in_request("getAlbum", Some(&url), Some(200));
+        assert_eq!(err.endpoint(), Some("getAlbum"));
+        assert_eq!(
+            err.url().map(Url::as_str),
+            Some("https://music.example.com/rest/getAlbum?u=admin&t=REDACTED&s=REDACTED&id=1")
+        );
+        assert_eq!(err.status(), Some(200));
+        assert!(matches!(err.inner(), Error::Parse(_)));
+        assert_eq!(
+            err.to_string(),
+            "getAlbum (HTTP 200): Parse error: Missing 'album' in response"
+        );
+    }
+}
@@ -0,0 +1,304 @@
+//! HLS (HTTP Live Streaming) playlists.
+//!
+//! [`Client::hls_playlist`] fetches the `hls.m3u8` endpoint and parses it into an
+//! [`HlsPlaylist`]: a master playlist listing one variant per requested bitrate, or a media
+//! playlist listing the segments to play. Every URI is resolved against the server and
+//! carries the client's authentication parameters, so it can be fetched directly.
+//!
+//! ```no_run
+//! # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+//! use opensubsonic::hls::HlsPlaylist;
+//!
+//! match client.hls_playlist("vi-1", Some("1000"), None).await? {
+//!     HlsPlaylist::Master(variants) => {
+//!         for variant in variants {
+//!             println!("{:?} bps: {}", variant.bandwidth, variant.uri);
+//!         }
+//!     }
+//!     HlsPlaylist::Media(media) => {
+//!         for segment in media.segments {
+//!             println!("{:?}: {}", segment.duration, segment.uri);
+//!         }
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::time::Duration;
+
+use url::Url;
+
+use crate::Client;
+use crate::data::SongId;
+use crate::error::Error;
+
+/// Query parameters carrying authentication and client identification.
+const AUTH_PARAMS: [&str; 7] = ["u", "t", "s", "p", "apiKey", "v", "c"];
+
+/// A parsed `m3u8` playlist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HlsPlaylist {
+    /// A master playlist: the same media at different bitrates.
+    Master(Vec<HlsVariant>),
+    /// A media playlist: the segments of one rendition.
+    Media(HlsMediaPlaylist),
+}
+
+/// One rendition listed in a master playlist (`#EXT-X-STREAM-INF`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HlsVariant {
+    /// Peak bitrate in bits per second (`BANDWIDTH`).
+    pub bandwidth: Option<u64>,
+    /// Video resolution, e.g. `1280x720` (`RESOLUTION`).
+    pub resolution: Option<String>,
+    /// Codecs of the rendition (`CODECS`).
+    pub codecs: Option<String>,
+    /// The media playlist of the rendition.
+    pub uri: Url,
+}
+
+/// The segments of a media playlist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HlsMediaPlaylist {
+    /// Maximum segment duration (`#EXT-X-TARGETDURATION`).
+    pub target_duration: Option<Duration>,
+    /// Sequence number of the first segment (`#EXT-X-MEDIA-SEQUENCE`).
+    pub media_sequence: u64,
+    /// The segments, in playback order.
+    pub segments: Vec<HlsSegment>,
+    /// Whether the playlist is complete (`#EXT-X-ENDLIST`).
+    pub ended: bool,
+}
+
+/// One segment of a media playlist (`#EXTINF`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HlsSegment {
+    /// Duration of the segment.
+    pub duration: Duration,
+    /// Title of the segment, if any.
+    pub title: Option<String>,
+    /// Where to fetch the segment.
+    pub uri: Url,
+}
+
+impl HlsPlaylist {
+    /// Parse an `m3u8` playlist, resolving relative URIs against `base` (the playlist's URL).
+    ///
+    /// # Errors
+    /// Returns [`Error::Parse`] if the text is not an extended M3U playlist or contains an
+    /// invalid URI or duration.
+    pub fn parse(text: &str, base: &Url) -> Result<Self, Error> {
+        let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+        if lines.next() != Some("#EXTM3U") {
+            return Err(Error::Parse(
+                "HLS playlist does not start with #EXTM3U".into(),
+            ));
+        }
+
+        let mut variants = Vec::new();
+        let mut media = HlsMediaPlaylist {
+            target_duration: None,
+            media_sequence: 0,
+            segments: Vec::new(),
+            ended: false,
+        };
+        let mut pending_variant = None;
+        let mut pending_segment = None;
+        for line in lines {
+            if let Some(attrs) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+                pending_variant = Some(attrs);
+            } else if let Some(info) = line.strip_prefix("#EXTINF:") {
+                let (duration, title) = info.split_once(',').unwrap_or((info, ""));
+                let duration = parse_duration(duration)?;
+                let title = Some(title.trim()).filter(|t| !t.is_empty());
+                pending_segment = Some((duration, title.map(str::to_owned)));
+            } else if let Some(value) = line.strip_prefix("#EXT-X-TARGETDURATION:") {
+                media.target_duration = Some(parse_duration(value)?);
+            } else if let Some(value) = line.strip_prefix("#EXT-X-MEDIA-SEQUENCE:") {
+                media.media_sequence = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| Error::Parse(format!("Invalid HLS media sequence '{value}'")))?;
+            } else if line == "#EXT-X-ENDLIST" {
+                media.ended = true;
+            } else if !line.starts_with('#') {
+                let uri = base
+                    .join(line)
+                    .map_err(|e| Error::Parse(format!("Invalid HLS URI '{line}': {e}")))?;
+                if let Some(attrs) = pending_variant.take() {
+                    variants.push(HlsVariant::from_attributes(attrs, uri));
+                } else {
+                    let (duration, title) = pending_segment.take().unwrap_or_default();
+                    media.segments.push(HlsSegment {
+                        duration,
+                        title,
+                        uri,
+                    });
+                }
+            }
+        }
+
+        if variants.is_empty() {
+            Ok(Self::Media(media))
+        } else {
+            Ok(Self::Master(variants))
+        }
+    }
+
+    /// Add the authentication parameters of `base` to every URI on the same server that
+    /// lacks them.
+    fn inherit_auth(&mut self, base: &Url) {
+        let auth: Vec<(String, String)> = base
+            .query_pairs()
+            .filter(|(k, _)| AUTH_PARAMS.contains(&k.as_ref()))
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect();
+        let uris: Vec<&mut Url> = match self {
+            Self::Master(variants) => variants.iter_mut().map(|v| &mut v.uri).collect(),
+            Self::Media(media) => media.segments.iter_mut().map(|s| &mut s.uri).collect(),
+        };
+        for uri in uris {
+            if uri.origin() != base.origin() {
+                continue;
+            }
+            let present: Vec<String> = uri.query_pairs().map(|(k, _)| k.into_owned()).collect();
+            let mut query = uri.query_pairs_mut();
+            for (k, v) in &auth {
+                if !present.contains(k) {
+                    query.append_pair(k, v);
+                }
+            }
+        }
+    }
+}
+
+impl HlsVariant {
+    /// Build a variant from an `#EXT-X-STREAM-INF` attribute list.
+    fn from_attributes(attrs: &str, uri: Url) -> Self {
+        let mut variant = Self {
+            bandwidth: None,
+            resolution: None,
+            codecs: None,
+            uri,
+        };
+        for (name, value) in attributes(attrs) {
+            match name {
+                "BANDWIDTH" => variant.bandwidth = value.parse().ok(),
+                "RESOLUTION" => variant.resolution = Some(value.to_owned()),
+                "CODECS" => variant.codecs = Some(value.to_owned()),
+                _ => {}
+            }
+        }
+        variant
+    }
+}
+
+/// Split an attribute list (`A=1,B="x,y"`) into names and unquoted values.
+fn attributes(list: &str) -> Vec<(&str, &str)> {
+    let mut attrs = Vec::new();
+    let mut rest = list;
+    while let Some((name, tail)) = rest.split_once('=') {
+        let (value, tail) = match tail.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"').unwrap_or(quoted.len());
+                let after = quoted[end..].trim_start_matches('"');
+                (&quoted[..end], after.strip_prefix(',').unwrap_or(after))
+            }
+            None => tail.split_once(',').unwrap_or((tail, "")),
+        };
+        attrs.push((name.trim(), value));
+        rest = tail;
+    }
+    attrs
+}
+
+/// Parse a duration in (possibly fractional) seconds.
+fn parse_duration(value: &str) -> Result<Duration, Error> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .ok_or_else(|| Error::Parse(format!("Invalid HLS duration '{value}'")))
+}
+
+impl Client {
+    /// Get the HLS playlist for a video or song, parsed.
+    ///
+    /// Relative URIs are resolved against the server and every URI on the server carries the
+    /// client's authentication parameters.
+    ///
+    /// See <https://opensubsonic.netlify.app/docs/endpoints/hls/>
+    pub async fn hls_playlist(
+        &self,
+        id: impl Into<SongId>,
+        bit_rate: Option<&str>,
+        audio_track: Option<&str>,
+    ) -> Result<HlsPlaylist, Error> {
+        let id: SongId = id.into();
+        let base = self.hls_url(id.clone(), bit_rate, audio_track)?;
+        let bytes = self.hls(id, bit_rate, audio_track).await?;
+        let text = std::str::from_utf8(&bytes)
+            .map_err(|e| Error::Parse(format!("HLS playlist is not UTF-8: {e}")))?;
+        let mut playlist = HlsPlaylist::parse(text, &base)?;
+        playlist.inherit_auth(&base);
+        Ok(playlist)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> Url {
+        Url::parse("https://music.example.com/rest/hls.m3u8?id=vi-1&u=admin&t=abc&s=salt&v=1.16.1")
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_master_playlist() {
+        let text = "#EXTM3U\n\
+                    #EXT-X-STREAM-INF:BANDWIDTH=1000000,CODECS=\"avc1.4d401f,mp4a.40.2\",RESOLUTION=1280x720\n\
+                    hls.m3u8?id=vi-1&bitRate=1000\n";
+        let mut playlist = HlsPlaylist::parse(text, &base()).unwrap();
+        playlist.inherit_auth(&base());
+        let HlsPlaylist::Master(variants) = playlist else {
+            panic!("expected a master playlist");
+        };
+        assert_eq!(variants[0].bandwidth, Some(1_000_000));
+        assert_eq!(variants[0].codecs.as_deref(), Some("avc1.4d401f,mp4a.40.2"));
+        assert_eq!(variants[0].resolution.as_deref(), Some("1280x720"));
+        assert_eq!(
+            variants[0].uri.as_str(),
+            "https://music.example.com/rest/hls.m3u8?id=vi-1&bitRate=1000&u=admin&t=abc&s=salt&v=1.16.1"
+        );
+    }
+
+    #[test]
+    fn parses_media_playlist() {
+        let text = "#EXTM3U\n#EXT-X-TARGETDURATION:10\n#EXT-X-MEDIA-SEQUENCE:3\n\
+                    #EXTINF:10.5,\nstream.view?id=vi-1&hls=true&timeOffset=0\n\
+                    #EXTINF:4,End\nhttps://cdn.example.com/seg-1.ts\n#EXT-X-ENDLIST\n";
+        let mut playlist = HlsPlaylist::parse(text, &base()).unwrap();
+        playlist.inherit_auth(&base());
+        let HlsPlaylist::Media(media) = playlist else {
+            panic!("expected a media playlist");
+        };
+        assert_eq!(media.target_duration, Some(Duration::from_secs(10)));
+        assert_eq!(media.media_sequence, 3);
+        assert!(media.ended);
+        assert_eq!(media.segments[0].duration, Duration::from_millis(10_500));
+        assert!(
+            media.segments[0]
+                .uri
+                .as_str()
+                .ends_with("timeOffset=0&u=admin&t=abc&s=salt&v=1.16.1")
+        );
+        assert_eq!(media.segments[1].title.as_deref(), Some("End"));
+        assert_eq!(
+            media.segments[1].uri.as_str(),
+            "https://cdn.example.com/seg-1.ts"
+        );
+    }
+}
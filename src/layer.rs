@@ -0,0 +1,135 @@
+//! Request/response middleware.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::error::Error;
+
+/// A hook run around every HTTP request the client makes.
+///
+/// Layers are added with [`Client::with_layer`](crate::Client::with_layer) or
+/// [`ClientBuilder::layer`](crate::ClientBuilder::layer) and run in the order they were
+/// added. They see every request, including binary endpoints (`stream`, `getCoverArt`, …),
+/// after authentication parameters have been added. Use them for logging, metrics, custom
+/// headers or request rewriting.
+///
+/// ```
+/// use opensubsonic::{Auth, Client, Error, Layer};
+///
+/// struct UserAgent;
+///
+/// impl Layer for UserAgent {
+///     fn before_request(
+///         &self,
+///         _endpoint: &str,
+///         request: &mut reqwest::Request,
+///     ) -> Result<(), Error> {
+///         request
+///             .headers_mut()
+///             .insert(reqwest::header::USER_AGENT, "my-player/1.0".parse().unwrap());
+///         Ok(())
+///     }
+/// }
+///
+/// let client = Client::new("https://music.example.com", Auth::token("admin", "pass"))?
+///     .with_layer(UserAgent);
+/// # Ok::<(), opensubsonic::Error>(())
+/// ```
+pub trait Layer: Send + Sync + 'static {
+    /// Called before `request` is sent for `endpoint` (e.g. `"getAlbum"`).
+    ///
+    /// The request may be modified. Returning an error aborts the call with that error.
+    fn before_request(&self, endpoint: &str, request: &mut reqwest::Request) -> Result<(), Error> {
+        let _ = (endpoint, request);
+        Ok(())
+    }
+
+    /// Called when the response headers for `endpoint` have been received, before the body
+    /// is read or the status is checked.
+    ///
+    /// Returning an error aborts the call with that error.
+    fn after_response(&self, endpoint: &str, response: &reqwest::Response) -> Result<(), Error> {
+        let _ = (endpoint, response);
+        Ok(())
+    }
+}
+
+/// The layers of a client, in the order they run.
+#[derive(Clone, Default)]
+pub(crate) struct Layers(Vec<Arc<dyn Layer>>);
+
+impl Layers {
+    pub(crate) fn push(&mut self, layer: impl Layer) {
+        self.0.push(Arc::new(layer));
+    }
+
+    pub(crate) fn before_request(
+        &self,
+        endpoint: &str,
+        request: &mut reqwest::Request,
+    ) -> Result<(), Error> {
+        self.0
+            .iter()
+            .try_for_each(|layer| layer.before_request(endpoint, request))
+    }
+
+    pub(crate) fn after_response(
+        &self,
+        endpoint: &str,
+        response: &reqwest::Response,
+    ) -> Result<(), Error> {
+        self.0
+            .iter()
+            .try_for_each(|layer| layer.after_response(endpoint, response))
+    }
+}
+
+impl fmt::Debug for Layers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Layers({})", self.0.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::{Auth, Client};
+
+    struct Reject(Arc<Mutex<Vec<String>>>);
+
+    impl Layer for Reject {
+        fn before_request(
+            &self,
+            endpoint: &str,
+            request: &mut reqwest::Request,
+        ) -> Result<(), Error> {
+            assert!(request.url().query().unwrap().contains("id=al-1"));
+            self.0.lock().unwrap().push(endpoint.to_owned());
+            Err(Error::Other("rejected".into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn layers_see_every_request_and_can_abort() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let client = Client::new("http://127.0.0.1:1", Auth::token("u", "p"))
+            .unwrap()
+            .with_layer(Reject(seen.clone()));
+
+        assert!(matches!(
+            client.get_album("al-1").await.unwrap_err().inner(),
+            Error::Other(_)
+        ));
+        assert!(matches!(
+            client
+                .get_cover_art("al-1", None)
+                .await
+                .unwrap_err()
+                .inner(),
+            Error::Other(_)
+        ));
+        assert_eq!(*seen.lock().unwrap(), ["getAlbum", "getCoverArt"]);
+    }
+}
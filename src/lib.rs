@@ -62,32 +62,96 @@
 //! - **Media Retrieval**: `stream`, `download`, `hls`, `getCaptions`, `getCoverArt`,
 //!   `getLyrics`, `getLyricsBySongId`, `getAvatar`
 //! - **Media Annotation**: `star`, `unstar`, `setRating`, `scrobble`, `reportPlayback`
-//! - **Sharing**: `getShares`, `createShare`, `updateShare`, `deleteShare`
-//! - **Podcast**: `getPodcasts`, `getNewestPodcasts`, `getPodcastEpisode`, `refreshPodcasts`,
+//! - **Sharing** (feature `sharing`): `getShares`, `createShare`, `updateShare`, `deleteShare`
+//! - **Podcast** (feature `podcast`): `getPodcasts`, `getNewestPodcasts`, `getPodcastEpisode`, `refreshPodcasts`,
 //!   `createPodcastChannel`, `deletePodcastChannel`, `deletePodcastEpisode`,
 //!   `downloadPodcastEpisode`
-//! - **Jukebox**: `jukeboxControl`
-//! - **Internet Radio**: `getInternetRadioStations`, `createInternetRadioStation`,
+//! - **Jukebox** (feature `jukebox`): `jukeboxControl`
+//! - **Internet Radio** (feature `internet-radio`): `getInternetRadioStations`, `createInternetRadioStation`,
 //!   `updateInternetRadioStation`, `deleteInternetRadioStation`
-//! - **Chat**: `getChatMessages`, `addChatMessage`
-//! - **User Management**: `getUser`, `getUsers`, `createUser`, `updateUser`, `deleteUser`,
+//! - **Chat** (feature `chat`): `getChatMessages`, `addChatMessage`
+//! - **User Management** (feature `user-management`): `getUser`, `getUsers`, `createUser`, `updateUser`, `deleteUser`,
 //!   `changePassword`
 //! - **Bookmarks**: `getBookmarks`, `createBookmark`, `deleteBookmark`, `getPlayQueue`,
 //!   `savePlayQueue`, `getPlayQueueByIndex`, `savePlayQueueByIndex`
 //! - **Scanning**: `getScanStatus`, `startScan`
 //! - **Transcoding** (OpenSubsonic): `getTranscodeDecision`, `getTranscodeStream`
 //! - **Sonic Similarity** (OpenSubsonic): `getSonicSimilarTracks`, `findSonicPath`
+//!
+//! The sections marked with a feature, and the video endpoints (`getVideos`, `getVideoInfo`,
+//! `getCaptions`; feature `video`), are enabled by default and can be left out by disabling
+//! default features.
 
 pub mod api;
 mod auth;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod builder;
+#[cfg(feature = "cache")]
+pub mod cache;
+mod cancel;
+#[cfg(feature = "video")]
+pub mod captions;
 mod client;
+pub mod conditional;
+mod config;
 pub mod data;
+mod diff;
+#[cfg(feature = "discovery")]
+pub mod discovery;
+pub mod download;
 mod error;
+pub mod hls;
+mod layer;
+pub mod lrc;
+pub mod metrics;
+pub mod multi;
+pub mod playlist_io;
+#[cfg(feature = "podcast")]
+pub mod podcast_io;
+mod profile;
+#[cfg(feature = "internet-radio")]
+pub mod radio_io;
+mod rate_limit;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod sort;
+pub mod stats;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod trace;
+mod version;
+#[cfg(feature = "xml")]
+mod xml;
 
-pub use auth::Auth;
-pub use client::Client;
+pub use auth::{Auth, AuthProvider, Secret, redact_url};
+pub use builder::ClientBuilder;
+pub use cancel::CancelToken;
+pub use client::{Client, Format, ResponseMeta};
+pub use config::{AuthMethod, ClientConfig};
+pub use download::{
+    BatchReport, DownloadEvent, DownloadHandle, DownloadItem, DownloadManifest, DownloadOptions,
+    DownloadReport, Downloader, Verification,
+};
 pub use error::{Error, SubsonicApiError, SubsonicErrorCode};
+pub use layer::Layer;
+pub use profile::ServerProfile;
 
 // Re-export commonly used API types that live in api modules.
-pub use api::jukebox::{JukeboxAction, JukeboxResult};
-pub use api::lists::{AlbumListType, Starred2Content, StarredContent};
+pub use api::bookmarks::PlayQueueSession;
+pub use api::browsing::Discography;
+#[cfg(feature = "jukebox")]
+pub use api::jukebox::{JukeboxAction, JukeboxController, JukeboxResult};
+pub use api::lists::{
+    AlbumListParams, AlbumListType, NowPlayingChange, RandomSongsParams, StarDate, Starred2Content,
+    StarredContent, StarredItem, StarredIter,
+};
+pub use api::media_annotation::{Annotation, AnnotationReport, Annotations, StarItem};
+pub use api::media_retrieval::{MediaProbe, MediaResponse, StreamOptions};
+pub use api::playlists::PlaylistManager;
+pub use api::searching::{Search2Params, Search3Params, SearchParams};
+#[cfg(feature = "sharing")]
+pub use api::sharing::ShareOptions;
+pub use api::system::{Capabilities, KnownExtension, ServerInfo};
+#[cfg(feature = "user-management")]
+pub use api::user_management::{CreateUserParams, UpdateUserParams};
@@ -0,0 +1,206 @@
+//! LRC lyrics files.
+//!
+//! [`StructuredLyrics::from_lrc`] reads a `.lrc` file, and [`StructuredLyrics::to_lrc`]
+//! writes lyrics back out, so players can cache lyrics on disk or import local files:
+//!
+//! ```
+//! use opensubsonic::data::StructuredLyrics;
+//!
+//! let lyrics = StructuredLyrics::from_lrc("[ti:Song]\n[offset:500]\n[00:12.30]First line\n");
+//! assert!(lyrics.synced);
+//! assert_eq!(lyrics.line[0].start, Some(12_300.0));
+//! // The offset is applied when writing: the line shows 500 ms sooner.
+//! assert_eq!(lyrics.to_lrc(), "[ti:Song]\n[00:11.80]First line\n");
+//! ```
+
+use std::fmt::Write as _;
+
+use crate::data::{Line, StructuredLyrics};
+
+/// Language of lyrics read from LRC files, which do not record one.
+const UNKNOWN_LANGUAGE: &str = "und";
+
+impl StructuredLyrics {
+    /// Parse LRC text.
+    ///
+    /// Lines carry the time of each `[mm:ss.xx]` tag in milliseconds; a line with several
+    /// tags is repeated at each time, and lines are ordered by time. `[ar:]` and `[ti:]` set
+    /// the display artist and title, and `[offset:]` the [`offset`](Self::offset), which is
+    /// not applied to the lines. Word timings (`<mm:ss.xx>`) are removed from the text.
+    ///
+    /// Text without any time tag is read as unsynced lyrics, one line per line. The language
+    /// is unknown (`und`).
+    pub fn from_lrc(text: &str) -> Self {
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        let mut lyrics = Self::new(UNKNOWN_LANGUAGE);
+        let mut timed = Vec::new();
+        let mut plain = Vec::new();
+        for raw in text.lines() {
+            let (times, rest) = split_time_tags(raw.trim());
+            if !times.is_empty() {
+                let value = strip_word_times(rest);
+                timed.extend(times.into_iter().map(|t| (t, value.clone())));
+            } else if let Some((key, value)) = id_tag(rest) {
+                match key.to_ascii_lowercase().as_str() {
+                    "ar" => lyrics.display_artist = Some(value.to_owned()),
+                    "ti" => lyrics.display_title = Some(value.to_owned()),
+                    "offset" => lyrics.offset = value.parse().ok(),
+                    _ => {}
+                }
+            } else {
+                plain.push(Line::new(raw.trim_end()));
+            }
+        }
+        if timed.is_empty() {
+            // Drop the blank lines around the text, keeping those between stanzas.
+            while plain.last().is_some_and(|l| l.value.is_empty()) {
+                plain.pop();
+            }
+            let first = plain.iter().position(|l| !l.value.is_empty());
+            lyrics.line = plain.split_off(first.unwrap_or(plain.len()));
+        } else {
+            timed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            lyrics.synced = true;
+            lyrics.line = timed
+                .into_iter()
+                .map(|(start, value)| Line::new(value).with_start(start))
+                .collect();
+        }
+        lyrics
+    }
+
+    /// Write the lyrics as LRC text.
+    ///
+    /// Synced lines get a `[mm:ss.xx]` tag with the [`offset`](Self::offset) applied, so the
+    /// file needs no `[offset:]` tag; unsynced lyrics are written as plain lines. The display
+    /// artist and title become `[ar:]` and `[ti:]` tags.
+    pub fn to_lrc(&self) -> String {
+        let mut out = String::new();
+        if let Some(artist) = &self.display_artist {
+            let _ = writeln!(out, "[ar:{artist}]");
+        }
+        if let Some(title) = &self.display_title {
+            let _ = writeln!(out, "[ti:{title}]");
+        }
+        let offset = self.offset.unwrap_or(0.0);
+        for line in &self.line {
+            match line.start.filter(|_| self.synced) {
+                Some(start) => {
+                    let _ = writeln!(out, "{}{}", format_time(start - offset), line.value);
+                }
+                None => {
+                    let _ = writeln!(out, "{}", line.value);
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Split the leading `[mm:ss.xx]` tags off a line, returning their times in milliseconds.
+fn split_time_tags(mut line: &str) -> (Vec<f64>, &str) {
+    let mut times = Vec::new();
+    while let Some((tag, rest)) = line.strip_prefix('[').and_then(|l| l.split_once(']')) {
+        match parse_time(tag) {
+            Some(time) => times.push(time),
+            None => break,
+        }
+        line = rest;
+    }
+    (times, line)
+}
+
+/// Parse `[key:value]`.
+fn id_tag(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.strip_prefix('[')?.strip_suffix(']')?.split_once(':')?;
+    Some((key.trim(), value.trim()))
+}
+
+/// Parse `mm:ss`, `mm:ss.xx` or `mm:ss.xxx` (also with `:` before the fraction) into
+/// milliseconds.
+fn parse_time(value: &str) -> Option<f64> {
+    let (minutes, rest) = value.trim().split_once(':')?;
+    let (seconds, fraction) = rest.split_once(['.', ':']).unwrap_or((rest, ""));
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if !digits(minutes) || !digits(seconds) || !(fraction.is_empty() || digits(fraction)) {
+        return None;
+    }
+    let minutes: f64 = minutes.parse().ok()?;
+    let seconds: f64 = seconds.parse().ok()?;
+    // `.5` is half a second, `.05` and `.050` are 50 ms.
+    let fraction: f64 = format!("0.{fraction}").parse().ok()?;
+    Some(((minutes * 60.0 + seconds + fraction) * 1000.0).round())
+}
+
+/// Remove enhanced LRC word timings (`<mm:ss.xx>`) from a line.
+fn strip_word_times(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(open) = rest.find('<') {
+        let timing = rest[open + 1..]
+            .split_once('>')
+            .filter(|(tag, _)| parse_time(tag).is_some());
+        match timing {
+            Some((_, after)) => {
+                out.push_str(&rest[..open]);
+                rest = after;
+            }
+            None => {
+                out.push_str(&rest[..=open]);
+                rest = &rest[open + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out.trim().to_owned()
+}
+
+/// Format milliseconds as `[mm:ss.xx]`, clamping negative times to zero.
+fn format_time(millis: f64) -> String {
+    let centis = (millis.max(0.0) / 10.0).round() as u64;
+    format!(
+        "[{:02}:{:02}.{:02}]",
+        centis / 6000,
+        centis / 100 % 60,
+        centis % 100
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_and_writes_lrc() {
+        let text = "\u{feff}[ar:Queen]\n[length:05:55]\n[offset:-250]\n\
+                    [00:49.5][02:10.00]Mama<00:50.10> just killed a man\n\
+                    [00:05.123]Is this the real life?\n";
+        let lyrics = StructuredLyrics::from_lrc(text);
+        assert!(lyrics.synced);
+        assert_eq!(lyrics.display_artist.as_deref(), Some("Queen"));
+        assert_eq!(lyrics.offset, Some(-250.0));
+        let lines: Vec<(f64, &str)> = lyrics
+            .line
+            .iter()
+            .map(|l| (l.start.unwrap(), l.value.as_str()))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                (5123.0, "Is this the real life?"),
+                (49_500.0, "Mama just killed a man"),
+                (130_000.0, "Mama just killed a man"),
+            ]
+        );
+        assert_eq!(
+            lyrics.to_lrc(),
+            "[ar:Queen]\n[00:05.37]Is this the real life?\n\
+             [00:49.75]Mama just killed a man\n[02:10.25]Mama just killed a man\n"
+        );
+
+        let plain = StructuredLyrics::from_lrc("\nFirst verse\n\nSecond verse\n\n");
+        assert!(!plain.synced);
+        assert_eq!(plain.line.len(), 3);
+        assert_eq!(plain.to_lrc(), "First verse\n\nSecond verse\n");
+    }
+}
@@ -0,0 +1,227 @@
+//! Request telemetry.
+//!
+//! Attach a [`MetricsSink`] with [`Client::with_metrics`](crate::Client::with_metrics) or
+//! [`ClientBuilder::metrics`](crate::ClientBuilder::metrics) to observe every HTTP request the
+//! client makes. [`CountingMetrics`] keeps simple totals:
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use opensubsonic::metrics::CountingMetrics;
+//! use opensubsonic::{Auth, Client};
+//!
+//! let metrics = Arc::new(CountingMetrics::new());
+//! let client = Client::new("https://music.example.com", Auth::token("admin", "pass"))?
+//!     .with_metrics(metrics.clone());
+//! // … use the client …
+//! let totals = metrics.snapshot();
+//! println!("{} requests, {} bytes received", totals.requests, totals.bytes_received);
+//! # Ok::<(), opensubsonic::Error>(())
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::error::Error;
+
+/// Receives telemetry for every HTTP request a client makes.
+///
+/// All methods default to doing nothing. They are called on the request path, so they should
+/// return quickly.
+pub trait MetricsSink: Send + Sync + 'static {
+    /// A request to `endpoint` (e.g. `"getAlbum"`) is about to be sent.
+    fn request_started(&self, endpoint: &str) {
+        let _ = endpoint;
+    }
+
+    /// The response headers for `endpoint` were received.
+    ///
+    /// This includes HTTP error statuses; Subsonic API errors (`status="failed"` envelopes)
+    /// are successful requests at this level.
+    fn request_completed(&self, endpoint: &str, stats: &RequestStats) {
+        let _ = (endpoint, stats);
+    }
+
+    /// The request to `endpoint` failed after `duration` without a response (connection
+    /// error, timeout, or a [`Layer`](crate::Layer) aborting it before it was sent).
+    fn request_failed(&self, endpoint: &str, duration: Duration, error: &Error) {
+        let _ = (endpoint, duration, error);
+    }
+}
+
+impl<T: MetricsSink> MetricsSink for Arc<T> {
+    fn request_started(&self, endpoint: &str) {
+        (**self).request_started(endpoint);
+    }
+
+    fn request_completed(&self, endpoint: &str, stats: &RequestStats) {
+        (**self).request_completed(endpoint, stats);
+    }
+
+    fn request_failed(&self, endpoint: &str, duration: Duration, error: &Error) {
+        (**self).request_failed(endpoint, duration, error);
+    }
+}
+
+/// Details of a completed request.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct RequestStats {
+    /// HTTP status code.
+    pub status: u16,
+    /// Time from sending the request to receiving the response headers.
+    pub duration: Duration,
+    /// Size of the request body (the parameters, when API calls use `formPost`).
+    pub bytes_sent: u64,
+    /// Size of the response body, from `Content-Length`, if the server sent one.
+    pub bytes_received: Option<u64>,
+}
+
+/// A [`MetricsSink`] that ignores everything; the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopMetrics;
+
+impl MetricsSink for NoopMetrics {}
+
+/// A [`MetricsSink`] keeping running totals, read with [`CountingMetrics::snapshot`].
+#[derive(Debug, Default)]
+pub struct CountingMetrics {
+    requests: AtomicU64,
+    completed: AtomicU64,
+    failed: AtomicU64,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    duration_us: AtomicU64,
+}
+
+/// Totals recorded by a [`CountingMetrics`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    /// Requests started.
+    pub requests: u64,
+    /// Requests that received a response.
+    pub completed: u64,
+    /// Requests that failed without a response.
+    pub failed: u64,
+    /// Request body bytes sent.
+    pub bytes_sent: u64,
+    /// Response body bytes announced by `Content-Length`.
+    pub bytes_received: u64,
+    /// Total time spent waiting for responses, completed and failed.
+    pub total_duration: Duration,
+}
+
+impl CountingMetrics {
+    /// Counters starting at zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The totals so far.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            requests: self.requests.load(Ordering::Relaxed),
+            completed: self.completed.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            total_duration: Duration::from_micros(self.duration_us.load(Ordering::Relaxed)),
+        }
+    }
+
+    fn add_duration(&self, duration: Duration) {
+        let micros = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+        self.duration_us.fetch_add(micros, Ordering::Relaxed);
+    }
+}
+
+impl MetricsSink for CountingMetrics {
+    fn request_started(&self, _endpoint: &str) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn request_completed(&self, _endpoint: &str, stats: &RequestStats) {
+        self.completed.fetch_add(1, Ordering::Relaxed);
+        self.bytes_sent
+            .fetch_add(stats.bytes_sent, Ordering::Relaxed);
+        self.bytes_received
+            .fetch_add(stats.bytes_received.unwrap_or(0), Ordering::Relaxed);
+        self.add_duration(stats.duration);
+    }
+
+    fn request_failed(&self, _endpoint: &str, duration: Duration, _error: &Error) {
+        self.failed.fetch_add(1, Ordering::Relaxed);
+        self.add_duration(duration);
+    }
+}
+
+/// The metrics sink of a client.
+#[derive(Clone)]
+pub(crate) struct Metrics(Arc<dyn MetricsSink>);
+
+impl Metrics {
+    pub(crate) fn new(sink: impl MetricsSink) -> Self {
+        Self(Arc::new(sink))
+    }
+
+    pub(crate) fn sink(&self) -> &dyn MetricsSink {
+        &*self.0
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new(NoopMetrics)
+    }
+}
+
+impl fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Metrics")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Auth, Client};
+
+    #[tokio::test]
+    async fn counts_completed_and_failed_requests() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 4096];
+            let _ = socket.read(&mut buf).await.unwrap();
+            let body = r#"{"subsonic-response":{"status":"ok","version":"1.16.1"}}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+                 Connection: close\r\n\r\n{body}",
+                body.len()
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        let metrics = Arc::new(CountingMetrics::new());
+        let client = Client::new(&format!("http://{addr}"), Auth::token("u", "p"))
+            .unwrap()
+            .with_metrics(metrics.clone());
+        client.ping().await.unwrap();
+
+        let offline = Client::new("http://127.0.0.1:1", Auth::token("u", "p"))
+            .unwrap()
+            .with_metrics(metrics.clone());
+        assert!(offline.ping().await.is_err());
+
+        let totals = metrics.snapshot();
+        assert_eq!(totals.requests, 2);
+        assert_eq!(totals.completed, 1);
+        assert_eq!(totals.failed, 1);
+        assert_eq!(totals.bytes_received, 56);
+        assert_eq!(totals.bytes_sent, 0);
+    }
+}
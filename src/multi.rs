@@ -0,0 +1,255 @@
+//! Federating several servers.
+//!
+//! A [`MultiClient`] holds one [`Client`] per server under a name of your choosing. Browse
+//! and search calls are sent to every server at once and their results merged, each item
+//! [`Tagged`] with the server it came from; calls about one item (streaming, downloading,
+//! fetching an album) are routed back to that server. A server that fails does not fail
+//! the whole call: its error is reported next to the other servers' results.
+//!
+//! ```no_run
+//! # async fn example(home: opensubsonic::Client, friend: opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+//! use opensubsonic::Search3Params;
+//! use opensubsonic::multi::MultiClient;
+//!
+//! let multi = MultiClient::new().server("home", home).server("friend", friend);
+//! let results = multi.search3(&Search3Params::new("love")).await;
+//! for song in &results.song {
+//!     println!("[{}] {}", song.server, song.title);
+//!     let url = multi.stream_url(song, None, None)?;
+//! }
+//! for failure in &results.errors {
+//!     eprintln!("{} failed: {}", failure.server, failure.item);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures_util::future::join_all;
+use url::Url;
+
+use crate::Client;
+use crate::api::lists::{AlbumListParams, AlbumListType};
+use crate::api::searching::Search3Params;
+use crate::data::{AlbumId3, AlbumWithSongsId3, ArtistId3, Child};
+use crate::error::Error;
+
+/// An item together with the name of the server it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tagged<T> {
+    /// Name of the server, as given to [`MultiClient::server`].
+    pub server: Arc<str>,
+    /// The item.
+    pub item: T,
+}
+
+impl<T> Deref for Tagged<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.item
+    }
+}
+
+/// Results merged from every server, with the errors of the servers that failed.
+#[derive(Debug)]
+pub struct Merged<T> {
+    /// The items, grouped by server in the order the servers were added.
+    pub items: Vec<Tagged<T>>,
+    /// One error per failed server.
+    pub errors: Vec<Tagged<Error>>,
+}
+
+/// `search3` results merged from every server.
+#[derive(Debug, Default)]
+pub struct MultiSearchResult {
+    /// Matching artists.
+    pub artist: Vec<Tagged<ArtistId3>>,
+    /// Matching albums.
+    pub album: Vec<Tagged<AlbumId3>>,
+    /// Matching songs.
+    pub song: Vec<Tagged<Child>>,
+    /// One error per failed server.
+    pub errors: Vec<Tagged<Error>>,
+}
+
+/// A client for several servers at once.
+#[derive(Debug, Clone, Default)]
+pub struct MultiClient {
+    servers: Vec<(Arc<str>, Client)>,
+}
+
+impl MultiClient {
+    /// Create a client without servers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a server under `name`, replacing any server of the same name.
+    #[must_use]
+    pub fn server(mut self, name: &str, client: Client) -> Self {
+        self.servers.retain(|(n, _)| &**n != name);
+        self.servers.push((Arc::from(name), client));
+        self
+    }
+
+    /// Names of the servers, in the order they were added.
+    pub fn server_names(&self) -> impl Iterator<Item = &str> {
+        self.servers.iter().map(|(name, _)| &**name)
+    }
+
+    /// The client of the server called `name`.
+    pub fn client(&self, name: &str) -> Option<&Client> {
+        self.servers
+            .iter()
+            .find(|(n, _)| &**n == name)
+            .map(|(_, client)| client)
+    }
+
+    /// The client of the server `item` came from.
+    ///
+    /// # Errors
+    /// Returns [`Error::Other`] if no server has the item's server name.
+    pub fn route<T>(&self, item: &Tagged<T>) -> Result<&Client, Error> {
+        self.client(&item.server)
+            .ok_or_else(|| Error::Other(format!("Unknown server '{}'", item.server)))
+    }
+
+    /// Get all artists (ID3-based) of every server.
+    pub async fn get_artists(&self) -> Merged<ArtistId3> {
+        self.fan_out(|client| async move {
+            let artists = client.get_artists(None).await?;
+            Ok(artists.index.into_iter().flat_map(|index| index.artist))
+        })
+        .await
+    }
+
+    /// Get an album list (ID3-based) from every server.
+    pub async fn get_album_list2(
+        &self,
+        list_type: AlbumListType,
+        params: &AlbumListParams,
+    ) -> Merged<AlbumId3> {
+        self.fan_out(|client| client.get_album_list2_with(list_type.clone(), params))
+            .await
+    }
+
+    /// Search every server with `search3`.
+    pub async fn search3(&self, params: &Search3Params) -> MultiSearchResult {
+        let results = join_all(
+            self.servers
+                .iter()
+                .map(|(_, client)| client.search3_with(params)),
+        )
+        .await;
+        let mut merged = MultiSearchResult::default();
+        for ((server, _), result) in self.servers.iter().zip(results) {
+            match result {
+                Ok(found) => {
+                    merged
+                        .artist
+                        .extend(found.artist.into_iter().map(|a| tag(server, a)));
+                    merged
+                        .album
+                        .extend(found.album.into_iter().map(|a| tag(server, a)));
+                    merged
+                        .song
+                        .extend(found.song.into_iter().map(|s| tag(server, s)));
+                }
+                Err(e) => merged.errors.push(tag(server, e)),
+            }
+        }
+        merged
+    }
+
+    /// Get an album with its songs from the server it came from.
+    pub async fn get_album(
+        &self,
+        album: &Tagged<AlbumId3>,
+    ) -> Result<Tagged<AlbumWithSongsId3>, Error> {
+        let item = self.route(album)?.get_album(&album.id).await?;
+        Ok(tag(&album.server, item))
+    }
+
+    /// Build a streaming URL for a song on the server it came from.
+    pub fn stream_url(
+        &self,
+        song: &Tagged<Child>,
+        max_bit_rate: Option<i32>,
+        format: Option<&str>,
+    ) -> Result<Url, Error> {
+        self.route(song)?.stream_url(&song.id, max_bit_rate, format)
+    }
+
+    /// Build a download URL for a song on the server it came from.
+    pub fn download_url(&self, song: &Tagged<Child>) -> Result<Url, Error> {
+        self.route(song)?.download_url(&song.id)
+    }
+
+    /// Download a song from the server it came from.
+    pub async fn download(&self, song: &Tagged<Child>) -> Result<Bytes, Error> {
+        self.route(song)?.download(&song.id).await
+    }
+
+    /// Run `call` against every server concurrently and merge the items it returns.
+    async fn fan_out<'a, F, Fut, I>(&'a self, call: F) -> Merged<I::Item>
+    where
+        F: Fn(&'a Client) -> Fut,
+        Fut: Future<Output = Result<I, Error>>,
+        I: IntoIterator,
+    {
+        let results = join_all(self.servers.iter().map(|(_, client)| call(client))).await;
+        let mut merged = Merged {
+            items: Vec::new(),
+            errors: Vec::new(),
+        };
+        for ((server, _), result) in self.servers.iter().zip(results) {
+            match result {
+                Ok(items) => merged
+                    .items
+                    .extend(items.into_iter().map(|item| tag(server, item))),
+                Err(e) => merged.errors.push(tag(server, e)),
+            }
+        }
+        merged
+    }
+}
+
+/// Tag `item` as coming from `server`.
+fn tag<T>(server: &Arc<str>, item: T) -> Tagged<T> {
+    Tagged {
+        server: server.clone(),
+        item,
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::testing::MockServer;
+
+    #[tokio::test]
+    async fn merges_and_routes() {
+        let home = MockServer::start().await;
+        let friend = MockServer::start().await;
+        friend.fail_with("search3", 0, "Search is down").await;
+        let multi = MultiClient::new()
+            .server("home", home.client())
+            .server("friend", friend.client());
+
+        let artists = multi.get_artists().await;
+        assert_eq!(artists.items.len(), 2);
+        assert_eq!(&*artists.items[1].server, "friend");
+
+        let results = multi.search3(&Search3Params::new("queen")).await;
+        assert_eq!(results.song.len(), 1);
+        assert_eq!(&*results.errors[0].server, "friend");
+
+        let url = multi.stream_url(&results.song[0], None, None).unwrap();
+        assert!(url.as_str().starts_with(&home.uri()));
+    }
+}
@@ -0,0 +1,488 @@
+//! Playlist import and export (M3U, M3U8 and XSPF).
+//!
+//! [`Client::export_playlist`] renders a [`PlaylistWithSongs`] as a playlist file whose entries
+//! point at stream URLs or file paths. [`Client::import_playlist`] parses such a file and
+//! resolves every entry to a song on the server, returning an [`ImportReport`]:
+//!
+//! ```no_run
+//! # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+//! use opensubsonic::playlist_io::PlaylistFormat;
+//!
+//! let text = std::fs::read_to_string("road-trip.m3u8")?;
+//! let report = client.import_playlist(&text, PlaylistFormat::M3u8).await?;
+//! for entry in report.unmatched() {
+//!     eprintln!("not found: {}", entry.display_name());
+//! }
+//! let ids = report.matched_ids();
+//! client.create_playlist(None, Some("Road trip"), &ids).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use url::Url;
+
+use crate::Client;
+use crate::api::searching::Search3Params;
+use crate::data::{Child, PlaylistWithSongs};
+use crate::error::Error;
+
+/// Number of `search3` candidates considered when resolving an entry.
+const SEARCH_CANDIDATES: i32 = 20;
+/// Maximum duration difference (seconds) for a song to still count as the same track.
+const DURATION_TOLERANCE: i64 = 3;
+
+/// A playlist file format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaylistFormat {
+    /// Extended M3U (`.m3u`).
+    M3u,
+    /// Extended M3U in UTF-8 (`.m3u8`).
+    M3u8,
+    /// XML Shareable Playlist Format (`.xspf`). Importing requires the `xml` feature.
+    Xspf,
+}
+
+impl PlaylistFormat {
+    /// Guess the format from a file extension (`m3u`, `m3u8` or `xspf`).
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "m3u" => Some(Self::M3u),
+            "m3u8" => Some(Self::M3u8),
+            "xspf" => Some(Self::Xspf),
+            _ => None,
+        }
+    }
+}
+
+/// Where exported entries point to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryLocation {
+    /// Authenticated `stream` URLs, playable by any media player.
+    StreamUrl,
+    /// The song's server-side file path ([`Child::path`]) below the given root directory.
+    /// Songs without a path fall back to their stream URL.
+    FilePath(String),
+}
+
+/// One entry read from a playlist file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlaylistEntry {
+    /// URL or file path of the entry.
+    pub location: Option<String>,
+    /// Track title, if the file provides one.
+    pub title: Option<String>,
+    /// Track artist, if the file provides one.
+    pub artist: Option<String>,
+    /// Album name, if the file provides one.
+    pub album: Option<String>,
+    /// Duration in seconds, if the file provides one.
+    pub duration: Option<i64>,
+}
+
+impl PlaylistEntry {
+    /// A human-readable name for the entry: `Artist - Title`, the title, or the location.
+    pub fn display_name(&self) -> String {
+        match (&self.artist, &self.title, &self.location) {
+            (Some(artist), Some(title), _) => format!("{artist} - {title}"),
+            (None, Some(title), _) => title.clone(),
+            (_, None, Some(location)) => location.clone(),
+            (_, None, None) => String::new(),
+        }
+    }
+
+    /// The title to search for: the given title, or the file name without extension.
+    fn search_title(&self) -> Option<String> {
+        if let Some(title) = &self.title {
+            return Some(title.clone());
+        }
+        let location = self.location.as_deref()?;
+        let name = location.rsplit(['/', '\\']).next()?;
+        let stem = Path::new(name).file_stem()?.to_str()?;
+        // Strip a leading track number such as "01 - " or "01. ".
+        let stem = stem
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .trim_start_matches([' ', '-', '.', '_']);
+        (!stem.is_empty()).then(|| stem.to_owned())
+    }
+
+    /// The song ID if the location is a `stream` or `download` URL of a Subsonic server.
+    fn stream_id(&self) -> Option<String> {
+        let url = Url::parse(self.location.as_deref()?).ok()?;
+        let endpoint = url.path_segments()?.next_back()?;
+        let endpoint = endpoint.strip_suffix(".view").unwrap_or(endpoint);
+        if endpoint != "stream" && endpoint != "download" {
+            return None;
+        }
+        url.query_pairs()
+            .find(|(k, _)| k == "id")
+            .map(|(_, v)| v.into_owned())
+    }
+}
+
+/// How an imported entry was resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryMatch {
+    /// The entry as read from the file.
+    pub entry: PlaylistEntry,
+    /// The matching song on the server, if one was found.
+    pub song: Option<Child>,
+}
+
+/// Result of [`Client::import_playlist`]: every entry with the song it resolved to.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportReport {
+    /// Playlist title, if the file has one.
+    pub title: Option<String>,
+    /// All entries, in file order.
+    pub entries: Vec<EntryMatch>,
+}
+
+impl ImportReport {
+    /// IDs of the matched songs, in file order.
+    pub fn matched_ids(&self) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter_map(|m| m.song.as_ref().map(|s| s.id.as_str()))
+            .collect()
+    }
+
+    /// Entries that could not be resolved.
+    pub fn unmatched(&self) -> impl Iterator<Item = &PlaylistEntry> {
+        self.entries
+            .iter()
+            .filter(|m| m.song.is_none())
+            .map(|m| &m.entry)
+    }
+}
+
+impl Client {
+    /// Render `playlist` as a playlist file in the given format.
+    ///
+    /// # Errors
+    /// Returns [`Error::Url`] if a stream URL cannot be built.
+    pub fn export_playlist(
+        &self,
+        playlist: &PlaylistWithSongs,
+        format: PlaylistFormat,
+        location: &EntryLocation,
+    ) -> Result<String, Error> {
+        let mut locations = Vec::with_capacity(playlist.entry.len());
+        for song in &playlist.entry {
+            let path = match location {
+                EntryLocation::FilePath(root) => song.path.as_deref().map(|p| join(root, p)),
+                EntryLocation::StreamUrl => None,
+            };
+            match path {
+                Some(path) => locations.push(path),
+                None => locations.push(self.stream_url(&song.id, None, None)?.to_string()),
+            }
+        }
+        Ok(match format {
+            PlaylistFormat::M3u | PlaylistFormat::M3u8 => write_m3u(playlist, &locations),
+            PlaylistFormat::Xspf => write_xspf(playlist, &locations),
+        })
+    }
+
+    /// Parse a playlist file and resolve its entries against the server.
+    ///
+    /// Entries pointing at a `stream`/`download` URL are looked up by ID; all others are
+    /// searched with `search3` by title (or file name) and matched on title, artist and
+    /// duration.
+    ///
+    /// # Errors
+    /// Returns [`Error::Parse`] if the file is malformed (or is XSPF without the `xml`
+    /// feature), or any error from the lookups.
+    pub async fn import_playlist(
+        &self,
+        text: &str,
+        format: PlaylistFormat,
+    ) -> Result<ImportReport, Error> {
+        let (title, entries) = parse_playlist(text, format)?;
+        let mut report = ImportReport {
+            title,
+            entries: Vec::with_capacity(entries.len()),
+        };
+        for entry in entries {
+            let song = self.resolve_entry(&entry).await?;
+            report.entries.push(EntryMatch { entry, song });
+        }
+        Ok(report)
+    }
+
+    async fn resolve_entry(&self, entry: &PlaylistEntry) -> Result<Option<Child>, Error> {
+        if let Some(id) = entry.stream_id() {
+            match self.get_song(&id).await {
+                Ok(song) => return Ok(Some(song)),
+                // Not on this server (anymore): fall back to searching.
+                Err(e) if e.api_error().is_some() => {}
+                Err(e) => return Err(e),
+            }
+        }
+        let Some(title) = entry.search_title() else {
+            return Ok(None);
+        };
+        let params = Search3Params::new(&title)
+            .artist_count(0)
+            .album_count(0)
+            .song_count(SEARCH_CANDIDATES);
+        let candidates = self.search3_with(&params).await?.song;
+        Ok(best_match(entry, &title, candidates))
+    }
+}
+
+/// Pick the candidate with the same title that best agrees with the entry's other fields.
+fn best_match(entry: &PlaylistEntry, title: &str, candidates: Vec<Child>) -> Option<Child> {
+    let same = |a: &str, b: &str| a.trim().eq_ignore_ascii_case(b.trim());
+    candidates
+        .into_iter()
+        .filter(|song| same(&song.title, title))
+        .filter(|song| match (entry.duration, song.duration) {
+            (Some(a), Some(b)) if a > 0 => (a - b).abs() <= DURATION_TOLERANCE,
+            _ => true,
+        })
+        .max_by_key(|song| {
+            let artist = match (&entry.artist, &song.artist) {
+                (Some(a), Some(b)) => same(a, b),
+                _ => false,
+            };
+            let album = match (&entry.album, &song.album) {
+                (Some(a), Some(b)) => same(a, b),
+                _ => false,
+            };
+            (artist, album)
+        })
+}
+
+fn join(root: &str, path: &str) -> String {
+    if root.is_empty() {
+        return path.to_owned();
+    }
+    format!(
+        "{}/{}",
+        root.trim_end_matches('/'),
+        path.trim_start_matches('/')
+    )
+}
+
+// ── Writers ─────────────────────────────────────────────────────────────────
+
+fn write_m3u(playlist: &PlaylistWithSongs, locations: &[String]) -> String {
+    let mut out = String::from("#EXTM3U\n");
+    let _ = writeln!(out, "#PLAYLIST:{}", playlist.name);
+    for (song, location) in playlist.entry.iter().zip(locations) {
+        let duration = song.duration.unwrap_or(-1);
+        match &song.artist {
+            Some(artist) => {
+                let _ = writeln!(out, "#EXTINF:{duration},{artist} - {}", song.title);
+            }
+            None => {
+                let _ = writeln!(out, "#EXTINF:{duration},{}", song.title);
+            }
+        }
+        out.push_str(location);
+        out.push('\n');
+    }
+    out
+}
+
+fn write_xspf(playlist: &PlaylistWithSongs, locations: &[String]) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\">\n",
+    );
+    let _ = writeln!(out, "  <title>{}</title>", escape(&playlist.name));
+    out.push_str("  <trackList>\n");
+    for (song, location) in playlist.entry.iter().zip(locations) {
+        out.push_str("    <track>\n");
+        let _ = writeln!(out, "      <location>{}</location>", escape(location));
+        let _ = writeln!(out, "      <title>{}</title>", escape(&song.title));
+        if let Some(artist) = &song.artist {
+            let _ = writeln!(out, "      <creator>{}</creator>", escape(artist));
+        }
+        if let Some(album) = &song.album {
+            let _ = writeln!(out, "      <album>{}</album>", escape(album));
+        }
+        if let Some(duration) = song.duration {
+            let _ = writeln!(out, "      <duration>{}</duration>", duration * 1000);
+        }
+        out.push_str("    </track>\n");
+    }
+    out.push_str("  </trackList>\n</playlist>\n");
+    out
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// ── Readers ─────────────────────────────────────────────────────────────────
+
+/// Parse a playlist file into its title and entries.
+fn parse_playlist(
+    text: &str,
+    format: PlaylistFormat,
+) -> Result<(Option<String>, Vec<PlaylistEntry>), Error> {
+    let text = text.trim_start_matches('\u{feff}');
+    match format {
+        PlaylistFormat::M3u | PlaylistFormat::M3u8 => Ok(parse_m3u(text)),
+        #[cfg(feature = "xml")]
+        PlaylistFormat::Xspf => parse_xspf(text),
+        #[cfg(not(feature = "xml"))]
+        PlaylistFormat::Xspf => Err(Error::Parse(
+            "Importing XSPF playlists requires the `xml` feature".into(),
+        )),
+    }
+}
+
+fn parse_m3u(text: &str) -> (Option<String>, Vec<PlaylistEntry>) {
+    let mut title = None;
+    let mut entries = Vec::new();
+    let mut pending = PlaylistEntry::default();
+    for line in text.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix("#PLAYLIST:") {
+            title = Some(name.trim().to_owned());
+        } else if let Some(info) = line.strip_prefix("#EXTINF:") {
+            let (duration, name) = info.split_once(',').unwrap_or((info, ""));
+            pending.duration = duration.trim().parse().ok().filter(|d| *d >= 0);
+            match name.split_once(" - ") {
+                Some((artist, track)) => {
+                    pending.artist = Some(artist.trim().to_owned());
+                    pending.title = Some(track.trim().to_owned());
+                }
+                None if !name.trim().is_empty() => pending.title = Some(name.trim().to_owned()),
+                None => {}
+            }
+        } else if !line.is_empty() && !line.starts_with('#') {
+            pending.location = Some(line.to_owned());
+            entries.push(std::mem::take(&mut pending));
+        }
+    }
+    (title, entries)
+}
+
+#[cfg(feature = "xml")]
+fn parse_xspf(text: &str) -> Result<(Option<String>, Vec<PlaylistEntry>), Error> {
+    use quick_xml::Reader;
+    use quick_xml::events::Event;
+
+    use crate::xml::{resolve_reference, xml_error};
+
+    let mut reader = Reader::from_str(text);
+    let mut title = None;
+    let mut entries = Vec::new();
+    let mut track: Option<PlaylistEntry> = None;
+    let mut content = String::new();
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(e) => {
+                content.clear();
+                if e.local_name().as_ref() == b"track" {
+                    track = Some(PlaylistEntry::default());
+                }
+            }
+            Event::Text(t) => content.push_str(&t.decode().map_err(xml_error)?),
+            Event::CData(t) => content.push_str(&t.decode().map_err(xml_error)?),
+            Event::GeneralRef(r) => content.push_str(&resolve_reference(&r)?),
+            Event::End(e) => {
+                let value = content.trim().to_owned();
+                content.clear();
+                match (&mut track, e.local_name().as_ref()) {
+                    (Some(_), b"track") => entries.extend(track.take()),
+                    (Some(entry), b"location") => entry.location = Some(value),
+                    (Some(entry), b"title") => entry.title = Some(value),
+                    (Some(entry), b"creator") => entry.artist = Some(value),
+                    (Some(entry), b"album") => entry.album = Some(value),
+                    (Some(entry), b"duration") => {
+                        entry.duration = value.parse::<i64>().ok().map(|ms| ms / 1000);
+                    }
+                    (None, b"title") => title = Some(value),
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok((title, entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_extended_m3u() {
+        let text = "\u{feff}#EXTM3U\r\n#PLAYLIST:Mix\r\n\
+                    #EXTINF:215,Queen - Bohemian Rhapsody\r\n\
+                    /music/Queen/01 - Bohemian Rhapsody.flac\r\n\
+                    /music/Unknown/03. Intro.mp3\r\n";
+        let (title, entries) = parse_playlist(text, PlaylistFormat::M3u8).unwrap();
+        assert_eq!(title.as_deref(), Some("Mix"));
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].artist.as_deref(), Some("Queen"));
+        assert_eq!(entries[0].title.as_deref(), Some("Bohemian Rhapsody"));
+        assert_eq!(entries[0].duration, Some(215));
+        assert_eq!(entries[1].title, None);
+        assert_eq!(entries[1].search_title().as_deref(), Some("Intro"));
+    }
+
+    #[test]
+    fn recognises_stream_urls() {
+        let entry = PlaylistEntry {
+            location: Some("https://music.example.com/rest/stream?u=a&id=tr-1&v=1.16.1".into()),
+            ..PlaylistEntry::default()
+        };
+        assert_eq!(entry.stream_id().as_deref(), Some("tr-1"));
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn unknown_ids_fall_back_to_search() {
+        let server = crate::testing::MockServer::start().await;
+        server.fail_with("getSong", 70, "Song not found").await;
+        let text = "#EXTM3U\n#EXTINF:354,Queen - Bohemian Rhapsody\n\
+                    https://old.example.com/rest/stream?id=gone&u=a\n";
+        let report = server
+            .client()
+            .import_playlist(text, PlaylistFormat::M3u8)
+            .await
+            .unwrap();
+        let song = report.entries[0].song.as_ref().unwrap();
+        assert_eq!(song.id, "tr-1");
+        assert_eq!(server.calls("getSong").await, 1);
+        assert_eq!(server.calls("search3").await, 1);
+    }
+
+    #[cfg(feature = "xml")]
+    #[test]
+    fn xspf_round_trips_through_writer() {
+        let playlist: PlaylistWithSongs = serde_json::from_value(serde_json::json!({
+            "id": "1",
+            "name": "Rock & Roll",
+            "entry": [{"id": "s1", "isDir": false, "title": "<Intro>", "artist": "AC/DC", "duration": 61}],
+        }))
+        .unwrap();
+        let xspf = write_xspf(&playlist, &["/music/intro.flac".to_owned()]);
+        let (title, entries) = parse_playlist(&xspf, PlaylistFormat::Xspf).unwrap();
+        assert_eq!(title.as_deref(), Some("Rock & Roll"));
+        assert_eq!(entries[0].title.as_deref(), Some("<Intro>"));
+        assert_eq!(entries[0].artist.as_deref(), Some("AC/DC"));
+        assert_eq!(entries[0].duration, Some(61));
+        assert_eq!(entries[0].location.as_deref(), Some("/music/intro.flac"));
+    }
+}
@@ -0,0 +1,231 @@
+//! Podcast subscription import and export (OPML).
+//!
+//! [`Client::export_podcasts_opml`] writes the server's podcast channels as an OPML file,
+//! which other podcast apps can import. [`Client::import_podcasts_opml`] subscribes to the
+//! feeds of such a file, skipping those already subscribed, and reports the outcome of each
+//! feed. Reading OPML requires the `xml` feature.
+//!
+//! ```no_run
+//! # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+//! let text = std::fs::read_to_string("subscriptions.opml")?;
+//! let report = client.import_podcasts_opml(&text).await?;
+//! for (feed, error) in report.failed() {
+//!     eprintln!("{}: {error}", feed.url);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+use url::Url;
+
+use crate::Client;
+use crate::data::PodcastChannel;
+use crate::error::Error;
+use crate::playlist_io::escape;
+
+/// A podcast feed listed in an OPML file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OpmlFeed {
+    /// Feed URL (`xmlUrl`).
+    pub url: String,
+    /// Feed title (`title`, or `text`), if the file has one.
+    pub title: Option<String>,
+}
+
+/// What happened to one feed of an imported OPML file.
+#[derive(Debug)]
+pub enum FeedOutcome {
+    /// The server subscribed to the feed.
+    Created,
+    /// The server already had the feed, or it appeared earlier in the file.
+    AlreadySubscribed,
+    /// `createPodcastChannel` failed.
+    Failed(Error),
+}
+
+/// Result of [`Client::import_podcasts_opml`]: every feed of the file with its outcome.
+#[derive(Debug, Default)]
+pub struct OpmlImportReport {
+    /// All feeds, in file order.
+    pub feeds: Vec<(OpmlFeed, FeedOutcome)>,
+}
+
+impl OpmlImportReport {
+    /// Feeds the server subscribed to.
+    pub fn created(&self) -> impl Iterator<Item = &OpmlFeed> {
+        self.feeds
+            .iter()
+            .filter(|(_, outcome)| matches!(outcome, FeedOutcome::Created))
+            .map(|(feed, _)| feed)
+    }
+
+    /// Feeds that could not be subscribed to, with their error.
+    pub fn failed(&self) -> impl Iterator<Item = (&OpmlFeed, &Error)> {
+        self.feeds
+            .iter()
+            .filter_map(|(feed, outcome)| match outcome {
+                FeedOutcome::Failed(e) => Some((feed, e)),
+                _ => None,
+            })
+    }
+
+    /// Whether no feed failed.
+    pub fn is_complete(&self) -> bool {
+        self.failed().next().is_none()
+    }
+}
+
+impl Client {
+    /// Write the server's podcast channels as an OPML 2.0 file.
+    pub async fn export_podcasts_opml(&self) -> Result<String, Error> {
+        let channels = self.get_podcasts(Some(false), None).await?;
+        Ok(write_opml(&channels))
+    }
+
+    /// Subscribe to the feeds of an OPML file.
+    ///
+    /// Feeds whose URL the server already has (ignoring the case of the scheme and host, and
+    /// a trailing `/`) are not created again; the others are created one by one with
+    /// `createPodcastChannel`, and a failure is recorded in the report without stopping the
+    /// import.
+    ///
+    /// # Errors
+    /// Returns [`Error::Parse`] if the file is malformed (or without the `xml` feature), and
+    /// the errors of `getPodcasts`.
+    pub async fn import_podcasts_opml(&self, text: &str) -> Result<OpmlImportReport, Error> {
+        let feeds = parse_opml(text)?;
+        let channels = self.get_podcasts(Some(false), None).await?;
+        let mut known: HashSet<String> = channels.iter().map(|c| feed_key(&c.url)).collect();
+        let mut report = OpmlImportReport::default();
+        for feed in feeds {
+            let outcome = if !known.insert(feed_key(&feed.url)) {
+                FeedOutcome::AlreadySubscribed
+            } else {
+                match self.create_podcast_channel(&feed.url).await {
+                    Ok(()) => FeedOutcome::Created,
+                    Err(e) => FeedOutcome::Failed(e),
+                }
+            };
+            report.feeds.push((feed, outcome));
+        }
+        Ok(report)
+    }
+}
+
+/// The form of a feed URL used to detect duplicates: the scheme and host are lowercased,
+/// as the rest of the URL may be case-sensitive.
+fn feed_key(url: &str) -> String {
+    let url = url.trim();
+    match Url::parse(url) {
+        Ok(parsed) => parsed.as_str().trim_end_matches('/').to_owned(),
+        Err(_) => url.trim_end_matches('/').to_owned(),
+    }
+}
+
+fn write_opml(channels: &[PodcastChannel]) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <opml version=\"2.0\">\n  <head>\n    <title>Podcasts</title>\n  </head>\n  <body>\n",
+    );
+    for channel in channels {
+        let title = escape(channel.title.as_deref().unwrap_or(&channel.url));
+        let _ = writeln!(
+            out,
+            "    <outline type=\"rss\" text=\"{title}\" title=\"{title}\" xmlUrl=\"{}\"/>",
+            escape(&channel.url)
+        );
+    }
+    out.push_str("  </body>\n</opml>\n");
+    out
+}
+
+/// Read the feeds of an OPML file, at any nesting depth.
+#[cfg(feature = "xml")]
+fn parse_opml(text: &str) -> Result<Vec<OpmlFeed>, Error> {
+    use quick_xml::events::Event;
+    use quick_xml::{Reader, XmlVersion};
+
+    use crate::xml::xml_error;
+
+    let mut reader = Reader::from_str(text.trim_start_matches('\u{feff}'));
+    let mut feeds = Vec::new();
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"outline" => {
+                let mut feed = OpmlFeed::default();
+                let mut text = None;
+                for attr in e.attributes() {
+                    let attr = attr.map_err(xml_error)?;
+                    let value = attr
+                        .normalized_value(XmlVersion::Implicit1_0)
+                        .map_err(xml_error)?
+                        .trim()
+                        .to_owned();
+                    match attr.key.local_name().as_ref() {
+                        b"xmlUrl" => feed.url = value,
+                        b"title" => feed.title = Some(value),
+                        b"text" => text = Some(value),
+                        _ => {}
+                    }
+                }
+                if !feed.url.is_empty() {
+                    feed.title = feed.title.or(text).filter(|t| !t.is_empty());
+                    feeds.push(feed);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(feeds)
+}
+
+#[cfg(not(feature = "xml"))]
+fn parse_opml(_text: &str) -> Result<Vec<OpmlFeed>, Error> {
+    Err(Error::Parse(
+        "Importing OPML files requires the `xml` feature".into(),
+    ))
+}
+
+#[cfg(all(test, feature = "xml"))]
+mod tests {
+    use super::*;
+    use crate::data::PodcastStatus;
+
+    #[test]
+    fn opml_round_trips_through_writer() {
+        let channels = [
+            PodcastChannel::new("pc-1", "https://example.com/feed.xml", PodcastStatus::New)
+                .with_title("Q & A"),
+            PodcastChannel::new("pc-2", "https://example.com/other", PodcastStatus::New),
+        ];
+        let feeds = parse_opml(&write_opml(&channels)).unwrap();
+        assert_eq!(feeds.len(), 2);
+        assert_eq!(feeds[0].title.as_deref(), Some("Q & A"));
+        assert_eq!(feeds[1].url, "https://example.com/other");
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn imports_new_feeds_once() {
+        let server = crate::testing::MockServer::start().await;
+        let client = server.client();
+        let known = &client.get_podcasts(None, None).await.unwrap()[0].url;
+        let opml = format!(
+            r#"<opml version="1.0"><body><outline text="Group">
+                 <outline text="Known" xmlUrl="{known}/"/>
+                 <outline text="New" xmlUrl="https://example.com/new.xml"/>
+                 <outline text="Again" xmlUrl="HTTPS://Example.COM/new.xml"/>
+                 <outline text="Other" xmlUrl="https://example.com/NEW.xml"/>
+               </outline></body></opml>"#
+        );
+        let report = client.import_podcasts_opml(&opml).await.unwrap();
+        assert!(report.is_complete());
+        let created: Vec<_> = report.created().map(|f| f.title.as_deref()).collect();
+        assert_eq!(created, [Some("New"), Some("Other")]);
+        assert_eq!(server.calls("createPodcastChannel").await, 2);
+    }
+}
@@ -0,0 +1,137 @@
+//! Per-server workarounds.
+
+use serde_json::{Map, Value};
+
+/// A known server implementation, with the workarounds the client applies for it.
+///
+/// The profile is detected from the `type` field of the response envelope (see
+/// [`ResponseMeta::server_type`](crate::ResponseMeta::server_type)) once the first response
+/// has been received, or can be fixed with
+/// [`Client::with_server_profile`](crate::Client::with_server_profile).
+/// Until then, and for servers that do not report their type, [`ServerProfile::Generic`]
+/// applies no workarounds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ServerProfile {
+    /// A spec-conforming or unknown server.
+    #[default]
+    Generic,
+    /// Navidrome.
+    Navidrome,
+    /// gonic.
+    Gonic,
+    /// Airsonic-Advanced.
+    AirsonicAdvanced,
+    /// Ampache.
+    Ampache,
+    /// LMS (Lightweight Music Server).
+    Lms,
+    /// Funkwhale.
+    Funkwhale,
+}
+
+/// Fields holding IDs, which some servers send as JSON numbers.
+pub(crate) const ID_FIELDS: [&str; 10] = [
+    "id",
+    "parent",
+    "albumId",
+    "artistId",
+    "coverArt",
+    "playlistId",
+    "channelId",
+    "streamId",
+    "current",
+    "musicFolderId",
+];
+
+impl ServerProfile {
+    /// The profile for an envelope `type` value (case-insensitive).
+    pub fn from_server_type(server_type: &str) -> Self {
+        match server_type.to_ascii_lowercase().as_str() {
+            "navidrome" => Self::Navidrome,
+            "gonic" => Self::Gonic,
+            "airsonic-advanced" | "airsonic advanced" => Self::AirsonicAdvanced,
+            "ampache" => Self::Ampache,
+            "lms" => Self::Lms,
+            "funkwhale" => Self::Funkwhale,
+            _ => Self::Generic,
+        }
+    }
+
+    /// Whether the server sends IDs as JSON numbers, which are converted to strings before
+    /// decoding.
+    pub fn numeric_ids(self) -> bool {
+        matches!(self, Self::Ampache | Self::Funkwhale)
+    }
+
+    /// The endpoint called in place of `endpoint`, and the response key of the replacement
+    /// that is renamed to the key the original endpoint uses.
+    ///
+    /// LMS only implements ID3 browsing, so `getIndexes` is served by `getArtists`, whose
+    /// result has the same shape.
+    pub(crate) fn substitute(
+        self,
+        endpoint: &str,
+    ) -> Option<(&'static str, &'static str, &'static str)> {
+        match (self, endpoint) {
+            (Self::Lms, "getIndexes") => Some(("getArtists", "artists", "indexes")),
+            _ => None,
+        }
+    }
+
+    /// Apply the deserialization workarounds to the response data.
+    pub(crate) fn normalize(self, data: &mut Map<String, Value>) {
+        if self.numeric_ids() {
+            for (key, value) in data.iter_mut() {
+                stringify_ids(key, value);
+            }
+        }
+    }
+}
+
+/// Convert numeric ID fields in `value` (the value of `key`) to strings, recursively.
+fn stringify_ids(key: &str, value: &mut Value) {
+    match value {
+        Value::Number(n) if ID_FIELDS.contains(&key) => *value = Value::String(n.to_string()),
+        Value::Array(items) => items.iter_mut().for_each(|item| stringify_ids(key, item)),
+        Value::Object(map) => map.iter_mut().for_each(|(k, v)| stringify_ids(k, v)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn detects_profiles() {
+        assert_eq!(
+            ServerProfile::from_server_type("Navidrome"),
+            ServerProfile::Navidrome
+        );
+        assert_eq!(
+            ServerProfile::from_server_type("funkwhale"),
+            ServerProfile::Funkwhale
+        );
+        assert_eq!(
+            ServerProfile::from_server_type("subsonic"),
+            ServerProfile::Generic
+        );
+    }
+
+    #[test]
+    fn stringifies_numeric_ids() {
+        let mut data = json!({
+            "album": { "id": 12, "songCount": 2, "song": [{ "id": 1, "parent": 12, "track": 1 }] }
+        });
+        ServerProfile::Funkwhale.normalize(data.as_object_mut().unwrap());
+        assert_eq!(
+            data,
+            json!({
+                "album": { "id": "12", "songCount": 2, "song": [{ "id": "1", "parent": "12", "track": 1 }] }
+            })
+        );
+    }
+}
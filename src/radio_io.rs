@@ -0,0 +1,327 @@
+//! Internet radio station import and export (PLS and M3U).
+//!
+//! [`Client::export_internet_radio_stations`] writes the server's stations as a PLS or M3U
+//! file that media players open directly. [`Client::import_internet_radio_stations`] creates
+//! the stations of such a file on the server, skipping stream URLs it already has:
+//!
+//! ```no_run
+//! # async fn example(
+//! #     from: &opensubsonic::Client,
+//! #     to: &opensubsonic::Client,
+//! # ) -> Result<(), opensubsonic::Error> {
+//! use opensubsonic::radio_io::StationFormat;
+//!
+//! let pls = from.export_internet_radio_stations(StationFormat::Pls).await?;
+//! let report = to.import_internet_radio_stations(&pls, StationFormat::Pls).await?;
+//! for (station, error) in report.failed() {
+//!     eprintln!("{}: {error}", station.name);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use url::Url;
+
+use crate::Client;
+use crate::data::InternetRadioStation;
+use crate::error::Error;
+
+/// A station list file format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StationFormat {
+    /// PLS playlist (`.pls`).
+    Pls,
+    /// Extended M3U (`.m3u` or `.m3u8`).
+    M3u,
+}
+
+impl StationFormat {
+    /// Guess the format from a file extension (`pls`, `m3u` or `m3u8`).
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "pls" => Some(Self::Pls),
+            "m3u" | "m3u8" => Some(Self::M3u),
+            _ => None,
+        }
+    }
+}
+
+/// A station read from a PLS or M3U file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StationEntry {
+    /// Stream URL.
+    pub stream_url: String,
+    /// Station name: the entry's title, or the stream URL if it has none.
+    pub name: String,
+}
+
+/// What happened to one station of an imported file.
+#[derive(Debug)]
+pub enum StationOutcome {
+    /// The station was created on the server.
+    Created,
+    /// The server already had the stream URL, or it appeared earlier in the file.
+    AlreadyExists,
+    /// `createInternetRadioStation` failed.
+    Failed(Error),
+}
+
+/// Result of [`Client::import_internet_radio_stations`]: every station of the file with its
+/// outcome.
+#[derive(Debug, Default)]
+pub struct StationImportReport {
+    /// All stations, in file order.
+    pub stations: Vec<(StationEntry, StationOutcome)>,
+}
+
+impl StationImportReport {
+    /// Stations created on the server.
+    pub fn created(&self) -> impl Iterator<Item = &StationEntry> {
+        self.stations
+            .iter()
+            .filter(|(_, outcome)| matches!(outcome, StationOutcome::Created))
+            .map(|(station, _)| station)
+    }
+
+    /// Stations that could not be created, with their error.
+    pub fn failed(&self) -> impl Iterator<Item = (&StationEntry, &Error)> {
+        self.stations
+            .iter()
+            .filter_map(|(station, outcome)| match outcome {
+                StationOutcome::Failed(e) => Some((station, e)),
+                _ => None,
+            })
+    }
+
+    /// Whether no station failed.
+    pub fn is_complete(&self) -> bool {
+        self.failed().next().is_none()
+    }
+}
+
+impl Client {
+    /// Write the server's internet radio stations as a station list file.
+    pub async fn export_internet_radio_stations(
+        &self,
+        format: StationFormat,
+    ) -> Result<String, Error> {
+        let stations = self.get_internet_radio_stations().await?;
+        Ok(write_stations(&stations, format))
+    }
+
+    /// Create the stations of a PLS or M3U file on the server.
+    ///
+    /// Stations whose stream URL the server already has (ignoring the case of the scheme and
+    /// host, and a trailing `/`) are skipped; the others are created one by one, and a
+    /// failure is recorded in the report without stopping the import. Home pages are not
+    /// part of these formats, so imported stations have none.
+    ///
+    /// # Errors
+    /// Returns the errors of `getInternetRadioStations`.
+    pub async fn import_internet_radio_stations(
+        &self,
+        text: &str,
+        format: StationFormat,
+    ) -> Result<StationImportReport, Error> {
+        let entries = parse_stations(text, format);
+        let existing = self.get_internet_radio_stations().await?;
+        let mut known: HashSet<String> =
+            existing.iter().map(|s| stream_key(&s.stream_url)).collect();
+        let mut report = StationImportReport::default();
+        for entry in entries {
+            let outcome = if !known.insert(stream_key(&entry.stream_url)) {
+                StationOutcome::AlreadyExists
+            } else {
+                match self
+                    .create_internet_radio_station(&entry.stream_url, &entry.name, None)
+                    .await
+                {
+                    Ok(()) => StationOutcome::Created,
+                    Err(e) => StationOutcome::Failed(e),
+                }
+            };
+            report.stations.push((entry, outcome));
+        }
+        Ok(report)
+    }
+}
+
+/// The form of a stream URL used to detect duplicates: the scheme and host are lowercased,
+/// as the rest of the URL may be case-sensitive.
+fn stream_key(url: &str) -> String {
+    let url = url.trim();
+    match Url::parse(url) {
+        Ok(parsed) => parsed.as_str().trim_end_matches('/').to_owned(),
+        Err(_) => url.trim_end_matches('/').to_owned(),
+    }
+}
+
+/// Render stations as a PLS or M3U file.
+pub fn write_stations(stations: &[InternetRadioStation], format: StationFormat) -> String {
+    let mut out = String::new();
+    match format {
+        StationFormat::Pls => {
+            out.push_str("[playlist]\n");
+            for (i, station) in stations.iter().enumerate() {
+                let n = i + 1;
+                let _ = writeln!(out, "File{n}={}", station.stream_url);
+                let _ = writeln!(out, "Title{n}={}", station.name);
+                let _ = writeln!(out, "Length{n}=-1");
+            }
+            let _ = writeln!(out, "NumberOfEntries={}", stations.len());
+            out.push_str("Version=2\n");
+        }
+        StationFormat::M3u => {
+            out.push_str("#EXTM3U\n");
+            for station in stations {
+                let _ = writeln!(out, "#EXTINF:-1,{}", station.name);
+                let _ = writeln!(out, "{}", station.stream_url);
+            }
+        }
+    }
+    out
+}
+
+/// Read the stations of a PLS or M3U file, in file order.
+pub fn parse_stations(text: &str, format: StationFormat) -> Vec<StationEntry> {
+    let text = text.trim_start_matches('\u{feff}');
+    let mut entries = match format {
+        StationFormat::Pls => parse_pls(text),
+        StationFormat::M3u => parse_m3u(text),
+    };
+    for entry in &mut entries {
+        if entry.name.is_empty() {
+            entry.name.clone_from(&entry.stream_url);
+        }
+    }
+    entries
+}
+
+fn parse_pls(text: &str) -> Vec<StationEntry> {
+    // Entries are numbered, and `TitleN` may come before `FileN`.
+    let mut numbered: Vec<(u32, StationEntry)> = Vec::new();
+    for line in text.lines().map(str::trim) {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().to_ascii_lowercase();
+        let (field, number) = match (key.strip_prefix("file"), key.strip_prefix("title")) {
+            (Some(n), _) => ("file", n),
+            (_, Some(n)) => ("title", n),
+            _ => continue,
+        };
+        let Ok(number) = number.parse::<u32>() else {
+            continue;
+        };
+        let index = match numbered.iter().position(|(n, _)| *n == number) {
+            Some(index) => index,
+            None => {
+                numbered.push((number, StationEntry::default()));
+                numbered.len() - 1
+            }
+        };
+        let entry = &mut numbered[index].1;
+        if field == "file" {
+            entry.stream_url = value.trim().to_owned();
+        } else {
+            entry.name = value.trim().to_owned();
+        }
+    }
+    numbered.sort_by_key(|(n, _)| *n);
+    numbered
+        .into_iter()
+        .map(|(_, entry)| entry)
+        .filter(|entry| !entry.stream_url.is_empty())
+        .collect()
+}
+
+fn parse_m3u(text: &str) -> Vec<StationEntry> {
+    let mut entries = Vec::new();
+    let mut name = String::new();
+    for line in text.lines().map(str::trim) {
+        if let Some(info) = line.strip_prefix("#EXTINF:") {
+            name = info
+                .split_once(',')
+                .map(|(_, n)| n.trim().to_owned())
+                .unwrap_or_default();
+        } else if !line.is_empty() && !line.starts_with('#') {
+            entries.push(StationEntry {
+                stream_url: line.to_owned(),
+                name: std::mem::take(&mut name),
+            });
+        }
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn station_files_round_trip() {
+        let stations = [
+            InternetRadioStation::new("ir-1", "Jazz FM", "https://jazz.example.com/live"),
+            InternetRadioStation::new("ir-2", "News", "http://news.example.com:8000/"),
+        ];
+        for format in [StationFormat::Pls, StationFormat::M3u] {
+            let entries = parse_stations(&write_stations(&stations, format), format);
+            let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+            assert_eq!(names, ["Jazz FM", "News"]);
+            assert_eq!(entries[1].stream_url, "http://news.example.com:8000/");
+        }
+    }
+
+    #[test]
+    fn pls_entries_are_matched_by_number() {
+        let pls = "\u{feff}[Playlist]\nTitle2=Second\nTITLE3=No stream\nFile2=http://b\n\
+                   file1 = http://a \nFileX=http://c\nNumberOfEntries=2\n";
+        let entries = parse_stations(pls, StationFormat::Pls);
+        assert_eq!(
+            entries,
+            [
+                StationEntry {
+                    stream_url: "http://a".into(),
+                    name: "http://a".into(),
+                },
+                StationEntry {
+                    stream_url: "http://b".into(),
+                    name: "Second".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn m3u_titles_apply_to_the_next_stream() {
+        let m3u = "#EXTM3U\n#EXTINF:-1,First\n\n# comment\nhttp://a\nhttp://b\n\
+                   #EXTINF:-1\nhttp://c\n#EXTINF:120, Last \nhttp://d\n";
+        let entries = parse_stations(m3u, StationFormat::M3u);
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["First", "http://b", "http://c", "Last"]);
+        assert_eq!(entries[3].stream_url, "http://d");
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn imports_new_stations_once() {
+        let server = crate::testing::MockServer::start().await;
+        let client = server.client();
+        let m3u = "#EXTM3U\n#EXTINF:-1,Example\nhttps://radio.example.com/stream/\n\
+                   #EXTINF:-1,Other\nhttps://other.example.com/live\n\
+                   HTTPS://Other.Example.com/live\n#EXTINF:-1,Live\nhttps://other.example.com/LIVE\n";
+        let report = client
+            .import_internet_radio_stations(m3u, StationFormat::M3u)
+            .await
+            .unwrap();
+        assert!(report.is_complete());
+        let created: Vec<&str> = report.created().map(|s| s.name.as_str()).collect();
+        assert_eq!(created, ["Other", "Live"]);
+        assert_eq!(server.calls("createInternetRadioStation").await, 2);
+    }
+}
@@ -0,0 +1,100 @@
+//! Client-side request rate limiting.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::Error;
+
+/// A token bucket: `burst` requests may be sent at once, refilled at `rate` per second.
+///
+/// Callers that find the bucket empty reserve a future slot, so waiting requests are served
+/// in the order they arrived.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    state: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Available tokens; negative when slots have been reserved ahead of time.
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// A limiter allowing `requests_per_second` on average and bursts of `burst` requests.
+    ///
+    /// Returns [`Error::Other`] if `requests_per_second` is not positive.
+    pub(crate) fn new(requests_per_second: f64, burst: u32) -> Result<Self, Error> {
+        if requests_per_second.is_nan() || requests_per_second <= 0.0 {
+            return Err(Error::Other(format!(
+                "Rate limit must be a positive number of requests per second, not \
+                 {requests_per_second}"
+            )));
+        }
+        let burst = f64::from(burst.max(1));
+        Ok(Self {
+            rate: requests_per_second,
+            burst,
+            state: Mutex::new(Bucket {
+                tokens: burst,
+                updated: Instant::now(),
+            }),
+        })
+    }
+
+    /// Wait until a request may be sent.
+    pub(crate) async fn acquire(&self) {
+        let wait = self.reserve(Instant::now());
+        if !wait.is_zero() {
+            log::debug!("Rate limit reached, waiting {wait:?}");
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Take a token at `now`, returning how long to wait before it becomes valid.
+    fn reserve(&self, now: Instant) -> Duration {
+        let mut bucket = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+        bucket.updated = now;
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            // Tiny rates give waits too long for a `Duration`.
+            Duration::try_from_secs_f64(-bucket.tokens / self.rate).unwrap_or(Duration::MAX)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bursts_then_spaces_requests() {
+        let limiter = RateLimiter::new(10.0, 2).unwrap();
+        let start = Instant::now();
+        assert_eq!(limiter.reserve(start), Duration::ZERO);
+        assert_eq!(limiter.reserve(start), Duration::ZERO);
+        assert_eq!(limiter.reserve(start), Duration::from_millis(100));
+        assert_eq!(limiter.reserve(start), Duration::from_millis(200));
+
+        // After a second of inactivity the bucket is full again, but no fuller.
+        let later = start + Duration::from_secs(2);
+        assert_eq!(limiter.reserve(later), Duration::ZERO);
+        assert_eq!(limiter.reserve(later), Duration::ZERO);
+        assert_eq!(limiter.reserve(later), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn tiny_rates_wait_without_overflowing() {
+        let limiter = RateLimiter::new(1e-300, 1).unwrap();
+        let now = Instant::now();
+        assert_eq!(limiter.reserve(now), Duration::ZERO);
+        assert_eq!(limiter.reserve(now), Duration::MAX);
+    }
+}
@@ -0,0 +1,321 @@
+//! Building blocks for Subsonic-compatible servers and proxies (requires the `server`
+//! feature).
+//!
+//! Handlers reuse the [`data`](crate::data) types for their responses: [`Params`] reads the
+//! request parameters, and [`Reply`] wraps a value in the `subsonic-response` envelope, as
+//! JSON or XML depending on the `f` parameter. [`router`] answers the system endpoints, and
+//! [`SubsonicRouter::endpoint`] adds others under `/rest/{name}` and `/rest/{name}.view`.
+//!
+//! ```no_run
+//! use opensubsonic::data::AlbumWithSongsId3;
+//! use opensubsonic::server::{self, Params, Reply, SubsonicRouter};
+//! use opensubsonic::SubsonicErrorCode;
+//!
+//! async fn get_album(params: Params) -> Reply {
+//!     if !params.check_password("secret") {
+//!         return params.error(SubsonicErrorCode::WrongCredentials, "Wrong username or password");
+//!     }
+//!     match params.get("id") {
+//!         Some(id) => params.reply("album", &AlbumWithSongsId3::new(id, "Album")),
+//!         None => params.error(SubsonicErrorCode::MissingParameter, "Required parameter is missing: id"),
+//!     }
+//! }
+//!
+//! # async fn example() -> std::io::Result<()> {
+//! let app = server::router().endpoint("getAlbum", get_album);
+//! let listener = tokio::net::TcpListener::bind("127.0.0.1:4533").await?;
+//! server::axum::serve(listener, app).await
+//! # }
+//! ```
+
+pub use axum;
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::rejection::BytesRejection;
+use axum::extract::{FromRequest, Request};
+use axum::handler::Handler;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::{IntoResponse, Response};
+use axum::routing::any;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use url::form_urlencoded;
+
+use crate::auth::{compute_token, hex_encode};
+use crate::client::{DEFAULT_API_VERSION, Format};
+use crate::data::{License, OpenSubsonicExtension};
+use crate::error::SubsonicErrorCode;
+
+/// Server type reported in responses unless overridden with [`Reply::server`].
+const SERVER_TYPE: &str = "opensubsonic-rs";
+
+/// Parameters of a Subsonic request, from the query string and, for `formPost` requests,
+/// the `application/x-www-form-urlencoded` body.
+///
+/// Extract it in a handler to read the parameters, check the credentials and build the
+/// [`Reply`] in the format the client asked for. As it reads the body, it must be the last
+/// argument of the handler.
+#[derive(Debug, Clone, Default)]
+pub struct Params {
+    pairs: Vec<(String, String)>,
+}
+
+impl Params {
+    /// Parse the parameters of a query string (without the leading `?`).
+    pub fn from_query(query: &str) -> Self {
+        Self {
+            pairs: form_urlencoded::parse(query.as_bytes())
+                .into_owned()
+                .collect(),
+        }
+    }
+
+    /// The first value of parameter `name`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Every value of a repeated parameter such as `id` or `songIdToAdd`.
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.pairs
+            .iter()
+            .filter(move |(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The user name (`u`).
+    pub fn username(&self) -> Option<&str> {
+        self.get("u")
+    }
+
+    /// The response format asked for with `f`; XML unless it is `json`, as in Subsonic.
+    pub fn format(&self) -> Format {
+        match self.get("f") {
+            Some("json") => Format::Json,
+            _ => Format::Xml,
+        }
+    }
+
+    /// Whether the request authenticates with `password`, either as a token and salt
+    /// (`t`, `s`) or as a plain or `enc:`-prefixed hex password (`p`).
+    ///
+    /// Only the password is checked; look up the user from [`username`](Self::username).
+    pub fn check_password(&self, password: &str) -> bool {
+        if let (Some(token), Some(salt)) = (self.get("t"), self.get("s")) {
+            return token.eq_ignore_ascii_case(&compute_token(password, salt));
+        }
+        match self.get("p") {
+            Some(p) => match p.strip_prefix("enc:") {
+                Some(hex) => hex.eq_ignore_ascii_case(&hex_encode(password.as_bytes())),
+                None => p == password,
+            },
+            None => false,
+        }
+    }
+
+    /// A successful response in the requested format, carrying `value` under `key`.
+    pub fn reply(&self, key: &str, value: &impl Serialize) -> Reply {
+        Reply::new(self.format()).with(key, value)
+    }
+
+    /// An empty successful response in the requested format.
+    pub fn ok(&self) -> Reply {
+        Reply::new(self.format())
+    }
+
+    /// A failed response in the requested format.
+    pub fn error(&self, code: SubsonicErrorCode, message: &str) -> Reply {
+        Reply::error(self.format(), code, message)
+    }
+}
+
+impl<S: Send + Sync> FromRequest<S> for Params {
+    type Rejection = BytesRejection;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, BytesRejection> {
+        let mut params = request
+            .uri()
+            .query()
+            .map(Self::from_query)
+            .unwrap_or_default();
+        let is_form = request
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("application/x-www-form-urlencoded"));
+        if is_form {
+            let body = Bytes::from_request(request, state).await?;
+            params
+                .pairs
+                .extend(form_urlencoded::parse(&body).into_owned());
+        }
+        Ok(params)
+    }
+}
+
+/// A `subsonic-response`, rendered as JSON or XML.
+///
+/// Subsonic clients read the status from the body, so errors are sent with HTTP status 200
+/// as well.
+#[derive(Debug, Clone)]
+pub struct Reply {
+    format: Format,
+    fields: Map<String, Value>,
+}
+
+impl Reply {
+    /// An empty successful response.
+    pub fn new(format: Format) -> Self {
+        let mut fields = Map::new();
+        fields.insert("status".into(), "ok".into());
+        fields.insert("version".into(), DEFAULT_API_VERSION.into());
+        fields.insert("type".into(), SERVER_TYPE.into());
+        fields.insert("serverVersion".into(), env!("CARGO_PKG_VERSION").into());
+        fields.insert("openSubsonic".into(), true.into());
+        Self { format, fields }
+    }
+
+    /// A failed response with the given error code and message.
+    pub fn error(format: Format, code: SubsonicErrorCode, message: &str) -> Self {
+        let mut reply = Self::new(format);
+        reply.fields.insert("status".into(), "failed".into());
+        reply.with(
+            "error",
+            &serde_json::json!({ "code": code.code(), "message": message }),
+        )
+    }
+
+    /// Add `value` under `key`, e.g. `"album"` for an
+    /// [`AlbumWithSongsId3`](crate::data::AlbumWithSongsId3).
+    ///
+    /// # Panics
+    /// Panics if `value` fails to serialize, which the data types never do.
+    #[must_use]
+    pub fn with(mut self, key: &str, value: &impl Serialize) -> Self {
+        let value = serde_json::to_value(value).expect("response values serialize to JSON");
+        self.fields.insert(key.to_owned(), value);
+        self
+    }
+
+    /// Report another server type and version than `opensubsonic-rs` and the crate version.
+    #[must_use]
+    pub fn server(mut self, name: &str, version: &str) -> Self {
+        self.fields.insert("type".into(), name.into());
+        self.fields.insert("serverVersion".into(), version.into());
+        self
+    }
+
+    /// The response body.
+    pub fn body(&self) -> String {
+        match self.format {
+            Format::Json => {
+                let mut envelope = Map::new();
+                envelope.insert("subsonic-response".into(), self.fields.clone().into());
+                Value::Object(envelope).to_string()
+            }
+            Format::Xml => crate::xml::write_response(&self.fields),
+        }
+    }
+}
+
+impl IntoResponse for Reply {
+    fn into_response(self) -> Response {
+        let content_type = match self.format {
+            Format::Json => "application/json",
+            Format::Xml => "text/xml; charset=utf-8",
+        };
+        ([(CONTENT_TYPE, content_type)], self.body()).into_response()
+    }
+}
+
+/// Registering handlers for Subsonic endpoints.
+pub trait SubsonicRouter<S> {
+    /// Route `/rest/{name}` and `/rest/{name}.view`, for any method, to `handler`.
+    #[must_use]
+    fn endpoint<H, T>(self, name: &str, handler: H) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static;
+}
+
+impl<S: Clone + Send + Sync + 'static> SubsonicRouter<S> for Router<S> {
+    fn endpoint<H, T>(self, name: &str, handler: H) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        self.route(&format!("/rest/{name}"), any(handler.clone()))
+            .route(&format!("/rest/{name}.view"), any(handler))
+    }
+}
+
+/// A router answering `ping`, `getLicense` (always valid) and `getOpenSubsonicExtensions`
+/// (none), and unknown endpoints with a "not found" error.
+///
+/// Add the other endpoints with [`SubsonicRouter::endpoint`]. Credentials are not checked.
+pub fn router<S: Clone + Send + Sync + 'static>() -> Router<S> {
+    Router::new()
+        .endpoint("ping", |params: Params| async move { params.ok() })
+        .endpoint("getLicense", |params: Params| async move {
+            params.reply("license", &License::new().with_valid(true))
+        })
+        .endpoint("getOpenSubsonicExtensions", |params: Params| async move {
+            params.reply(
+                "openSubsonicExtensions",
+                &Vec::<OpenSubsonicExtension>::new(),
+            )
+        })
+        .fallback(|params: Params| async move {
+            params.error(SubsonicErrorCode::NotFound, "Unknown endpoint")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{AlbumWithSongsId3, Child};
+    use crate::{Auth, Client};
+
+    async fn get_album(params: Params) -> Reply {
+        if !params.check_password("secret") {
+            return params.error(SubsonicErrorCode::WrongCredentials, "Wrong password");
+        }
+        let id = params.get("id").unwrap_or_default();
+        let album = AlbumWithSongsId3::new(id, "Album").with_song(vec![Child::new("s-1", "Song")]);
+        params.reply("album", &album)
+    }
+
+    #[tokio::test]
+    async fn serves_the_client() {
+        let app = router().endpoint("getAlbum", get_album);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let client = Client::new(&url, Auth::token("bob", "secret")).unwrap();
+        client.ping().await.unwrap();
+        assert!(client.get_license().await.unwrap().valid);
+        let album = client.get_album("al-1").await.unwrap();
+        assert_eq!((album.id.as_str(), album.song.len()), ("al-1", 1));
+
+        let xml = Client::builder(&url, Auth::plain("bob", "secret"))
+            .response_format(Format::Xml)
+            .build()
+            .unwrap();
+        assert_eq!(xml.get_album("al-2").await.unwrap().song[0].title, "Song");
+
+        let form = client.clone().with_form_post(true);
+        assert_eq!(form.get_album("al-3").await.unwrap().id, "al-3");
+
+        let wrong = Client::new(&url, Auth::token("bob", "wrong")).unwrap();
+        let error = wrong.get_album("al-1").await.unwrap_err();
+        assert_eq!(
+            error.api_error().map(|e| e.error_code()),
+            Some(SubsonicErrorCode::WrongCredentials)
+        );
+        assert!(client.get_song("s-1").await.is_err());
+    }
+}
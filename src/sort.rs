@@ -0,0 +1,143 @@
+//! Sorting and grouping songs.
+//!
+//! The comparators order songs the way players list them and are meant for
+//! [`slice::sort_by`]; fields a song lacks sort it after the songs that have them.
+//! [`AlbumWithSongsId3::discs`] splits an album into its discs.
+//!
+//! ```
+//! use opensubsonic::data::Child;
+//! use opensubsonic::sort;
+//!
+//! let mut songs = vec![
+//!     Child::new("tr-2", "Love of My Life").with_track(9),
+//!     Child::new("tr-1", "Death on Two Legs").with_track(1),
+//! ];
+//! songs.sort_by(sort::by_disc_and_track);
+//! assert_eq!(songs[0].title, "Death on Two Legs");
+//! ```
+
+use std::cmp::Ordering;
+
+use crate::data::{AlbumWithSongsId3, Child};
+
+/// Order songs by disc, then track number. Songs without a disc number are on disc 1.
+pub fn by_disc_and_track(a: &Child, b: &Child) -> Ordering {
+    a.disc_number
+        .unwrap_or(1)
+        .cmp(&b.disc_number.unwrap_or(1))
+        .then_with(|| present_first(a.track, b.track))
+}
+
+/// Order songs by album name (ignoring case), then disc and track number.
+///
+/// Albums of the same name are kept apart by their ID.
+pub fn by_album_and_track(a: &Child, b: &Child) -> Ordering {
+    let album = |song: &Child| song.album.as_deref().map(str::to_lowercase);
+    present_first(album(a), album(b))
+        .then_with(|| present_first(a.album_id.as_ref(), b.album_id.as_ref()))
+        .then_with(|| by_disc_and_track(a, b))
+}
+
+/// Order songs most recently added first.
+///
+/// `created` dates in different offsets or formats are compared by the time they denote.
+/// Songs with an unrecognised date follow those with a valid one.
+pub fn newest_first(a: &Child, b: &Child) -> Ordering {
+    match (&a.created, &b.created) {
+        (Some(a), Some(b)) => b.cmp_chronological(a),
+        (a, b) => a.is_none().cmp(&b.is_none()),
+    }
+}
+
+/// Compare optional values, with `None` last.
+fn present_first<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (a, b) => a.is_none().cmp(&b.is_none()),
+    }
+}
+
+/// The songs of one disc of an album.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Disc<'a> {
+    /// Disc number, starting at 1.
+    pub number: i32,
+    /// Disc title from the album's `discTitles`, if the server sent a non-empty one.
+    pub title: Option<&'a str>,
+    /// Songs of the disc, by track number.
+    pub songs: Vec<&'a Child>,
+}
+
+impl AlbumWithSongsId3 {
+    /// The songs of the album grouped by disc, in disc order.
+    ///
+    /// Songs without a disc number are on disc 1. Titles come from
+    /// [`disc_titles`](Self::disc_titles).
+    pub fn discs(&self) -> Vec<Disc<'_>> {
+        let mut songs: Vec<&Child> = self.song.iter().collect();
+        songs.sort_by(|a, b| by_disc_and_track(a, b));
+        let mut discs: Vec<Disc<'_>> = Vec::new();
+        for song in songs {
+            let number = song.disc_number.unwrap_or(1);
+            match discs.last_mut() {
+                Some(disc) if disc.number == number => disc.songs.push(song),
+                _ => discs.push(Disc {
+                    number,
+                    title: self.disc_title(number),
+                    songs: vec![song],
+                }),
+            }
+        }
+        discs
+    }
+
+    fn disc_title(&self, number: i32) -> Option<&str> {
+        self.disc_titles
+            .iter()
+            .flatten()
+            .find(|t| t.disc == number && !t.title.is_empty())
+            .map(|t| t.title.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::DiscTitle;
+
+    #[test]
+    fn sorts_and_groups_songs() {
+        let mut songs = vec![
+            Child::new("c", "c").with_album("B").with_track(1),
+            Child::new("b", "b")
+                .with_album("a")
+                .with_disc_number(2)
+                .with_track(1),
+            Child::new("a", "a").with_album("a").with_track(2),
+            Child::new("d", "d").with_created("2024-02-01T00:00:00Z"),
+        ];
+        songs.sort_by(by_album_and_track);
+        let ids: Vec<&str> = songs.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b", "c", "d"]);
+
+        songs.sort_by(newest_first);
+        assert_eq!(songs[0].id, "d");
+
+        let mut added = [
+            Child::new("e", "e").with_created("not a date"),
+            Child::new("f", "f").with_created("2024-01-01T01:00:00+02:00"),
+            Child::new("g", "g").with_created("2024-01-01T00:00:00Z"),
+        ];
+        added.sort_by(newest_first);
+        let ids: Vec<&str> = added.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["g", "f", "e"]);
+
+        let album = AlbumWithSongsId3::new("al-1", "A")
+            .with_song(songs)
+            .with_disc_titles(vec![DiscTitle::new(2, "Live")]);
+        let discs = album.discs();
+        assert_eq!(discs.iter().map(|d| d.number).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(discs[0].songs.len(), 3);
+        assert_eq!(discs[1].title, Some("Live"));
+    }
+}
@@ -0,0 +1,184 @@
+//! Library statistics.
+//!
+//! [`LibraryStats`] totals the songs of a library: counts, size, duration, and breakdowns by
+//! genre, year and format. [`Client::library_stats`] computes it by walking the whole
+//! library; [`LibraryStats::from_songs`] does the same for songs obtained elsewhere, such as
+//! a [`LibraryCache`](crate::cache::LibraryCache).
+//!
+//! ```no_run
+//! # async fn example(client: &opensubsonic::Client) -> Result<(), opensubsonic::Error> {
+//! let stats = client.library_stats(None).await?;
+//! println!("{} songs, {} hours", stats.songs, stats.duration / 3600);
+//! println!("{}", serde_json::to_string_pretty(&stats).unwrap());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashSet};
+
+use futures_util::TryStreamExt;
+use serde::{Deserialize, Serialize};
+
+use crate::Client;
+use crate::data::Child;
+use crate::error::Error;
+
+/// File suffixes of lossless formats.
+const LOSSLESS_SUFFIXES: [&str; 10] = [
+    "flac", "alac", "ape", "wv", "tta", "wav", "aif", "aiff", "dsf", "dff",
+];
+
+/// Totals over the songs of a library.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LibraryStats {
+    /// Number of songs.
+    pub songs: u64,
+    /// Number of distinct albums the songs belong to.
+    pub albums: u64,
+    /// Number of distinct song artists.
+    pub artists: u64,
+    /// Total file size in bytes.
+    pub size: u64,
+    /// Total duration in seconds.
+    pub duration: u64,
+    /// Songs in a lossless format (FLAC, ALAC, WAV, …).
+    pub lossless: u64,
+    /// Songs in a lossy format. Songs whose format is unknown count as neither.
+    pub lossy: u64,
+    /// Number of songs per genre. Songs with several genres count once in each.
+    pub genres: BTreeMap<String, u64>,
+    /// Number of songs per release year.
+    pub years: BTreeMap<i32, u64>,
+}
+
+impl LibraryStats {
+    /// Compute the statistics of `songs`.
+    pub fn from_songs<'a>(songs: impl IntoIterator<Item = &'a Child>) -> Self {
+        let mut tally = Tally::default();
+        for song in songs {
+            tally.add(song);
+        }
+        tally.finish()
+    }
+}
+
+/// [`LibraryStats`] being accumulated, with the albums and artists seen so far.
+#[derive(Default)]
+struct Tally {
+    stats: LibraryStats,
+    albums: HashSet<String>,
+    artists: HashSet<String>,
+}
+
+impl Tally {
+    fn add(&mut self, song: &Child) {
+        let stats = &mut self.stats;
+        stats.songs += 1;
+        stats.size += song.size.unwrap_or(0).max(0) as u64;
+        stats.duration += song.duration.unwrap_or(0).max(0) as u64;
+        match is_lossless(song) {
+            Some(true) => stats.lossless += 1,
+            Some(false) => stats.lossy += 1,
+            None => {}
+        }
+        match song.genres.as_deref() {
+            Some(genres) if !genres.is_empty() => {
+                for genre in genres {
+                    *stats.genres.entry(genre.name.clone()).or_default() += 1;
+                }
+            }
+            _ => {
+                if let Some(genre) = &song.genre {
+                    *stats.genres.entry(genre.to_string()).or_default() += 1;
+                }
+            }
+        }
+        if let Some(year) = song.year.filter(|&y| y > 0) {
+            *stats.years.entry(year).or_default() += 1;
+        }
+        // Fall back to names for servers that omit the IDs.
+        let album = song.album_id.as_deref().or(song.album.as_deref());
+        if let Some(album) = album {
+            if !self.albums.contains(album) {
+                self.albums.insert(album.to_owned());
+            }
+        }
+        let artist = song.artist_id.as_deref().or(song.artist.as_deref());
+        if let Some(artist) = artist {
+            if !self.artists.contains(artist) {
+                self.artists.insert(artist.to_owned());
+            }
+        }
+    }
+
+    fn finish(mut self) -> LibraryStats {
+        self.stats.albums = self.albums.len() as u64;
+        self.stats.artists = self.artists.len() as u64;
+        self.stats
+    }
+}
+
+/// Whether a song is in a lossless format, or `None` if its format is unknown.
+fn is_lossless(song: &Child) -> Option<bool> {
+    let suffix = song.suffix.as_deref()?.to_ascii_lowercase();
+    // `m4a` holds either AAC or ALAC; servers only report a bit depth for the latter.
+    Some(LOSSLESS_SUFFIXES.contains(&suffix.as_str()) || song.bit_depth.is_some_and(|d| d > 0))
+}
+
+impl Client {
+    /// Compute statistics over every song in the library, or in one music folder.
+    ///
+    /// Walks the library with [`Client::walk_library`], so this issues one request per
+    /// artist and per album.
+    pub async fn library_stats(
+        &self,
+        music_folder_id: Option<&str>,
+    ) -> Result<LibraryStats, Error> {
+        let tally = self
+            .walk_library(music_folder_id)
+            .try_fold(Tally::default(), |mut tally, song| async move {
+                tally.add(&song);
+                Ok(tally)
+            })
+            .await?;
+        Ok(tally.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn song(id: &str, album: &str, suffix: &str, genre: &str, year: i32) -> Child {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "title": id,
+            "albumId": album,
+            "artistId": "ar-1",
+            "suffix": suffix,
+            "genre": genre,
+            "year": year,
+            "size": 1000,
+            "duration": 60,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn totals_songs() {
+        let songs = [
+            song("tr-1", "al-1", "flac", "Rock", 1975),
+            song("tr-2", "al-1", "flac", "Rock", 1975),
+            song("tr-3", "al-2", "mp3", "Pop", 1980),
+        ];
+        let stats = LibraryStats::from_songs(&songs);
+        assert_eq!(stats.songs, 3);
+        assert_eq!(stats.albums, 2);
+        assert_eq!(stats.artists, 1);
+        assert_eq!(stats.size, 3000);
+        assert_eq!(stats.duration, 180);
+        assert_eq!((stats.lossless, stats.lossy), (2, 1));
+        assert_eq!(stats.genres["Rock"], 2);
+        assert_eq!(stats.years[&1980], 1);
+    }
+}
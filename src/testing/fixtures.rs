@@ -0,0 +1,478 @@
+//! Realistic sample data for tests.
+//!
+//! The typed constructors return fully populated data types (a song with replay gain,
+//! contributors and MusicBrainz IDs, an album with its release dates and disc titles, …), so
+//! tests only spell out the fields they care about:
+//!
+//! ```
+//! use opensubsonic::testing::fixtures;
+//!
+//! let mut song = fixtures::song();
+//! song.title = "Love of My Life".to_owned();
+//! assert!(song.replay_gain.is_some());
+//! ```
+//!
+//! The same data is available as JSON (`song_json`, …) for custom responses with
+//! [`MockServer::respond_with`](super::MockServer::respond_with), and [`response`] returns
+//! whole `subsonic-response` documents the way a given server implementation sends them.
+//!
+//! All fixtures describe the library served by [`MockServer`](super::MockServer): artist
+//! `ar-1`, album `al-1`, song `tr-1`, video `vi-1`, playlist `pl-1`, share `sh-1`, podcast
+//! channel `pc-1` with episode `ep-1`, and user `test`.
+
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+use super::{canned_data, error_envelope, ok_envelope, with};
+use crate::ServerProfile;
+#[cfg(feature = "sharing")]
+use crate::data::Share;
+#[cfg(feature = "user-management")]
+use crate::data::User;
+use crate::data::{
+    AlbumId3, AlbumWithSongsId3, ArtistId3, ArtistInfo2, ArtistWithAlbumsId3, Child, Directory,
+    Playlist, PlaylistWithSongs,
+};
+#[cfg(feature = "podcast")]
+use crate::data::{PodcastChannel, PodcastEpisode};
+use crate::profile::ID_FIELDS;
+
+/// Decode a fixture, which is valid by construction.
+fn decode<T: DeserializeOwned>(json: Value) -> T {
+    serde_json::from_value(json).expect("fixture decodes")
+}
+
+/// Add the fields of `extra` to the object `base`.
+fn merge(mut base: Value, extra: Value) -> Value {
+    if let (Some(fields), Value::Object(extra)) = (base.as_object_mut(), extra) {
+        fields.extend(extra);
+    }
+    base
+}
+
+// ── Typed ───────────────────────────────────────────────────────────────────
+
+/// Artist `ar-1`.
+pub fn artist() -> ArtistId3 {
+    decode(artist_json())
+}
+
+/// Artist `ar-1` with its album, as returned by `getArtist`.
+pub fn artist_with_albums() -> ArtistWithAlbumsId3 {
+    decode(with(artist_json(), "album", json!([album_json()])))
+}
+
+/// Biography and similar artists of `ar-1`, as returned by `getArtistInfo2`.
+pub fn artist_info() -> ArtistInfo2 {
+    decode(artist_info_json())
+}
+
+/// Album `al-1`.
+pub fn album() -> AlbumId3 {
+    decode(album_json())
+}
+
+/// Album `al-1` with its song, as returned by `getAlbum`.
+pub fn album_with_songs() -> AlbumWithSongsId3 {
+    decode(with(album_json(), "song", json!([song_json()])))
+}
+
+/// The folder of album `al-1` with its song, as returned by `getMusicDirectory`.
+pub fn directory() -> Directory {
+    decode(json!({
+        "id": "al-1",
+        "parent": "ar-1",
+        "name": "A Night at the Opera",
+        "playCount": 12,
+        "child": [song_json()],
+    }))
+}
+
+/// Song `tr-1`, with every field a current server fills in.
+pub fn song() -> Child {
+    decode(song_json())
+}
+
+/// Video `vi-1`.
+pub fn video() -> Child {
+    decode(video_json())
+}
+
+/// Playlist `pl-1`, as listed by `getPlaylists`.
+pub fn playlist() -> Playlist {
+    decode(playlist_json())
+}
+
+/// Playlist `pl-1` with its song, as returned by `getPlaylist`.
+pub fn playlist_with_songs() -> PlaylistWithSongs {
+    decode(with(playlist_json(), "entry", json!([song_json()])))
+}
+
+/// Share `sh-1` of song `tr-1`.
+#[cfg(feature = "sharing")]
+pub fn share() -> Share {
+    decode(share_json())
+}
+
+/// Podcast channel `pc-1` with its episode.
+#[cfg(feature = "podcast")]
+pub fn podcast_channel() -> PodcastChannel {
+    decode(channel_json())
+}
+
+/// Podcast episode `ep-1`.
+#[cfg(feature = "podcast")]
+pub fn podcast_episode() -> PodcastEpisode {
+    decode(episode_json())
+}
+
+/// User `test`, an administrator.
+#[cfg(feature = "user-management")]
+pub fn user() -> User {
+    decode(user_json())
+}
+
+// ── Responses ───────────────────────────────────────────────────────────────
+
+/// The complete `subsonic-response` document `server` sends for `endpoint`, or `None` for
+/// unknown and binary endpoints.
+///
+/// The envelope carries the server's `type`, `serverVersion` and protocol version, and the
+/// data its quirks: Ampache and Funkwhale send IDs as numbers, and LMS rejects `getIndexes`.
+/// [`ServerProfile::Generic`] gives the responses of [`MockServer`](super::MockServer).
+pub fn response(endpoint: &str, server: ServerProfile) -> Option<Value> {
+    let mut data = canned_data(endpoint)?;
+    let (server_type, server_version, version, open_subsonic) = match server {
+        ServerProfile::Generic => return Some(ok_envelope(data)),
+        ServerProfile::Navidrome => ("navidrome", "0.53.3 (13af8ed4)", "1.16.1", true),
+        ServerProfile::Gonic => ("gonic", "0.16.4", "1.15.0", true),
+        ServerProfile::AirsonicAdvanced => ("airsonic-advanced", "11.1.4", "1.15.0", false),
+        ServerProfile::Ampache => ("ampache", "6.6.0", "1.16.1", true),
+        ServerProfile::Lms => ("lms", "3.58.0", "1.16.0", true),
+        ServerProfile::Funkwhale => ("funkwhale", "1.4.0", "1.16.0", false),
+    };
+    if server == ServerProfile::Lms && endpoint == "getIndexes" {
+        return Some(error_envelope(0, "Not implemented"));
+    }
+    if server.numeric_ids() {
+        numeric_ids("", &mut data);
+    }
+    let mut envelope = ok_envelope(data);
+    let fields = &mut envelope["subsonic-response"];
+    fields["type"] = json!(server_type);
+    fields["serverVersion"] = json!(server_version);
+    fields["version"] = json!(version);
+    fields["openSubsonic"] = json!(open_subsonic);
+    Some(envelope)
+}
+
+/// Replace the fixture IDs (`al-1`, `tr-ep-1`, …) in `value`, the value of `key`, with their
+/// number.
+fn numeric_ids(key: &str, value: &mut Value) {
+    match value {
+        Value::String(id) if ID_FIELDS.contains(&key) => {
+            if let Some(n) = id.rsplit('-').next().and_then(|n| n.parse::<u64>().ok()) {
+                *value = json!(n);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| numeric_ids(key, item)),
+        Value::Object(fields) => fields.iter_mut().for_each(|(k, v)| numeric_ids(k, v)),
+        _ => {}
+    }
+}
+
+// ── JSON ────────────────────────────────────────────────────────────────────
+
+/// JSON of [`artist`].
+pub fn artist_json() -> Value {
+    json!({
+        "id": "ar-1",
+        "name": "Queen",
+        "coverArt": "ar-1",
+        "artistImageUrl": "https://music.example.com/images/ar-1.jpg",
+        "albumCount": 1,
+        "musicBrainzId": "0383dadf-2a4e-4d10-a46a-e9e041da8eb3",
+        "sortName": "Queen",
+        "roles": ["artist", "albumartist", "composer"],
+    })
+}
+
+/// JSON of [`artist_info`].
+pub fn artist_info_json() -> Value {
+    json!({
+        "biography": "British rock band formed in London in 1970.",
+        "musicBrainzId": "0383dadf-2a4e-4d10-a46a-e9e041da8eb3",
+        "lastFmUrl": "https://www.last.fm/music/Queen",
+        "smallImageUrl": "https://music.example.com/images/ar-1-small.jpg",
+        "mediumImageUrl": "https://music.example.com/images/ar-1-medium.jpg",
+        "largeImageUrl": "https://music.example.com/images/ar-1-large.jpg",
+        "similarArtist": [{ "id": "ar-2", "name": "David Bowie", "albumCount": 0 }],
+    })
+}
+
+/// JSON of [`album`].
+pub fn album_json() -> Value {
+    json!({
+        "id": "al-1",
+        "name": "A Night at the Opera",
+        "artist": "Queen",
+        "artistId": "ar-1",
+        "coverArt": "al-1",
+        "songCount": 1,
+        "duration": 355,
+        "playCount": 12,
+        "created": "2024-01-01T00:00:00.000Z",
+        "year": 1975,
+        "genre": "Rock",
+        "played": "2024-03-01T20:15:00.000Z",
+        "userRating": 5,
+        "recordLabels": [{ "name": "EMI" }],
+        "musicBrainzId": "f4a0e0f9-a7ff-3c3c-9dc5-6d1e8d0a6b6c",
+        "genres": [{ "name": "Rock" }, { "name": "Progressive Rock" }],
+        "artists": [{ "id": "ar-1", "name": "Queen" }],
+        "displayArtist": "Queen",
+        "releaseTypes": ["Album"],
+        "originalReleaseDate": { "year": 1975, "month": 11, "day": 21 },
+        "releaseDate": { "year": 2011, "month": 3, "day": 14 },
+        "isCompilation": false,
+        "sortName": "Night at the Opera, A",
+        "discTitles": [{ "disc": 1, "title": "" }],
+        "explicitStatus": "clean",
+        "moods": ["Dramatic"],
+    })
+}
+
+/// JSON of the folder-based album `al-1`, as listed by `getAlbumList`.
+pub fn folder_json() -> Value {
+    json!({
+        "id": "al-1",
+        "parent": "ar-1",
+        "isDir": true,
+        "title": "A Night at the Opera",
+        "album": "A Night at the Opera",
+        "artist": "Queen",
+        "year": 1975,
+        "genre": "Rock",
+        "coverArt": "al-1",
+        "created": "2024-01-01T00:00:00.000Z",
+    })
+}
+
+/// JSON of [`song`].
+pub fn song_json() -> Value {
+    let song = json!({
+        "id": "tr-1",
+        "parent": "al-1",
+        "isDir": false,
+        "title": "Bohemian Rhapsody",
+        "album": "A Night at the Opera",
+        "artist": "Queen",
+        "track": 11,
+        "year": 1975,
+        "genre": "Rock",
+        "coverArt": "al-1",
+        "size": 8_520_000,
+        "contentType": "audio/mpeg",
+        "suffix": "mp3",
+        "duration": 355,
+        "bitRate": 192,
+        "bitDepth": 16,
+        "samplingRate": 44_100,
+        "channelCount": 2,
+        "path": "Queen/A Night at the Opera/11 - Bohemian Rhapsody.mp3",
+        "isVideo": false,
+        "userRating": 5,
+        "averageRating": 4.5,
+        "playCount": 12,
+        "discNumber": 1,
+        "created": "2024-01-01T00:00:00.000Z",
+        "albumId": "al-1",
+        "artistId": "ar-1",
+        "type": "music",
+    });
+    // OpenSubsonic additions.
+    let extensions = json!({
+        "mediaType": "song",
+        "played": "2024-03-01T20:15:00.000Z",
+        "bpm": 72,
+        "comment": "Remastered 2011",
+        "sortName": "Bohemian Rhapsody",
+        "musicBrainzId": "ebf79ba5-085e-48d2-9eb8-2d992fbf0f6d",
+        "isrc": ["GBUM71029604"],
+        "genres": [{ "name": "Rock" }],
+        "artists": [{ "id": "ar-1", "name": "Queen" }],
+        "displayArtist": "Queen",
+        "albumArtists": [{ "id": "ar-1", "name": "Queen" }],
+        "displayAlbumArtist": "Queen",
+        "contributors": [
+            { "role": "composer", "artist": { "id": "ar-3", "name": "Freddie Mercury" } },
+            {
+                "role": "performer",
+                "subRole": "piano",
+                "artist": { "id": "ar-3", "name": "Freddie Mercury" },
+            },
+        ],
+        "displayComposer": "Freddie Mercury",
+        "moods": ["Dramatic"],
+        "replayGain": {
+            "trackGain": -6.2,
+            "albumGain": -5.8,
+            "trackPeak": 0.98,
+            "albumPeak": 1.0,
+        },
+        "explicitStatus": "clean",
+    });
+    merge(song, extensions)
+}
+
+/// JSON of [`video`].
+pub fn video_json() -> Value {
+    json!({
+        "id": "vi-1",
+        "isDir": false,
+        "title": "Live Aid",
+        "size": 734_003_200,
+        "contentType": "video/mp4",
+        "suffix": "mp4",
+        "duration": 1260,
+        "bitRate": 4500,
+        "path": "Videos/Queen - Live Aid.mp4",
+        "isVideo": true,
+        "created": "2024-01-01T00:00:00.000Z",
+        "type": "video",
+        "originalWidth": 1920,
+        "originalHeight": 1080,
+    })
+}
+
+/// JSON of [`playlist`].
+pub fn playlist_json() -> Value {
+    json!({
+        "id": "pl-1",
+        "name": "Favourites",
+        "comment": "Songs to sing along to",
+        "owner": "test",
+        "public": false,
+        "songCount": 1,
+        "duration": 355,
+        "created": "2024-01-01T00:00:00.000Z",
+        "changed": "2024-01-01T00:00:00.000Z",
+        "coverArt": "pl-1",
+        "allowedUser": ["guest"],
+        "readonly": false,
+    })
+}
+
+/// JSON of share `sh-1`.
+pub fn share_json() -> Value {
+    json!({
+        "id": "sh-1",
+        "url": "https://music.example.com/share/sh-1",
+        "description": "Listen to this",
+        "username": "test",
+        "created": "2024-01-01T00:00:00.000Z",
+        "expires": "2025-01-01T00:00:00.000Z",
+        "lastVisited": "2024-02-01T00:00:00.000Z",
+        "visitCount": 3,
+        "entry": [song_json()],
+    })
+}
+
+/// JSON of podcast channel `pc-1`.
+pub fn channel_json() -> Value {
+    json!({
+        "id": "pc-1",
+        "url": "https://podcasts.example.com/feed.xml",
+        "title": "Example Podcast",
+        "description": "Conversations about music.",
+        "coverArt": "pc-1",
+        "originalImageUrl": "https://podcasts.example.com/cover.jpg",
+        "status": "completed",
+        "episode": [episode_json()],
+    })
+}
+
+/// JSON of podcast episode `ep-1`.
+pub fn episode_json() -> Value {
+    json!({
+        "id": "ep-1",
+        "parent": "pc-1",
+        "isDir": false,
+        "title": "Episode 1",
+        "album": "Example Podcast",
+        "coverArt": "pc-1",
+        "size": 28_800_000,
+        "contentType": "audio/mpeg",
+        "suffix": "mp3",
+        "duration": 1800,
+        "bitRate": 128,
+        "type": "podcast",
+        "streamId": "tr-ep-1",
+        "channelId": "pc-1",
+        "description": "The making of A Night at the Opera.",
+        "status": "completed",
+        "publishDate": "2024-01-01T00:00:00.000Z",
+    })
+}
+
+/// JSON of the status of the jukebox, which plays song `tr-1`.
+pub fn jukebox_status_json() -> Value {
+    json!({ "currentIndex": 0, "playing": false, "gain": 0.5, "volume": 0.5, "position": 0 })
+}
+
+/// JSON of user `test`.
+pub fn user_json() -> Value {
+    json!({
+        "username": "test",
+        "email": "test@example.com",
+        "scrobblingEnabled": true,
+        "maxBitRate": 0,
+        "adminRole": true,
+        "settingsRole": true,
+        "downloadRole": true,
+        "uploadRole": true,
+        "playlistRole": true,
+        "coverArtRole": true,
+        "commentRole": true,
+        "podcastRole": true,
+        "streamRole": true,
+        "jukeboxRole": true,
+        "shareRole": true,
+        "videoConversionRole": false,
+        "avatarLastChanged": "2024-01-01T00:00:00.000Z",
+        "folder": [1],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Indexes;
+
+    #[test]
+    fn fixtures_are_populated() {
+        let song = song();
+        assert!(song.replay_gain.is_some() && song.contributors.is_some());
+        assert_eq!(album_with_songs().song, vec![song.clone()]);
+        assert_eq!(playlist_with_songs().entry, vec![song]);
+        assert_eq!(artist_with_albums().album, vec![album()]);
+        #[cfg(feature = "podcast")]
+        assert_eq!(podcast_channel().episode, vec![podcast_episode()]);
+    }
+
+    #[test]
+    fn responses_follow_the_server() {
+        let navidrome = response("getSong", ServerProfile::Navidrome).unwrap();
+        assert_eq!(navidrome["subsonic-response"]["type"], "navidrome");
+
+        let ampache = response("getArtists", ServerProfile::Ampache).unwrap();
+        let artist = &ampache["subsonic-response"]["artists"]["index"][0]["artist"][0];
+        assert_eq!(artist["id"], 1);
+
+        let lms = response("getIndexes", ServerProfile::Lms).unwrap();
+        assert_eq!(lms["subsonic-response"]["status"], "failed");
+        let generic = response("getIndexes", ServerProfile::Generic).unwrap();
+        let data = generic["subsonic-response"]["indexes"].clone();
+        assert_eq!(decode::<Indexes>(data).index.len(), 1);
+    }
+}
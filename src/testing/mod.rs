@@ -0,0 +1,529 @@
+//! Mock Subsonic server for testing applications (requires the `testing` feature).
+//!
+//! [`MockServer`] runs a local HTTP server that answers every endpoint of the API with a
+//! canned, well-formed response, so code built on [`Client`] can be tested without a real
+//! server. Individual endpoints can be overridden with [`MockServer::respond_with`] and
+//! [`MockServer::fail_with`]. The data comes from [`fixtures`], which also provides populated
+//! data types for tests that don't go through a server.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), opensubsonic::Error> {
+//! use opensubsonic::testing::MockServer;
+//!
+//! let server = MockServer::start().await;
+//! let client = server.client();
+//!
+//! let album = client.get_album("al-1").await?;
+//! assert_eq!(album.song.len(), 1);
+//!
+//! server.fail_with("getSong", 70, "Song not found").await;
+//! assert!(client.get_song("missing").await.is_err());
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod fixtures;
+
+use serde_json::{Value, json};
+use wiremock::matchers::path_regex;
+use wiremock::{Mock, Request, Respond, ResponseTemplate};
+
+use crate::{Auth, Client};
+use fixtures::{
+    album_json, artist_info_json, artist_json, channel_json, episode_json, folder_json,
+    jukebox_status_json, playlist_json, share_json, song_json, user_json, video_json,
+};
+
+/// Priority of the canned responses; mocks mounted later with the default priority win.
+const CANNED_PRIORITY: u8 = 10;
+
+/// A local mock Subsonic server pre-loaded with canned responses for every endpoint.
+pub struct MockServer {
+    server: wiremock::MockServer,
+}
+
+impl MockServer {
+    /// Start a mock server answering every endpoint with the data from [`canned_data`] (and
+    /// small binary payloads for media endpoints).
+    pub async fn start() -> Self {
+        let server = wiremock::MockServer::start().await;
+        Mock::given(path_regex(r"/rest/[A-Za-z0-9.]+$"))
+            .respond_with(Canned)
+            .with_priority(CANNED_PRIORITY)
+            .mount(&server)
+            .await;
+        Self { server }
+    }
+
+    /// Base URL of the server (e.g. `http://127.0.0.1:41234`).
+    pub fn uri(&self) -> String {
+        self.server.uri()
+    }
+
+    /// A [`Client`] connected to this server with token authentication.
+    pub fn client(&self) -> Client {
+        Client::new(&self.uri(), Auth::token("test", "test"))
+            .expect("mock server URI is a valid URL")
+    }
+
+    /// Answer `endpoint` with a successful envelope containing the fields of `data`.
+    ///
+    /// ```no_run
+    /// # async fn example(server: &opensubsonic::testing::MockServer) {
+    /// server
+    ///     .respond_with("getScanStatus", serde_json::json!({"scanStatus": {"scanning": true}}))
+    ///     .await;
+    /// # }
+    /// ```
+    pub async fn respond_with(&self, endpoint: &str, data: Value) {
+        self.mount(
+            endpoint,
+            ResponseTemplate::new(200).set_body_json(ok_envelope(data)),
+        )
+        .await;
+    }
+
+    /// Answer `endpoint` with a failed envelope carrying the given Subsonic error.
+    pub async fn fail_with(&self, endpoint: &str, code: i32, message: &str) {
+        self.mount(
+            endpoint,
+            ResponseTemplate::new(200).set_body_json(error_envelope(code, message)),
+        )
+        .await;
+    }
+
+    /// Number of requests received for `endpoint` so far.
+    pub async fn calls(&self, endpoint: &str) -> usize {
+        self.server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .filter(|r| endpoint_of(r) == Some(endpoint))
+            .count()
+    }
+
+    /// The underlying [`wiremock::MockServer`], for custom matchers and verification.
+    pub fn inner(&self) -> &wiremock::MockServer {
+        &self.server
+    }
+
+    async fn mount(&self, endpoint: &str, response: ResponseTemplate) {
+        let pattern = format!(r"/rest/{}(\.view)?$", endpoint.replace('.', r"\."));
+        Mock::given(path_regex(pattern))
+            .respond_with(response)
+            .mount(&self.server)
+            .await;
+    }
+}
+
+/// Wrap `data` in a successful `subsonic-response` envelope.
+pub fn ok_envelope(data: Value) -> Value {
+    let mut response = json!({
+        "status": "ok",
+        "version": "1.16.1",
+        "type": "opensubsonic-mock",
+        "serverVersion": env!("CARGO_PKG_VERSION"),
+        "openSubsonic": true,
+    });
+    if let (Some(response), Value::Object(fields)) = (response.as_object_mut(), data) {
+        response.extend(fields);
+    }
+    json!({ "subsonic-response": response })
+}
+
+/// A failed `subsonic-response` envelope with the given error.
+pub fn error_envelope(code: i32, message: &str) -> Value {
+    json!({
+        "subsonic-response": {
+            "status": "failed",
+            "version": "1.16.1",
+            "error": { "code": code, "message": message },
+        }
+    })
+}
+
+/// The endpoint name of a request path (`/rest/getAlbum.view` → `getAlbum`).
+fn endpoint_of(request: &Request) -> Option<&str> {
+    let name = request.url.path().rsplit('/').next()?;
+    Some(name.strip_suffix(".view").unwrap_or(name))
+}
+
+/// Responder serving the canned responses.
+struct Canned;
+
+impl Respond for Canned {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let Some(endpoint) = endpoint_of(request) else {
+            return ResponseTemplate::new(404);
+        };
+        if let Some((content_type, body)) = canned_binary(endpoint) {
+            return ResponseTemplate::new(200)
+                .insert_header("Content-Type", content_type)
+                .set_body_bytes(body);
+        }
+        let query = |name: &str| {
+            request
+                .url
+                .query_pairs()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.into_owned())
+        };
+        let data = if endpoint == "jukeboxControl" && query("action").as_deref() != Some("get") {
+            Some(json!({ "jukeboxStatus": jukebox_status_json() }))
+        } else {
+            canned_data(endpoint)
+        };
+        let Some(mut data) = data else {
+            return ResponseTemplate::new(200)
+                .set_body_json(error_envelope(0, "Unknown endpoint in mock server"));
+        };
+        // Paginated callers stop at the first empty page.
+        let paged = request
+            .url
+            .query_pairs()
+            .any(|(k, v)| k.ends_with("ffset") && v != "0");
+        if paged {
+            empty_lists(&mut data);
+        }
+        ResponseTemplate::new(200).set_body_json(ok_envelope(data))
+    }
+}
+
+/// Remove all lists from the top-level containers of a response.
+fn empty_lists(data: &mut Value) {
+    if let Some(containers) = data.as_object_mut() {
+        for container in containers.values_mut() {
+            if let Some(fields) = container.as_object_mut() {
+                fields.retain(|_, v| !v.is_array());
+            }
+        }
+    }
+}
+
+/// Content type and body of the canned response for a binary endpoint.
+fn canned_binary(endpoint: &str) -> Option<(&'static str, Vec<u8>)> {
+    Some(match endpoint {
+        "stream" | "download" | "getTranscodeStream" => {
+            ("audio/mpeg", b"ID3\x04\x00\x00\x00\x00\x00\x00".to_vec())
+        }
+        "getCoverArt" | "getAvatar" => ("image/png", b"\x89PNG\r\n\x1a\n".to_vec()),
+        "getCaptions" => (
+            "text/vtt",
+            b"WEBVTT\n\n00:00.000 --> 00:02.000\nHello\n".to_vec(),
+        ),
+        "hls.m3u8" => (
+            "application/vnd.apple.mpegurl",
+            b"#EXTM3U\n#EXT-X-TARGETDURATION:10\n#EXTINF:10,\nsegment-0.ts\n#EXT-X-ENDLIST\n"
+                .to_vec(),
+        ),
+        _ => return None,
+    })
+}
+
+/// The canned response data (the fields inside `subsonic-response`) for `endpoint`, or
+/// `None` for unknown endpoints and binary endpoints.
+///
+/// All responses describe the same small library: artist `ar-1`, album `al-1`, song `tr-1`,
+/// playlist `pl-1`, podcast channel `pc-1` with episode `ep-1`, and user `test`.
+pub fn canned_data(endpoint: &str) -> Option<Value> {
+    let data = match endpoint {
+        // System
+        "ping" => json!({}),
+        "getLicense" => json!({ "license": { "valid": true, "email": "test@example.com" } }),
+        "getOpenSubsonicExtensions" => json!({
+            "openSubsonicExtensions": [
+                { "name": "formPost", "versions": [1] },
+                { "name": "songLyrics", "versions": [1] },
+                { "name": "transcodeOffset", "versions": [1] },
+            ]
+        }),
+        "tokenInfo" => json!({ "tokenInfo": { "username": "test" } }),
+
+        // Browsing
+        "getMusicFolders" => {
+            json!({ "musicFolders": { "musicFolder": [{ "id": 1, "name": "Music" }] } })
+        }
+        "getIndexes" => json!({
+            "indexes": {
+                "lastModified": 1_700_000_000_000_i64,
+                "ignoredArticles": "The El La Los Las Le Les",
+                "index": [{ "name": "Q", "artist": [{ "id": "ar-1", "name": "Queen" }] }],
+            }
+        }),
+        "getMusicDirectory" => json!({
+            "directory": { "id": "al-1", "parent": "ar-1", "name": "A Night at the Opera", "child": [song_json()] }
+        }),
+        "getGenres" => {
+            json!({ "genres": { "genre": [{ "value": "Rock", "songCount": 1, "albumCount": 1 }] } })
+        }
+        "getArtists" => json!({
+            "artists": { "ignoredArticles": "The", "index": [{ "name": "Q", "artist": [artist_json()] }] }
+        }),
+        "getArtist" => json!({ "artist": with(artist_json(), "album", json!([album_json()])) }),
+        "getAlbum" => json!({ "album": with(album_json(), "song", json!([song_json()])) }),
+        "getSong" => json!({ "song": song_json() }),
+        "getVideos" => json!({ "videos": { "video": [video_json()] } }),
+        "getVideoInfo" => json!({ "videoInfo": { "id": "vi-1" } }),
+        "getArtistInfo" => json!({ "artistInfo": artist_info_json() }),
+        "getArtistInfo2" => json!({ "artistInfo2": artist_info_json() }),
+        "getAlbumInfo" | "getAlbumInfo2" => json!({
+            "albumInfo": { "notes": "Fourth studio album by Queen.", "musicBrainzId": "mbid-al-1" }
+        }),
+        "getSimilarSongs" => json!({ "similarSongs": { "song": [song_json()] } }),
+        "getSimilarSongs2" => json!({ "similarSongs2": { "song": [song_json()] } }),
+        "getTopSongs" => json!({ "topSongs": { "song": [song_json()] } }),
+
+        // Lists
+        "getAlbumList" => json!({ "albumList": { "album": [folder_json()] } }),
+        "getAlbumList2" => json!({ "albumList2": { "album": [album_json()] } }),
+        "getRandomSongs" => json!({ "randomSongs": { "song": [song_json()] } }),
+        "getSongsByGenre" => json!({ "songsByGenre": { "song": [song_json()] } }),
+        "getNowPlaying" => json!({
+            "nowPlaying": {
+                "entry": [with(with(song_json(), "username", json!("test")), "minutesAgo", json!(0))]
+            }
+        }),
+        "getStarred" => json!({
+            "starred": {
+                "artist": [{ "id": "ar-1", "name": "Queen" }],
+                "album": [folder_json()],
+                "song": [song_json()],
+            }
+        }),
+        "getStarred2" => json!({
+            "starred2": { "artist": [artist_json()], "album": [album_json()], "song": [song_json()] }
+        }),
+
+        // Searching
+        "search" => {
+            json!({ "searchResult": { "offset": 0, "totalHits": 1, "match": [song_json()] } })
+        }
+        "search2" => json!({
+            "searchResult2": {
+                "artist": [{ "id": "ar-1", "name": "Queen" }],
+                "album": [folder_json()],
+                "song": [song_json()],
+            }
+        }),
+        "search3" => json!({
+            "searchResult3": { "artist": [artist_json()], "album": [album_json()], "song": [song_json()] }
+        }),
+
+        // Playlists
+        "getPlaylists" => json!({ "playlists": { "playlist": [playlist_json()] } }),
+        "getPlaylist" | "createPlaylist" => {
+            json!({ "playlist": with(playlist_json(), "entry", json!([song_json()])) })
+        }
+
+        // Media retrieval
+        "getLyrics" => json!({
+            "lyrics": {
+                "artist": "Queen",
+                "title": "Bohemian Rhapsody",
+                "value": "Is this the real life?\nIs this just fantasy?",
+            }
+        }),
+        "getLyricsBySongId" => json!({
+            "lyricsList": {
+                "structuredLyrics": [{
+                    "lang": "eng",
+                    "synced": true,
+                    "line": [
+                        { "start": 0, "value": "Is this the real life?" },
+                        { "start": 3500, "value": "Is this just fantasy?" },
+                    ],
+                }]
+            }
+        }),
+
+        // Sharing
+        "getShares" | "createShare" => json!({ "shares": { "share": [share_json()] } }),
+
+        // Podcast
+        "getPodcasts" => json!({ "podcasts": { "channel": [channel_json()] } }),
+        "getNewestPodcasts" => json!({ "newestPodcasts": { "episode": [episode_json()] } }),
+        "getPodcastEpisode" => json!({ "podcastEpisode": episode_json() }),
+
+        // Jukebox
+        "jukeboxControl" => json!({
+            "jukeboxPlaylist": with(jukebox_status_json(), "entry", json!([song_json()]))
+        }),
+
+        // Internet radio
+        "getInternetRadioStations" => json!({
+            "internetRadioStations": {
+                "internetRadioStation": [{
+                    "id": "ir-1",
+                    "name": "Example Radio",
+                    "streamUrl": "https://radio.example.com/stream",
+                    "homePageUrl": "https://radio.example.com",
+                }]
+            }
+        }),
+
+        // Chat
+        "getChatMessages" => json!({
+            "chatMessages": {
+                "chatMessage": [{ "username": "test", "time": 1_700_000_000_000_i64, "message": "Hello" }]
+            }
+        }),
+
+        // User management
+        "getUser" => json!({ "user": user_json() }),
+        "getUsers" => json!({ "users": { "user": [user_json()] } }),
+
+        // Bookmarks
+        "getBookmarks" => json!({
+            "bookmarks": {
+                "bookmark": [{
+                    "position": 60_000,
+                    "username": "test",
+                    "created": "2024-01-01T00:00:00.000Z",
+                    "changed": "2024-01-01T00:00:00.000Z",
+                    "entry": song_json(),
+                }]
+            }
+        }),
+        "getPlayQueue" => json!({
+            "playQueue": {
+                "current": "tr-1",
+                "position": 0,
+                "username": "test",
+                "changed": "2024-01-01T00:00:00.000Z",
+                "changedBy": "opensubsonic-rs",
+                "entry": [song_json()],
+            }
+        }),
+        "getPlayQueueByIndex" => json!({
+            "playQueueByIndex": {
+                "currentIndex": 0,
+                "position": 0,
+                "username": "test",
+                "changed": "2024-01-01T00:00:00.000Z",
+                "changedBy": "opensubsonic-rs",
+                "entry": [song_json()],
+            }
+        }),
+
+        // Scanning
+        "getScanStatus" | "startScan" => json!({ "scanStatus": { "scanning": false, "count": 1 } }),
+
+        // Transcoding
+        "getTranscodeDecision" => json!({
+            "transcodeDecision": { "canDirectPlay": true, "canTranscode": true }
+        }),
+
+        // Sonic similarity
+        "getSonicSimilarTracks" => json!({
+            "sonicSimilarTracks": { "sonicMatch": [with(song_json(), "similarity", json!(0.9))] }
+        }),
+        "findSonicPath" => json!({
+            "sonicPath": { "sonicMatch": [with(song_json(), "similarity", json!(1.0))] }
+        }),
+
+        // Endpoints that only acknowledge the request.
+        "updatePlaylist"
+        | "deletePlaylist"
+        | "star"
+        | "unstar"
+        | "setRating"
+        | "scrobble"
+        | "reportPlayback"
+        | "updateShare"
+        | "deleteShare"
+        | "refreshPodcasts"
+        | "createPodcastChannel"
+        | "deletePodcastChannel"
+        | "deletePodcastEpisode"
+        | "downloadPodcastEpisode"
+        | "createInternetRadioStation"
+        | "updateInternetRadioStation"
+        | "deleteInternetRadioStation"
+        | "addChatMessage"
+        | "createUser"
+        | "updateUser"
+        | "deleteUser"
+        | "changePassword"
+        | "createBookmark"
+        | "deleteBookmark"
+        | "savePlayQueue"
+        | "savePlayQueueByIndex" => json!({}),
+
+        _ => return None,
+    };
+    Some(data)
+}
+
+/// Set `key` of a JSON object.
+fn with(mut object: Value, key: &str, value: Value) -> Value {
+    if let Some(fields) = object.as_object_mut() {
+        fields.insert(key.to_owned(), value);
+    }
+    object
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::TryStreamExt;
+
+    use super::*;
+    use crate::AlbumListParams;
+    use crate::api::lists::AlbumListType;
+
+    #[tokio::test]
+    async fn canned_responses_decode() {
+        let server = MockServer::start().await;
+        let client = server.client();
+
+        client.ping().await.unwrap();
+        assert_eq!(client.get_album("al-1").await.unwrap().song.len(), 1);
+        assert_eq!(client.get_artists(None).await.unwrap().index.len(), 1);
+        let discography = client.get_artist_discography("ar-1").await.unwrap();
+        assert_eq!(discography.albums[0].song.len(), 1);
+        assert_eq!(client.get_playlist("pl-1").await.unwrap().entry.len(), 1);
+        #[cfg(feature = "podcast")]
+        assert_eq!(client.get_podcasts(None, None).await.unwrap().len(), 1);
+        #[cfg(feature = "jukebox")]
+        assert!(client.jukebox().get().await.unwrap().entry.len() == 1);
+        assert!(
+            !client
+                .stream("tr-1", None, None, None, None)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let albums: Vec<_> = client
+            .album_list2_stream(AlbumListType::Newest, AlbumListParams::new())
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(albums.len(), 1);
+        assert_eq!(server.calls("getAlbumList2").await, 2);
+    }
+
+    #[tokio::test]
+    async fn overrides_take_precedence() {
+        let server = MockServer::start().await;
+        let client = server.client();
+
+        server.fail_with("getSong", 70, "Song not found").await;
+        match client.get_song("tr-1").await {
+            Err(e) => {
+                assert_eq!(e.api_error().map(|api| api.code), Some(70));
+                assert_eq!(e.endpoint(), Some("getSong"));
+                assert!(e.url().is_some_and(|url| url.as_str().contains("id=tr-1")));
+            }
+            other => panic!("expected an API error, got {other:?}"),
+        }
+
+        server
+            .respond_with(
+                "getScanStatus",
+                json!({ "scanStatus": { "scanning": true } }),
+            )
+            .await;
+        assert!(client.get_scan_status().await.unwrap().scanning);
+    }
+}
@@ -0,0 +1,86 @@
+//! Spans for API calls and HTTP requests (emitted with the `tracing` feature).
+//!
+//! Every API call runs in a `subsonic.call` span (fields `endpoint`, `duration_ms`,
+//! `error.code`, `error`) and every HTTP request in a nested `subsonic.http` span (fields
+//! `endpoint`, `http.method`, `http.status`, `duration_ms`). Without the feature these
+//! helpers just await the wrapped future.
+
+use std::future::Future;
+
+use crate::error::Error;
+
+/// Run an API call in a `subsonic.call` span recording its duration and outcome.
+///
+/// `endpoint` may be empty when it is not known up front; it is then filled in by
+/// [`http`] once the request is built.
+pub(crate) async fn call<T>(
+    endpoint: &str,
+    call: impl Future<Output = Result<T, Error>>,
+) -> Result<T, Error> {
+    #[cfg(feature = "tracing")]
+    {
+        use tracing::Instrument;
+        use tracing::field::{Empty, display};
+
+        let span = tracing::info_span!(
+            "subsonic.call",
+            endpoint = Empty,
+            duration_ms = Empty,
+            error.code = Empty,
+            error = Empty,
+        );
+        if !endpoint.is_empty() {
+            span.record("endpoint", endpoint);
+        }
+        let start = std::time::Instant::now();
+        let result = call.instrument(span.clone()).await;
+        span.record("duration_ms", start.elapsed().as_millis() as u64);
+        if let Err(e) = &result {
+            if let Some(api) = e.api_error() {
+                span.record("error.code", api.code);
+            }
+            span.record("error", display(e));
+        }
+        result
+    }
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = endpoint;
+        call.await
+    }
+}
+
+/// Run an HTTP request in a `subsonic.http` span recording its duration and status.
+pub(crate) async fn http(
+    endpoint: &str,
+    method: &reqwest::Method,
+    request: impl Future<Output = Result<reqwest::Response, Error>>,
+) -> Result<reqwest::Response, Error> {
+    #[cfg(feature = "tracing")]
+    {
+        use tracing::Instrument;
+        use tracing::field::Empty;
+
+        // Name the enclosing call span if it did not know its endpoint.
+        tracing::Span::current().record("endpoint", endpoint);
+        let span = tracing::debug_span!(
+            "subsonic.http",
+            endpoint,
+            http.method = %method,
+            http.status = Empty,
+            duration_ms = Empty,
+        );
+        let start = std::time::Instant::now();
+        let result = request.instrument(span.clone()).await;
+        span.record("duration_ms", start.elapsed().as_millis() as u64);
+        if let Ok(resp) = &result {
+            span.record("http.status", resp.status().as_u16());
+        }
+        result
+    }
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = (endpoint, method);
+        request.await
+    }
+}
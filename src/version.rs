@@ -0,0 +1,216 @@
+//! API version negotiation.
+//!
+//! Endpoints were added to the Subsonic API over time. With
+//! [`Client::with_version_negotiation`](crate::Client::with_version_negotiation) enabled,
+//! once a response has told the client which protocol version the server implements, calls
+//! to newer endpoints are either sent to an older equivalent and their results converted,
+//! or fail with [`Error::UnsupportedByServer`] without a request being made.
+
+use serde_json::{Map, Value};
+
+use crate::error::Error;
+
+/// Endpoints newer than 1.0.0 and the protocol version that introduced them.
+const MIN_VERSIONS: [(&str, &str); 63] = [
+    ("addChatMessage", "1.2.0"),
+    ("changePassword", "1.1.0"),
+    ("createBookmark", "1.9.0"),
+    ("createInternetRadioStation", "1.16.0"),
+    ("createPlaylist", "1.2.0"),
+    ("createPodcastChannel", "1.9.0"),
+    ("createShare", "1.6.0"),
+    ("createUser", "1.1.0"),
+    ("deleteBookmark", "1.9.0"),
+    ("deleteInternetRadioStation", "1.16.0"),
+    ("deletePlaylist", "1.2.0"),
+    ("deletePodcastChannel", "1.9.0"),
+    ("deletePodcastEpisode", "1.9.0"),
+    ("deleteShare", "1.6.0"),
+    ("deleteUser", "1.3.0"),
+    ("downloadPodcastEpisode", "1.9.0"),
+    ("getAlbum", "1.8.0"),
+    ("getAlbumInfo", "1.14.0"),
+    ("getAlbumInfo2", "1.14.0"),
+    ("getAlbumList", "1.2.0"),
+    ("getAlbumList2", "1.8.0"),
+    ("getArtist", "1.8.0"),
+    ("getArtistInfo", "1.11.0"),
+    ("getArtistInfo2", "1.11.0"),
+    ("getArtists", "1.8.0"),
+    ("getAvatar", "1.8.0"),
+    ("getBookmarks", "1.9.0"),
+    ("getCaptions", "1.14.0"),
+    ("getChatMessages", "1.2.0"),
+    ("getGenres", "1.9.0"),
+    ("getInternetRadioStations", "1.9.0"),
+    ("getLyrics", "1.2.0"),
+    ("getNewestPodcasts", "1.13.0"),
+    ("getPlayQueue", "1.12.0"),
+    ("getPodcasts", "1.6.0"),
+    ("getRandomSongs", "1.2.0"),
+    ("getScanStatus", "1.15.0"),
+    ("getShares", "1.6.0"),
+    ("getSimilarSongs", "1.11.0"),
+    ("getSimilarSongs2", "1.11.0"),
+    ("getSong", "1.8.0"),
+    ("getSongsByGenre", "1.9.0"),
+    ("getStarred", "1.8.0"),
+    ("getStarred2", "1.8.0"),
+    ("getTopSongs", "1.13.0"),
+    ("getUser", "1.3.0"),
+    ("getUsers", "1.8.0"),
+    ("getVideoInfo", "1.14.0"),
+    ("getVideos", "1.8.0"),
+    ("hls", "1.8.0"),
+    ("jukeboxControl", "1.2.0"),
+    ("refreshPodcasts", "1.9.0"),
+    ("savePlayQueue", "1.12.0"),
+    ("scrobble", "1.5.0"),
+    ("search2", "1.4.0"),
+    ("search3", "1.8.0"),
+    ("setRating", "1.6.0"),
+    ("star", "1.8.0"),
+    ("startScan", "1.15.0"),
+    ("unstar", "1.8.0"),
+    ("updateInternetRadioStation", "1.16.0"),
+    ("updatePlaylist", "1.8.0"),
+    ("updateUser", "1.10.1"),
+];
+
+/// Older endpoints taking the same parameters, used in place of the ID3 ones.
+///
+/// `getStarred2` and `getStarred` appeared in the same version, so there is nothing to fall
+/// back to for the former.
+const FALLBACKS: [Fallback; 2] = [
+    Fallback {
+        endpoint: "search3",
+        replacement: "search2",
+        from: "searchResult2",
+        to: "searchResult3",
+    },
+    Fallback {
+        endpoint: "getAlbumList2",
+        replacement: "getAlbumList",
+        from: "albumList",
+        to: "albumList2",
+    },
+];
+
+/// An older endpoint called in place of a newer one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Fallback {
+    /// The endpoint that was asked for.
+    endpoint: &'static str,
+    /// The endpoint called instead.
+    pub(crate) replacement: &'static str,
+    /// The response key of the replacement.
+    from: &'static str,
+    /// The response key of the endpoint asked for.
+    to: &'static str,
+}
+
+impl Fallback {
+    /// Convert the replacement's response data to the shape of the original endpoint's.
+    ///
+    /// Albums are folder-based [`Child`](crate::data::Child) entries, which become
+    /// [`AlbumId3`](crate::data::AlbumId3) ones named after their album or title; their IDs
+    /// remain directory IDs.
+    pub(crate) fn convert(self, data: &mut Map<String, Value>) {
+        let Some(mut value) = data.remove(self.from) else {
+            return;
+        };
+        if let Some(Value::Array(albums)) = value.get_mut("album") {
+            for album in albums.iter_mut().filter_map(Value::as_object_mut) {
+                if !album.contains_key("name") {
+                    let name = album.get("album").or_else(|| album.get("title")).cloned();
+                    album.insert("name".to_owned(), name.unwrap_or_default());
+                }
+            }
+        }
+        data.insert(self.to.to_owned(), value);
+    }
+}
+
+/// Check whether a server implementing protocol version `has` supports `endpoint`.
+///
+/// Returns the fallback to call instead if it only supports an older equivalent.
+///
+/// # Errors
+/// Returns [`Error::UnsupportedByServer`] if neither the endpoint nor a fallback is
+/// supported.
+pub(crate) fn negotiate(endpoint: &str, has: &str) -> Result<Option<Fallback>, Error> {
+    let Some(needs) = min_version(endpoint) else {
+        return Ok(None);
+    };
+    if at_least(has, needs) {
+        return Ok(None);
+    }
+    let fallback = FALLBACKS.into_iter().find(|f| f.endpoint == endpoint);
+    match fallback {
+        Some(f) if min_version(f.replacement).is_none_or(|v| at_least(has, v)) => Ok(Some(f)),
+        _ => Err(Error::UnsupportedByServer {
+            endpoint: endpoint.to_owned(),
+            needs: needs.to_owned(),
+            has: has.to_owned(),
+        }),
+    }
+}
+
+/// The protocol version that introduced `endpoint`, if newer than 1.0.0 and known.
+pub(crate) fn min_version(endpoint: &str) -> Option<&'static str> {
+    MIN_VERSIONS
+        .iter()
+        .find(|(name, _)| *name == endpoint)
+        .map(|(_, version)| *version)
+}
+
+/// Whether version `has` is `needs` or later. Unparsable versions are assumed recent.
+fn at_least(has: &str, needs: &str) -> bool {
+    match (parse(has), parse(needs)) {
+        (Some(has), Some(needs)) => has >= needs,
+        _ => true,
+    }
+}
+
+/// Parse `major.minor[.patch]`.
+fn parse(version: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = version.trim().split('.').map(str::parse::<u32>);
+    let major = parts.next()?.ok()?;
+    let minor = parts.next()?.ok()?;
+    let patch = parts.next().transpose().ok()?.unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn negotiates_endpoints() {
+        assert_eq!(negotiate("search3", "1.16.1").unwrap(), None);
+        assert_eq!(negotiate("ping", "1.0.0").unwrap(), None);
+        assert_eq!(
+            negotiate("search3", "1.4.0")
+                .unwrap()
+                .map(|f| f.replacement),
+            Some("search2")
+        );
+        let err = negotiate("getScanStatus", "1.13.0").unwrap_err();
+        assert!(matches!(err, Error::UnsupportedByServer { ref needs, .. } if needs == "1.15.0"));
+        assert!(negotiate("search3", "1.2.0").is_err());
+        assert!(at_least("1.10.1", "1.9.0"));
+        assert!(!at_least("1.10", "1.10.1"));
+    }
+
+    #[test]
+    fn converts_fallback_results() {
+        let fallback = negotiate("getAlbumList2", "1.5.0").unwrap().unwrap();
+        let mut data =
+            json!({ "albumList": { "album": [{ "id": "1", "title": "Jazz", "isDir": true }] } });
+        let data = data.as_object_mut().unwrap();
+        fallback.convert(data);
+        assert_eq!(data["albumList2"]["album"][0]["name"], "Jazz");
+    }
+}
@@ -0,0 +1,496 @@
+//! XML response support (`f=xml`) for servers whose JSON output is unusable.
+//!
+//! XML responses are converted into the same [`serde_json::Value`] shape the JSON format
+//! produces and then decoded through [`Lenient`], which papers over what XML cannot express
+//! (value types, and whether a single child element is a list of one). With the `server`
+//! feature, [`write_response`] turns that shape back into XML.
+
+#[cfg(feature = "server")]
+use quick_xml::Writer;
+use quick_xml::escape::resolve_predefined_entity;
+#[cfg(feature = "server")]
+use quick_xml::events::{BytesEnd, BytesText};
+use quick_xml::events::{BytesRef, BytesStart, Event};
+use quick_xml::{Reader, XmlVersion};
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{Deserializer, IntoDeserializer, Visitor};
+use serde_json::{Map, Value};
+
+use crate::error::Error;
+
+/// Nested elements that never repeat, kept as plain objects instead of one-element arrays
+/// (they can appear inside flattened structs, where [`Lenient`] cannot unwrap them).
+const SINGLE_ELEMENTS: &[&str] = &["replayGain", "originalReleaseDate", "releaseDate"];
+
+/// Parse an XML `subsonic-response` document into the JSON shape of its root element.
+///
+/// Attributes and child elements become object fields. Text content becomes a `value` field,
+/// or the element itself when it has no attributes or children. Numbers and booleans are
+/// inferred only where the text round-trips exactly. Below the top level every repeatable
+/// child element is collected into an array, since XML cannot tell a list of one from a
+/// single object.
+pub(crate) fn parse_response(text: &str) -> Result<Value, Error> {
+    let mut reader = Reader::from_str(text);
+    let mut stack: Vec<Element> = Vec::new();
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(e) => stack.push(Element::open(&e)?),
+            Event::Empty(e) => {
+                let element = Element::open(&e)?;
+                if let Some(root) = close(&mut stack, element) {
+                    return Ok(root);
+                }
+            }
+            Event::End(_) => {
+                let element = stack
+                    .pop()
+                    .ok_or_else(|| Error::Parse("Unbalanced XML response".into()))?;
+                if let Some(root) = close(&mut stack, element) {
+                    return Ok(root);
+                }
+            }
+            Event::Text(t) => push_text(&mut stack, &t.decode().map_err(xml_error)?),
+            Event::CData(t) => push_text(&mut stack, &t.decode().map_err(xml_error)?),
+            Event::GeneralRef(r) => push_text(&mut stack, &resolve_reference(&r)?),
+            Event::Eof => return Err(Error::Parse("Truncated XML response".into())),
+            _ => {}
+        }
+    }
+}
+
+/// Resolve a character or predefined entity reference (`&#10;`, `&amp;`, …).
+pub(crate) fn resolve_reference(reference: &BytesRef<'_>) -> Result<String, Error> {
+    let resolved = if reference.is_char_ref() {
+        reference
+            .resolve_char_ref()
+            .map_err(xml_error)?
+            .map(String::from)
+    } else {
+        let name = reference.decode().map_err(xml_error)?;
+        resolve_predefined_entity(&name).map(str::to_owned)
+    };
+    resolved.ok_or_else(|| Error::Parse("Unknown entity in XML document".into()))
+}
+
+/// An element whose end tag has not been read yet.
+struct Element {
+    name: String,
+    fields: Map<String, Value>,
+    text: String,
+}
+
+impl Element {
+    fn open(start: &BytesStart<'_>) -> Result<Self, Error> {
+        let mut fields = Map::new();
+        for attr in start.attributes() {
+            let attr = attr.map_err(xml_error)?;
+            if attr.key.as_namespace_binding().is_some() {
+                continue;
+            }
+            let key = String::from_utf8_lossy(attr.key.local_name().as_ref()).into_owned();
+            let value = attr
+                .normalized_value(XmlVersion::Implicit1_0)
+                .map_err(xml_error)?;
+            fields.insert(key, infer(&value));
+        }
+        Ok(Self {
+            name: String::from_utf8_lossy(start.local_name().as_ref()).into_owned(),
+            fields,
+            text: String::new(),
+        })
+    }
+
+    fn add_child(&mut self, name: String, value: Value, top_level: bool) {
+        let wrap = !top_level && !SINGLE_ELEMENTS.contains(&name.as_str());
+        match self.fields.get_mut(&name) {
+            Some(Value::Array(items)) => items.push(value),
+            Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+            None if wrap => {
+                self.fields.insert(name, Value::Array(vec![value]));
+            }
+            None => {
+                self.fields.insert(name, value);
+            }
+        }
+    }
+
+    fn into_value(self) -> Value {
+        let has_text = !self.text.trim().is_empty();
+        if self.fields.is_empty() && has_text {
+            return infer(&self.text);
+        }
+        let mut fields = self.fields;
+        if has_text {
+            fields.insert("value".into(), Value::String(self.text));
+        }
+        Value::Object(fields)
+    }
+}
+
+/// Attach a finished element to its parent, or return it if it was the root.
+fn close(stack: &mut [Element], element: Element) -> Option<Value> {
+    let top_level = stack.len() == 1;
+    let Some(parent) = stack.last_mut() else {
+        return Some(element.into_value());
+    };
+    let name = element.name.clone();
+    parent.add_child(name, element.into_value(), top_level);
+    None
+}
+
+fn push_text(stack: &mut [Element], text: &str) {
+    if let Some(element) = stack.last_mut() {
+        element.text.push_str(text);
+    }
+}
+
+/// Interpret text as a boolean or number when that is exactly what it spells.
+fn infer(text: &str) -> Value {
+    match text {
+        "true" => return Value::Bool(true),
+        "false" => return Value::Bool(false),
+        _ => {}
+    }
+    if let Ok(n) = text.parse::<i64>() {
+        if n.to_string() == text {
+            return Value::from(n);
+        }
+    }
+    if let Ok(f) = text.parse::<f64>() {
+        if f.to_string() == text {
+            if let Some(n) = serde_json::Number::from_f64(f) {
+                return Value::Number(n);
+            }
+        }
+    }
+    Value::String(text.to_owned())
+}
+
+/// Namespace of the `subsonic-response` element.
+#[cfg(feature = "server")]
+const NAMESPACE: &str = "http://subsonic.org/restapi";
+
+/// Write the fields of a `subsonic-response` as an XML document, the inverse of
+/// [`parse_response`].
+///
+/// Scalar fields become attributes and a `value` field the text content; objects become
+/// child elements, and lists one child element per item.
+#[cfg(feature = "server")]
+pub(crate) fn write_response(response: &Map<String, Value>) -> String {
+    let mut writer = Writer::new(br#"<?xml version="1.0" encoding="UTF-8"?>"#.to_vec());
+    write_element(&mut writer, "subsonic-response", response, Some(NAMESPACE))
+        .expect("writing to a Vec cannot fail");
+    String::from_utf8(writer.into_inner()).expect("the XML writer produces UTF-8")
+}
+
+#[cfg(feature = "server")]
+fn write_element(
+    writer: &mut Writer<Vec<u8>>,
+    name: &str,
+    fields: &Map<String, Value>,
+    namespace: Option<&str>,
+) -> std::io::Result<()> {
+    let mut start = BytesStart::new(name);
+    if let Some(namespace) = namespace {
+        start.push_attribute(("xmlns", namespace));
+    }
+    let mut text = None;
+    let mut children = Vec::new();
+    for (key, value) in fields {
+        match value {
+            Value::Null => {}
+            Value::Object(_) | Value::Array(_) => children.push((key, value)),
+            _ if key == "value" => text = Some(scalar(value)),
+            _ => start.push_attribute((key.as_str(), scalar(value).as_str())),
+        }
+    }
+    if text.is_none() && children.is_empty() {
+        return writer.write_event(Event::Empty(start));
+    }
+    writer.write_event(Event::Start(start))?;
+    if let Some(text) = text {
+        writer.write_event(Event::Text(BytesText::new(&text)))?;
+    }
+    for (key, value) in children {
+        write_child(writer, key, value)?;
+    }
+    writer.write_event(Event::End(BytesEnd::new(name)))
+}
+
+#[cfg(feature = "server")]
+fn write_child(writer: &mut Writer<Vec<u8>>, name: &str, value: &Value) -> std::io::Result<()> {
+    match value {
+        Value::Null => Ok(()),
+        Value::Array(items) => items
+            .iter()
+            .try_for_each(|item| write_child(writer, name, item)),
+        Value::Object(fields) => write_element(writer, name, fields, None),
+        _ => {
+            writer.write_event(Event::Start(BytesStart::new(name)))?;
+            writer.write_event(Event::Text(BytesText::new(&scalar(value))))?;
+            writer.write_event(Event::End(BytesEnd::new(name)))
+        }
+    }
+}
+
+/// The text of a string, number or boolean.
+#[cfg(feature = "server")]
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+pub(crate) fn xml_error(e: impl std::fmt::Display) -> Error {
+    Error::Parse(format!("Invalid XML: {e}"))
+}
+
+/// Deserializes a converted XML [`Value`], coercing between strings, numbers and booleans
+/// and between single values and one-element lists as the target type requires.
+pub(crate) struct Lenient(pub(crate) Value);
+
+impl Lenient {
+    /// The value to use where a single item is expected.
+    fn single(self) -> Value {
+        match self.0 {
+            Value::Array(mut items) if items.len() == 1 => items.remove(0),
+            other => other,
+        }
+    }
+
+    /// The value to use where a number is expected.
+    fn number(self) -> Value {
+        match self.single() {
+            Value::String(s) => s
+                .trim()
+                .parse::<i64>()
+                .map(Value::from)
+                .or_else(|_| s.trim().parse::<u64>().map(Value::from))
+                .ok()
+                .or_else(|| {
+                    let f = s.trim().parse::<f64>().ok()?;
+                    serde_json::Number::from_f64(f).map(Value::Number)
+                })
+                .unwrap_or(Value::String(s)),
+            other => other,
+        }
+    }
+}
+
+macro_rules! deserialize_number {
+    ($($method:ident)*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            self.number().$method(visitor)
+        }
+    )*};
+}
+
+impl<'de> Deserializer<'de> for Lenient {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Array(items) => visit_seq(items, visitor),
+            Value::Object(fields) => visit_map(fields, visitor),
+            other => other.deserialize_any(visitor),
+        }
+    }
+
+    deserialize_number! {
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.single() {
+            Value::String(s) if s == "1" => visitor.visit_bool(true),
+            Value::String(s) if s == "0" => visitor.visit_bool(false),
+            other => other.deserialize_bool(visitor),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.single() {
+            Value::Number(n) => visitor.visit_string(n.to_string()),
+            Value::Bool(b) => visitor.visit_string(b.to_string()),
+            other => other.deserialize_string(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            other => visitor.visit_some(Lenient(other)),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Array(items) => visit_seq(items, visitor),
+            Value::Null => visit_seq(Vec::new(), visitor),
+            other => visit_seq(vec![other], visitor),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.single() {
+            Value::Object(fields) => visit_map(fields, visitor),
+            other => other.deserialize_map(visitor),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.single().deserialize_enum(name, variants, visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        char bytes byte_buf unit unit_struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, serde_json::Error> for Lenient {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+fn visit_seq<'de, V: Visitor<'de>>(
+    items: Vec<Value>,
+    visitor: V,
+) -> Result<V::Value, serde_json::Error> {
+    let mut seq = SeqDeserializer::new(items.into_iter().map(Lenient));
+    let value = visitor.visit_seq(&mut seq)?;
+    seq.end()?;
+    Ok(value)
+}
+
+fn visit_map<'de, V: Visitor<'de>>(
+    fields: Map<String, Value>,
+    visitor: V,
+) -> Result<V::Value, serde_json::Error> {
+    let mut map = MapDeserializer::new(fields.into_iter().map(|(k, v)| (k, Lenient(v))));
+    let value = visitor.visit_map(&mut map)?;
+    map.end()?;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::data::AlbumWithSongsId3;
+
+    #[test]
+    fn converts_attributes_children_and_text() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <subsonic-response xmlns="http://subsonic.org/restapi" status="ok" version="1.16.1">
+                <lyrics artist="A &amp; B" title="Song">First line&#10;Second line</lyrics>
+                <user username="bob" adminRole="false"><folder>1</folder><folder>007</folder></user>
+            </subsonic-response>"#;
+        let value = parse_response(xml).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "status": "ok",
+                "version": "1.16.1",
+                "lyrics": {"artist": "A & B", "title": "Song", "value": "First line\nSecond line"},
+                "user": {"username": "bob", "adminRole": false, "folder": [1, "007"]},
+            })
+        );
+    }
+
+    #[test]
+    fn lenient_decodes_single_children_and_strings() {
+        let xml = r#"<subsonic-response status="ok">
+                <album id="42" name="1999" songCount="1" duration="300" created="2020-01-01T00:00:00Z">
+                    <song id="7" title="Intro" isDir="false" track="1"/>
+                </album>
+            </subsonic-response>"#;
+        let mut value = parse_response(xml).unwrap();
+        let album = AlbumWithSongsId3::deserialize(Lenient(value["album"].take())).unwrap();
+        assert_eq!(album.id, "42");
+        assert_eq!(album.name, "1999");
+        assert_eq!(album.song.len(), 1);
+        assert_eq!(album.song[0].track, Some(1));
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn written_responses_parse_back() {
+        let response = serde_json::json!({
+            "status": "ok",
+            "version": "1.16.1",
+            "lyrics": { "artist": "A & B", "title": "Song", "value": "First line" },
+            "user": { "username": "bob", "adminRole": false, "folder": [1, 2] },
+        });
+        let xml = write_response(response.as_object().unwrap());
+        assert!(xml.contains(r#"<lyrics artist="A &amp; B" title="Song">First line</lyrics>"#));
+        let mut parsed = parse_response(&xml).unwrap();
+        parsed.as_object_mut().unwrap().remove("xmlns");
+        assert_eq!(parsed, response);
+    }
+
+    #[cfg(feature = "jukebox")]
+    #[test]
+    fn lenient_decodes_single_entries() {
+        use crate::data::{Child, JukeboxPlaylist};
+
+        let xml = r#"<subsonic-response status="ok">
+                <jukeboxPlaylist currentIndex="0" playing="true" volume="0.5">
+                    <entry id="1" isDir="false" title="Only"/>
+                </jukeboxPlaylist>
+            </subsonic-response>"#;
+        let mut value = parse_response(xml).unwrap();
+        let playlist = JukeboxPlaylist::deserialize(Lenient(value["jukeboxPlaylist"].take()));
+        let entry: Vec<Child> = playlist.unwrap().entry;
+        assert_eq!(entry[0].title, "Only");
+    }
+}